@@ -25,6 +25,24 @@ pub enum SubCommand {
 /// A subcommand for controlling the Voter
 #[derive(Clap, Debug)]
 pub struct Voter {
+    /// The voter subcommands
+    #[clap(subcommand)]
+    pub subcmd: VoterSubCommand,
+}
+
+#[derive(Clap, Debug)]
+pub enum VoterSubCommand {
+    #[clap(name = "vote")]
+    CreateVotes(CreateVotes),
+    #[clap(name = "audit")]
+    AuditBallot(AuditBallot),
+    #[clap(name = "verify-audit")]
+    VerifyAudit(VerifyAudit),
+}
+
+/// A subcommand for creating and submitting votes
+#[derive(Clap, Debug)]
+pub struct CreateVotes {
     /// The id of the vote
     #[clap(short, long)]
     pub vote: String,
@@ -37,6 +55,37 @@ pub struct Voter {
     /// The set of allowed votes
     #[clap(long)]
     pub votes: Vec<u32>,
+    /// Free-text answers to a write-in question (one ballot per answer)
+    #[clap(long)]
+    pub write_ins: Vec<String>,
+}
+
+/// A subcommand to audit ballots before casting one (cast-or-audit)
+#[derive(Clap, Debug)]
+pub struct AuditBallot {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+    /// The id of the question
+    #[clap(short, long)]
+    pub question: String,
+    /// The answer to encrypt
+    #[clap(short, long)]
+    pub answer: u32,
+    /// The index of the voter (i.e. the account //<index>)
+    #[clap(long, default_value = "0")]
+    pub index: u64,
+}
+
+/// A subcommand to independently verify an audited ballot
+#[derive(Clap, Debug)]
+pub struct VerifyAudit {
+    /// The path to the public key of the vote (JSON)
+    #[clap(short, long)]
+    pub public_key: String,
+    /// The path to the audit data of the ballot (JSON)
+    #[clap(short, long)]
+    pub audit: String,
 }
 
 /// A subcommand for controlling the Voting Authority
@@ -3,12 +3,15 @@ mod voting;
 
 use async_std::task;
 use clap::Clap;
-use cli::cli::{Opts, SealerSubCommand, SubCommand, VASubCommand};
+use cli::cli::{Opts, SealerSubCommand, SubCommand, VASubCommand, VoterSubCommand};
 use voting::{
     sealer::{decrypt, keygen},
     va::{change_vote_phase, get_result, setup_question, setup_vote},
 };
-use voting::{va::combine_public_key_shares, va::tally_question, voter::create_votes};
+use voting::{va::combine_public_key_shares, va::tally_question};
+use voting::{
+    voter::audit_ballot, voter::create_votes, voter::create_write_in_votes, voter::verify_audit,
+};
 
 fn main() {
     let opts: Opts = Opts::parse();
@@ -16,17 +19,49 @@ fn main() {
     // You can handle information about subcommands by requesting their matches by name
     // (as below), requesting just the name used, or both at the same time
     match opts.subcmd {
-        SubCommand::Voter(t) => {
-            println!("Voter. Creating votes... {:?}", t);
-            task::block_on(async {
-                let result =
-                    task::spawn(create_votes(t.vote, t.question, t.nr_of_votes, t.votes)).await;
-                match result {
-                    Ok(_) => println!("successfully created {:?} votes.", t.nr_of_votes),
-                    Err(err) => println!("failed to create vote: {:?}", err),
+        SubCommand::Voter(t) => match t.subcmd {
+            VoterSubCommand::CreateVotes(t) if !t.write_ins.is_empty() => {
+                println!("Voter. Creating write-in votes... {:?}", t);
+                task::block_on(async {
+                    let nr_of_votes = t.write_ins.len();
+                    let result =
+                        task::spawn(create_write_in_votes(t.vote, t.question, t.write_ins)).await;
+                    match result {
+                        Ok(_) => println!("successfully created {:?} write-in votes.", nr_of_votes),
+                        Err(err) => println!("failed to create vote: {:?}", err),
+                    }
+                });
+            }
+            VoterSubCommand::CreateVotes(t) => {
+                println!("Voter. Creating votes... {:?}", t);
+                task::block_on(async {
+                    let result =
+                        task::spawn(create_votes(t.vote, t.question, t.nr_of_votes, t.votes)).await;
+                    match result {
+                        Ok(_) => println!("successfully created {:?} votes.", t.nr_of_votes),
+                        Err(err) => println!("failed to create vote: {:?}", err),
+                    }
+                });
+            }
+            VoterSubCommand::AuditBallot(t) => {
+                println!("Voter. Cast or audit ballot... {:?}", t);
+                task::block_on(async {
+                    let result =
+                        task::spawn(audit_ballot(t.vote, t.question, t.answer, t.index)).await;
+                    match result {
+                        Ok(_) => println!("successfully cast ballot!"),
+                        Err(err) => println!("failed to cast ballot: {:?}", err),
+                    }
+                });
+            }
+            VoterSubCommand::VerifyAudit(t) => {
+                println!("Voter. Verifying audited ballot... {:?}", t);
+                match verify_audit(t.public_key, t.audit) {
+                    Ok(_) => println!("the audited ballot encrypts the answer!"),
+                    Err(err) => println!("failed to verify the audited ballot: {:?}", err),
                 }
-            });
-        }
+            }
+        },
         SubCommand::VotingAuthority(t) => match t.subcmd {
             VASubCommand::SetupVote(t) => {
                 println!("VA. Creating vote... {:?}", t);
@@ -1,13 +1,13 @@
 use crypto::{
     encryption::ElGamal,
-    helper::Helper,
+    helper::{Helper, ProofContext},
     proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof},
     random::Random,
-    types::Cipher as BigCipher,
+    types::{Cipher as BigCipher, Secret},
 };
 use hex_literal::hex;
 use num_bigint::BigUint;
-use pallet_mixnet::types::{Cipher, PublicKeyShare, Wrapper};
+use pallet_mixnet::types::{Canonical, Cipher, PublicKeyShare};
 use sp_keyring::{sr25519::sr25519::Pair, AccountKeyring};
 use substrate_subxt::{Client, PairSigner};
 use substrate_subxt::{ClientBuilder, Error, NodeTemplateRuntime};
@@ -50,11 +50,15 @@ pub async fn keygen(vote: String, sk_as_string: String, sealer: String) -> Resul
     let (sealer, sealer_id): (Pair, [u8; 32]) = get_sealer(sealer);
 
     // create public key share + proof
-    let r = Random::get_random_less_than(&params.q());
-    let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, &sealer_id);
+    let r = Secret::new(Random::get_random_less_than(
+        &params.q(),
+        &mut rand::thread_rng(),
+    ));
+    let context = ProofContext::new(vote.as_bytes());
+    let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, &sealer_id, &context);
     let pk_share = PublicKeyShare {
-        proof: proof.clone().into(),
-        pk: pk.h.to_bytes_be(),
+        proof: Canonical::encode_canonical(&proof, &params),
+        pk: params.encode_value(&pk.h),
     };
     let vote_id = vote.as_bytes().to_vec();
 
@@ -90,7 +94,9 @@ pub async fn decrypt(
     let topic_id = question.as_bytes().to_vec();
     let nr_of_shuffles = 3;
     let encryptions: Vec<Cipher> = get_ciphers(&client, topic_id.clone(), nr_of_shuffles).await?;
-    let encryptions: Vec<BigCipher> = Wrapper(encryptions).into();
+    let encryptions: Vec<BigCipher> = encryptions
+        .decode_canonical(&params)
+        .ok_or("the ciphers are not canonically encoded!")?;
 
     // get partial decryptions
     let partial_decryptions = encryptions
@@ -98,15 +104,18 @@ pub async fn decrypt(
         .map(|cipher| ElGamal::partial_decrypt_a(cipher, &sk))
         .collect::<Vec<BigUint>>();
 
-    // convert the decrypted shares: Vec<BigUint> to Vec<Vec<u8>>
+    // convert the decrypted shares: Vec<BigUint> to Vec<Vec<u8>> (canonical encoding)
     let shares: Vec<Vec<u8>> = partial_decryptions
         .iter()
-        .map(|c| c.to_bytes_be())
+        .map(|c| params.encode_value(c))
         .collect::<Vec<Vec<u8>>>();
 
     // create proof using public and private key share
-    let r = Random::get_random_less_than(&params.q());
-    let proof = DecryptionProof::generate(
+    let r = Secret::new(Random::get_random_less_than(
+        &params.q(),
+        &mut rand::thread_rng(),
+    ));
+    let (proof, commitments) = DecryptionProof::generate_with_commitments(
         &params,
         &sk.x,
         &pk.h.into(),
@@ -114,8 +123,15 @@ pub async fn decrypt(
         encryptions,
         partial_decryptions,
         &sealer_id,
+        &ProofContext::new(vote.as_bytes()),
     );
 
+    // the commitments of the proof allow to verify the proofs of all sealers at once
+    let commitments: Vec<Vec<u8>> = commitments
+        .iter()
+        .map(|t| params.encode_value(t))
+        .collect::<Vec<Vec<u8>>>();
+
     // submit the partial decryption + proof
     let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(sealer);
     let response = submit_partial_decryptions(
@@ -124,7 +140,8 @@ pub async fn decrypt(
         vote_id,
         topic_id,
         shares,
-        proof.into(),
+        Canonical::encode_canonical(&proof, &params),
+        commitments,
         nr_of_shuffles,
     )
     .await?;
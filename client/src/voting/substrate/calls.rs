@@ -1,9 +1,12 @@
 use codec::Encode;
 use pallet_mixnet::types::{
-    Ballot, DecryptedShare, DecryptedShareProof, NrOfShuffles, PublicKey as SubstratePK,
-    PublicKeyShare, PublicParameters, Title, Topic, TopicId, TopicResult, VoteId, VotePhase,
+    Ballot, DecryptedShare, DecryptedShareCommitment, DecryptedShareProof, Misbehaviour,
+    NrOfShuffles, PublicKey as SubstratePK, PublicKeyShare, PublicParameters, Title, Topic,
+    TopicId, TopicResult, VoteId, VotePhase,
 };
-use substrate_subxt::{Call, EventsDecoder, NodeTemplateRuntime};
+use substrate_subxt::{system::System, Call, EventsDecoder, NodeTemplateRuntime};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
 
 #[derive(Encode)]
 pub struct CreateVote {
@@ -12,6 +15,7 @@ pub struct CreateVote {
     pub params: PublicParameters,
     pub topics: Vec<Topic>,
     pub batch_size: u64,
+    pub sealers: Vec<AccountId>,
 }
 
 impl Call<NodeTemplateRuntime> for CreateVote {
@@ -23,6 +27,7 @@ impl Call<NodeTemplateRuntime> for CreateVote {
         _decoder.register_type_size::<PublicParameters>("PublicParameters");
         _decoder.register_type_size::<Vec<Topic>>("Vec<Topic>");
         _decoder.register_type_size::<u64>("batch_size");
+        _decoder.register_type_size::<Vec<AccountId>>("Vec<AccountId>");
     }
 }
 
@@ -70,6 +75,7 @@ impl Call<NodeTemplateRuntime> for StorePublicKeyShare {
     fn events_decoder(_decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
         _decoder.register_type_size::<VoteId>("VoteId");
         _decoder.register_type_size::<PublicKeyShare>("PublicKeyShare");
+        _decoder.register_type_size::<Misbehaviour>("Misbehaviour");
     }
 }
 
@@ -123,6 +129,7 @@ pub struct SubmitPartialDecryption {
     pub topic_id: TopicId,
     pub shares: Vec<DecryptedShare>,
     pub proof: DecryptedShareProof,
+    pub commitments: Vec<DecryptedShareCommitment>,
     pub nr_of_shuffles: NrOfShuffles,
 }
 
@@ -134,7 +141,10 @@ impl Call<NodeTemplateRuntime> for SubmitPartialDecryption {
         _decoder.register_type_size::<TopicId>("TopicId");
         _decoder.register_type_size::<Vec<DecryptedShare>>("Vec<DecryptedShare>");
         _decoder.register_type_size::<DecryptedShareProof>("DecryptedShareProof");
+        _decoder
+            .register_type_size::<Vec<DecryptedShareCommitment>>("Vec<DecryptedShareCommitment>");
         _decoder.register_type_size::<NrOfShuffles>("NrOfShuffles");
+        _decoder.register_type_size::<Misbehaviour>("Misbehaviour");
     }
 }
 
@@ -2,10 +2,13 @@ use crate::voting::substrate::calls::{
     CastBallot, CombineDecryptedShares, CombinePublicKeyShares, CreateVote, SetVotePhase,
     StorePublicKey, StorePublicKeyShare, StoreQuestion, SubmitPartialDecryption,
 };
-use crate::voting::substrate::stores::{CiphersStore, PublicKeyStore, TallyStore};
+use crate::voting::substrate::stores::{
+    CiphersStore, PublicKeyStore, TallyStore, TopicDefinitionStore,
+};
 use pallet_mixnet::types::{
-    Ballot, Cipher, DecryptedShare, DecryptedShareProof, NrOfShuffles, PublicKey as SubstratePK,
-    PublicKeyShare, PublicParameters, Title, Topic, TopicId, TopicResult, VoteId, VotePhase,
+    Ballot, Cipher, DecryptedShare, DecryptedShareCommitment, DecryptedShareProof, NrOfShuffles,
+    PublicKey as SubstratePK, PublicKeyShare, PublicParameters, Title, Topic, TopicDefinition,
+    TopicId, TopicResult, VoteId, VotePhase,
 };
 use sp_keyring::{sr25519::sr25519::Pair, AccountKeyring};
 use substrate_subxt::{system::System, Call, Client, ExtrinsicSuccess};
@@ -50,6 +53,18 @@ pub async fn get_tally(
     Ok(tally)
 }
 
+pub async fn get_topic_definition(
+    client: &Client<NodeTemplateRuntime>,
+    topic_id: TopicId,
+) -> Result<TopicDefinition, Error> {
+    let store = TopicDefinitionStore { topic_id };
+    let definition = client
+        .fetch(&store, None)
+        .await?
+        .ok_or("failed to fetch topic definition!")?;
+    Ok(definition)
+}
+
 pub async fn create_vote(
     client: &Client<NodeTemplateRuntime>,
     params: PublicParameters,
@@ -57,6 +72,7 @@ pub async fn create_vote(
     vote_id: VoteId,
     topics: Vec<Topic>,
     batch_size: u64,
+    sealers: Vec<<NodeTemplateRuntime as System>::AccountId>,
 ) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
     let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(AccountKeyring::Alice.pair());
     let call = CreateVote {
@@ -65,6 +81,7 @@ pub async fn create_vote(
         vote_id,
         topics,
         batch_size,
+        sealers,
     };
     return watch(&signer, client, call).await;
 }
@@ -158,6 +175,7 @@ pub async fn submit_partial_decryptions(
     topic_id: TopicId,
     shares: Vec<DecryptedShare>,
     proof: DecryptedShareProof,
+    commitments: Vec<DecryptedShareCommitment>,
     nr_of_shuffles: NrOfShuffles,
 ) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
     let call = SubmitPartialDecryption {
@@ -165,6 +183,7 @@ pub async fn submit_partial_decryptions(
         topic_id,
         shares,
         proof,
+        commitments,
         nr_of_shuffles,
     };
     return watch(&signer, client, call).await;
@@ -1,6 +1,6 @@
 use codec::{Decode, Encode};
 use pallet_mixnet::types::{
-    Cipher, NrOfShuffles, PublicKey as SubstratePK, TopicId, TopicResult, VoteId,
+    Cipher, NrOfShuffles, PublicKey as SubstratePK, TopicDefinition, TopicId, TopicResult, VoteId,
 };
 use substrate_subxt::{
     sp_core::storage::StorageKey, Metadata, MetadataError, NodeTemplateRuntime, Store,
@@ -142,3 +142,37 @@ impl Store<NodeTemplateRuntime> for TallyStore {
             .default()
     }
 }
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Decode)]
+pub struct TopicDefinitionStore {
+    pub topic_id: TopicId,
+}
+
+impl Store<NodeTemplateRuntime> for TopicDefinitionStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "TopicDefinitions";
+    /// Return type.
+    type Returns = TopicDefinition;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.topic_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
@@ -4,6 +4,7 @@ use crate::voting::substrate::rpc::{
 };
 use crypto::helper::Helper;
 use pallet_mixnet::types::{Topic, VotePhase};
+use sp_keyring::AccountKeyring;
 use std::str::FromStr;
 use substrate_subxt::Client;
 use substrate_subxt::{ClientBuilder, Error, NodeTemplateRuntime};
@@ -33,6 +34,12 @@ pub async fn setup_vote(vote_title: String, topic_question: String) -> Result<()
     let topic: Topic = (topic_id.clone(), topic_question);
     let topics = vec![topic];
 
+    // the sealers taking part in the vote
+    let sealers = vec![
+        AccountKeyring::Bob.to_account_id(),
+        AccountKeyring::Charlie.to_account_id(),
+    ];
+
     // setup the vote
     let create_vote_response = create_vote(
         &client,
@@ -41,6 +48,7 @@ pub async fn setup_vote(vote_title: String, topic_question: String) -> Result<()
         vote_id.clone(),
         topics,
         75,
+        sealers,
     )
     .await?;
     println!(
@@ -1,20 +1,26 @@
 use crate::voting::substrate::rpc::submit_ballot;
 use crypto::{
+    encoding::Encoding,
+    encryption::ElGamal,
+    helper::{Helper, ProofContext},
     proofs::re_encryption::ReEncryptionProof,
     types::{Cipher, PublicKey},
 };
 use crypto::{random::Random, types::PublicKey as ElGamalPK};
-use pallet_mixnet::types::Ballot;
+use num_bigint::BigUint;
+use num_traits::One;
+use pallet_mixnet::types::{Ballot, Canonical, Cipher as SubstrateCipher, QuestionType, TopicId};
 use serde::{Deserialize, Serialize};
 use sp_keyring::sr25519::sr25519::Pair;
 use substrate_subxt::{sp_core::Pair as KeyPairGenerator, Client};
 use substrate_subxt::{ClientBuilder, Error, NodeTemplateRuntime, PairSigner};
 use surf::Body;
 
-use super::substrate::rpc::get_vote_public_key;
+use super::substrate::rpc::{get_topic_definition, get_vote_public_key};
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 pub struct RequestBody {
+    pub vote_id: String,
     pub pk: PublicKey,
     pub cipher: Cipher,
 }
@@ -25,6 +31,15 @@ pub struct ResponseBody {
     pub cipher: Cipher,
 }
 
+/// The revealed encryption of an audited ballot (see `verify_audit`)
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct AuditData {
+    pub vote_id: String,
+    pub cipher: Cipher,
+    pub answer: BigUint,
+    pub randomness: BigUint,
+}
+
 async fn init() -> Result<Client<NodeTemplateRuntime>, Error> {
     env_logger::init();
     let url = "ws://127.0.0.1:9944";
@@ -49,9 +64,11 @@ pub async fn create_votes(
     let topic_id = question.as_bytes().to_vec();
     let pk: ElGamalPK = get_vote_public_key(&client, vote_id.clone()).await?.into();
     let q = &pk.params.q();
+    let context = ProofContext::new(vote.as_bytes());
 
     // generate random encryptions
-    let encryptions = Random::generate_encryptions(&pk, q, nr_of_votes, votes);
+    let encryptions =
+        Random::generate_encryptions(&pk, q, nr_of_votes, votes, &mut rand::thread_rng());
 
     // submit some ballots
     for (index, cipher) in encryptions.into_iter().enumerate() {
@@ -60,12 +77,13 @@ pub async fn create_votes(
         let voter = PairSigner::<NodeTemplateRuntime, Pair>::new(voter_keypair);
 
         let body = RequestBody {
+            vote_id: vote.clone(),
             pk: pk.clone(),
             cipher: cipher.clone(),
         };
         let response: ResponseBody = randomize_cipher(&body).await.unwrap();
         let proof_is_valid =
-            ReEncryptionProof::verify(&pk, &response.proof, &cipher, &response.cipher);
+            ReEncryptionProof::verify(&pk, &response.proof, &cipher, &response.cipher, &context);
         assert!(proof_is_valid);
         let re_encrypted_cipher = response.cipher;
         println!(
@@ -74,8 +92,10 @@ pub async fn create_votes(
         );
 
         // create ballot
+        let re_encrypted_cipher =
+            SubstrateCipher::encode_canonical(&re_encrypted_cipher, &pk.params);
         let ballot: Ballot = Ballot {
-            answers: vec![(topic_id.clone(), re_encrypted_cipher.into())],
+            answers: vec![(topic_id.clone(), re_encrypted_cipher)],
         };
 
         // submit ballot
@@ -86,6 +106,188 @@ pub async fn create_votes(
     Ok(())
 }
 
+pub async fn create_write_in_votes(
+    vote: String,
+    question: String,
+    write_ins: Vec<String>,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = init().await?;
+
+    let vote_id = vote.as_bytes().to_vec();
+    let topic_id = question.as_bytes().to_vec();
+    let pk: ElGamalPK = get_vote_public_key(&client, vote_id.clone()).await?.into();
+    let q = &pk.params.q();
+    let context = ProofContext::new(vote.as_bytes());
+
+    // every write-in is padded to the number of elements of the maximal length
+    let definition = get_topic_definition(&client, topic_id.clone()).await?;
+    let max_length = match definition.question_type {
+        QuestionType::WriteIn { max_length } => max_length as usize,
+        _ => return Err("the question does not accept write-ins!".into()),
+    };
+    let nr_of_elements = Encoding::nr_of_elements(max_length, &pk.params.p);
+
+    // submit one ballot per write-in
+    for (index, write_in) in write_ins.into_iter().enumerate() {
+        let index_string = (index as u64).to_string();
+        let voter_keypair = KeyPairGenerator::from_string(&format!("//{}", index_string), None)?;
+        let voter = PairSigner::<NodeTemplateRuntime, Pair>::new(voter_keypair);
+
+        // encode the text as quadratic residues and encrypt each of them
+        let encoded =
+            Encoding::encode_bytes_padded(write_in.as_bytes(), nr_of_elements, &pk.params.p)
+                .ok_or("the write-in is too long!")?;
+        let mut answers: Vec<(TopicId, SubstrateCipher)> = Vec::with_capacity(nr_of_elements);
+        for m in encoded.iter() {
+            let r = Random::get_random_less_than(q, &mut rand::thread_rng());
+            let cipher = ElGamal::encrypt(m, &r, &pk);
+
+            let body = RequestBody {
+                vote_id: vote.clone(),
+                pk: pk.clone(),
+                cipher: cipher.clone(),
+            };
+            let response: ResponseBody = randomize_cipher(&body).await.unwrap();
+            let proof_is_valid = ReEncryptionProof::verify(
+                &pk,
+                &response.proof,
+                &cipher,
+                &response.cipher,
+                &context,
+            );
+            assert!(proof_is_valid);
+            let cipher = SubstrateCipher::encode_canonical(&response.cipher, &pk.params);
+            answers.push((topic_id.clone(), cipher));
+        }
+        println!(
+            "randomized write-in + verified proofs for voter: {:?}",
+            index_string
+        );
+
+        // the ciphers of the write-in are submitted consecutively
+        let ballot: Ballot = Ballot { answers };
+        let ballot_submission_hash =
+            submit_ballot(&client, &voter, vote_id.clone(), ballot).await?;
+        println!("ballot_submission_hash: {:?}", ballot_submission_hash);
+    }
+    Ok(())
+}
+
+pub async fn audit_ballot(
+    vote: String,
+    question: String,
+    answer: u32,
+    index: u64,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = init().await?;
+
+    let vote_id = vote.as_bytes().to_vec();
+    let topic_id = question.as_bytes().to_vec();
+    let pk: ElGamalPK = get_vote_public_key(&client, vote_id.clone()).await?.into();
+    let q = &pk.params.q();
+    let context = ProofContext::new(vote.as_bytes());
+
+    // the answers are encrypted without encoding i.e. they must be quadratic residues
+    let message = BigUint::from(answer);
+    if message.modpow(q, &pk.params.p) != BigUint::one() {
+        return Err("the answer is not a quadratic residue!".into());
+    }
+
+    let voter_keypair = KeyPairGenerator::from_string(&format!("//{}", index), None)?;
+    let voter = PairSigner::<NodeTemplateRuntime, Pair>::new(voter_keypair);
+
+    // the public key an audit is verified against (see `verify_audit`)
+    let pk_json = serde_json::to_string(&pk).map_err(|_| "failed to serialize the public key!")?;
+    println!("public key: {}", pk_json);
+
+    // the voter audits as many ballots as they want and casts the last one
+    loop {
+        // encrypt the answer and commit to the encryption
+        let r = Random::get_random_less_than(q, &mut rand::thread_rng());
+        let cipher = ElGamal::encrypt(&message, &r, &pk);
+        println!("ballot commitment: {}", hash_cipher(&cipher));
+        println!("cast (c) or audit (a) the ballot?");
+
+        let mut choice = String::new();
+        std::io::stdin()
+            .read_line(&mut choice)
+            .map_err(|_| "failed to read the choice!")?;
+        match choice.trim() {
+            "a" | "audit" => {
+                // reveal the randomness, the audit data is verified independently
+                // of this client using `voter verify-audit`
+                let audit = AuditData {
+                    vote_id: vote.clone(),
+                    cipher: cipher.clone(),
+                    answer: message.clone(),
+                    randomness: r,
+                };
+                let audit_json =
+                    serde_json::to_string(&audit).map_err(|_| "failed to serialize the audit!")?;
+                println!("audit data: {}", audit_json);
+
+                // the audited ballot is discarded
+                println!("the ballot is discarded.");
+            }
+            "c" | "cast" => {
+                let body = RequestBody {
+                    vote_id: vote.clone(),
+                    pk: pk.clone(),
+                    cipher: cipher.clone(),
+                };
+                let response: ResponseBody = randomize_cipher(&body).await.unwrap();
+                let proof_is_valid = ReEncryptionProof::verify(
+                    &pk,
+                    &response.proof,
+                    &cipher,
+                    &response.cipher,
+                    &context,
+                );
+                assert!(proof_is_valid);
+
+                let cipher = SubstrateCipher::encode_canonical(&response.cipher, &pk.params);
+                let ballot: Ballot = Ballot {
+                    answers: vec![(topic_id.clone(), cipher)],
+                };
+                let ballot_submission_hash =
+                    submit_ballot(&client, &voter, vote_id.clone(), ballot).await?;
+                println!("ballot hash: {}", hash_cipher(&response.cipher));
+                println!("ballot_submission_hash: {:?}", ballot_submission_hash);
+                return Ok(());
+            }
+            _ => println!("unknown choice, the ballot is discarded."),
+        }
+    }
+}
+
+/// Verifies the audit data of an audited ballot using only the public key of the vote
+pub fn verify_audit(public_key: String, audit: String) -> Result<(), Error> {
+    let pk = std::fs::read_to_string(public_key).map_err(|_| "failed to read the public key!")?;
+    let pk: ElGamalPK = serde_json::from_str(&pk).map_err(|_| "invalid public key!")?;
+    let audit = std::fs::read_to_string(audit).map_err(|_| "failed to read the audit data!")?;
+    let audit: AuditData = serde_json::from_str(&audit).map_err(|_| "invalid audit data!")?;
+
+    if !ElGamal::verify_encryption(&audit.cipher, &audit.answer, &audit.randomness, &pk) {
+        return Err("the ballot does not encrypt the answer!".into());
+    }
+    println!(
+        "vote: {}, ballot commitment: {}, answer: {}",
+        audit.vote_id,
+        hash_cipher(&audit.cipher),
+        audit.answer
+    );
+    Ok(())
+}
+
+fn hash_cipher(cipher: &Cipher) -> String {
+    Helper::hash_vec_ciphers(vec![cipher.clone()])
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 pub async fn randomize_cipher(body: &RequestBody) -> Result<ResponseBody, surf::Error> {
     let body = Body::from_json(body)?;
     let response = surf::post("http://0.0.0.0:8080/randomize")
@@ -1,9 +1,11 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use crypto::{
     encryption::ElGamal,
-    helper::Helper,
+    fixed_base::{FixedBaseTable, FixedBaseTables},
+    helper::{Helper, ProofContext},
+    multi_exp::MultiExp,
     proofs::keygen::KeyGenerationProof,
-    types::{Cipher, PublicKey},
+    types::{Cipher, PublicKey, Secret},
 };
 use num_bigint::BigUint;
 use num_traits::One;
@@ -12,7 +14,7 @@ fn setup_shuffling(
     nr_of_votes: usize,
     encoded: bool,
     pk: PublicKey,
-) -> (Vec<Cipher>, Vec<usize>, Vec<BigUint>, PublicKey) {
+) -> (Vec<Cipher>, Vec<usize>, Vec<Secret>, PublicKey) {
     let q = pk.params.q();
 
     // encryption of three and one
@@ -32,7 +34,7 @@ fn setup_shuffling(
     }
 
     let mut encryptions: Vec<Cipher> = Vec::new();
-    let mut randoms: Vec<BigUint> = Vec::new();
+    let mut randoms: Vec<Secret> = Vec::new();
     let power = BigUint::parse_bytes(b"ABCDEF123456789ABCDEF123412341241241241124", 16).unwrap();
     let mut permutation: Vec<usize> = Vec::new();
 
@@ -42,7 +44,7 @@ fn setup_shuffling(
         let mut random = BigUint::from(i);
         random *= BigUint::from(i);
         random = random.modpow(&power, &q);
-        randoms.push(random);
+        randoms.push(Secret::new(random));
 
         if i % 2 == 0 {
             encryptions.push(enc_three.clone());
@@ -200,6 +202,89 @@ fn bench_elgamal(c: &mut Criterion) {
     });
 }
 
+fn bench_fixed_base(c: &mut Criterion) {
+    let (_, _, pk1) = Helper::setup_md_system();
+    let (_, _, pk2) = Helper::setup_lg_system();
+    let (_, _, pk3) = Helper::setup_xl_system();
+    let setups = vec![
+        (pk1, "fixed-base 1024bit"),
+        (pk2, "fixed-base 2048bit"),
+        (pk3, "fixed-base 3072bit"),
+    ];
+
+    for (pk, name) in setups {
+        // benchmark config
+        let mut group = c.benchmark_group(name);
+        let r = BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap();
+        let table = FixedBaseTable::new(&pk.params.g, &pk.params.p);
+        let tables = FixedBaseTables::from_public_key(&pk);
+
+        group.bench_function("precomputation", |b| {
+            b.iter(|| FixedBaseTable::new(&pk.params.g, &pk.params.p))
+        });
+
+        group.bench_function("g^r: modpow", |b| {
+            b.iter(|| pk.params.g.modpow(&r, &pk.params.p))
+        });
+
+        group.bench_function("g^r: table", |b| b.iter(|| table.pow(&r)));
+
+        group.bench_function("re-encryption: modpow", |b| {
+            let cipher = ElGamal::encrypt_encode(&BigUint::one(), &r, &pk);
+            b.iter(|| {
+                let p = &pk.params.p;
+                let a = cipher.a.clone() * pk.params.g.modpow(&r, p) % p;
+                let b = cipher.b.clone() * pk.h.modpow(&r, p) % p;
+                Cipher { a, b }
+            })
+        });
+
+        group.bench_function("re-encryption: table", |b| {
+            let cipher = ElGamal::encrypt_encode(&BigUint::one(), &r, &pk);
+            b.iter(|| ElGamal::re_encrypt_with(&cipher, &r, &pk, &tables))
+        });
+
+        group.finish();
+    }
+}
+
+fn bench_multi_exp(c: &mut Criterion) {
+    let (params, _, _) = Helper::setup_lg_system();
+    let p = &params.p;
+
+    for size in [2usize, 10, 100, 1000].iter() {
+        // benchmark config
+        let mut group = c.benchmark_group(format!("multi-exponentiation {} bases", size));
+        group.sample_size(10);
+
+        let bases: Vec<BigUint> = (1..=*size)
+            .map(|i| params.g.modpow(&BigUint::from(i), p))
+            .collect();
+        let power =
+            BigUint::parse_bytes(b"ABCDEF123456789ABCDEF123412341241241241124", 16).unwrap();
+        let exponents: Vec<BigUint> = (1..=*size)
+            .map(|i| BigUint::from(i).modpow(&power, &params.q()))
+            .collect();
+
+        group.bench_function("modpow", |b| {
+            b.iter(|| {
+                bases
+                    .iter()
+                    .zip(exponents.iter())
+                    .fold(BigUint::one(), |prod, (base, exponent)| {
+                        prod * base.modpow(exponent, p) % p
+                    })
+            })
+        });
+
+        group.bench_function("multi_modpow", |b| {
+            b.iter(|| MultiExp::multi_modpow(&bases, &exponents, p))
+        });
+
+        group.finish();
+    }
+}
+
 fn bench_proofs(c: &mut Criterion) {
     // benchmark config
     let mut group = c.benchmark_group("proofs");
@@ -209,11 +294,14 @@ fn bench_proofs(c: &mut Criterion) {
             || {
                 let sealer_id = "Bob".as_bytes();
                 let (params, sk, pk) = Helper::setup_lg_system();
-                let r = BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap();
+                let r = Secret::new(
+                    BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap(),
+                );
                 (params, sk.x, pk.h, r, sealer_id)
             },
             |(params, x, h, r, sealer_id)| {
-                KeyGenerationProof::generate(&params, &x, &h, &r, sealer_id)
+                let context = ProofContext::new(b"2020-12-12_01");
+                KeyGenerationProof::generate(&params, &x, &h, &r, sealer_id, &context)
             },
         )
     });
@@ -223,12 +311,17 @@ fn bench_proofs(c: &mut Criterion) {
             || {
                 let sealer_id = "Bob".as_bytes();
                 let (params, sk, pk) = Helper::setup_lg_system();
-                let r = BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap();
-                let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id);
+                let r = Secret::new(
+                    BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap(),
+                );
+                let context = ProofContext::new(b"2020-12-12_01");
+                let proof =
+                    KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id, &context);
                 (params, pk.h, proof, sealer_id)
             },
             |(params, h, proof, sealer_id)| {
-                KeyGenerationProof::verify(&params, &h, &proof, sealer_id)
+                let context = ProofContext::new(b"2020-12-12_01");
+                KeyGenerationProof::verify(&params, &h, &proof, sealer_id, &context)
             },
         )
     });
@@ -445,6 +538,8 @@ fn bench_decryption_encoded_different_votes(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_elgamal,
+    bench_fixed_base,
+    bench_multi_exp,
     bench_proofs,
     bench_shuffle,
     bench_decryption_encoded_different_votes
@@ -0,0 +1,261 @@
+use alloc::{vec, vec::Vec};
+use crypto_bigint::{
+    modular::runtime_mod::{DynResidue, DynResidueParams},
+    Limb, Uint, U1024, U128, U2048, U256, U3072, U4096, U512, U64,
+};
+use num_bigint::BigUint;
+use zeroize::Zeroizing;
+
+/// Calls the function with the smallest supported fixed-width integer (64 - 4096 bits)
+/// which fits the modulus. Returns `None` if the modulus is larger than 4096 bits.
+macro_rules! with_width {
+    ($modulus:expr, $f:ident($($arg:expr),*)) => {{
+        let bits = $modulus.bits() as usize;
+        if bits <= 64 {
+            Some($f::<{ U64::LIMBS }>($($arg),*))
+        } else if bits <= 128 {
+            Some($f::<{ U128::LIMBS }>($($arg),*))
+        } else if bits <= 256 {
+            Some($f::<{ U256::LIMBS }>($($arg),*))
+        } else if bits <= 512 {
+            Some($f::<{ U512::LIMBS }>($($arg),*))
+        } else if bits <= 1024 {
+            Some($f::<{ U1024::LIMBS }>($($arg),*))
+        } else if bits <= 2048 {
+            Some($f::<{ U2048::LIMBS }>($($arg),*))
+        } else if bits <= 3072 {
+            Some($f::<{ U3072::LIMBS }>($($arg),*))
+        } else if bits <= 4096 {
+            Some($f::<{ U4096::LIMBS }>($($arg),*))
+        } else {
+            None
+        }
+    }};
+}
+
+/// The operations of the backend for secret values (feature `constant-time`).
+///
+/// All values are converted into fixed-width integers of the size of the modulus and
+/// the operations are computed in Montgomery form (see `crypto_bigint::modular`).
+/// The running time only depends on the size of the modulus, not on the values.
+/// The conversion from and to `BigUint` is not constant time, it only leaks the length of a value.
+///
+/// Every operation returns `None` if the backend does not support the input, i.e.
+/// an even modulus, a modulus larger than 4096 bits or a value larger than the modulus width.
+/// The secret operations of `ModuloOperations` panic in this case, they never fall back
+/// to the variable-time operations.
+pub struct ConstantTime;
+
+impl ConstantTime {
+    /// Returns true if the backend supports the modulus: odd and at most 4096 bits.
+    pub fn supports(modulus: &BigUint) -> bool {
+        modulus.bit(0) && modulus.bits() <= 4096
+    }
+
+    /// Returns base^exponent mod modulus.
+    pub fn modpow(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+        with_width!(modulus, pow(base, exponent, modulus))?
+    }
+
+    /// Returns lhs * rhs mod modulus.
+    pub fn modmul(lhs: &BigUint, rhs: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+        with_width!(modulus, mul(lhs, rhs, modulus))?
+    }
+
+    /// Returns (lhs + rhs) mod modulus.
+    pub fn modadd(lhs: &BigUint, rhs: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+        with_width!(modulus, add(lhs, rhs, modulus))?
+    }
+
+    /// Returns (lhs - rhs) mod modulus.
+    pub fn modsub(lhs: &BigUint, rhs: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+        with_width!(modulus, sub(lhs, rhs, modulus))?
+    }
+}
+
+fn pow<const LIMBS: usize>(
+    base: &BigUint,
+    exponent: &BigUint,
+    modulus: &BigUint,
+) -> Option<BigUint> {
+    let params = params::<LIMBS>(modulus)?;
+    let base = residue(base, params)?;
+    // the whole width of the exponent is processed, independent of its value
+    let exponent = to_uint::<LIMBS>(exponent)?;
+    Some(to_biguint(&base.pow(&exponent).retrieve()))
+}
+
+fn mul<const LIMBS: usize>(lhs: &BigUint, rhs: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let params = params::<LIMBS>(modulus)?;
+    let product = residue(lhs, params)?.mul(&residue(rhs, params)?);
+    Some(to_biguint(&product.retrieve()))
+}
+
+fn add<const LIMBS: usize>(lhs: &BigUint, rhs: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let params = params::<LIMBS>(modulus)?;
+    let sum = residue(lhs, params)?.add(&residue(rhs, params)?);
+    Some(to_biguint(&sum.retrieve()))
+}
+
+fn sub<const LIMBS: usize>(lhs: &BigUint, rhs: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let params = params::<LIMBS>(modulus)?;
+    let difference = residue(lhs, params)?.sub(&residue(rhs, params)?);
+    Some(to_biguint(&difference.retrieve()))
+}
+
+/// The Montgomery parameters of an odd modulus.
+fn params<const LIMBS: usize>(modulus: &BigUint) -> Option<DynResidueParams<LIMBS>> {
+    if !modulus.bit(0) {
+        return None;
+    }
+    Some(DynResidueParams::new(&to_uint::<LIMBS>(modulus)?))
+}
+
+/// The residue of a value, any value of the width is reduced modulo the modulus.
+fn residue<const LIMBS: usize>(
+    value: &BigUint,
+    params: DynResidueParams<LIMBS>,
+) -> Option<DynResidue<LIMBS>> {
+    Some(DynResidue::new(&to_uint::<LIMBS>(value)?, params))
+}
+
+/// Converts a BigUint into a fixed-width integer, `None` if the value is too large.
+/// The digits are copied into a buffer of the full width (all widths are multiples of 64 bits),
+/// i.e. the allocation does not depend on the length of the value.
+fn to_uint<const LIMBS: usize>(value: &BigUint) -> Option<Uint<LIMBS>> {
+    let width = Limb::BYTES * LIMBS;
+    if value.bits() as usize > 8 * width {
+        return None;
+    }
+    let mut bytes: Zeroizing<Vec<u8>> = Zeroizing::new(vec![0u8; width]);
+    for (chunk, digit) in bytes.chunks_exact_mut(8).zip(value.iter_u64_digits()) {
+        chunk.copy_from_slice(&digit.to_le_bytes());
+    }
+    Some(Uint::from_le_slice(&bytes))
+}
+
+/// Converts a fixed-width integer into a BigUint using a buffer of the full width.
+fn to_biguint<const LIMBS: usize>(value: &Uint<LIMBS>) -> BigUint {
+    let mut bytes: Zeroizing<Vec<u8>> = Zeroizing::new(vec![0u8; Limb::BYTES * LIMBS]);
+    for (chunk, limb) in bytes.chunks_exact_mut(Limb::BYTES).zip(value.as_limbs()) {
+        chunk.copy_from_slice(&limb.0.to_le_bytes());
+    }
+    BigUint::from_bytes_le(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constant_time::ConstantTime, helper::Helper, random::Random, types::ModuloOperations,
+    };
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn it_should_compute_operations_tiny() {
+        let (params, _, _) = Helper::setup_tiny_system();
+        let p = &params.p;
+
+        for a in 0..10u32 {
+            for b in 0..10u32 {
+                let (x, y) = (BigUint::from(a), BigUint::from(b));
+                let expected = x.modpow(&y, p);
+                assert_eq!(ConstantTime::modpow(&x, &y, p), Some(expected));
+                assert_eq!(ConstantTime::modmul(&x, &y, p), Some(&x * &y % p));
+                assert_eq!(ConstantTime::modadd(&x, &y, p), Some((&x + &y) % p));
+                let difference = (&x % p + p - &y % p) % p;
+                assert_eq!(ConstantTime::modsub(&x, &y, p), Some(difference));
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_compute_operations() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, sk, _) = Helper::setup_lg_system();
+        let (p, q) = (&params.p, &params.q());
+
+        for _ in 0..5 {
+            let r = Random::get_random_less_than(q, &mut rng);
+            let expected = params.g.modpow(&r, p);
+            assert_eq!(ConstantTime::modpow(&params.g, &r, p), Some(expected));
+            assert_eq!(
+                ConstantTime::modmul(&r, &sk.x.expose(), q),
+                Some(&r * &sk.x.expose() % q)
+            );
+            assert_eq!(
+                ConstantTime::modadd(&r, &sk.x.expose(), q),
+                Some((&r + &sk.x.expose()) % q)
+            );
+            assert_eq!(
+                ConstantTime::modsub(&r, &sk.x.expose(), q),
+                Some((&r + q - &sk.x.expose()) % q)
+            );
+        }
+        assert_eq!(
+            ConstantTime::modpow(&params.g, &BigUint::zero(), p),
+            Some(BigUint::one())
+        );
+    }
+
+    #[test]
+    fn it_should_support_all_parameter_sizes() {
+        let setups = vec![
+            Helper::setup_tiny_system(),
+            Helper::setup_sm_system(),
+            Helper::setup_256bit_system(),
+            Helper::setup_512bit_system(),
+            Helper::setup_md_system(),
+            Helper::setup_lg_system(),
+            Helper::setup_xl_system(),
+        ];
+        for (params, sk, _) in setups {
+            let (p, q) = (&params.p, &params.q());
+            assert!(ConstantTime::supports(p));
+            assert!(ConstantTime::supports(q));
+
+            // the secret operations take the constant-time path (they panic otherwise)
+            let r = q - BigUint::one();
+            let expected = params.g.modpow(&r, p);
+            assert_eq!(
+                ConstantTime::modpow(&params.g, &r, p),
+                Some(expected.clone())
+            );
+            assert_eq!(params.g.modpow_secret(&r, p), expected);
+            assert_eq!(r.modmul_secret(&sk.x.expose(), q), &r * &sk.x.expose() % q);
+            assert_eq!(
+                r.modadd_secret(&sk.x.expose(), q),
+                (&r + &sk.x.expose()) % q
+            );
+            assert_eq!(
+                r.modsub_secret(&sk.x.expose(), q),
+                (&r + q - &sk.x.expose()) % q
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the constant-time backend does not support the modulus")]
+    fn it_should_not_fall_back_to_variable_time() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let even = &params.p + BigUint::one();
+        BigUint::from(2u32).modpow_secret(&BigUint::from(3u32), &even);
+    }
+
+    #[test]
+    fn it_should_not_support_even_or_large_moduli() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let two = BigUint::from(2u32);
+        let even = &params.p + BigUint::one();
+        assert!(!ConstantTime::supports(&even));
+        assert_eq!(ConstantTime::modpow(&two, &two, &even), None);
+
+        let large = (BigUint::one() << 4097) + BigUint::one();
+        assert_eq!(ConstantTime::modmul(&two, &two, &large), None);
+
+        // the exponent has to fit the width of the modulus
+        let exponent = BigUint::one() << 128;
+        assert_eq!(ConstantTime::modpow(&two, &exponent, &params.p), None);
+    }
+}
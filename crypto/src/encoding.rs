@@ -0,0 +1,261 @@
+use alloc::{string::String, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Marks the end of an encoded message, all following bytes are zero.
+const PADDING_MARKER: u8 = 0x80;
+
+/// Encodes arbitrary byte strings into quadratic residues mod p
+/// such that they can be encrypted (without exponential encoding) using `ElGamal::encrypt`.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Encoding;
+
+impl Encoding {
+    /// Returns the number of message bytes that fit into a single group element.
+    /// A chunk of k bytes is mapped to an integer in [1, 256^k] which must not exceed q.
+    ///
+    /// ## Arguments
+    ///
+    /// * `p` - The group modulus p (BigUint)
+    pub fn chunk_size(p: &BigUint) -> usize {
+        let q: BigUint = (p - 1u32) >> 1;
+        let chunk_size = (q.bits() as usize - 1) / 8;
+        assert!(chunk_size > 0, "the group is too small to encode messages!");
+        chunk_size
+    }
+
+    /// Returns the number of group elements required to encode a message of `length` bytes.
+    /// The message is always followed by at least one byte of padding.
+    ///
+    /// ## Arguments
+    ///
+    /// * `length` - The length of the message in bytes
+    /// * `p`      - The group modulus p (BigUint)
+    pub fn nr_of_elements(length: usize, p: &BigUint) -> usize {
+        let chunk_size = Self::chunk_size(p);
+        (length + chunk_size) / chunk_size
+    }
+
+    /// Encodes a message into the minimal number of quadratic residues mod p.
+    ///
+    /// ## Arguments
+    ///
+    /// * `message` - The message (arbitrary bytes)
+    /// * `p`       - The group modulus p (BigUint)
+    pub fn encode_bytes(message: &[u8], p: &BigUint) -> Vec<BigUint> {
+        let nr_of_elements = Self::nr_of_elements(message.len(), p);
+        Self::encode_bytes_padded(message, nr_of_elements, p)
+            .expect("the minimal number of elements always fits the message")
+    }
+
+    /// Encodes a message into exactly `nr_of_elements` quadratic residues mod p.
+    /// Padding the message to a fixed number of elements hides its length.
+    /// Returns `None` if the message does not fit.
+    ///
+    /// ## Arguments
+    ///
+    /// * `message`        - The message (arbitrary bytes)
+    /// * `nr_of_elements` - The number of group elements to produce
+    /// * `p`              - The group modulus p (BigUint)
+    pub fn encode_bytes_padded(
+        message: &[u8],
+        nr_of_elements: usize,
+        p: &BigUint,
+    ) -> Option<Vec<BigUint>> {
+        if Self::nr_of_elements(message.len(), p) > nr_of_elements {
+            return None;
+        }
+        let chunk_size = Self::chunk_size(p);
+        let q: BigUint = (p - 1u32) >> 1;
+
+        // padding: message || 0x80 || 0x00 ... 0x00
+        let mut padded: Vec<u8> = Vec::with_capacity(nr_of_elements * chunk_size);
+        padded.extend_from_slice(message);
+        padded.push(PADDING_MARKER);
+        padded.resize(nr_of_elements * chunk_size, 0u8);
+
+        let encoded = padded
+            .chunks(chunk_size)
+            .map(|chunk| {
+                // shift by one to avoid zero: x in [1, 256^k] <= q
+                let x: BigUint = BigUint::from_bytes_be(chunk) + 1u32;
+
+                // either x or -x = p - x is a quadratic residue (since p = 2q + 1 and q is odd)
+                if x.modpow(&q, p) == BigUint::one() {
+                    x
+                } else {
+                    p - x
+                }
+            })
+            .collect();
+        Some(encoded)
+    }
+
+    /// Decodes quadratic residues mod p back into the original message.
+    /// Returns `None` if the elements are not a valid encoding.
+    ///
+    /// ## Arguments
+    ///
+    /// * `encoded` - The encoded message (group elements)
+    /// * `p`       - The group modulus p (BigUint)
+    pub fn decode_bytes(encoded: &[BigUint], p: &BigUint) -> Option<Vec<u8>> {
+        let chunk_size = Self::chunk_size(p);
+        let q: BigUint = (p - 1u32) >> 1;
+        let max_chunk: BigUint = BigUint::one() << (8 * chunk_size);
+
+        let mut padded: Vec<u8> = Vec::with_capacity(encoded.len() * chunk_size);
+        for y in encoded.iter() {
+            if y.is_zero() || y >= p {
+                return None;
+            }
+            // undo the mapping to a quadratic residue: x in [1, q]
+            let x: BigUint = if *y <= q { y.clone() } else { p - y };
+            if x > max_chunk {
+                return None;
+            }
+
+            // undo the shift by one and restore leading zero bytes
+            let chunk: BigUint = x - 1u32;
+            let bytes: Vec<u8> = if chunk.is_zero() {
+                Vec::new()
+            } else {
+                chunk.to_bytes_be()
+            };
+            padded.resize(padded.len() + chunk_size - bytes.len(), 0u8);
+            padded.extend_from_slice(&bytes);
+        }
+
+        // remove the padding: trailing zeros + the padding marker
+        while padded.last() == Some(&0u8) {
+            padded.pop();
+        }
+        match padded.pop() {
+            Some(PADDING_MARKER) => Some(padded),
+            _ => None,
+        }
+    }
+
+    /// Encodes a UTF-8 string into the minimal number of quadratic residues mod p.
+    ///
+    /// ## Arguments
+    ///
+    /// * `message` - The message (UTF-8 string)
+    /// * `p`       - The group modulus p (BigUint)
+    pub fn encode_str(message: &str, p: &BigUint) -> Vec<BigUint> {
+        Self::encode_bytes(message.as_bytes(), p)
+    }
+
+    /// Decodes quadratic residues mod p back into a UTF-8 string.
+    /// Returns `None` if the elements are not a valid encoding of a UTF-8 string.
+    ///
+    /// ## Arguments
+    ///
+    /// * `encoded` - The encoded message (group elements)
+    /// * `p`       - The group modulus p (BigUint)
+    pub fn decode_str(encoded: &[BigUint], p: &BigUint) -> Option<String> {
+        let bytes = Self::decode_bytes(encoded, p)?;
+        String::from_utf8(bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{encoding::Encoding, encryption::ElGamal, helper::Helper, random::Random};
+    use alloc::vec::Vec;
+    use num_bigint::BigUint;
+    use num_traits::One;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn it_should_compute_the_chunk_size() {
+        // 48bit p -> 47bit q -> 5 bytes per element
+        let (params, _, _) = Helper::setup_sm_system();
+        assert_eq!(Encoding::chunk_size(&params.p), 5);
+        assert_eq!(Encoding::nr_of_elements(0, &params.p), 1);
+        assert_eq!(Encoding::nr_of_elements(4, &params.p), 1);
+        assert_eq!(Encoding::nr_of_elements(5, &params.p), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "the group is too small to encode messages!")]
+    fn it_should_panic_for_a_tiny_group() {
+        let (params, _, _) = Helper::setup_tiny_system();
+        Encoding::chunk_size(&params.p);
+    }
+
+    #[test]
+    fn it_should_encode_and_decode_a_string() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let q = params.q();
+
+        for message in ["", "a", "Provotum", "Grüezi mitenand! 🗳", "\0\0\0\0\0\0"].iter() {
+            let encoded = Encoding::encode_str(message, &params.p);
+            assert_eq!(
+                encoded.len(),
+                Encoding::nr_of_elements(message.len(), &params.p)
+            );
+
+            // every element must be a quadratic residue
+            for element in encoded.iter() {
+                assert_eq!(element.modpow(&q, &params.p), BigUint::one());
+            }
+
+            let decoded = Encoding::decode_str(&encoded, &params.p).unwrap();
+            assert_eq!(decoded, *message);
+        }
+    }
+
+    #[test]
+    fn it_should_encode_to_a_fixed_number_of_elements() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let message = b"write-in";
+
+        let encoded = Encoding::encode_bytes_padded(message, 4, &params.p).unwrap();
+        assert_eq!(encoded.len(), 4);
+        let decoded = Encoding::decode_bytes(&encoded, &params.p).unwrap();
+        assert_eq!(decoded, message.to_vec());
+
+        // the message does not fit into a single element
+        assert!(Encoding::encode_bytes_padded(message, 1, &params.p).is_none());
+    }
+
+    #[test]
+    fn it_should_reject_an_invalid_encoding() {
+        let (params, _, _) = Helper::setup_sm_system();
+
+        // no padding marker
+        let without_padding: Vec<BigUint> = vec![BigUint::from(0x6162636465u64) + 1u32];
+        assert!(Encoding::decode_bytes(&without_padding, &params.p).is_none());
+
+        // elements outside the group
+        assert!(Encoding::decode_bytes(&[&params.p + 1u32], &params.p).is_none());
+        assert!(Encoding::decode_bytes(&[BigUint::from(0u32)], &params.p).is_none());
+
+        // invalid utf-8
+        let encoded_bytes = Encoding::encode_bytes(&[0xff, 0xfe], &params.p);
+        assert!(Encoding::decode_str(&encoded_bytes, &params.p).is_none());
+        assert!(Encoding::decode_bytes(&encoded_bytes, &params.p).is_some());
+    }
+
+    #[test]
+    fn it_should_encrypt_and_decrypt_an_encoded_string() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let q = params.q();
+        let message = "a free-text write-in answer";
+
+        let encoded = Encoding::encode_str(message, &params.p);
+        assert!(encoded.len() > 1);
+
+        let decrypted: Vec<BigUint> = encoded
+            .iter()
+            .map(|m| {
+                let r = Random::get_random_less_than(&q, &mut rng);
+                let cipher = ElGamal::encrypt(m, &r, &pk);
+                ElGamal::decrypt(&cipher, &sk)
+            })
+            .collect();
+        let decoded = Encoding::decode_str(&decrypted, &params.p).unwrap();
+        assert_eq!(decoded, message);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::types::{Cipher, ModuloOperations, PrivateKey, PublicKey};
+use crate::{
+    fixed_base::FixedBaseTables,
+    parallel,
+    types::{Cipher, ModuloOperations, PrivateKey, PublicKey, Secret},
+};
 use alloc::vec::Vec;
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
@@ -16,23 +20,50 @@ impl ElGamal {
     /// * `r`  - The random number used to encrypt_encode the vote
     /// * `pk` - The public key used to encrypt_encode the vote
     pub fn encrypt_encode(m: &BigUint, r: &BigUint, pk: &PublicKey) -> Cipher {
+        // use the cached tables of the public key (std only, see `FixedBaseTables::cached`)
+        if let Some(tables) = FixedBaseTables::cached(pk) {
+            return Self::encrypt_encode_with(m, r, pk, &tables);
+        }
         let g = &pk.params.g;
         let p = &pk.params.p;
         let h = &pk.h;
 
         // a = g^r
-        let a = g.modpow(r, p);
+        let a = g.modpow_secret(r, p);
 
         // encode the message: g^m (exponential elgamal)
         let enc_m = ElGamal::encode_message(m, g, p);
 
         // b = h^r * g^m
-        let h_pow_r = h.modpow(r, p);
+        let h_pow_r = h.modpow_secret(r, p);
         let b = h_pow_r.modmul(&enc_m, p);
 
         Cipher { a, b }
     }
 
+    /// Same as `encrypt_encode` but uses the precomputed tables of the public key.
+    ///
+    /// ## Arguments
+    ///
+    /// * `m`      - The message (BigUint)
+    /// * `r`      - The random number used to encrypt_encode the vote
+    /// * `pk`     - The public key used to encrypt_encode the vote
+    /// * `tables` - The fixed-base tables of g and h (see `FixedBaseTables::from_public_key`)
+    pub fn encrypt_encode_with(
+        m: &BigUint,
+        r: &BigUint,
+        pk: &PublicKey,
+        tables: &FixedBaseTables,
+    ) -> Cipher {
+        let p = &pk.params.p;
+
+        // a = g^r, b = h^r * g^m
+        let a = tables.g.pow(r);
+        let b = tables.h.pow(r).modmul(&tables.g.pow(m), p);
+
+        Cipher { a, b }
+    }
+
     /// Returns an ElGamal Encryption of a message.
     /// NOTE! No message encoding done! If message encoding is required use: `encrypt_encode`
     /// - (a, b) = (g^r, pk.h^r * m)
@@ -43,6 +74,10 @@ impl ElGamal {
     /// * `r`  - The random number used to encrypt the vote
     /// * `pk` - The public key used to encrypt the vote
     pub fn encrypt(m: &BigUint, r: &BigUint, pk: &PublicKey) -> Cipher {
+        // use the cached tables of the public key (std only, see `FixedBaseTables::cached`)
+        if let Some(tables) = FixedBaseTables::cached(pk) {
+            return Self::encrypt_with(m, r, pk, &tables);
+        }
         let g = &pk.params.g;
         let p = &pk.params.p;
         let q = &pk.params.q();
@@ -53,15 +88,86 @@ impl ElGamal {
         assert!(m.modpow(q, p) == BigUint::one());
 
         // a = g^r
-        let a = g.modpow(r, p);
+        let a = g.modpow_secret(r, p);
 
         // b = h^r * m
-        let h_pow_r = h.modpow(r, p);
+        let h_pow_r = h.modpow_secret(r, p);
         let b = h_pow_r.modmul(m, p);
 
         Cipher { a, b }
     }
 
+    /// Same as `encrypt` but uses the precomputed tables of the public key.
+    ///
+    /// ## Arguments
+    ///
+    /// * `m`      - The message (BigUint)
+    /// * `r`      - The random number used to encrypt the vote
+    /// * `pk`     - The public key used to encrypt the vote
+    /// * `tables` - The fixed-base tables of g and h (see `FixedBaseTables::from_public_key`)
+    pub fn encrypt_with(
+        m: &BigUint,
+        r: &BigUint,
+        pk: &PublicKey,
+        tables: &FixedBaseTables,
+    ) -> Cipher {
+        let p = &pk.params.p;
+        let q = &pk.params.q();
+
+        // perform quadratic residue check: m^q mod p == 1
+        // to ensure DDH is given
+        assert!(m.modpow(q, p) == BigUint::one());
+
+        // a = g^r, b = h^r * m
+        let a = tables.g.pow(r);
+        let b = tables.h.pow(r).modmul(m, p);
+
+        Cipher { a, b }
+    }
+
+    /// Returns true if the cipher is the encryption (`encrypt_encode`) of the message using the random number.
+    /// Used to audit a ballot (Benaloh challenge) after the voter revealed the message and the random number.
+    /// - (a, b) == (g^r, pk.h^r * g^m)
+    ///
+    /// ## Arguments
+    ///
+    /// * `cipher` - The ElGamal Encryption to audit (a: BigUint, b: BigUint)
+    /// * `m`      - The revealed message (BigUint)
+    /// * `r`      - The revealed random number (r ∈ Zq)
+    /// * `pk`     - The public key used to encrypt the vote
+    pub fn verify_encryption_encode(
+        cipher: &Cipher,
+        m: &BigUint,
+        r: &BigUint,
+        pk: &PublicKey,
+    ) -> bool {
+        if *r >= pk.params.q() {
+            return false;
+        }
+        Self::encrypt_encode(m, r, pk) == *cipher
+    }
+
+    /// Returns true if the cipher is the encryption (`encrypt`) of the message using the random number.
+    /// Used to audit a ballot (Benaloh challenge) after the voter revealed the message and the random number.
+    /// - (a, b) == (g^r, pk.h^r * m)
+    ///
+    /// ## Arguments
+    ///
+    /// * `cipher` - The ElGamal Encryption to audit (a: BigUint, b: BigUint)
+    /// * `m`      - The revealed message (BigUint), a quadratic residue mod p
+    /// * `r`      - The revealed random number (r ∈ Zq)
+    /// * `pk`     - The public key used to encrypt the vote
+    pub fn verify_encryption(cipher: &Cipher, m: &BigUint, r: &BigUint, pk: &PublicKey) -> bool {
+        let p = &pk.params.p;
+        let q = &pk.params.q();
+
+        // the message must be an element of the group, otherwise `encrypt` panics
+        if *r >= *q || m.is_zero() || m >= p || m.modpow(q, p) != BigUint::one() {
+            return false;
+        }
+        Self::encrypt(m, r, pk) == *cipher
+    }
+
     /// Returns the plaintext contained in an ElGamal Encryption.
     /// Decrypts the ciphertext and decodes the result.
     /// Important! Requires that the encryption was done using `encrypt_encode`.
@@ -78,10 +184,10 @@ impl ElGamal {
 
         let g = &sk.params.g;
         let p = &sk.params.p;
-        let x = &sk.x;
+        let x = &sk.x.expose();
 
         // a = g^r -> a^x = g^r^x
-        let s = a.modpow(x, p);
+        let s = a.modpow_secret(x, p);
 
         // compute multiplicative inverse of s
         let s_1 = s.invmod(p).expect("cannot compute mod_inverse!");
@@ -106,10 +212,10 @@ impl ElGamal {
         let b = &cipher.b;
 
         let p = &sk.params.p;
-        let x = &sk.x;
+        let x = &sk.x.expose();
 
         // a = g^r -> a^x = g^r^x
-        let s = a.modpow(x, p);
+        let s = a.modpow_secret(x, p);
 
         // compute multiplicative inverse of s
         let s_1 = s.invmod(p).expect("cannot compute mod_inverse!");
@@ -131,9 +237,9 @@ impl ElGamal {
     pub fn partial_decrypt_a(cipher: &Cipher, sk: &PrivateKey) -> BigUint {
         let a = &cipher.a;
         let p = &sk.params.p;
-        let x = &sk.x;
+        let x = &sk.x.expose();
 
-        a.modpow(x, p)
+        a.modpow_secret(x, p)
     }
 
     /// Similar to GetVotes Algorithm 8.53 (CHVoteSpec 3.2)
@@ -288,15 +394,40 @@ impl ElGamal {
     /// * `r`      - The random number used to re-encrypt_encode the vote    
     /// * `pk`     - The public key used to re-encrypt_encode the vote
     pub fn re_encrypt(cipher: &Cipher, r: &BigUint, pk: &PublicKey) -> Cipher {
+        // use the cached tables of the public key (std only, see `FixedBaseTables::cached`)
+        if let Some(tables) = FixedBaseTables::cached(pk) {
+            return Self::re_encrypt_with(cipher, r, pk, &tables);
+        }
         let p = &pk.params.p;
-        let a_ = pk.params.g.modpow(r, p);
-        let b_ = pk.h.modpow(r, p);
+        let a_ = pk.params.g.modpow_secret(r, p);
+        let b_ = pk.h.modpow_secret(r, p);
         Cipher {
             a: cipher.a.modmul(&a_, p),
             b: cipher.b.modmul(&b_, p),
         }
     }
 
+    /// Same as `re_encrypt` but uses the precomputed tables of the public key.
+    ///
+    /// ## Arguments
+    ///
+    /// * `cipher` - An ElGamal Encryption { a: BigUint, b: BigUint }
+    /// * `r`      - The random number used to re-encrypt_encode the vote
+    /// * `pk`     - The public key used to re-encrypt_encode the vote
+    /// * `tables` - The fixed-base tables of g and h (see `FixedBaseTables::from_public_key`)
+    pub fn re_encrypt_with(
+        cipher: &Cipher,
+        r: &BigUint,
+        pk: &PublicKey,
+        tables: &FixedBaseTables,
+    ) -> Cipher {
+        let p = &pk.params.p;
+        Cipher {
+            a: cipher.a.modmul(&tables.g.pow(r), p),
+            b: cipher.b.modmul(&tables.h.pow(r), p),
+        }
+    }
+
     /// Returns an ElGamal re-encryption of a message
     /// - message:      (a, b)      = (g^r, h^r * g^m)
     /// - zero:         (a', b')    = (g^r', h^r' * g^0) = (g^r', h^r')
@@ -324,9 +455,9 @@ impl ElGamal {
     pub fn shuffle(
         encryptions: &[Cipher],
         permutation: &[usize],
-        randoms: &[BigUint],
+        randoms: &[Secret],
         pk: &PublicKey,
-    ) -> Vec<(Cipher, BigUint, usize)> {
+    ) -> Vec<(Cipher, Secret, usize)> {
         assert!(
             encryptions.len() == randoms.len(),
             "encryptions and randoms need to have the same length!"
@@ -337,19 +468,73 @@ impl ElGamal {
         );
         assert!(!encryptions.is_empty(), "vectors cannot be empty!");
 
-        // generate a permutatinon of size of the encryptions
-        let mut re_encryptions: Vec<(Cipher, BigUint, usize)> = Vec::new();
+        // the tables of g and h are shared by all re-encryptions (and cached with std)
+        let tables = FixedBaseTables::for_public_key(pk);
 
-        for entry in permutation {
+        // the re-encryptions are independent of each other (see `parallel::map`)
+        parallel::map(permutation, |entry| {
             // get the encryption and the random value at the permutation position
             let encryption = &encryptions[*entry];
             let random = &randoms[*entry];
 
             // re-encrypt_encode
-            let re_encryption = ElGamal::re_encrypt(&encryption, &random, pk);
-            re_encryptions.push((re_encryption, random.clone(), *entry));
-        }
-        re_encryptions
+            let re_encryption = ElGamal::re_encrypt_with(encryption, &random.expose(), pk, &tables);
+            (re_encryption, random.clone(), *entry)
+        })
+    }
+
+    /// Returns a shuffled (permuted & re-encrypted) list of rows of ElGamal encryptions.
+    /// All rows are permuted using the same permutation and every encryption in a row
+    /// is re-encrypted with its own random value, i.e. a row (e.g. a ballot) stays together.
+    ///
+    /// ## Arguments
+    ///
+    /// * `rows`        - A list of rows, each row has the same width w
+    /// * `permutation` - The permutation of the rows
+    /// * `randoms`     - The random values used to re-encrypt the rows (one row of w randoms per row)
+    /// * `pk`          - The public key used to re-encrypt the rows
+    pub fn shuffle_rows(
+        rows: &[Vec<Cipher>],
+        permutation: &[usize],
+        randoms: &[Vec<Secret>],
+        pk: &PublicKey,
+    ) -> Vec<(Vec<Cipher>, Vec<Secret>, usize)> {
+        assert!(
+            rows.len() == randoms.len(),
+            "rows and randoms need to have the same length!"
+        );
+        assert!(
+            rows.len() == permutation.len(),
+            "rows and permutation need to have the same length!"
+        );
+        assert!(!rows.is_empty(), "vectors cannot be empty!");
+        let width = rows[0].len();
+        assert!(width > 0, "rows cannot be empty!");
+        assert!(
+            rows.iter().all(|row| row.len() == width)
+                && randoms.iter().all(|row| row.len() == width),
+            "all rows need to have the same width!"
+        );
+
+        // the tables of g and h are shared by all re-encryptions (and cached with std)
+        let tables = FixedBaseTables::for_public_key(pk);
+
+        // the rows are re-encrypted independently of each other (see `parallel::map`)
+        parallel::map(permutation, |entry| {
+            // get the row and the random values at the permutation position
+            let row = &rows[*entry];
+            let row_randoms = &randoms[*entry];
+
+            // re-encrypt every encryption of the row
+            let re_encryption = row
+                .iter()
+                .zip(row_randoms.iter())
+                .map(|(cipher, random)| {
+                    ElGamal::re_encrypt_with(cipher, &random.expose(), pk, &tables)
+                })
+                .collect::<Vec<Cipher>>();
+            (re_encryption, row_randoms.clone(), *entry)
+        })
     }
 }
 
@@ -357,15 +542,17 @@ impl ElGamal {
 mod tests {
     use crate::{
         encryption::ElGamal,
+        fixed_base::FixedBaseTables,
         helper::Helper,
         random::Random,
         types::Cipher,
         types::ElGamalParams,
-        types::{ModuloOperations, PublicKey},
+        types::{ModuloOperations, PublicKey, Secret},
     };
     use alloc::vec::Vec;
     use num_bigint::BigUint;
     use num_traits::{One, Zero};
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn it_should_encode_a_message() {
@@ -381,6 +568,26 @@ mod tests {
         assert_eq!(encoded_message, BigUint::from(1u32));
     }
 
+    #[test]
+    fn it_should_encrypt_with_fixed_base_tables() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, pk) = Helper::setup_sm_system();
+        let tables = FixedBaseTables::from_public_key(&pk);
+        let q = params.q();
+        let r = Random::get_random_less_than(&q, &mut rng);
+        let message = BigUint::from(7u32);
+        let qr = BigUint::from(4u32);
+
+        let cipher = ElGamal::encrypt_encode(&message, &r, &pk);
+        let with_tables = ElGamal::encrypt_encode_with(&message, &r, &pk, &tables);
+        assert_eq!(cipher, with_tables);
+        let cipher = ElGamal::encrypt(&qr, &r, &pk);
+        assert_eq!(cipher, ElGamal::encrypt_with(&qr, &r, &pk, &tables));
+        let re_encryption = ElGamal::re_encrypt(&cipher, &r, &pk);
+        let with_tables = ElGamal::re_encrypt_with(&cipher, &r, &pk, &tables);
+        assert_eq!(re_encryption, with_tables);
+    }
+
     #[test]
     fn it_should_decode_zero() {
         let (params, _, _) = Helper::setup_sm_system();
@@ -475,6 +682,60 @@ mod tests {
         assert_eq!(encrypted_message.b, BigUint::from(2u32));
     }
 
+    #[test]
+    fn it_should_verify_a_revealed_encryption_encoded() {
+        let (_, _, pk) = Helper::setup_sm_system();
+        let message = BigUint::from(3u32);
+        let r = BigUint::from(5u32);
+        let cipher = ElGamal::encrypt_encode(&message, &r, &pk);
+
+        assert!(ElGamal::verify_encryption_encode(
+            &cipher, &message, &r, &pk
+        ));
+
+        // another message or another random number
+        let other = BigUint::from(4u32);
+        assert!(!ElGamal::verify_encryption_encode(&cipher, &other, &r, &pk));
+        assert!(!ElGamal::verify_encryption_encode(
+            &cipher, &message, &other, &pk
+        ));
+
+        // the random number must be reduced modulo q
+        let unreduced = &r + pk.params.q();
+        assert!(!ElGamal::verify_encryption_encode(
+            &cipher, &message, &unreduced, &pk
+        ));
+    }
+
+    #[test]
+    fn it_should_verify_a_revealed_encryption() {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let message = BigUint::from(4u32);
+        let r = BigUint::from(5u32);
+        let cipher = ElGamal::encrypt(&message, &r, &pk);
+
+        assert!(ElGamal::verify_encryption(&cipher, &message, &r, &pk));
+
+        // another message or another random number
+        let other_message = BigUint::from(9u32);
+        let other_r = BigUint::from(6u32);
+        assert!(!ElGamal::verify_encryption(
+            &cipher,
+            &other_message,
+            &r,
+            &pk
+        ));
+        assert!(!ElGamal::verify_encryption(
+            &cipher, &message, &other_r, &pk
+        ));
+
+        // messages which are not quadratic residues are rejected instead of panicking
+        let non_residue = params.p - BigUint::one();
+        assert!(!ElGamal::verify_encryption(&cipher, &non_residue, &r, &pk));
+        let zero = BigUint::zero();
+        assert!(!ElGamal::verify_encryption(&cipher, &zero, &r, &pk));
+    }
+
     #[test]
     fn it_should_encrypt_decrypt_decode_two() {
         let (_, sk, pk) = Helper::setup_sm_system();
@@ -631,6 +892,7 @@ mod tests {
 
     #[test]
     fn it_should_add_many_and_result_equals_five_encoded() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
 
         let q = params.q();
@@ -640,19 +902,19 @@ mod tests {
 
         // start with an encryption of zero
         // use a random number < q
-        let r = Random::get_random_less_than(&q);
+        let r = Random::get_random_less_than(&q, &mut rng);
         let mut base = ElGamal::encrypt_encode(&zero, &r, &pk);
 
         // add five encryptions of one
         for _ in 0..5 {
-            let r = Random::get_random_less_than(&q);
+            let r = Random::get_random_less_than(&q, &mut rng);
             let encryption_of_one = ElGamal::encrypt_encode(&one, &r, &pk);
             base = ElGamal::homomorphic_addition(&base, &encryption_of_one, &params.p);
         }
 
         // add five encryptions of zero
         for _ in 0..5 {
-            let r = Random::get_random_less_than(&q);
+            let r = Random::get_random_less_than(&q, &mut rng);
             let encryption_of_zero = ElGamal::encrypt_encode(&zero, &r, &pk);
             base = ElGamal::homomorphic_addition(&base, &encryption_of_zero, &params.p);
         }
@@ -664,17 +926,18 @@ mod tests {
 
     #[test]
     fn it_should_re_encrypt_five_encoded() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
 
         let q = params.q();
         let five = BigUint::from(5u32);
 
         // use a random number < q
-        let r = Random::get_random_less_than(&q);
+        let r = Random::get_random_less_than(&q, &mut rng);
         let encrypted_five = ElGamal::encrypt_encode(&five, &r, &pk);
 
         // re-encryption + check that encryption != re-encryption
-        let r_ = Random::get_random_less_than(&q);
+        let r_ = Random::get_random_less_than(&q, &mut rng);
         let re_encrypted_five = ElGamal::re_encrypt(&encrypted_five, &r_, &pk);
         assert!(encrypted_five != re_encrypted_five);
 
@@ -685,17 +948,18 @@ mod tests {
 
     #[test]
     fn it_should_re_encrypt_five() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
 
         let q = params.q();
         let five = BigUint::from(5u32);
 
         // use a random number < q
-        let r = Random::get_random_less_than(&q);
+        let r = Random::get_random_less_than(&q, &mut rng);
         let encrypted_five = ElGamal::encrypt(&five, &r, &pk);
 
         // re-encryption + check that encryption != re-encryption
-        let r_ = Random::get_random_less_than(&q);
+        let r_ = Random::get_random_less_than(&q, &mut rng);
         let re_encrypted_five = ElGamal::re_encrypt(&encrypted_five, &r_, &pk);
         assert!(encrypted_five != re_encrypted_five);
 
@@ -706,15 +970,16 @@ mod tests {
 
     #[test]
     fn it_should_re_encrypt_five_by_addition() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
 
         let q = params.q();
         let five = BigUint::from(5u32);
 
         // use a random number < q
-        let r = Random::get_random_less_than(&q);
+        let r = Random::get_random_less_than(&q, &mut rng);
         let encrypted_five = ElGamal::encrypt_encode(&five, &r, &pk);
-        let r_ = Random::get_random_less_than(&q);
+        let r_ = Random::get_random_less_than(&q, &mut rng);
 
         // homomorphic addition with zero: 5 + 0 = 5 + check that encryption != re-encryption
         // only works if messages are encoded i.e. g^m
@@ -728,17 +993,18 @@ mod tests {
 
     #[test]
     fn it_should_show_that_both_re_encryptions_are_equal_encoded() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
 
         let q = params.q();
         let five = BigUint::from(5u32);
 
         // use a random number < q
-        let r = Random::get_random_less_than(&q);
+        let r = Random::get_random_less_than(&q, &mut rng);
         let encrypted_five = ElGamal::encrypt_encode(&five, &r, &pk);
 
         // option one: homomorphic addition with zero: 5 + 0 = 5
-        let r_ = Random::get_random_less_than(&q);
+        let r_ = Random::get_random_less_than(&q, &mut rng);
 
         // only works if messages are encoded i.e. g^m
         let re_encrypted_addition = ElGamal::re_encrypt_via_addition(&encrypted_five, &r_, &pk);
@@ -760,22 +1026,24 @@ mod tests {
     #[test]
     #[should_panic(expected = "encryptions and randoms need to have the same length!")]
     fn shuffle_vectors_encryptions_randoms_different_size_should_panic() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (_, _, pk) = Helper::setup_md_system();
         let encryptions = vec![];
-        let randoms = vec![BigUint::one()];
+        let randoms = vec![Secret::new(BigUint::one())];
         let size = 1;
-        let permutation = Random::generate_permutation(&size);
+        let permutation = Random::generate_permutation(&size, &mut rng);
         ElGamal::shuffle(&encryptions, &permutation, &randoms, &pk);
     }
 
     #[test]
     #[should_panic(expected = "encryptions and permutation need to have the same length!")]
     fn shuffle_vectors_encryptions_permutations_different_size_should_panic() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (_, _, pk) = Helper::setup_md_system();
         let encryptions = vec![];
         let randoms = vec![];
         let size = 1;
-        let permutation = Random::generate_permutation(&size);
+        let permutation = Random::generate_permutation(&size, &mut rng);
         ElGamal::shuffle(&encryptions, &permutation, &randoms, &pk);
     }
 
@@ -791,6 +1059,7 @@ mod tests {
 
     #[test]
     fn it_should_shuffle_a_list_of_encrypted_votes_encoded() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
         let q = params.q();
         let zero = BigUint::zero();
@@ -798,18 +1067,18 @@ mod tests {
         let two = BigUint::from(2u32);
 
         // get three encrypted values: 0, 1, 2
-        let encryptions = Random::generate_random_encryptions_encoded(&pk, &q, 3);
+        let encryptions = Random::generate_random_encryptions_encoded(&pk, &q, 3, &mut rng);
 
         // create three random values < q
         let randoms = [
-            Random::get_random_less_than(&q),
-            Random::get_random_less_than(&q),
-            Random::get_random_less_than(&q),
+            Secret::new(Random::get_random_less_than(&q, &mut rng)),
+            Secret::new(Random::get_random_less_than(&q, &mut rng)),
+            Secret::new(Random::get_random_less_than(&q, &mut rng)),
         ];
 
         // create a permutation of size 3
         let size = encryptions.len();
-        let permutation = Random::generate_permutation(&size);
+        let permutation = Random::generate_permutation(&size, &mut rng);
 
         // shuffle (permute + re-encrypt_encode) the encryptions
         let shuffle = ElGamal::shuffle(&encryptions, &permutation, &randoms, &pk);
@@ -822,7 +1091,7 @@ mod tests {
         let randoms = shuffle
             .iter()
             .map(|item| item.1.clone())
-            .collect::<Vec<BigUint>>();
+            .collect::<Vec<Secret>>();
         let permutation = shuffle.iter().map(|item| item.2).collect::<Vec<usize>>();
         assert!(shuffled_encryptions.len() == 3usize);
         assert!(randoms.len() == 3usize);
@@ -848,6 +1117,7 @@ mod tests {
 
     #[test]
     fn it_should_shuffle_a_list_of_encrypted_votes() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
         let q = params.q();
         let one = BigUint::one();
@@ -855,18 +1125,18 @@ mod tests {
         let four = BigUint::from(4u32);
 
         // get three encrypted values: 1, 3, 5
-        let encryptions = Random::generate_random_encryptions(&pk, &q, 3);
+        let encryptions = Random::generate_random_encryptions(&pk, &q, 3, &mut rng);
 
         // create three random values < q
         let randoms = [
-            Random::get_random_less_than(&q),
-            Random::get_random_less_than(&q),
-            Random::get_random_less_than(&q),
+            Secret::new(Random::get_random_less_than(&q, &mut rng)),
+            Secret::new(Random::get_random_less_than(&q, &mut rng)),
+            Secret::new(Random::get_random_less_than(&q, &mut rng)),
         ];
 
         // create a permutation of size 3
         let size = encryptions.len();
-        let permutation = Random::generate_permutation(&size);
+        let permutation = Random::generate_permutation(&size, &mut rng);
 
         // shuffle (permute + re-encrypt_encode) the encryptions
         let shuffle = ElGamal::shuffle(&encryptions, &permutation, &randoms, &pk);
@@ -879,7 +1149,7 @@ mod tests {
         let randoms = shuffle
             .iter()
             .map(|item| item.1.clone())
-            .collect::<Vec<BigUint>>();
+            .collect::<Vec<Secret>>();
         let permutation = shuffle.iter().map(|item| item.2).collect::<Vec<usize>>();
         assert!(shuffled_encryptions.len() == 3usize);
         assert!(randoms.len() == 3usize);
@@ -904,14 +1174,83 @@ mod tests {
         assert!(decryptions.iter().any(|value| value.clone() == four));
     }
 
+    #[test]
+    fn it_should_shuffle_rows_of_encrypted_votes() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, sk, pk) = Helper::setup_md_system();
+        let q = params.q();
+
+        // three ballots with two answers each: (1, 2), (3, 4), (5, 6)
+        let messages = [[1u32, 2u32], [3u32, 4u32], [5u32, 6u32]];
+        let rows = messages
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|m| {
+                        let r = Random::get_random_less_than(&q, &mut rng);
+                        ElGamal::encrypt_encode(&BigUint::from(*m), &r, &pk)
+                    })
+                    .collect::<Vec<Cipher>>()
+            })
+            .collect::<Vec<Vec<Cipher>>>();
+        let randoms = (0..rows.len())
+            .map(|_| {
+                vec![
+                    Secret::new(Random::get_random_less_than(&q, &mut rng)),
+                    Secret::new(Random::get_random_less_than(&q, &mut rng)),
+                ]
+            })
+            .collect::<Vec<Vec<Secret>>>();
+
+        // shuffle (permute + re-encrypt) the rows
+        let permutation = Random::generate_permutation(&rows.len(), &mut rng);
+        let shuffle = ElGamal::shuffle_rows(&rows, &permutation, &randoms, &pk);
+        assert_eq!(shuffle.len(), 3usize);
+
+        for (shuffled_row, row_randoms, position) in shuffle {
+            assert_eq!(row_randoms, randoms[position]);
+            // the row is re-encrypted but the answers of a ballot stay together
+            assert!(shuffled_row
+                .iter()
+                .zip(rows[position].iter())
+                .all(|(shuffled, original)| shuffled != original));
+            let decryptions = shuffled_row
+                .iter()
+                .map(|cipher| ElGamal::decrypt_decode(cipher, &sk))
+                .collect::<Vec<BigUint>>();
+            let expected = messages[position]
+                .iter()
+                .map(|m| BigUint::from(*m))
+                .collect::<Vec<BigUint>>();
+            assert_eq!(decryptions, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "all rows need to have the same width!")]
+    fn it_should_panic_shuffle_rows_different_widths() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, pk) = Helper::setup_md_system();
+        let q = params.q();
+
+        let rows = vec![
+            Random::generate_random_encryptions(&pk, &q, 2, &mut rng),
+            Random::generate_random_encryptions(&pk, &q, 1, &mut rng),
+        ];
+        let one = Secret::new(BigUint::one());
+        let randoms = vec![vec![one.clone(), one.clone()], vec![one]];
+        ElGamal::shuffle_rows(&rows, &[1, 0], &randoms, &pk);
+    }
+
     #[test]
     fn it_should_show_that_partial_decryption_works() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
         let q = params.q();
 
         // create an encrypted vote
         let five = BigUint::from(5u32);
-        let r = Random::get_random_less_than(&q);
+        let r = Random::get_random_less_than(&q, &mut rng);
         let encrypted_five = ElGamal::encrypt(&five, &r, &pk);
 
         // parital decrypte vote - part 1 (component a)
@@ -924,6 +1263,7 @@ mod tests {
 
     #[test]
     fn it_should_show_that_combined_partial_decryptions_work() {
+        let mut rng = StdRng::seed_from_u64(42);
         // create system parameters
         let params = ElGamalParams {
             // 48bit key -> sm_system
@@ -935,11 +1275,11 @@ mod tests {
         let p = &params.p;
 
         // create bob's public and private key
-        let bob_sk_x = Random::get_random_less_than(q);
+        let bob_sk_x = Random::get_random_less_than(q, &mut rng);
         let (bob_pk, bob_sk) = Helper::generate_key_pair(&params, &bob_sk_x);
 
         // create charlie's public and private key
-        let charlie_sk_x = Random::get_random_less_than(q);
+        let charlie_sk_x = Random::get_random_less_than(q, &mut rng);
         let (charlie_pk, charlie_sk) = Helper::generate_key_pair(&params, &charlie_sk_x);
 
         // create common public key
@@ -950,7 +1290,7 @@ mod tests {
 
         // create an encrypted vote using the combined public key
         let five = BigUint::from(5u32);
-        let r = Random::get_random_less_than(q);
+        let r = Random::get_random_less_than(q, &mut rng);
         let encrypted_five = ElGamal::encrypt(&five, &r, &combined_pk);
 
         // get bob's partial decryption
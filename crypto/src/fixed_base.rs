@@ -0,0 +1,293 @@
+use crate::types::{ElGamalParams, ModuloOperations, PublicKey};
+use alloc::{sync::Arc, vec, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// The number of bits of the exponent which are handled per table lookup.
+const WINDOW: usize = 4;
+
+/// The number of windows per 32-bit digit of the exponent.
+const WINDOWS_PER_DIGIT: usize = 32 / WINDOW;
+
+/// The number of keys (p, g, h) for which the tables are kept in the cache.
+#[cfg(any(feature = "std", test))]
+const CACHE_SIZE: usize = 4;
+
+#[cfg(any(feature = "std", test))]
+type CacheKey = (BigUint, BigUint, BigUint);
+
+#[cfg(any(feature = "std", test))]
+static CACHE: std::sync::RwLock<Vec<(CacheKey, Arc<FixedBaseTables>)>> =
+    std::sync::RwLock::new(Vec::new());
+
+/// A precomputed table for the exponentiation of a fixed base: base^e mod p.
+///
+/// The exponent is split into windows of 4 bits. For every window i the table contains
+/// base^(j * 2^(4i)) for all digits j = 0..15. An exponentiation is therefore reduced to
+/// a single multiplication per window, no squarings are required.
+/// Every window is processed, also the ones with the digit 0 (base^0 = 1), and the entries
+/// are stored with the width of the modulus, such that neither the sequence of operations
+/// nor the accessed memory depends on the exponent (see `pow`).
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FixedBaseTable {
+    modulus: BigUint,
+    /// the number of 32-bit digits of an entry (the width of the modulus)
+    width: usize,
+    windows: Vec<Vec<Vec<u32>>>,
+}
+
+impl FixedBaseTable {
+    /// Precomputes the table of a base for all exponents up to the bit size of the modulus.
+    ///
+    /// ## Arguments
+    ///
+    /// * `base`    - The fixed base (BigUint)
+    /// * `modulus` - The group modulus p (BigUint)
+    pub fn new(base: &BigUint, modulus: &BigUint) -> Self {
+        let bits = modulus.bits() as usize;
+        let width = modulus.to_u32_digits().len();
+        let mut windows: Vec<Vec<Vec<u32>>> = Vec::with_capacity(bits / WINDOW + 1);
+
+        // the first entry of window i: base^(2^(4i))
+        let mut first = base % modulus;
+        for _ in (0..bits).step_by(WINDOW) {
+            let mut entry = BigUint::one();
+            let mut window: Vec<Vec<u32>> = Vec::with_capacity(1 << WINDOW);
+            window.push(Self::to_digits(&entry, width));
+            for _ in 1..(1 << WINDOW) {
+                entry = entry.modmul(&first, modulus);
+                window.push(Self::to_digits(&entry, width));
+            }
+            // base^(2^(4(i+1))) = (base^(15 * 2^(4i))) * base^(2^(4i))
+            first = entry.modmul(&first, modulus);
+            windows.push(window);
+        }
+
+        FixedBaseTable {
+            modulus: modulus.clone(),
+            width,
+            windows,
+        }
+    }
+
+    /// Returns base^exponent mod p.
+    /// The exponent is usually secret (e.g. the random value of an encryption):
+    /// all windows are processed and multiplied using `modmul_secret`, independent of the
+    /// digits and the length of the exponent. The entry of a window is selected by scanning
+    /// all entries of the window (see `select`), i.e. the memory access pattern does not
+    /// depend on the digit either.
+    ///
+    /// Panics if the exponent is larger than the table (the bit size of the modulus).
+    ///
+    /// ## Arguments
+    ///
+    /// * `exponent` - The exponent (BigUint)
+    pub fn pow(&self, exponent: &BigUint) -> BigUint {
+        assert!(
+            exponent.bits() as usize <= self.windows.len() * WINDOW,
+            "the exponent is larger than the table!"
+        );
+
+        // the exponent is padded to the width of the modulus (the size of the table)
+        let mut digits: Vec<u32> = vec![0u32; self.width];
+        for (digit, value) in digits.iter_mut().zip(exponent.iter_u32_digits()) {
+            *digit = value;
+        }
+
+        let mut result = BigUint::one();
+        for (index, window) in self.windows.iter().enumerate() {
+            let shift = WINDOW * (index % WINDOWS_PER_DIGIT);
+            let digit = (digits[index / WINDOWS_PER_DIGIT] >> shift) & 0x0f;
+            result = result.modmul_secret(&self.select(window, digit), &self.modulus);
+        }
+        result
+    }
+
+    /// Returns the entry of the digit. Every entry of the window is read and masked:
+    /// the entry of the digit with all ones, all others with zeros.
+    fn select(&self, window: &[Vec<u32>], digit: u32) -> BigUint {
+        let mut selected: Vec<u32> = vec![0u32; self.width];
+        for (j, entry) in window.iter().enumerate() {
+            let mask = eq_mask(j as u32, digit);
+            for (value, entry_value) in selected.iter_mut().zip(entry.iter()) {
+                *value |= entry_value & mask;
+            }
+        }
+        BigUint::new(selected)
+    }
+
+    fn to_digits(value: &BigUint, width: usize) -> Vec<u32> {
+        let mut digits = value.to_u32_digits();
+        digits.resize(width, 0u32);
+        digits
+    }
+}
+
+/// Returns all ones if a == b and zero otherwise, without branching on the values.
+fn eq_mask(a: u32, b: u32) -> u32 {
+    let difference = a ^ b;
+    // the highest bit of (d | -d) is set for all d != 0
+    ((difference | difference.wrapping_neg()) >> 31).wrapping_sub(1)
+}
+
+/// The precomputed tables of the two fixed bases of an ElGamal setup: the generator g and
+/// either the second generator h of the parameters or the public key h.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FixedBaseTables {
+    pub g: FixedBaseTable,
+    pub h: FixedBaseTable,
+}
+
+impl FixedBaseTables {
+    /// Precomputes the tables of the generators g and h of the parameters.
+    pub fn from_params(params: &ElGamalParams) -> Self {
+        FixedBaseTables {
+            g: FixedBaseTable::new(&params.g, &params.p),
+            h: FixedBaseTable::new(&params.h, &params.p),
+        }
+    }
+
+    /// Precomputes the tables of the generator g and the public key h.
+    pub fn from_public_key(pk: &PublicKey) -> Self {
+        FixedBaseTables {
+            g: FixedBaseTable::new(&pk.params.g, &pk.params.p),
+            h: FixedBaseTable::new(&pk.h, &pk.params.p),
+        }
+    }
+
+    /// Returns the cached tables of a public key, keyed by (p, g, h).
+    /// The tables are precomputed on first use outside of the lock and a lookup only takes
+    /// a read lock, i.e. concurrent encryptions are not serialized.
+    /// The cache is only available with `std`, otherwise `None` is returned.
+    #[cfg(any(feature = "std", test))]
+    pub fn cached(pk: &PublicKey) -> Option<Arc<Self>> {
+        let key: CacheKey = (pk.params.p.clone(), pk.params.g.clone(), pk.h.clone());
+        {
+            let cache = CACHE.read().expect("the fixed-base cache is poisoned!");
+            if let Some((_, tables)) = cache.iter().find(|(cached, _)| cached == &key) {
+                return Some(tables.clone());
+            }
+        }
+
+        let tables = Arc::new(Self::from_public_key(pk));
+        let mut cache = CACHE.write().expect("the fixed-base cache is poisoned!");
+        if let Some((_, cached)) = cache.iter().find(|(cached, _)| cached == &key) {
+            // the tables have been added concurrently
+            return Some(cached.clone());
+        }
+
+        // evict the oldest key
+        if cache.len() == CACHE_SIZE {
+            cache.remove(0);
+        }
+        cache.push((key, tables.clone()));
+        Some(tables)
+    }
+
+    /// Returns the cached tables of a public key, keyed by (p, g, h).
+    /// The cache is only available with `std`, otherwise `None` is returned.
+    #[cfg(not(any(feature = "std", test)))]
+    pub fn cached(_pk: &PublicKey) -> Option<Arc<Self>> {
+        None
+    }
+
+    /// Returns the tables of a public key for a batch of operations.
+    /// The cached tables are used if available, otherwise they are precomputed.
+    pub fn for_public_key(pk: &PublicKey) -> Arc<Self> {
+        Self::cached(pk).unwrap_or_else(|| Arc::new(Self::from_public_key(pk)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixed_base::{eq_mask, FixedBaseTable, FixedBaseTables},
+        helper::Helper,
+        random::Random,
+    };
+    use alloc::sync::Arc;
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn it_should_compute_fixed_base_exponentiation_tiny() {
+        let (params, _, _) = Helper::setup_tiny_system();
+        let table = FixedBaseTable::new(&params.g, &params.p);
+
+        for e in 0..100u32 {
+            let exponent = BigUint::from(e);
+            assert_eq!(table.pow(&exponent), params.g.modpow(&exponent, &params.p));
+        }
+    }
+
+    #[test]
+    fn it_should_compute_fixed_base_exponentiation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, pk) = Helper::setup_md_system();
+        let tables = FixedBaseTables::from_public_key(&pk);
+        let q = params.q();
+
+        for _ in 0..10 {
+            let r = Random::get_random_less_than(&q, &mut rng);
+            assert_eq!(tables.g.pow(&r), params.g.modpow(&r, &params.p));
+            assert_eq!(tables.h.pow(&r), pk.h.modpow(&r, &params.p));
+        }
+        assert_eq!(tables.g.pow(&BigUint::zero()), BigUint::one());
+    }
+
+    #[test]
+    fn it_should_compute_fixed_base_exponentiation_of_params() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, _) = Helper::setup_sm_system();
+        let tables = FixedBaseTables::from_params(&params);
+        let r = Random::get_random_less_than(&params.q(), &mut rng);
+
+        assert_eq!(tables.g.pow(&r), params.g.modpow(&r, &params.p));
+        assert_eq!(tables.h.pow(&r), params.h.modpow(&r, &params.p));
+    }
+
+    #[test]
+    fn it_should_compute_exponentiation_up_to_the_table_size() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let table = FixedBaseTable::new(&params.g, &params.p);
+
+        // an exponent with all bits of the modulus set
+        let bits = params.p.bits() as usize;
+        let exponent = (BigUint::one() << bits) - BigUint::one();
+        assert_eq!(table.pow(&exponent), params.g.modpow(&exponent, &params.p));
+    }
+
+    #[test]
+    #[should_panic(expected = "the exponent is larger than the table!")]
+    fn it_should_not_compute_exponentiation_larger_than_table() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let table = FixedBaseTable::new(&params.g, &params.p);
+
+        // the exponent has more bits than the modulus
+        let exponent = &params.p * &params.p + BigUint::from(7u32);
+        table.pow(&exponent);
+    }
+
+    #[test]
+    fn it_should_select_entries_by_mask() {
+        assert_eq!(eq_mask(3, 3), u32::MAX);
+        assert_eq!(eq_mask(0, 0), u32::MAX);
+        assert_eq!(eq_mask(3, 4), 0);
+        assert_eq!(eq_mask(0, 15), 0);
+    }
+
+    #[test]
+    fn it_should_cache_the_tables_per_key() {
+        let (_, _, pk) = Helper::setup_sm_system();
+        let (_, _, other) = Helper::setup_tiny_system();
+
+        let tables = FixedBaseTables::cached(&pk).unwrap();
+        assert!(Arc::ptr_eq(&tables, &FixedBaseTables::cached(&pk).unwrap()));
+        assert!(!Arc::ptr_eq(
+            &tables,
+            &FixedBaseTables::cached(&other).unwrap()
+        ));
+        assert_eq!(*tables, FixedBaseTables::from_public_key(&pk));
+    }
+}
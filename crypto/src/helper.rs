@@ -1,6 +1,10 @@
-use crate::types::{BigT, BigY, Cipher, ElGamalParams, PrivateKey, PublicKey};
-use alloc::vec::Vec;
+use crate::types::{
+    BigT, BigTRows, BigY, BigYRows, Cipher, ElGamalParams, ModuloOperations, PrivateKey, PublicKey,
+    Secret,
+};
+use alloc::{vec, vec::Vec};
 use blake2::{Blake2b, Digest};
+use core::convert::TryFrom;
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 
@@ -18,9 +22,9 @@ impl Helper {
         );
         let sk = PrivateKey {
             params: params.clone(),
-            x: r.clone(),
+            x: Secret::new(r.clone()),
         };
-        let h = params.g.modpow(&sk.x, &params.p);
+        let h = params.g.modpow_secret(&sk.x.expose(), &params.p);
         let pk = PublicKey {
             params: params.clone(),
             h,
@@ -105,11 +109,11 @@ impl Helper {
         );
         let sk = PrivateKey {
             params: params.clone(),
-            x,
+            x: Secret::new(x),
         };
         let pk = PublicKey {
             params: params.clone(),
-            h: params.g.modpow(&sk.x, &params.p),
+            h: params.g.modpow(&sk.x.expose(), &params.p),
         };
         (params, sk, pk)
     }
@@ -196,6 +200,19 @@ impl Helper {
         hash.finalize().to_vec()
     }
 
+    /// Uses the Blak2 hash function and produces a hash of a vector of rows of Ciphers. The result is returned as a Vec<u8>.
+    /// The width of the rows is part of the hash.
+    pub fn hash_vec_cipher_rows(inputs: Vec<Vec<Cipher>>) -> Vec<u8> {
+        let width = inputs.first().map_or(0usize, |row| row.len());
+        let mut hash = Blake2b::new();
+        hash = hash.chain(width.to_be_bytes());
+
+        for row in inputs.into_iter() {
+            hash = hash.chain(Helper::hash_vec_ciphers(row));
+        }
+        hash.finalize().to_vec()
+    }
+
     /// Uses the Blak2 hash function and produces a hash of a vector of BigUints. The result is returned as a BigUint.
     pub fn hash_vec_biguints_to_biguint(inputs: Vec<BigUint>) -> BigUint {
         let mut hash = Blake2b::new();
@@ -218,6 +235,7 @@ impl Helper {
         BigUint::from_bytes_be(&digest)
     }
 
+    /// Computes the challenge of the key generation proof (proof version 1).
     pub fn hash_key_gen_proof_inputs(
         id: &[u8],
         constant: &str,
@@ -234,6 +252,7 @@ impl Helper {
         BigUint::from_bytes_be(&hash)
     }
 
+    /// Computes the challenge of the decryption proof (proof version 1).
     pub fn hash_decryption_proof_inputs(
         id: &[u8],
         constant: &str,
@@ -263,7 +282,7 @@ impl Helper {
     }
 
     /// Computes the hash of all inputs.
-    /// Used in the multiplicative homomorphic re-encryption proof
+    /// Used in the multiplicative homomorphic re-encryption proof (proof version 1)
     pub fn hash_re_encryption_proof_inputs(
         constant: &str,
         c_one: &Cipher,
@@ -282,7 +301,7 @@ impl Helper {
         BigUint::from_bytes_be(&hash)
     }
 
-    /// Computes the hash of all inputs.
+    /// Computes the hash of all inputs (proof version 1).
     ///
     /// Inputs:
     /// - encryptions: Vec<Cipher>
@@ -317,7 +336,42 @@ impl Helper {
         BigUint::from_bytes_be(&digest)
     }
 
-    /// Computes the hash of all inputs.
+    /// Computes the hash of all inputs of a shuffle of rows (proof version 1).
+    ///
+    /// Inputs:
+    /// - rows: Vec<Vec<Cipher>>
+    /// - shuffled_rows: Vec<Vec<Cipher>>
+    /// - commitments: Vec<BigUint>
+    /// - pk: PublicKey
+    pub fn hash_challenges_inputs_rows(
+        rows: Vec<Vec<Cipher>>,
+        shuffled_rows: Vec<Vec<Cipher>>,
+        commitments: Vec<BigUint>,
+        pk: &PublicKey,
+    ) -> BigUint {
+        // hash all inputs into a single BigUint
+        let mut hash = Blake2b::new();
+
+        // hash public value
+        let hash_rows = Helper::hash_vec_cipher_rows(rows);
+        hash = hash.chain(hash_rows);
+
+        let hash_shuffled_rows = Helper::hash_vec_cipher_rows(shuffled_rows);
+        hash = hash.chain(hash_shuffled_rows);
+
+        let hash_commitments = Helper::hash_vec_biguints(commitments);
+        hash = hash.chain(hash_commitments);
+
+        // transform the public key: h (BigUint) to byte array + hash it
+        let hash_pk = Helper::hash_biguint(&pk.h);
+        hash = hash.chain(hash_pk);
+
+        // final byte array of all chained hashes + transform back to BigUint
+        let digest = hash.finalize();
+        BigUint::from_bytes_be(&digest)
+    }
+
+    /// Computes the hash of all inputs (proof version 1).
     ///
     /// Inputs:
     /// - public_value: Y
@@ -357,83 +411,284 @@ impl Helper {
         let digest = hash.finalize();
         BigUint::from_bytes_be(&digest)
     }
+
+    /// Computes the hash of all inputs of a shuffle of rows (proof version 1).
+    ///
+    /// Inputs:
+    /// - public_value: Y (rows)
+    /// - public_commitment: T (rows)
+    pub fn hash_challenge_inputs_rows(
+        public_value: BigYRows,
+        public_commitment: BigTRows,
+    ) -> BigUint {
+        let (e, e_tilde, vec_c, vec_c_hat, public_key) = public_value;
+        let (t1, t2, t3, vec_t4, vec_t_hat) = public_commitment;
+
+        // hash all inputs into a single BigUint
+        let mut hash = Blake2b::new();
+
+        // hash public value
+        let hash_e = Helper::hash_vec_cipher_rows(e);
+        hash = hash.chain(hash_e);
+
+        let hash_e_tilde = Helper::hash_vec_cipher_rows(e_tilde);
+        hash = hash.chain(hash_e_tilde);
+
+        let hash_vec_c = Helper::hash_vec_biguints(vec_c);
+        hash = hash.chain(hash_vec_c);
+
+        let hash_vec_c_hat = Helper::hash_vec_biguints(vec_c_hat);
+        hash = hash.chain(hash_vec_c_hat);
+
+        let hash_pk = Helper::hash_biguint(public_key);
+        hash = hash.chain(hash_pk);
+
+        // hash public commitments
+        let mut t_values = vec![t1, t2, t3];
+        for (t4_1, t4_2) in vec_t4.into_iter() {
+            t_values.push(t4_1);
+            t_values.push(t4_2);
+        }
+        let hash_t_values = Helper::hash_vec_biguints(t_values);
+        hash = hash.chain(hash_t_values);
+
+        let hash_vec_t_hat = Helper::hash_vec_biguints(vec_t_hat);
+        hash = hash.chain(hash_vec_t_hat);
+
+        // final byte array of all chained hashes + transform back to BigUint
+        let digest = hash.finalize();
+        BigUint::from_bytes_be(&digest)
+    }
+}
+
+/// The version of the Fiat-Shamir transform which is used to compute the challenge of a proof.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub enum ProofVersion {
+    /// The inputs are hashed ad-hoc by each proof (see `Helper::hash_*_inputs`).
+    /// Only supported to verify proofs which were generated before the transcript existed.
+    V1 = 1,
+    /// The inputs are absorbed into a domain separated `Transcript`.
+    V2 = 2,
+}
+
+impl ProofVersion {
+    /// The version with which new proofs are generated.
+    pub const CURRENT: ProofVersion = ProofVersion::V2;
+}
+
+impl Default for ProofVersion {
+    fn default() -> Self {
+        ProofVersion::CURRENT
+    }
+}
+
+impl TryFrom<u8> for ProofVersion {
+    type Error = ();
+
+    fn try_from(version: u8) -> Result<Self, Self::Error> {
+        match version {
+            1 => Ok(ProofVersion::V1),
+            2 => Ok(ProofVersion::V2),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The context to which the challenge of a proof is bound:
+/// the vote and the version of the Fiat-Shamir transform.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct ProofContext<'a> {
+    pub vote_id: &'a [u8],
+    pub version: ProofVersion,
+}
+
+impl<'a> ProofContext<'a> {
+    /// The context of a proof of the vote `vote_id` using the current version.
+    pub fn new(vote_id: &'a [u8]) -> Self {
+        ProofContext {
+            vote_id,
+            version: ProofVersion::CURRENT,
+        }
+    }
+
+    /// The context of a proof of the vote `vote_id` using the given version.
+    pub fn with_version(vote_id: &'a [u8], version: ProofVersion) -> Self {
+        ProofContext { vote_id, version }
+    }
+
+    /// Starts the transcript of a proof of the given protocol in this context.
+    pub fn transcript(&self, protocol: &str, params: &ElGamalParams) -> Transcript {
+        Transcript::new(protocol, self.vote_id, params)
+    }
+}
+
+/// A Fiat-Shamir transcript (proof version 2).
+///
+/// Every input is absorbed together with a label and both are prefixed with their length,
+/// i.e. the absorbed byte string has a unique decoding.
+/// Every transcript starts with the version, the name of the protocol,
+/// the vote id and the group parameters (p, g, h).
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Blake2b,
+}
+
+impl Transcript {
+    pub fn new(protocol: &str, vote_id: &[u8], params: &ElGamalParams) -> Self {
+        let mut transcript = Transcript {
+            hasher: Blake2b::new(),
+        };
+        transcript.append_bytes("version", &[ProofVersion::V2 as u8]);
+        transcript.append_bytes("protocol", protocol.as_bytes());
+        transcript.append_bytes("vote_id", vote_id);
+        transcript.append_biguint("p", &params.p);
+        transcript.append_biguint("g", &params.g);
+        transcript.append_biguint("h", &params.h);
+        transcript
+    }
+
+    /// Absorbs a byte string prefixed with its length (u64, big endian).
+    fn absorb(&mut self, bytes: &[u8]) {
+        self.hasher.update((bytes.len() as u64).to_be_bytes());
+        self.hasher.update(bytes);
+    }
+
+    pub fn append_bytes(&mut self, label: &str, bytes: &[u8]) {
+        self.absorb(label.as_bytes());
+        self.absorb(bytes);
+    }
+
+    pub fn append_usize(&mut self, label: &str, value: usize) {
+        self.append_bytes(label, &(value as u64).to_be_bytes());
+    }
+
+    pub fn append_biguint(&mut self, label: &str, value: &BigUint) {
+        self.append_bytes(label, &value.to_bytes_be());
+    }
+
+    /// Absorbs the number of values followed by every value.
+    pub fn append_biguints(&mut self, label: &str, values: &[BigUint]) {
+        self.append_usize(label, values.len());
+        for value in values.iter() {
+            self.append_biguint(label, value);
+        }
+    }
+
+    pub fn append_cipher(&mut self, label: &str, cipher: &Cipher) {
+        self.append_biguint(label, &cipher.a);
+        self.append_biguint(label, &cipher.b);
+    }
+
+    /// Absorbs the number of ciphers followed by every cipher.
+    pub fn append_ciphers(&mut self, label: &str, ciphers: &[Cipher]) {
+        self.append_usize(label, ciphers.len());
+        for cipher in ciphers.iter() {
+            self.append_cipher(label, cipher);
+        }
+    }
+
+    /// Absorbs the number of rows followed by every row (see `append_ciphers`).
+    pub fn append_cipher_rows(&mut self, label: &str, rows: &[Vec<Cipher>]) {
+        self.append_usize(label, rows.len());
+        for row in rows.iter() {
+            self.append_ciphers(label, row);
+        }
+    }
+
+    /// Computes the challenge with the given label: hash(transcript, label) mod q.
+    /// The transcript is not modified, i.e. several challenges can be derived from it.
+    pub fn challenge(&self, label: &str, q: &BigUint) -> BigUint {
+        let mut hasher = self.clone();
+        hasher.absorb(label.as_bytes());
+        let digest = hasher.hasher.finalize();
+        BigUint::from_bytes_be(&digest) % q
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Helper;
+    use super::{Helper, ProofContext, ProofVersion, Transcript};
     use crate::{
         random::Random,
         types::{Cipher, ElGamalParams},
     };
     use num_bigint::BigUint;
     use num_traits::One;
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn it_should_create_sm_system() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_sm_system();
 
         // system parameters check: p, q, g
-        assert!(Random::is_prime(&params.p, 20));
+        assert!(Random::is_prime(&params.p, 20, &mut rng));
         assert_eq!(params.p, BigUint::parse_bytes(b"B7E151629927", 16).unwrap());
         assert_eq!(params.g, BigUint::from(4u32));
         assert_eq!(params.h, BigUint::from(9u32));
-        assert!(Random::is_prime(&params.q(), 20));
+        assert!(Random::is_prime(&params.q(), 20, &mut rng));
         assert_eq!(params.q(), BigUint::from(101089180470419u64));
 
         // private key check: x == x
-        assert_eq!(sk.x, BigUint::parse_bytes(b"5BF0A8B1", 16).unwrap());
+        assert_eq!(
+            sk.x.expose(),
+            BigUint::parse_bytes(b"5BF0A8B1", 16).unwrap()
+        );
 
         // public key check: verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
     fn it_should_create_tiny_system() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_tiny_system();
 
         // check that p & are prime
-        assert!(Random::is_prime(&params.p, 20));
-        assert!(Random::is_prime(&params.q(), 20));
+        assert!(Random::is_prime(&params.p, 20, &mut rng));
+        assert!(Random::is_prime(&params.q(), 20, &mut rng));
 
         // public key check: verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
     fn it_should_create_256bit_system() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_256bit_system();
 
         // check that p & are prime
-        assert!(Random::is_prime(&params.p, 20));
-        assert!(Random::is_prime(&params.q(), 20));
+        assert!(Random::is_prime(&params.p, 20, &mut rng));
+        assert!(Random::is_prime(&params.q(), 20, &mut rng));
 
         // public key check: verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
     fn it_should_create_512bit_system() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_512bit_system();
 
         // check that p & are prime
-        assert!(Random::is_prime(&params.p, 20));
-        assert!(Random::is_prime(&params.q(), 20));
+        assert!(Random::is_prime(&params.p, 20, &mut rng));
+        assert!(Random::is_prime(&params.q(), 20, &mut rng));
 
         // public key check: verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
     fn it_should_create_md_system() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_md_system();
 
         // check that p & are prime
-        assert!(Random::is_prime(&params.p, 20));
-        assert!(Random::is_prime(&params.q(), 20));
+        assert!(Random::is_prime(&params.p, 20, &mut rng));
+        assert!(Random::is_prime(&params.q(), 20, &mut rng));
 
         // public key check: verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
@@ -449,27 +704,29 @@ mod tests {
     #[test]
     #[ignore = "takes more than 10s to complete, only run when necessary"]
     fn it_should_create_lg_system() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_lg_system();
 
         // check that p & are prime
-        assert!(Random::is_prime(&params.p, 20));
-        assert!(Random::is_prime(&params.q(), 20));
+        assert!(Random::is_prime(&params.p, 20, &mut rng));
+        assert!(Random::is_prime(&params.q(), 20, &mut rng));
 
         // public key check: verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
     #[ignore = "takes more than 10s to complete, only run when necessary"]
     fn it_should_create_xl_system() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, sk, pk) = Helper::setup_xl_system();
 
         // check that p & are prime
-        assert!(Random::is_prime(&params.p, 20));
-        assert!(Random::is_prime(&params.q(), 20));
+        assert!(Random::is_prime(&params.p, 20, &mut rng));
+        assert!(Random::is_prime(&params.q(), 20, &mut rng));
 
         // public key check: verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
@@ -495,10 +752,10 @@ mod tests {
         assert_eq!(sk.params.p, BigUint::from(23u32));
         assert_eq!(sk.params.g, BigUint::from(4u32));
         assert_eq!(sk.params.h, BigUint::from(9u32));
-        assert_eq!(sk.x, BigUint::from(2u32));
+        assert_eq!(sk.x.expose(), BigUint::from(2u32));
 
         // verify that h == g^x mod p
-        assert_eq!(pk.h, sk.params.g.modpow(&sk.x, &sk.params.p));
+        assert_eq!(pk.h, sk.params.g.modpow(&sk.x.expose(), &sk.params.p));
     }
 
     #[test]
@@ -622,4 +879,63 @@ mod tests {
         assert_ne!(combined, hash1);
         assert_ne!(combined, hash2);
     }
+
+    #[test]
+    fn it_should_prefix_transcript_inputs_with_their_length() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let q = &params.q();
+
+        let mut transcript1 = Transcript::new("test", b"2020-12-12_01", &params);
+        transcript1.append_bytes("x", b"ab");
+        transcript1.append_bytes("y", b"c");
+
+        let mut transcript2 = Transcript::new("test", b"2020-12-12_01", &params);
+        transcript2.append_bytes("x", b"a");
+        transcript2.append_bytes("y", b"bc");
+
+        assert_ne!(
+            transcript1.challenge("challenge", q),
+            transcript2.challenge("challenge", q)
+        );
+    }
+
+    #[test]
+    fn it_should_bind_transcript_to_protocol_vote_and_params() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (other_params, _, _) = Helper::setup_md_system();
+        let q = &params.q();
+        let challenge = |transcript: Transcript| transcript.challenge("challenge", q);
+
+        let c = challenge(Transcript::new("test", b"2020-12-12_01", &params));
+        assert_eq!(
+            c,
+            challenge(Transcript::new("test", b"2020-12-12_01", &params))
+        );
+        assert_ne!(
+            c,
+            challenge(Transcript::new("other", b"2020-12-12_01", &params))
+        );
+        assert_ne!(
+            c,
+            challenge(Transcript::new("test", b"2020-12-12_02", &params))
+        );
+        assert_ne!(
+            c,
+            challenge(Transcript::new("test", b"2020-12-12_01", &other_params))
+        );
+
+        // different labels result in different challenges
+        let transcript = Transcript::new("test", b"2020-12-12_01", &params);
+        assert_ne!(transcript.challenge("a", q), transcript.challenge("b", q));
+    }
+
+    #[test]
+    fn it_should_convert_proof_versions() {
+        use core::convert::TryFrom;
+        assert_eq!(ProofVersion::try_from(1u8), Ok(ProofVersion::V1));
+        assert_eq!(ProofVersion::try_from(2u8), Ok(ProofVersion::V2));
+        assert_eq!(ProofVersion::try_from(3u8), Err(()));
+        assert_eq!(ProofVersion::CURRENT as u8, 2u8);
+        assert_eq!(ProofContext::new(b"").version, ProofVersion::CURRENT);
+    }
 }
@@ -14,18 +14,35 @@ extern crate std;
 extern crate alloc;
 
 // crates which this library exposes
+#[cfg(feature = "constant-time")]
+pub mod constant_time;
+
+#[macro_use]
+pub mod encoding;
+
 #[allow(clippy::many_single_char_names)]
 #[macro_use]
 pub mod encryption;
 
+#[macro_use]
+pub mod fixed_base;
+
 #[allow(clippy::many_single_char_names)]
 #[macro_use]
 pub mod helper;
 
-#[cfg(any(feature = "std", test))]
+#[macro_use]
+pub mod multi_exp;
+
+#[macro_use]
+pub mod parallel;
+
 #[macro_use]
 pub mod random;
 
+#[cfg(feature = "parity-scale-codec")]
+pub mod scale;
+
 #[allow(clippy::many_single_char_names)]
 #[macro_use]
 pub mod types;
@@ -0,0 +1,377 @@
+use alloc::{vec, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// The window size (in bits) of Straus' algorithm.
+const STRAUS_WINDOW: usize = 4;
+
+/// Starting at this number of bases Pippenger's algorithm is used instead of Straus'.
+const PIPPENGER_THRESHOLD: usize = 128;
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct MultiExp;
+
+impl MultiExp {
+    /// Returns the product of powers Π(bases_i^exponents_i) mod modulus.
+    ///
+    /// All exponentiations are computed simultaneously, i.e. the squarings are shared
+    /// between all bases. Straus' algorithm (interleaved windows) is used for a small
+    /// number of bases and Pippenger's algorithm (buckets) for a large number of bases.
+    ///
+    /// ## Arguments
+    ///
+    /// * `bases`     - The bases (BigUint)
+    /// * `exponents` - The exponents (BigUint), one per base
+    /// * `modulus`   - The group modulus p (BigUint)
+    pub fn multi_modpow(bases: &[BigUint], exponents: &[BigUint], modulus: &BigUint) -> BigUint {
+        assert!(
+            bases.len() == exponents.len(),
+            "bases and exponents need to have the same length!"
+        );
+        assert!(
+            !modulus.is_zero(),
+            "attempt to calculate with zero modulus!"
+        );
+
+        // the montgomery representation requires an odd modulus
+        if modulus.is_one() {
+            return BigUint::zero();
+        }
+        if !modulus.bit(0) {
+            return Self::naive(bases, exponents, modulus);
+        }
+
+        let bits = exponents
+            .iter()
+            .map(|e| e.bits() as usize)
+            .max()
+            .unwrap_or(0);
+        let monty = Montgomery::new(modulus);
+        let bases: Vec<Vec<u64>> = bases.iter().map(|base| monty.to_monty(base)).collect();
+        let exponents: Vec<Vec<u64>> = exponents.iter().map(|e| e.to_u64_digits()).collect();
+
+        let result = if bases.len() < PIPPENGER_THRESHOLD {
+            Self::straus(&monty, &bases, &exponents, bits)
+        } else {
+            Self::pippenger(&monty, &bases, &exponents, bits)
+        };
+        monty.to_biguint(&result)
+    }
+
+    /// Π(bases_i^exponents_i) mod modulus, one exponentiation at a time
+    fn naive(bases: &[BigUint], exponents: &[BigUint], modulus: &BigUint) -> BigUint {
+        bases
+            .iter()
+            .zip(exponents.iter())
+            .fold(BigUint::one(), |product, (base, exponent)| {
+                product * base.modpow(exponent, modulus) % modulus
+            })
+    }
+
+    /// Straus' algorithm: for every base the powers base^1..base^(2^w - 1) are precomputed.
+    /// Afterwards, all exponents are processed window by window, starting with the most
+    /// significant one. Every window requires w squarings (shared) and one multiplication
+    /// per base.
+    fn straus(
+        monty: &Montgomery,
+        bases: &[Vec<u64>],
+        exponents: &[Vec<u64>],
+        bits: usize,
+    ) -> Vec<u64> {
+        let tables: Vec<Vec<Vec<u64>>> = bases
+            .iter()
+            .map(|base| {
+                let mut table: Vec<Vec<u64>> = Vec::with_capacity((1 << STRAUS_WINDOW) - 1);
+                table.push(base.clone());
+                for j in 1..(1 << STRAUS_WINDOW) - 1 {
+                    let next = monty.mul(&table[j - 1], base);
+                    table.push(next);
+                }
+                table
+            })
+            .collect();
+
+        let mut result = monty.one();
+        for offset in (0..bits).step_by(STRAUS_WINDOW).rev() {
+            for _ in 0..STRAUS_WINDOW {
+                result = monty.mul(&result, &result);
+            }
+            for (table, exponent) in tables.iter().zip(exponents.iter()) {
+                let digit = get_window(exponent, offset, STRAUS_WINDOW);
+                if digit != 0 {
+                    result = monty.mul(&result, &table[digit - 1]);
+                }
+            }
+        }
+        result
+    }
+
+    /// Pippenger's algorithm: the exponents are processed window by window (c bits),
+    /// starting with the most significant one. Per window, every base is put into the bucket
+    /// of its digit. The buckets are combined using a running product: Π(bucket_d^d).
+    fn pippenger(
+        monty: &Montgomery,
+        bases: &[Vec<u64>],
+        exponents: &[Vec<u64>],
+        bits: usize,
+    ) -> Vec<u64> {
+        // the window size grows with the logarithm of the number of bases
+        let log_n = 8 * core::mem::size_of::<usize>() - bases.len().leading_zeros() as usize;
+        let c = if log_n > 3 { log_n - 2 } else { 1 };
+
+        let mut result = monty.one();
+        for offset in (0..bits).step_by(c).rev() {
+            for _ in 0..c {
+                result = monty.mul(&result, &result);
+            }
+
+            // put every base into the bucket of its digit
+            let mut buckets: Vec<Option<Vec<u64>>> = vec![None; (1 << c) - 1];
+            for (base, exponent) in bases.iter().zip(exponents.iter()) {
+                let digit = get_window(exponent, offset, c);
+                if digit != 0 {
+                    let bucket = &mut buckets[digit - 1];
+                    *bucket = Some(match bucket {
+                        Some(value) => monty.mul(value, base),
+                        None => base.clone(),
+                    });
+                }
+            }
+
+            // Π(bucket_d^d) = Π_d(Π_(k >= d) bucket_k)
+            let mut running: Option<Vec<u64>> = None;
+            let mut total: Option<Vec<u64>> = None;
+            for bucket in buckets.iter().rev() {
+                if let Some(value) = bucket {
+                    running = Some(match running {
+                        Some(running) => monty.mul(&running, value),
+                        None => value.clone(),
+                    });
+                }
+                if let Some(running) = running.as_ref() {
+                    total = Some(match total {
+                        Some(total) => monty.mul(&total, running),
+                        None => running.clone(),
+                    });
+                }
+            }
+            if let Some(total) = total {
+                result = monty.mul(&result, &total);
+            }
+        }
+        result
+    }
+}
+
+/// Returns the `size` bits of the exponent starting at bit `offset`.
+fn get_window(exponent: &[u64], offset: usize, size: usize) -> usize {
+    let limb = offset / 64;
+    let shift = offset % 64;
+    if limb >= exponent.len() {
+        return 0;
+    }
+    let mut value = exponent[limb] >> shift;
+    if shift + size > 64 && limb + 1 < exponent.len() {
+        value |= exponent[limb + 1] << (64 - shift);
+    }
+    (value & ((1 << size) - 1)) as usize
+}
+
+/// Montgomery arithmetic modulo an odd modulus n with R = 2^(64 * s), s = # of limbs of n.
+/// A value x is represented as x * R mod n, which allows to reduce a product
+/// without a (costly) division.
+struct Montgomery {
+    modulus: BigUint,
+    n: Vec<u64>,
+    // -n^-1 mod 2^64
+    n_prime: u64,
+}
+
+impl Montgomery {
+    fn new(modulus: &BigUint) -> Self {
+        let n = modulus.to_u64_digits();
+
+        // newton iteration: every step doubles the number of correct bits of n^-1 mod 2^64
+        let mut inverse: u64 = 1;
+        for _ in 0..6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(n[0].wrapping_mul(inverse)));
+        }
+
+        Montgomery {
+            modulus: modulus.clone(),
+            n,
+            n_prime: inverse.wrapping_neg(),
+        }
+    }
+
+    /// x -> x * R mod n
+    fn to_monty(&self, x: &BigUint) -> Vec<u64> {
+        let value = (x << (64 * self.n.len())) % &self.modulus;
+        self.pad(value.to_u64_digits())
+    }
+
+    /// x * R mod n -> x
+    fn to_biguint(&self, x: &[u64]) -> BigUint {
+        let mut one = vec![0u64; self.n.len()];
+        one[0] = 1;
+        let digits = self.mul(x, &one);
+        let digits: Vec<u32> = digits
+            .iter()
+            .flat_map(|digit| vec![*digit as u32, (*digit >> 32) as u32])
+            .collect();
+        BigUint::new(digits)
+    }
+
+    /// the neutral element: R mod n
+    fn one(&self) -> Vec<u64> {
+        self.to_monty(&BigUint::one())
+    }
+
+    fn pad(&self, mut digits: Vec<u64>) -> Vec<u64> {
+        digits.resize(self.n.len(), 0);
+        digits
+    }
+
+    /// Returns a * b * R^-1 mod n (finely integrated operand scanning)
+    fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let s = self.n.len();
+        let (a, b, n) = (&a[..s], &b[..s], &self.n[..s]);
+        let mut t = vec![0u64; s + 1];
+
+        for b_i in b.iter() {
+            // t = (t + a * b_i + m * n) / 2^64, m is chosen such that the lowest limb is zero
+            let (value, mut carry_a) = mac(t[0], a[0], *b_i, 0);
+            let m = value.wrapping_mul(self.n_prime);
+            let (_, mut carry_n) = mac(value, m, n[0], 0);
+            for j in 1..s {
+                let (value, c) = mac(t[j], a[j], *b_i, carry_a);
+                carry_a = c;
+                let (value, c) = mac(value, m, n[j], carry_n);
+                carry_n = c;
+                t[j - 1] = value;
+            }
+            let (value, c_a) = t[s].overflowing_add(carry_a);
+            let (value, c_n) = value.overflowing_add(carry_n);
+            t[s - 1] = value;
+            t[s] = c_a as u64 + c_n as u64;
+        }
+
+        // the result is < 2n, subtract n once if required
+        if t[s] != 0 || !is_less(&t[..s], n) {
+            let mut borrow = false;
+            for (t_j, n_j) in t.iter_mut().zip(n.iter()) {
+                let (value, b1) = t_j.overflowing_sub(*n_j);
+                let (value, b2) = value.overflowing_sub(borrow as u64);
+                *t_j = value;
+                borrow = b1 || b2;
+            }
+        }
+        t.truncate(s);
+        t
+    }
+}
+
+/// Returns a + b * c + carry as (low, high) limbs
+#[inline(always)]
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let value = (a as u128) + (b as u128) * (c as u128) + (carry as u128);
+    (value as u64, (value >> 64) as u64)
+}
+
+/// Returns a < b, both have the same number of limbs (little endian)
+fn is_less(a: &[u64], b: &[u64]) -> bool {
+    for (a_i, b_i) in a.iter().rev().zip(b.iter().rev()) {
+        if a_i != b_i {
+            return a_i < b_i;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{helper::Helper, multi_exp::MultiExp, random::Random};
+    use alloc::vec::Vec;
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn naive(bases: &[BigUint], exponents: &[BigUint], modulus: &BigUint) -> BigUint {
+        bases
+            .iter()
+            .zip(exponents.iter())
+            .fold(BigUint::one(), |product, (base, exponent)| {
+                product * base.modpow(exponent, modulus) % modulus
+            })
+    }
+
+    #[test]
+    fn it_should_compute_multi_exponentiation_tiny() {
+        let (params, _, _) = Helper::setup_tiny_system();
+        let p = &params.p;
+        let bases: Vec<BigUint> = (1..10u32).map(BigUint::from).collect();
+        let exponents: Vec<BigUint> = (10..19u32).map(BigUint::from).collect();
+
+        let result = MultiExp::multi_modpow(&bases, &exponents, p);
+        assert_eq!(result, naive(&bases, &exponents, p));
+    }
+
+    #[test]
+    fn it_should_compute_multi_exponentiation_straus() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, _) = Helper::setup_md_system();
+        let p = &params.p;
+        let q = &params.q();
+
+        for size in [1, 2, 3, 10].iter() {
+            let bases: Vec<BigUint> = (0..*size)
+                .map(|_| Random::get_random_less_than(p, &mut rng))
+                .collect();
+            let exponents: Vec<BigUint> = (0..*size)
+                .map(|_| Random::get_random_less_than(q, &mut rng))
+                .collect();
+
+            let result = MultiExp::multi_modpow(&bases, &exponents, p);
+            assert_eq!(result, naive(&bases, &exponents, p));
+        }
+    }
+
+    #[test]
+    fn it_should_compute_multi_exponentiation_pippenger() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, _) = Helper::setup_sm_system();
+        let p = &params.p;
+        let q = &params.q();
+        let bases: Vec<BigUint> = (0..300)
+            .map(|_| Random::get_random_less_than(p, &mut rng))
+            .collect();
+        let exponents: Vec<BigUint> = (0..300)
+            .map(|_| Random::get_random_less_than(q, &mut rng))
+            .collect();
+
+        let result = MultiExp::multi_modpow(&bases, &exponents, p);
+        assert_eq!(result, naive(&bases, &exponents, p));
+    }
+
+    #[test]
+    fn it_should_compute_multi_exponentiation_edge_cases() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let p = &params.p;
+
+        // no bases
+        assert_eq!(MultiExp::multi_modpow(&[], &[], p), BigUint::one());
+
+        // zero exponents, zero bases and bases larger than the modulus
+        let bases = [BigUint::zero(), params.g.clone(), p + BigUint::from(3u32)];
+        let exponents = [BigUint::from(5u32), BigUint::zero(), p * p];
+        let result = MultiExp::multi_modpow(&bases, &exponents, p);
+        assert_eq!(result, naive(&bases, &exponents, p));
+
+        // even modulus
+        let modulus = BigUint::from(1000u32);
+        let bases = [BigUint::from(7u32), BigUint::from(13u32)];
+        let exponents = [BigUint::from(123u32), BigUint::from(77u32)];
+        let result = MultiExp::multi_modpow(&bases, &exponents, &modulus);
+        assert_eq!(result, naive(&bases, &exponents, &modulus));
+    }
+}
@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+
+/// Maps every item of a slice, the items are processed in parallel if the feature
+/// `parallel` is enabled (std only). Otherwise, they are processed one after the other.
+/// In both cases, the results are returned in the order of the items.
+///
+/// ## Arguments
+///
+/// * `items` - The items
+/// * `f`     - The function applied to every item
+pub fn map<A, B, F>(items: &[A], f: F) -> Vec<B>
+where
+    A: Sync,
+    B: Send,
+    F: Fn(&A) -> B + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Maps every index 0..n, in parallel if the feature `parallel` is enabled (see `map`).
+///
+/// ## Arguments
+///
+/// * `n` - The number of indices
+/// * `f` - The function applied to every index
+pub fn map_range<B, F>(n: usize, f: F) -> Vec<B>
+where
+    B: Send,
+    F: Fn(usize) -> B + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        (0..n).into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..n).map(f).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parallel;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn it_should_map_in_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        let squares = parallel::map(&items, |x| x * x);
+        assert_eq!(squares, items.iter().map(|x| x * x).collect::<Vec<u64>>());
+
+        let indices = parallel::map_range(1000, |i| i as u64);
+        assert_eq!(indices, items);
+        assert!(parallel::map_range(0, |i| i).is_empty());
+    }
+}
@@ -1,29 +1,75 @@
 use crate::{
-    helper::Helper,
-    types::{Cipher, ElGamalParams, ModuloOperations},
+    helper::{Helper, ProofContext, ProofVersion},
+    multi_exp::MultiExp,
+    parallel,
+    types::{Cipher, ElGamalParams, ModuloOperations, Secret},
 };
-use alloc::vec::Vec;
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use core::iter;
 use num_bigint::BigUint;
+use num_traits::One;
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The bit size of the random exponents of the batch verification.
+const BATCH_EXPONENT_BITS: usize = 64;
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DecryptionProof {
     pub challenge: BigUint,
     pub response: BigUint,
 }
 
+/// A decryption proof together with its statement: the partial decryptions vec_c of the
+/// encryptions vec_e by the sealer `id` with the public key share pk.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct DecryptionStatement {
+    pub pk: BigUint,
+    pub proof: DecryptionProof,
+    /// The commitments (t_0, t_1, ..., t_n) of the proof, required by the batch verification
+    /// (see `generate_with_commitments`). They are not part of the proof, a statement
+    /// without commitments is verified one by one.
+    pub commitments: Vec<BigUint>,
+    pub encryptions: Vec<Cipher>,
+    pub partial_decryptions: Vec<BigUint>,
+    pub id: Vec<u8>,
+}
+
 impl DecryptionProof {
     /// GenDecryptionProof Algorithm 8.50 (CHVoteSpec 3.2)
     ///
     /// Generates a decryption proof relative to encryptions e and partial decryptions c. This is essentially a NIZKP of knowledge of the private key sk satisfying c_i = b_i ^ sk for all input encryptions e_i = (a_i, b_i) and pk = g^sk.
+    #[allow(clippy::too_many_arguments)]
     pub fn generate(
         params: &ElGamalParams,
-        sk: &BigUint, // private key of public key share
+        sk: &Secret,  // private key of public key share
         pk: &BigUint, // public key of public key share -> not system public key
-        r: &BigUint,
+        r: &Secret,
         vec_e: Vec<Cipher>,
         vec_c: Vec<BigUint>,
         id: &[u8],
+        context: &ProofContext,
     ) -> DecryptionProof {
+        let (proof, _) =
+            Self::generate_with_commitments(params, sk, pk, r, vec_e, vec_c, id, context);
+        proof
+    }
+
+    /// Same as `generate` but also returns the commitments (t_0, t_1, ..., t_n) of the proof.
+    /// The commitments are only required by the batch verification (see `verify_batch`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_with_commitments(
+        params: &ElGamalParams,
+        sk: &Secret,  // private key of public key share
+        pk: &BigUint, // public key of public key share -> not system public key
+        r: &Secret,
+        vec_e: Vec<Cipher>,
+        vec_c: Vec<BigUint>,
+        id: &[u8],
+        context: &ProofContext,
+    ) -> (DecryptionProof, Vec<BigUint>) {
         assert!(
             vec_e.len() == vec_c.len(),
             "encryptions and partial decryptions need to have the same length!"
@@ -34,31 +80,29 @@ impl DecryptionProof {
         let g = &params.g;
         let q = &params.q();
         let p = &params.p;
+        let sk = &sk.expose();
+        let r = &r.expose();
 
         // the commitment
-        let t_0 = g.modpow(r, p);
+        let t_0 = g.modpow_secret(r, p);
 
-        // get commitments for all encryptions
+        // get commitments for all encryptions: t_i = a_i^r mod p
         let mut vec_t: Vec<BigUint> = Vec::with_capacity(vec_e.len() + 1);
         vec_t.push(t_0);
-
-        for e_i in vec_e.iter() {
-            let t_i = e_i.a.modpow(r, p);
-            vec_t.push(t_i);
-        }
+        vec_t.extend(parallel::map(&vec_e, |e_i| e_i.a.modpow_secret(r, p)));
 
         // compute challenge
         // hash public values (hash(unique_id, constant, pk, vec_e, vec_c, vec_t) mod q)
-        let mut c = Helper::hash_decryption_proof_inputs(id, "decryption", pk, vec_e, vec_c, vec_t);
-        c %= q;
+        let c = Self::challenge(params, pk, vec_e, vec_c, vec_t.clone(), id, context);
 
         // compute the response: d = r - c * sk mod q
-        let d = r.modsub(&c.modmul(sk, q), q);
+        let d = r.modsub_secret(&c.modmul_secret(sk, q), q);
 
-        DecryptionProof {
+        let proof = DecryptionProof {
             challenge: c,
             response: d,
-        }
+        };
+        (proof, vec_t)
     }
 
     /// CheckDecryptionProof Algorithm 8.51 (CHVoteSpec 3.2)
@@ -71,6 +115,7 @@ impl DecryptionProof {
         vec_e: Vec<Cipher>,
         vec_c: Vec<BigUint>,
         id: &[u8],
+        context: &ProofContext,
     ) -> bool {
         assert!(
             vec_e.len() == vec_c.len(),
@@ -80,7 +125,6 @@ impl DecryptionProof {
 
         // system parameters
         let g = &params.g;
-        let q = &params.q();
         let p = &params.p;
 
         // the proof
@@ -89,9 +133,8 @@ impl DecryptionProof {
 
         // the recomputed commitment
         // t_0 = pk^c * g^d mod p
-        let pk_c = pk.modpow(&c, p);
-        let g_d = g.modpow(d, p);
-        let t_0 = pk_c.modmul(&g_d, p);
+        let exponents = [c.clone(), d.clone()];
+        let t_0 = MultiExp::multi_modpow(&[pk.clone(), g.clone()], &exponents, p);
 
         // recompute all commitments for all encryptions
         let mut recompute_vec_t: Vec<BigUint> = Vec::with_capacity(vec_e.len() + 1);
@@ -102,52 +145,202 @@ impl DecryptionProof {
             let c_i = &vec_c[index];
 
             // recompute t_i = c_i^c * a_i^d mod p
-            let c_i_c = c_i.modpow(&c, p);
-            let a_i_d = a_i.modpow(&d, p);
-            let t_i = c_i_c.modmul(&a_i_d, p);
+            let t_i = MultiExp::multi_modpow(&[c_i.clone(), a_i.clone()], &exponents, p);
             recompute_vec_t.push(t_i);
         }
 
         // recompute the challenge
         // hash public values (hash(unique_id, constant, pk, vec_e, vec_c, recompute_vec_t) mod q)
-        let mut recomputed_c = Helper::hash_decryption_proof_inputs(
-            id,
-            "decryption",
-            pk,
-            vec_e,
-            vec_c,
-            recompute_vec_t,
-        );
-        recomputed_c %= q;
+        let recomputed_c = Self::challenge(params, pk, vec_e, vec_c, recompute_vec_t, id, context);
 
         // verify that the challenges are the same
         &recomputed_c == c
     }
+
+    /// Verifies many decryption proofs at once, e.g. the proofs of all sealers and topics of a vote.
+    ///
+    /// First, the challenge of every proof is checked against its commitments:
+    /// c = hash(unique_id, pk, vec_e, vec_c, vec_t) mod q.
+    /// Afterwards, all equations t_0 = pk^c * g^d and t_i = c_i^c * a_i^d are combined
+    /// using small random exponents δ (64 bits) into a single check:
+    /// Π(t^δ) = Π(pk^(c * δ_0) * g^(d * δ_0)) * Π(c_i^(c * δ_i) * a_i^(d * δ_i)) mod p.
+    /// The bases of the right side are shared between the proofs (g, the encryptions of a topic),
+    /// their exponents are summed up. Both sides are computed using a multi-exponentiation.
+    ///
+    /// The exponents δ are derived from all statements and proofs (Fiat-Shamir). For a fixed
+    /// batch, an invalid proof is accepted with probability of at most 2^-64. This bound holds per
+    /// attempt only: the prover can grind, i.e. change a proof or its commitments until the
+    /// derived exponents accept it, and succeeds after about 2^64 hash evaluations. Therefore,
+    /// the batch verification only provides 64 bits of security, not the security of the group.
+    /// This requires that all values are elements of G_q: a factor of order 2 (e.g. p - 1)
+    /// cancels out whenever its exponent is even. Therefore, the bases and commitments of every
+    /// batched proof are checked first (see `ElGamalParams::is_element`).
+    /// Statements without commitments are verified one by one (see `verify`).
+    pub fn verify_batch(
+        params: &ElGamalParams,
+        statements: &[DecryptionStatement],
+        context: &ProofContext,
+    ) -> bool {
+        let mut batched: Vec<&DecryptionStatement> = Vec::with_capacity(statements.len());
+
+        for statement in statements.iter() {
+            let proof = &statement.proof;
+            let vec_e = &statement.encryptions;
+            let vec_c = &statement.partial_decryptions;
+            assert!(
+                vec_e.len() == vec_c.len(),
+                "encryptions and partial decryptions need to have the same length!"
+            );
+            assert!(!vec_e.is_empty(), "vectors cannot be empty!");
+
+            if statement.commitments.is_empty() {
+                let is_valid = Self::verify(
+                    params,
+                    &statement.pk,
+                    proof,
+                    vec_e.clone(),
+                    vec_c.clone(),
+                    &statement.id,
+                    context,
+                );
+                if !is_valid {
+                    return false;
+                }
+                continue;
+            }
+
+            // the small random exponents only bind elements of G_q
+            let mut values = iter::once(&statement.pk)
+                .chain(vec_c.iter())
+                .chain(vec_e.iter().map(|e_i| &e_i.a))
+                .chain(statement.commitments.iter());
+            if !values.all(|value| params.is_element(value)) {
+                return false;
+            }
+
+            // the challenge has to be the hash of the submitted commitments
+            if statement.commitments.len() != vec_e.len() + 1 {
+                return false;
+            }
+            let c = Self::challenge(
+                params,
+                &statement.pk,
+                vec_e.clone(),
+                vec_c.clone(),
+                statement.commitments.clone(),
+                &statement.id,
+                context,
+            );
+            if c != proof.challenge {
+                return false;
+            }
+            batched.push(statement);
+        }
+        if batched.is_empty() {
+            return true;
+        }
+
+        // derive the random exponents from all statements and proofs
+        let mut transcript = context.transcript("decryption_batch", params);
+        transcript.append_usize("statements", batched.len());
+        for statement in batched.iter() {
+            transcript.append_bytes("sealer_id", &statement.id);
+            transcript.append_biguint("pk_share", &statement.pk);
+            transcript.append_biguint("challenge", &statement.proof.challenge);
+            transcript.append_biguint("response", &statement.proof.response);
+            transcript.append_biguints("commitments", &statement.commitments);
+        }
+        let bound = BigUint::one() << BATCH_EXPONENT_BITS;
+
+        // the left side: Π(t^δ), the right side: the summed up exponent of every base
+        let mut commitments: Vec<BigUint> = Vec::new();
+        let mut deltas: Vec<BigUint> = Vec::new();
+        let mut exponents: BTreeMap<BigUint, BigUint> = BTreeMap::new();
+
+        for statement in batched.iter() {
+            let c = &statement.proof.challenge;
+            let d = &statement.proof.response;
+
+            // the bases of every equation: (pk, g) and (c_i, a_i)
+            let bases = iter::once((&statement.pk, &params.g)).chain(
+                statement
+                    .partial_decryptions
+                    .iter()
+                    .zip(statement.encryptions.iter().map(|e_i| &e_i.a)),
+            );
+            for ((base_c, base_d), t) in bases.zip(statement.commitments.iter()) {
+                let mut delta_transcript = transcript.clone();
+                delta_transcript.append_usize("index", deltas.len());
+                let delta = delta_transcript.challenge("delta", &bound);
+
+                *exponents.entry(base_c.clone()).or_default() += c * &delta;
+                *exponents.entry(base_d.clone()).or_default() += d * &delta;
+                commitments.push(t.clone());
+                deltas.push(delta);
+            }
+        }
+
+        let p = &params.p;
+        let (bases, exponents): (Vec<BigUint>, Vec<BigUint>) = exponents.into_iter().unzip();
+        MultiExp::multi_modpow(&commitments, &deltas, p)
+            == MultiExp::multi_modpow(&bases, &exponents, p)
+    }
+
+    /// Computes the challenge c = hash(unique_id, pk, vec_e, vec_c, vec_t) mod q
+    /// using the Fiat-Shamir transform of the version of the context.
+    fn challenge(
+        params: &ElGamalParams,
+        pk: &BigUint,
+        vec_e: Vec<Cipher>,
+        vec_c: Vec<BigUint>,
+        vec_t: Vec<BigUint>,
+        id: &[u8],
+        context: &ProofContext,
+    ) -> BigUint {
+        let q = &params.q();
+        match context.version {
+            ProofVersion::V1 => {
+                Helper::hash_decryption_proof_inputs(id, "decryption", pk, vec_e, vec_c, vec_t) % q
+            }
+            ProofVersion::V2 => {
+                let mut transcript = context.transcript("decryption", params);
+                transcript.append_bytes("sealer_id", id);
+                transcript.append_biguint("pk_share", pk);
+                transcript.append_ciphers("encryptions", &vec_e);
+                transcript.append_biguints("partial_decryptions", &vec_c);
+                transcript.append_biguints("commitments", &vec_t);
+                transcript.challenge("challenge", q)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         encryption::ElGamal,
-        helper::Helper,
-        proofs::decryption::DecryptionProof,
+        helper::{Helper, ProofContext, ProofVersion},
+        proofs::decryption::{DecryptionProof, DecryptionStatement},
         random::Random,
-        types::{ModuloOperations, PublicKey},
+        types::{Cipher, ElGamalParams, ModuloOperations, PrivateKey, PublicKey, Secret},
     };
     use alloc::vec::Vec;
     use num_bigint::BigUint;
     use num_traits::{One, Zero};
+    use rand::{rngs::StdRng, SeedableRng};
     use std::time::Instant;
 
     #[test]
     fn it_should_verify_decryption_proof() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let context = ProofContext::new(b"2020-12-12_01");
         let sealer_id = "Charlie".as_bytes();
         let (params, sk, pk) = Helper::setup_sm_system();
         let q = &params.q();
-        let r = Random::get_random_less_than(q);
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
 
         // get three encrypted values: 1, 3, 5
-        let encryptions = Random::generate_random_encryptions(&pk, q, 3);
+        let encryptions = Random::generate_random_encryptions(&pk, q, 3, &mut rng);
 
         // get partial decryptions -> only decrypt component a: g^r -> g^r^sk
         let decryptions = encryptions
@@ -164,28 +357,126 @@ mod tests {
             encryptions.clone(),
             decryptions.clone(),
             sealer_id,
+            &context,
         );
 
         // verify that the proof is correct
-        let is_correct =
-            DecryptionProof::verify(&params, &pk.h, &proof, encryptions, decryptions, sealer_id);
+        let is_correct = DecryptionProof::verify(
+            &params,
+            &pk.h,
+            &proof,
+            encryptions,
+            decryptions,
+            sealer_id,
+            &context,
+        );
         assert!(is_correct);
     }
 
+    #[test]
+    fn it_should_verify_decryption_proof_v1() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let context = ProofContext::with_version(b"2020-12-12_01", ProofVersion::V1);
+        let sealer_id = "Charlie".as_bytes();
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
+        let encryptions = Random::generate_random_encryptions(&pk, q, 3, &mut rng);
+        let decryptions = encryptions
+            .iter()
+            .map(|cipher| ElGamal::partial_decrypt_a(cipher, &sk))
+            .collect::<Vec<BigUint>>();
+
+        let proof = DecryptionProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            encryptions.clone(),
+            decryptions.clone(),
+            sealer_id,
+            &context,
+        );
+
+        // a proof of version 1 can still be verified, but only as such
+        let is_correct = DecryptionProof::verify(
+            &params,
+            &pk.h,
+            &proof,
+            encryptions.clone(),
+            decryptions.clone(),
+            sealer_id,
+            &context,
+        );
+        assert!(is_correct);
+        let current = ProofContext::new(b"2020-12-12_01");
+        let is_correct = DecryptionProof::verify(
+            &params,
+            &pk.h,
+            &proof,
+            encryptions,
+            decryptions,
+            sealer_id,
+            &current,
+        );
+        assert!(!is_correct);
+    }
+
+    #[test]
+    fn it_should_bind_decryption_proof_to_vote() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let context = ProofContext::new(b"2020-12-12_01");
+        let sealer_id = "Charlie".as_bytes();
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
+        let encryptions = Random::generate_random_encryptions(&pk, q, 3, &mut rng);
+        let decryptions = encryptions
+            .iter()
+            .map(|cipher| ElGamal::partial_decrypt_a(cipher, &sk))
+            .collect::<Vec<BigUint>>();
+
+        let proof = DecryptionProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            encryptions.clone(),
+            decryptions.clone(),
+            sealer_id,
+            &context,
+        );
+
+        // the proof cannot be replayed in another vote
+        let other = ProofContext::new(b"2020-12-12_02");
+        let is_correct = DecryptionProof::verify(
+            &params,
+            &pk.h,
+            &proof,
+            encryptions,
+            decryptions,
+            sealer_id,
+            &other,
+        );
+        assert!(!is_correct);
+    }
+
     #[test]
     fn it_should_verify_decryption_proof_multiple_partial_decryptions() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let context = ProofContext::new(b"2020-12-12_01");
         // create system parameters
         let (params, _, _) = Helper::setup_sm_system();
         let q = &params.q();
 
         // create bob's public and private key
         let bob_id = "Bob".as_bytes();
-        let bob_sk_x = Random::get_random_less_than(q);
+        let bob_sk_x = Random::get_random_less_than(q, &mut rng);
         let (bob_pk, bob_sk) = Helper::generate_key_pair(&params, &bob_sk_x);
 
         // create charlie's public and private key
         let charlie_id = "Charlie".as_bytes();
-        let charlie_sk_x = Random::get_random_less_than(q);
+        let charlie_sk_x = Random::get_random_less_than(q, &mut rng);
         let (charlie_pk, charlie_sk) = Helper::generate_key_pair(&params, &charlie_sk_x);
 
         // create common public key
@@ -198,7 +489,7 @@ mod tests {
         println!("start generation random encryptions");
 
         // get three encrypted values: 1, 3, 5 using the generated common public key
-        let encryptions = Random::generate_random_encryptions(&combined_pk, q, 3);
+        let encryptions = Random::generate_random_encryptions(&combined_pk, q, 3, &mut rng);
 
         let duration = start.elapsed();
         println!("duration generate_random_encryptions: {:?}", duration);
@@ -213,7 +504,7 @@ mod tests {
         println!("duration bob_partial_decrytpions: {:?}", duration);
 
         // create bob's proof
-        let r = Random::get_random_less_than(q);
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
         let bob_proof = DecryptionProof::generate(
             &params,
             &bob_sk.x,
@@ -222,6 +513,7 @@ mod tests {
             encryptions.clone(),
             bob_partial_decrytpions.clone(),
             bob_id,
+            &context,
         );
 
         let duration = start.elapsed();
@@ -235,6 +527,7 @@ mod tests {
             encryptions.clone(),
             bob_partial_decrytpions.clone(),
             bob_id,
+            &context,
         );
         assert!(bob_proof_is_correct);
         let duration = start.elapsed();
@@ -250,7 +543,7 @@ mod tests {
         println!("duration charlie_partial_decrytpions: {:?}", duration);
 
         // create charlie's proof
-        let r = Random::get_random_less_than(q);
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
         let charlie_proof = DecryptionProof::generate(
             &params,
             &charlie_sk.x,
@@ -259,6 +552,7 @@ mod tests {
             encryptions.clone(),
             charlie_partial_decrytpions.clone(),
             charlie_id,
+            &context,
         );
 
         let duration = start.elapsed();
@@ -272,6 +566,7 @@ mod tests {
             encryptions.clone(),
             charlie_partial_decrytpions.clone(),
             charlie_id,
+            &context,
         );
         assert!(charlie_proof_is_correct);
 
@@ -306,18 +601,20 @@ mod tests {
 
     #[test]
     fn it_should_verify_decryption_proof_multiple_partial_decryptions_encoded() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let context = ProofContext::new(b"2020-12-12_01");
         // create system parameters
         let (params, _, _) = Helper::setup_sm_system();
         let q = &params.q();
 
         // create bob's public and private key
         let bob_id = "Bob".as_bytes();
-        let bob_sk_x = Random::get_random_less_than(q);
+        let bob_sk_x = Random::get_random_less_than(q, &mut rng);
         let (bob_pk, bob_sk) = Helper::generate_key_pair(&params, &bob_sk_x);
 
         // create charlie's public and private key
         let charlie_id = "Charlie".as_bytes();
-        let charlie_sk_x = Random::get_random_less_than(q);
+        let charlie_sk_x = Random::get_random_less_than(q, &mut rng);
         let (charlie_pk, charlie_sk) = Helper::generate_key_pair(&params, &charlie_sk_x);
 
         // create common public key
@@ -329,7 +626,7 @@ mod tests {
         let start = Instant::now();
 
         // get three encrypted values: 0, 1, 2 using the generated common public key
-        let encryptions = Random::generate_random_encryptions_encoded(&combined_pk, q, 3);
+        let encryptions = Random::generate_random_encryptions_encoded(&combined_pk, q, 3, &mut rng);
 
         let duration = start.elapsed();
         println!(
@@ -347,7 +644,7 @@ mod tests {
         println!("duration bob_partial_decrytpions ENCODED: {:?}", duration);
 
         // create bob's proof
-        let r = Random::get_random_less_than(q);
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
         let bob_proof = DecryptionProof::generate(
             &params,
             &bob_sk.x,
@@ -356,6 +653,7 @@ mod tests {
             encryptions.clone(),
             bob_partial_decrytpions.clone(),
             bob_id,
+            &context,
         );
 
         let duration = start.elapsed();
@@ -369,6 +667,7 @@ mod tests {
             encryptions.clone(),
             bob_partial_decrytpions.clone(),
             bob_id,
+            &context,
         );
         assert!(bob_proof_is_correct);
         let duration = start.elapsed();
@@ -386,7 +685,7 @@ mod tests {
         );
 
         // create charlie's proof
-        let r = Random::get_random_less_than(q);
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
         let charlie_proof = DecryptionProof::generate(
             &params,
             &charlie_sk.x,
@@ -395,6 +694,7 @@ mod tests {
             encryptions.clone(),
             charlie_partial_decrytpions.clone(),
             charlie_id,
+            &context,
         );
         let duration = start.elapsed();
         println!("duration DecryptionProof::generate ENCODED: {:?}", duration);
@@ -407,6 +707,7 @@ mod tests {
             encryptions.clone(),
             charlie_partial_decrytpions.clone(),
             charlie_id,
+            &context,
         );
         assert!(charlie_proof_is_correct);
         let duration = start.elapsed();
@@ -449,4 +750,202 @@ mod tests {
         assert!(plaintexts.iter().any(|val| val == &BigUint::one()));
         assert!(plaintexts.iter().any(|val| val == &BigUint::from(2u32)));
     }
+
+    /// the decryption statements of the given sealers for the given encryptions
+    fn decryption_statements(
+        params: &ElGamalParams,
+        sealers: &[(&[u8], PublicKey, PrivateKey)],
+        encryptions: &[Cipher],
+        context: &ProofContext,
+        rng: &mut StdRng,
+    ) -> Vec<DecryptionStatement> {
+        let q = &params.q();
+        sealers
+            .iter()
+            .map(|(id, pk, sk)| {
+                let decryptions = encryptions
+                    .iter()
+                    .map(|cipher| ElGamal::partial_decrypt_a(cipher, sk))
+                    .collect::<Vec<BigUint>>();
+                let r = Secret::new(Random::get_random_less_than(q, rng));
+                let (proof, commitments) = DecryptionProof::generate_with_commitments(
+                    params,
+                    &sk.x,
+                    &pk.h,
+                    &r,
+                    encryptions.to_vec(),
+                    decryptions.clone(),
+                    id,
+                    context,
+                );
+                DecryptionStatement {
+                    pk: pk.h.clone(),
+                    proof,
+                    commitments,
+                    encryptions: encryptions.to_vec(),
+                    partial_decryptions: decryptions,
+                    id: id.to_vec(),
+                }
+            })
+            .collect()
+    }
+
+    /// the statements of three sealers for two topics with three encryptions each
+    fn setup_batch(context: &ProofContext) -> (ElGamalParams, Vec<DecryptionStatement>) {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, _) = Helper::setup_sm_system();
+        let q = &params.q();
+        let ids: [&[u8]; 3] = [b"Alice", b"Bob", b"Charlie"];
+        let sealers = ids
+            .iter()
+            .map(|id| {
+                let sk_x = Random::get_random_less_than(q, &mut rng);
+                let (pk, sk) = Helper::generate_key_pair(&params, &sk_x);
+                (*id, pk, sk)
+            })
+            .collect::<Vec<(&[u8], PublicKey, PrivateKey)>>();
+        let pk = PublicKey {
+            h: sealers
+                .iter()
+                .fold(BigUint::one(), |h, (_, pk, _)| h.modmul(&pk.h, &params.p)),
+            params: params.clone(),
+        };
+
+        let mut statements = Vec::new();
+        for _ in 0..2 {
+            let encryptions = Random::generate_random_encryptions(&pk, q, 3, &mut rng);
+            statements.extend(decryption_statements(
+                &params,
+                &sealers,
+                &encryptions,
+                context,
+                &mut rng,
+            ));
+        }
+        (params, statements)
+    }
+
+    #[test]
+    fn it_should_verify_decryption_proofs_batch() {
+        let context = ProofContext::new(b"2020-12-12_01");
+        let (params, statements) = setup_batch(&context);
+        assert_eq!(statements.len(), 6);
+        assert!(statements
+            .iter()
+            .all(|statement| statement.commitments.len() == 4));
+
+        assert!(DecryptionProof::verify_batch(
+            &params,
+            &statements,
+            &context
+        ));
+        assert!(DecryptionProof::verify_batch(&params, &[], &context));
+
+        // the batch is bound to the vote
+        let other = ProofContext::new(b"2020-12-12_02");
+        assert!(!DecryptionProof::verify_batch(&params, &statements, &other));
+    }
+
+    #[test]
+    fn it_should_verify_decryption_proofs_batch_v1() {
+        let context = ProofContext::with_version(b"2020-12-12_01", ProofVersion::V1);
+        let (params, statements) = setup_batch(&context);
+        assert!(DecryptionProof::verify_batch(
+            &params,
+            &statements,
+            &context
+        ));
+    }
+
+    #[test]
+    fn it_should_not_verify_decryption_proofs_batch_invalid_share() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let context = ProofContext::new(b"2020-12-12_01");
+        let (params, mut statements) = setup_batch(&context);
+        let q = &params.q();
+
+        // a proof of wrong partial decryptions with a consistent challenge
+        let (_, sk) =
+            Helper::generate_key_pair(&params, &Random::get_random_less_than(q, &mut rng));
+        let statement = &mut statements[4];
+        statement.partial_decryptions[1] =
+            statement.partial_decryptions[1].modmul(&params.g, &params.p);
+        let (proof, commitments) = DecryptionProof::generate_with_commitments(
+            &params,
+            &sk.x,
+            &statement.pk,
+            &Secret::new(Random::get_random_less_than(q, &mut rng)),
+            statement.encryptions.clone(),
+            statement.partial_decryptions.clone(),
+            &statement.id,
+            &context,
+        );
+        statement.proof = proof;
+        statement.commitments = commitments;
+        assert!(!DecryptionProof::verify_batch(
+            &params,
+            &statements,
+            &context
+        ));
+    }
+
+    #[test]
+    fn it_should_not_verify_decryption_proofs_batch_invalid_proof() {
+        let context = ProofContext::new(b"2020-12-12_01");
+        let (params, statements) = setup_batch(&context);
+
+        // a wrong response
+        let mut invalid = statements.clone();
+        invalid[2].proof.response += BigUint::one();
+        assert!(!DecryptionProof::verify_batch(&params, &invalid, &context));
+
+        // a wrong commitment
+        let mut invalid = statements.clone();
+        invalid[3].commitments[2] = invalid[3].commitments[2].modmul(&params.g, &params.p);
+        assert!(!DecryptionProof::verify_batch(&params, &invalid, &context));
+
+        // a missing commitment
+        let mut invalid = statements;
+        invalid[0].commitments.pop();
+        assert!(!DecryptionProof::verify_batch(&params, &invalid, &context));
+    }
+
+    #[test]
+    fn it_should_verify_decryption_proofs_batch_without_commitments() {
+        let context = ProofContext::new(b"2020-12-12_01");
+        let (params, mut statements) = setup_batch(&context);
+
+        // proofs without commitments are verified one by one
+        statements[1].commitments.clear();
+        statements[5].commitments.clear();
+        assert!(DecryptionProof::verify_batch(
+            &params,
+            &statements,
+            &context
+        ));
+
+        statements[5].proof.response += BigUint::one();
+        assert!(!DecryptionProof::verify_batch(
+            &params,
+            &statements,
+            &context
+        ));
+    }
+
+    #[test]
+    fn it_should_not_verify_decryption_proofs_batch_outside_of_the_group() {
+        let context = ProofContext::new(b"2020-12-12_01");
+        let (params, statements) = setup_batch(&context);
+        let p = &params.p;
+        let minus_one = p - BigUint::one();
+
+        // values of order 2 are rejected before the batch
+        let mut invalid = statements.clone();
+        invalid[2].partial_decryptions[0] = invalid[2].partial_decryptions[0].modmul(&minus_one, p);
+        assert!(!DecryptionProof::verify_batch(&params, &invalid, &context));
+
+        let mut invalid = statements;
+        invalid[4].commitments[1] = invalid[4].commitments[1].modmul(&minus_one, p);
+        assert!(!DecryptionProof::verify_batch(&params, &invalid, &context));
+    }
 }
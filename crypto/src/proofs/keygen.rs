@@ -1,10 +1,14 @@
 use crate::{
-    helper::Helper,
-    types::{ElGamalParams, ModuloOperations},
+    helper::{Helper, ProofContext, ProofVersion},
+    types::{ElGamalParams, ModuloOperations, Secret},
 };
 use num_bigint::BigUint;
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyGenerationProof {
     pub challenge: BigUint,
     pub response: BigUint,
@@ -21,10 +25,11 @@ impl KeyGenerationProof {
     /// 3. compute d = a + c*sk
     pub fn generate(
         params: &ElGamalParams,
-        sk: &BigUint,
+        sk: &Secret,
         pk_share: &BigUint,
-        r: &BigUint,
+        r: &Secret,
         id: &[u8],
+        context: &ProofContext,
     ) -> KeyGenerationProof {
         // system parameters
         let g = &params.g;
@@ -35,18 +40,17 @@ impl KeyGenerationProof {
         let h = pk_share;
 
         // the private key
-        let x = sk;
+        let x = &sk.expose();
 
         // the commitment
-        let a = r;
-        let b = &g.modpow(r, p);
+        let a = &r.expose();
+        let b = &g.modpow_secret(a, p);
 
         // compute challenge -> hash public values (hash(unique_id, h, b) mod q)
-        let mut c = Helper::hash_key_gen_proof_inputs(id, "keygen", h, b);
-        c %= q;
+        let c = Self::challenge(params, h, b, id, context);
 
         // compute the response: d = a + c*sk mod q
-        let d = a.modadd(&c.modmul(x, q), q);
+        let d = a.modadd_secret(&c.modmul_secret(x, q), q);
 
         KeyGenerationProof {
             challenge: c,
@@ -68,10 +72,10 @@ impl KeyGenerationProof {
         pk_share: &BigUint,
         proof: &KeyGenerationProof,
         id: &[u8],
+        context: &ProofContext,
     ) -> bool {
         // system parameters
         let g = &params.g;
-        let q = &params.q();
         let p = &params.p;
 
         // the public key
@@ -89,8 +93,7 @@ impl KeyGenerationProof {
             .expect("cannot compute mod_inverse in mod_div!");
 
         // recompute the hash
-        let mut c_ = Helper::hash_key_gen_proof_inputs(id, "keygen", h, &b);
-        c_ %= q;
+        let c_ = Self::challenge(params, h, &b, id, context);
 
         // verify that the challenges are the same
         let v1 = *c == c_;
@@ -100,34 +103,99 @@ impl KeyGenerationProof {
 
         v1 && v2
     }
+
+    /// Computes the challenge c = hash(unique_id, h, b) mod q
+    /// using the Fiat-Shamir transform of the version of the context.
+    fn challenge(
+        params: &ElGamalParams,
+        h: &BigUint,
+        b: &BigUint,
+        id: &[u8],
+        context: &ProofContext,
+    ) -> BigUint {
+        let q = &params.q();
+        match context.version {
+            ProofVersion::V1 => Helper::hash_key_gen_proof_inputs(id, "keygen", h, b) % q,
+            ProofVersion::V2 => {
+                let mut transcript = context.transcript("keygen", params);
+                transcript.append_bytes("sealer_id", id);
+                transcript.append_biguint("pk_share", h);
+                transcript.append_biguint("commitment", b);
+                transcript.challenge("challenge", q)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{helper::Helper, proofs::keygen::KeyGenerationProof, random::Random};
+    use crate::{
+        helper::{Helper, ProofContext, ProofVersion},
+        proofs::keygen::KeyGenerationProof,
+        random::Random,
+        types::Secret,
+    };
     use num_bigint::BigUint;
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn it_should_create_keygen_proof_tiny() {
         let sealer_id = "Bob".as_bytes();
+        let context = ProofContext::with_version(b"", ProofVersion::V1);
         let (params, sk, pk) = Helper::setup_tiny_system();
-        let r = BigUint::parse_bytes(b"B", 16).unwrap();
+        let r = Secret::new(BigUint::parse_bytes(b"B", 16).unwrap());
 
-        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id);
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id, &context);
         assert_eq!(proof.challenge, BigUint::from(15u32));
         assert_eq!(proof.response, BigUint::from(13u32));
     }
 
     #[test]
     fn it_should_verify_keygen_proof() {
+        let mut rng = StdRng::seed_from_u64(42);
         let sealer_id = "Charlie".as_bytes();
+        let context = ProofContext::new(b"2020-12-12_01");
         let (params, sk, pk) = Helper::setup_sm_system();
-        let r = Random::get_random_less_than(&params.q());
+        let r = Secret::new(Random::get_random_less_than(&params.q(), &mut rng));
 
-        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id);
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id, &context);
 
         // verify the proof
-        let is_correct = KeyGenerationProof::verify(&params, &pk.h, &proof, sealer_id);
+        let is_correct = KeyGenerationProof::verify(&params, &pk.h, &proof, sealer_id, &context);
         assert!(is_correct);
     }
+
+    #[test]
+    fn it_should_verify_keygen_proof_v1() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let sealer_id = "Charlie".as_bytes();
+        let context = ProofContext::with_version(b"2020-12-12_01", ProofVersion::V1);
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let r = Secret::new(Random::get_random_less_than(&params.q(), &mut rng));
+
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id, &context);
+
+        // a proof of version 1 can still be verified, but only as such
+        let is_correct = KeyGenerationProof::verify(&params, &pk.h, &proof, sealer_id, &context);
+        assert!(is_correct);
+        let current = ProofContext::new(b"2020-12-12_01");
+        let is_correct = KeyGenerationProof::verify(&params, &pk.h, &proof, sealer_id, &current);
+        assert!(!is_correct);
+    }
+
+    #[test]
+    fn it_should_bind_keygen_proof_to_vote() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let sealer_id = "Charlie".as_bytes();
+        let context = ProofContext::new(b"2020-12-12_01");
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let r = Secret::new(Random::get_random_less_than(&params.q(), &mut rng));
+
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id, &context);
+
+        // the proof cannot be replayed in another vote
+        let other = ProofContext::new(b"2020-12-12_02");
+        let is_correct = KeyGenerationProof::verify(&params, &pk.h, &proof, sealer_id, &other);
+        assert!(!is_correct);
+    }
 }
@@ -1,16 +1,16 @@
 use crate::{
     encryption::ElGamal,
-    helper::Helper,
+    helper::{Helper, ProofContext, ProofVersion},
     types::{Cipher, ModuloOperations, PublicKey},
 };
 use num_bigint::BigUint;
 use num_traits::One;
 
-#[cfg(feature = "std")]
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ReEncryptionProof {
     pub c_one_prime: Cipher,
     pub challenge: BigUint,
@@ -31,6 +31,7 @@ impl ReEncryptionProof {
         s2: &BigUint,
         c_one: &Cipher, // publicly known encryption of 1 using r1
         pk: &PublicKey,
+        context: &ProofContext,
     ) -> ReEncryptionProof {
         // common parameters
         let p = &pk.params.p;
@@ -51,9 +52,7 @@ impl ReEncryptionProof {
             .expect("cannot compute mod_inverse in mod_div!");
 
         // generate the challenge -> hash the commitment + the public values
-        let mut h =
-            Helper::hash_re_encryption_proof_inputs("re_encryption", c_one, &c_one_prime, &t2);
-        h %= q;
+        let h = Self::challenge(pk, c_one, &c_one_prime, &t2, context);
 
         // split the hash into two parts h1 = h - h2
         let h1 = h.modsub(h2, q);
@@ -76,6 +75,7 @@ impl ReEncryptionProof {
         proof: &ReEncryptionProof,
         cipher: &Cipher,
         re_enc_cipher: &Cipher,
+        context: &ProofContext,
     ) -> bool {
         // common parameters
         let p = &pk.params.p;
@@ -96,9 +96,7 @@ impl ReEncryptionProof {
         let c_one = ElGamal::homomorphic_subtraction(re_enc_cipher, cipher, p);
 
         // recompute the hash
-        let mut h_prime =
-            Helper::hash_re_encryption_proof_inputs("re_encryption", &c_one, c_one_prime, t2);
-        h_prime %= q;
+        let h_prime = Self::challenge(pk, &c_one, c_one_prime, t2, context);
 
         // add the two hash parts from the prover
         let h = h1.modadd(h2, q);
@@ -129,20 +127,50 @@ impl ReEncryptionProof {
         // the proof is correct if all three checks pass
         v1 && v2 && v3
     }
+
+    /// Computes the hash h = hash(c_one, c_one_prime, t2) mod q
+    /// using the Fiat-Shamir transform of the version of the context.
+    fn challenge(
+        pk: &PublicKey,
+        c_one: &Cipher,
+        c_one_prime: &Cipher,
+        t2: &BigUint,
+        context: &ProofContext,
+    ) -> BigUint {
+        let q = &pk.params.q();
+        match context.version {
+            ProofVersion::V1 => {
+                Helper::hash_re_encryption_proof_inputs("re_encryption", c_one, c_one_prime, t2) % q
+            }
+            ProofVersion::V2 => {
+                let mut transcript = context.transcript("re_encryption", &pk.params);
+                transcript.append_biguint("public_key", &pk.h);
+                transcript.append_cipher("c_one", c_one);
+                transcript.append_cipher("c_one_prime", c_one_prime);
+                transcript.append_biguint("commitment", t2);
+                transcript.challenge("challenge", q)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        encryption::ElGamal, helper::Helper, proofs::re_encryption::ReEncryptionProof,
+        encryption::ElGamal,
+        helper::{Helper, ProofContext, ProofVersion},
+        proofs::re_encryption::ReEncryptionProof,
         random::Random,
     };
     use num_bigint::BigUint;
     use num_traits::One;
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn it_should_verify_re_encryption_proofs() {
+        let mut rng = StdRng::seed_from_u64(42);
         // test setup
+        let context = ProofContext::new(b"2020-12-12_01");
         let (params, _, pk) = Helper::setup_sm_system();
         let q = &params.q();
 
@@ -156,11 +184,11 @@ mod tests {
 
         for vote in votes {
             // 1. the voter encrypts his vote
-            let r0 = Random::get_random_less_than(q);
+            let r0 = Random::get_random_less_than(q, &mut rng);
             let ballot = ElGamal::encrypt(&vote, &r0, &pk);
 
             // 2. the randomizer re-encrypts the ballot
-            let r1 = Random::get_random_less_than(q);
+            let r1 = Random::get_random_less_than(q, &mut rng);
             let ballot_prime = ElGamal::re_encrypt(&ballot, &r1, &pk);
 
             // 3. the randomizer generates a proof to show that the re-encryption is valid
@@ -169,14 +197,52 @@ mod tests {
             let c_one = ElGamal::encrypt(&one, &r1, &pk);
 
             // 3.2 generate the proof
-            let r2 = Random::get_random_less_than(q);
-            let h2 = Random::get_random_less_than(q);
-            let s2 = Random::get_random_less_than(q);
-            let proof = ReEncryptionProof::generate(&r1, &r2, &h2, &s2, &c_one, &pk);
+            let r2 = Random::get_random_less_than(q, &mut rng);
+            let h2 = Random::get_random_less_than(q, &mut rng);
+            let s2 = Random::get_random_less_than(q, &mut rng);
+            let proof = ReEncryptionProof::generate(&r1, &r2, &h2, &s2, &c_one, &pk, &context);
 
             // 4. the voter verifies the re-encryption proof
-            let proof_is_valid = ReEncryptionProof::verify(&pk, &proof, &ballot, &ballot_prime);
+            let proof_is_valid =
+                ReEncryptionProof::verify(&pk, &proof, &ballot, &ballot_prime, &context);
             assert!(proof_is_valid);
         }
     }
+
+    #[test]
+    fn it_should_verify_re_encryption_proof_v1() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let context = ProofContext::with_version(b"2020-12-12_01", ProofVersion::V1);
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+
+        let vote = BigUint::from(3u32);
+        let r0 = Random::get_random_less_than(q, &mut rng);
+        let ballot = ElGamal::encrypt(&vote, &r0, &pk);
+        let r1 = Random::get_random_less_than(q, &mut rng);
+        let ballot_prime = ElGamal::re_encrypt(&ballot, &r1, &pk);
+        let c_one = ElGamal::encrypt(&BigUint::one(), &r1, &pk);
+
+        let r2 = Random::get_random_less_than(q, &mut rng);
+        let h2 = Random::get_random_less_than(q, &mut rng);
+        let s2 = Random::get_random_less_than(q, &mut rng);
+        let proof = ReEncryptionProof::generate(&r1, &r2, &h2, &s2, &c_one, &pk, &context);
+
+        // a proof of version 1 can still be verified, but only as such
+        assert!(ReEncryptionProof::verify(
+            &pk,
+            &proof,
+            &ballot,
+            &ballot_prime,
+            &context
+        ));
+        let current = ProofContext::new(b"2020-12-12_01");
+        assert!(!ReEncryptionProof::verify(
+            &pk,
+            &proof,
+            &ballot,
+            &ballot_prime,
+            &current
+        ));
+    }
 }
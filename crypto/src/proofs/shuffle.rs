@@ -1,6 +1,7 @@
-use crate::types::{BigT, BigY, ElGamalParams, ModuloOperations};
+use crate::types::{BigT, BigTRows, BigY, BigYRows, ElGamalParams, ModuloOperations};
 use crate::{
-    helper::Helper,
+    helper::{Helper, ProofContext, ProofVersion, Transcript},
+    parallel,
     types::{Cipher, PermutationCommitment, PublicKey},
 };
 use alloc::{vec, vec::Vec};
@@ -43,20 +44,24 @@ impl ShuffleProof {
         let mut commitments: Vec<BigUint> = vec![too_large.clone(); randoms.len()];
         assert!(commitments.len() == randoms.len());
 
-        for i in 0..permutation.len() {
+        // the commitments are independent of each other (see `parallel::map_range`)
+        let values: Vec<BigUint> = parallel::map_range(permutation.len(), |i| {
             // get the random value r at position j_i
-            let j_i = permutation[i];
-            let r_j_i = &randoms[j_i];
+            let r_j_i = &randoms[permutation[i]];
 
             // a random independent generator ∈ G_q
             let h_i = &generators[i];
 
             // create commitment
-            // g_pow_r_j_i = g^(r_j_i) mod p
-            let g_pow_r_j_i = g.modpow(r_j_i, p);
+            // g_pow_r_j_i = g^(r_j_i) mod p, the random value is secret
+            let g_pow_r_j_i = g.modpow_secret(r_j_i, p);
 
             // c_j_i = (g^(r_j_i) * h_i) mod p
-            let c_j_i = g_pow_r_j_i.modmul(h_i, p);
+            g_pow_r_j_i.modmul(h_i, p)
+        });
+
+        for (i, c_j_i) in values.into_iter().enumerate() {
+            let j_i = permutation[i];
 
             // insert c_j_i at position j_i in commitments vector
             let removed = commitments.remove(j_i);
@@ -96,14 +101,13 @@ impl ShuffleProof {
         let g = &params.g;
         let h = &params.h;
 
-        let mut commitment_values = Vec::new();
+        let mut exponents = Vec::new();
         let mut commitment_randoms = Vec::new();
 
         // initialize the commitment and random values with
         // R_0 = 0, U_0 = 1
         let mut r_i = BigUint::zero();
         let mut u_i = BigUint::one();
-        let mut c_i: BigUint;
 
         for i in 0..challenges.len() {
             // retrieve and store the commitment random
@@ -114,21 +118,23 @@ impl ShuffleProof {
             let challenge_i = challenges[i].clone();
 
             // compute the commitment random: R_i = random_i + challenge_i * R_(i-1) mod q
-            r_i = random_i + challenge_i.clone() * r_i.clone();
-            r_i %= q;
+            // the randoms and the permuted challenges are secret
+            r_i = random_i.modadd_secret(&challenge_i.modmul_secret(&r_i, q), q);
 
             // compute U_i = challenge_i * U_(i-1) mod q
-            u_i = challenge_i * u_i.clone();
-            u_i %= q;
-
-            // compute the commitment value: c_i = g^r_i * h^u_i mod p
-            // g is the first and h the second public generator: g ∈ G_q, h ∈ G_q
-            let g_pow_r_i = g.modpow(&r_i, p);
-            let h_pow_u_i = h.modpow(&u_i, p);
-            c_i = g_pow_r_i * h_pow_u_i;
-            c_i %= p;
-            commitment_values.push(c_i);
+            u_i = challenge_i.modmul_secret(&u_i, q);
+
+            exponents.push((r_i.clone(), u_i.clone()));
         }
+
+        // compute the commitment values: c_i = g^r_i * h^u_i mod p
+        // g is the first and h the second public generator: g ∈ G_q, h ∈ G_q
+        // only the exponents form a chain, the commitments are independent of each other
+        let commitment_values: Vec<BigUint> = parallel::map(&exponents, |(r_i, u_i)| {
+            let g_pow_r_i = g.modpow_secret(r_i, p);
+            let h_pow_u_i = h.modpow_secret(u_i, p);
+            g_pow_r_i.modmul_secret(&h_pow_u_i, p)
+        });
         assert!(commitment_values.len() == commitment_randoms.len());
         PermutationCommitment {
             commitments: commitment_values,
@@ -145,12 +151,14 @@ impl ShuffleProof {
     /// - vec_e_tilde: Vec<Cipher> "Shuffled Encryptions"
     /// - vec_c: Vec<BigUint> "Permutation Commitments"
     /// - pk: PublicKey
+    /// - context: ProofContext
     pub fn get_challenges(
         n: usize,
         vec_e: Vec<Cipher>,
         vec_e_tilde: Vec<Cipher>,
         vec_c: Vec<BigUint>,
         pk: &PublicKey,
+        context: &ProofContext,
     ) -> Vec<BigUint> {
         assert!(n > 0, "at least one challenge must be generated!");
         assert!(
@@ -165,6 +173,15 @@ impl ShuffleProof {
         let q = &pk.params.q();
         let mut challenges: Vec<BigUint> = Vec::new();
 
+        if context.version == ProofVersion::V2 {
+            let mut transcript = context.transcript("shuffle", &pk.params);
+            transcript.append_ciphers("encryptions", &vec_e);
+            transcript.append_ciphers("shuffled_encryptions", &vec_e_tilde);
+            transcript.append_biguints("permutation_commitments", &vec_c);
+            transcript.append_biguint("public_key", &pk.h);
+            return Self::get_indexed_challenges(n, transcript, q);
+        }
+
         // hash all inputs into a single BigUint
         let h = Helper::hash_challenges_inputs(vec_e, vec_e_tilde, vec_c, pk);
 
@@ -189,19 +206,151 @@ impl ShuffleProof {
     /// Inputs:
     /// - public value: ((encryptions, shuffled_encryptions, permutation_commitments, chain_commitments, public_key)
     /// - public commitment: (t1, t2, t3, (t4_1, t4_2), (t_hat_0, ..., t_hat_(size-1)))
-    pub fn get_challenge(public_value: BigY, public_commitment: BigT, q: &BigUint) -> BigUint {
-        let value = Helper::hash_challenge_inputs(public_value, public_commitment);
-        value % q
+    /// - params: ElGamalParams
+    /// - context: ProofContext
+    pub fn get_challenge(
+        public_value: BigY,
+        public_commitment: BigT,
+        params: &ElGamalParams,
+        context: &ProofContext,
+    ) -> BigUint {
+        let q = &params.q();
+        match context.version {
+            ProofVersion::V1 => Helper::hash_challenge_inputs(public_value, public_commitment) % q,
+            ProofVersion::V2 => {
+                let (e, e_tilde, vec_c, vec_c_hat, public_key) = public_value;
+                let (t1, t2, t3, t4_1, t4_2, vec_t_hat) = public_commitment;
+
+                let mut transcript = context.transcript("shuffle", params);
+                transcript.append_ciphers("encryptions", &e);
+                transcript.append_ciphers("shuffled_encryptions", &e_tilde);
+                transcript.append_biguints("permutation_commitments", &vec_c);
+                transcript.append_biguints("chain_commitments", &vec_c_hat);
+                transcript.append_biguint("public_key", public_key);
+                transcript.append_biguints("t", &[t1, t2, t3, t4_1, t4_2]);
+                transcript.append_biguints("t_hat", &vec_t_hat);
+                transcript.challenge("challenge", q)
+            }
+        }
+    }
+
+    /// GetChallenges Algorithm 8.5 (CHVoteSpec 3.2) for a shuffle of rows (width w).
+    /// Computes n challenges, one per row, for a given public value (rows, shuffled_rows, vec_c).
+    ///
+    /// Inputs:
+    /// - n: usize
+    /// - rows: Vec<Vec<Cipher>> "Encryptions"
+    /// - shuffled_rows: Vec<Vec<Cipher>> "Shuffled Encryptions"
+    /// - vec_c: Vec<BigUint> "Permutation Commitments"
+    /// - pk: PublicKey
+    /// - context: ProofContext
+    pub fn get_challenges_rows(
+        n: usize,
+        rows: Vec<Vec<Cipher>>,
+        shuffled_rows: Vec<Vec<Cipher>>,
+        vec_c: Vec<BigUint>,
+        pk: &PublicKey,
+        context: &ProofContext,
+    ) -> Vec<BigUint> {
+        assert!(n > 0, "at least one challenge must be generated!");
+        assert!(
+            rows.len() == shuffled_rows.len(),
+            "rows and shuffled_rows need to have the same length!"
+        );
+        assert!(
+            rows.len() == vec_c.len(),
+            "rows and permutation_commitments need to have the same length!"
+        );
+        assert!(!rows.is_empty(), "vectors cannot be empty!");
+        let q = &pk.params.q();
+
+        if context.version == ProofVersion::V2 {
+            let mut transcript = context.transcript("row_shuffle", &pk.params);
+            transcript.append_cipher_rows("encryptions", &rows);
+            transcript.append_cipher_rows("shuffled_encryptions", &shuffled_rows);
+            transcript.append_biguints("permutation_commitments", &vec_c);
+            transcript.append_biguint("public_key", &pk.h);
+            return Self::get_indexed_challenges(n, transcript, q);
+        }
+
+        // hash all inputs into a single BigUint
+        let h = Helper::hash_challenges_inputs_rows(rows, shuffled_rows, vec_c, pk);
+
+        // c_i = hash(h, i) mod q, see get_challenges
+        (0..n)
+            .map(|i| {
+                let i_ = Helper::hash_vec_usize_to_biguint(&[i]);
+                Helper::hash_vec_biguints_to_biguint([h.clone(), i_].to_vec()) % q
+            })
+            .collect()
+    }
+
+    /// Algorithm 8.4 for a shuffle of rows (width w): Computes a NIZKP challenge
+    /// for a given public value y and a public commitment t.
+    ///
+    /// Inputs:
+    /// - public value: (rows, shuffled_rows, permutation_commitments, chain_commitments, public_key)
+    /// - public commitment: (t1, t2, t3, ((t4_1, t4_2) per column), (t_hat_0, ..., t_hat_(size-1)))
+    /// - params: ElGamalParams
+    /// - context: ProofContext
+    pub fn get_challenge_rows(
+        public_value: BigYRows,
+        public_commitment: BigTRows,
+        params: &ElGamalParams,
+        context: &ProofContext,
+    ) -> BigUint {
+        let q = &params.q();
+        match context.version {
+            ProofVersion::V1 => {
+                Helper::hash_challenge_inputs_rows(public_value, public_commitment) % q
+            }
+            ProofVersion::V2 => {
+                let (e, e_tilde, vec_c, vec_c_hat, public_key) = public_value;
+                let (t1, t2, t3, vec_t4, vec_t_hat) = public_commitment;
+                let mut t_values = vec![t1, t2, t3];
+                for (t4_1, t4_2) in vec_t4.into_iter() {
+                    t_values.push(t4_1);
+                    t_values.push(t4_2);
+                }
+
+                let mut transcript = context.transcript("row_shuffle", params);
+                transcript.append_cipher_rows("encryptions", &e);
+                transcript.append_cipher_rows("shuffled_encryptions", &e_tilde);
+                transcript.append_biguints("permutation_commitments", &vec_c);
+                transcript.append_biguints("chain_commitments", &vec_c_hat);
+                transcript.append_biguint("public_key", public_key);
+                transcript.append_biguints("t", &t_values);
+                transcript.append_biguints("t_hat", &vec_t_hat);
+                transcript.challenge("challenge", q)
+            }
+        }
+    }
+
+    /// Derives the n challenges c_i = hash(transcript, i) mod q (proof version 2).
+    fn get_indexed_challenges(n: usize, transcript: Transcript, q: &BigUint) -> Vec<BigUint> {
+        (0..n)
+            .map(|i| {
+                let mut transcript = transcript.clone();
+                transcript.append_usize("index", i);
+                transcript.challenge("u", q)
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ShuffleProof;
-    use crate::{helper::Helper, random::Random, types::Cipher};
+    use crate::{
+        encryption::ElGamal,
+        helper::{Helper, ProofContext},
+        random::Random,
+        types::{Cipher, Secret},
+    };
     use alloc::{vec, vec::Vec};
     use num_bigint::BigUint;
     use num_traits::{One, Zero};
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     #[should_panic(expected = "permutation and randoms need to have the same length!")]
@@ -258,6 +407,7 @@ mod tests {
 
     #[test]
     fn it_should_generate_permutation_commitment() {
+        let mut rng = StdRng::seed_from_u64(42);
         let (params, _, _) = Helper::setup_md_system();
         let p = &params.p;
         let q = params.q();
@@ -265,13 +415,13 @@ mod tests {
 
         // create a list of permutation
         let size = 3usize;
-        let permutation = Random::generate_permutation(&size);
+        let permutation = Random::generate_permutation(&size, &mut rng);
 
         // create three random values < q
         let randoms = [
-            Random::get_random_less_than(&q),
-            Random::get_random_less_than(&q),
-            Random::get_random_less_than(&q),
+            Random::get_random_less_than(&q, &mut rng),
+            Random::get_random_less_than(&q, &mut rng),
+            Random::get_random_less_than(&q, &mut rng),
         ];
 
         // get random generators ∈ G_q
@@ -302,6 +452,7 @@ mod tests {
     fn it_should_panic_get_challenges_zero_challenges() {
         // SETUP
         let (_, _, pk) = Helper::setup_md_system();
+        let context = ProofContext::new(b"2020-12-12_01");
 
         // fake values
         let size = 0usize;
@@ -310,7 +461,14 @@ mod tests {
         let commitments = Vec::new();
 
         // TEST
-        ShuffleProof::get_challenges(size, encryptions, shuffled_encryptions, commitments, &pk);
+        ShuffleProof::get_challenges(
+            size,
+            encryptions,
+            shuffled_encryptions,
+            commitments,
+            &pk,
+            &context,
+        );
     }
 
     #[test]
@@ -318,6 +476,7 @@ mod tests {
     fn it_should_panic_get_challenges_different_sizes_encryptions_shuffled_encryptions() {
         // SETUP
         let (_, _, pk) = Helper::setup_md_system();
+        let context = ProofContext::new(b"2020-12-12_01");
 
         // fake values
         let size = 1usize;
@@ -329,7 +488,14 @@ mod tests {
         let commitments = Vec::new();
 
         // TEST
-        ShuffleProof::get_challenges(size, encryptions, shuffled_encryptions, commitments, &pk);
+        ShuffleProof::get_challenges(
+            size,
+            encryptions,
+            shuffled_encryptions,
+            commitments,
+            &pk,
+            &context,
+        );
     }
 
     #[test]
@@ -339,6 +505,7 @@ mod tests {
     fn it_should_panic_get_challenges_different_sizes_encryptions_randoms() {
         // SETUP
         let (_, _, pk) = Helper::setup_md_system();
+        let context = ProofContext::new(b"2020-12-12_01");
 
         // fake values
         let size = 1usize;
@@ -353,7 +520,14 @@ mod tests {
         let commitments = Vec::new();
 
         // TEST
-        ShuffleProof::get_challenges(size, encryptions, shuffled_encryptions, commitments, &pk);
+        ShuffleProof::get_challenges(
+            size,
+            encryptions,
+            shuffled_encryptions,
+            commitments,
+            &pk,
+            &context,
+        );
     }
 
     #[test]
@@ -361,6 +535,7 @@ mod tests {
     fn it_should_panic_get_challenges_empty_inputs() {
         // SETUP
         let (_, _, pk) = Helper::setup_md_system();
+        let context = ProofContext::new(b"2020-12-12_01");
 
         // fake values
         let size = 1usize;
@@ -375,13 +550,16 @@ mod tests {
             shuffled_encryptions,
             re_encryption_randoms,
             &pk,
+            &context,
         );
     }
 
     #[test]
     fn it_should_get_challenges_encoded() {
+        let mut rng = StdRng::seed_from_u64(42);
         // SETUP
         let (_, _, pk) = Helper::setup_md_system();
+        let context = ProofContext::new(b"2020-12-12_01");
 
         let vote_id = "2020-12-12_01".as_bytes();
         let size = 3usize;
@@ -390,8 +568,9 @@ mod tests {
         let params = &pk.params;
 
         // generates a shuffle of three random encryptions of values: zero, one, two
-        let encryptions = Random::generate_random_encryptions_encoded(&pk, &pk.params.q(), 3);
-        let shuffle = Random::generate_shuffle(&pk, &pk.params.q(), encryptions.clone());
+        let encryptions =
+            Random::generate_random_encryptions_encoded(&pk, &pk.params.q(), 3, &mut rng);
+        let shuffle = Random::generate_shuffle(&pk, &pk.params.q(), encryptions.clone(), &mut rng);
 
         // get the shuffled_encryptions & permutation from the shuffle
         let shuffled_encryptions = shuffle
@@ -405,7 +584,7 @@ mod tests {
         // generate {size} random values
         let mut randoms: Vec<BigUint> = Vec::new();
         for _ in 0..size {
-            randoms.push(Random::get_random_less_than(q));
+            randoms.push(Random::get_random_less_than(q, &mut rng));
         }
 
         // get {size} independent generators
@@ -421,8 +600,14 @@ mod tests {
         let commitments = permutation_commitment.commitments;
 
         // TEST: challenge value generation
-        let challenges =
-            ShuffleProof::get_challenges(size, encryptions, shuffled_encryptions, commitments, &pk);
+        let challenges = ShuffleProof::get_challenges(
+            size,
+            encryptions,
+            shuffled_encryptions,
+            commitments,
+            &pk,
+            &context,
+        );
 
         // check that:
         // 1. three challenges are generated
@@ -433,8 +618,10 @@ mod tests {
 
     #[test]
     fn it_should_get_challenges() {
+        let mut rng = StdRng::seed_from_u64(42);
         // SETUP
         let (_, _, pk) = Helper::setup_md_system();
+        let context = ProofContext::new(b"2020-12-12_01");
 
         let vote_id = "2020-12-12_01".as_bytes();
         let size = 3usize;
@@ -443,8 +630,8 @@ mod tests {
         let params = &pk.params;
 
         // generates a shuffle of three random encryptions of values: 1, 3, 5
-        let encryptions = Random::generate_random_encryptions(&pk, &pk.params.q(), 3);
-        let shuffle = Random::generate_shuffle(&pk, &pk.params.q(), encryptions.clone());
+        let encryptions = Random::generate_random_encryptions(&pk, &pk.params.q(), 3, &mut rng);
+        let shuffle = Random::generate_shuffle(&pk, &pk.params.q(), encryptions.clone(), &mut rng);
 
         // get the shuffled_encryptions & permutation from the shuffle
         let shuffled_encryptions = shuffle
@@ -458,7 +645,7 @@ mod tests {
         // generate {size} random values
         let mut randoms: Vec<BigUint> = Vec::new();
         for _ in 0..size {
-            randoms.push(Random::get_random_less_than(q));
+            randoms.push(Random::get_random_less_than(q, &mut rng));
         }
 
         // get {size} independent generators
@@ -474,8 +661,14 @@ mod tests {
         let commitments = permutation_commitment.commitments;
 
         // TEST: challenge value generation
-        let challenges =
-            ShuffleProof::get_challenges(size, encryptions, shuffled_encryptions, commitments, &pk);
+        let challenges = ShuffleProof::get_challenges(
+            size,
+            encryptions,
+            shuffled_encryptions,
+            commitments,
+            &pk,
+            &context,
+        );
 
         // check that:
         // 1. three challenges are generated
@@ -484,6 +677,77 @@ mod tests {
         assert!(challenges.iter().all(|value| value < &pk.params.q()));
     }
 
+    #[test]
+    fn it_should_get_challenges_rows() {
+        let mut rng = StdRng::seed_from_u64(42);
+        // SETUP
+        let (_, _, pk) = Helper::setup_md_system();
+        let context = ProofContext::new(b"2020-12-12_01");
+
+        let vote_id = "2020-12-12_01".as_bytes();
+        let size = 3usize;
+        let q = &pk.params.q();
+        let p = &pk.params.p;
+        let params = &pk.params;
+
+        // three rows of width two
+        let rows = (0..size)
+            .map(|_| Random::generate_random_encryptions(&pk, q, 2, &mut rng))
+            .collect::<Vec<Vec<Cipher>>>();
+        let randoms = (0..size)
+            .map(|_| {
+                vec![
+                    Secret::new(Random::get_random_less_than(q, &mut rng)),
+                    Secret::new(Random::get_random_less_than(q, &mut rng)),
+                ]
+            })
+            .collect::<Vec<Vec<Secret>>>();
+        let permutation = Random::generate_permutation(&size, &mut rng);
+        let shuffle = ElGamal::shuffle_rows(&rows, &permutation, &randoms, &pk);
+        let shuffled_rows = shuffle
+            .iter()
+            .map(|item| item.0.clone())
+            .collect::<Vec<Vec<Cipher>>>();
+
+        // get the permutation commitents
+        let commitment_randoms = (0..size)
+            .map(|_| Random::get_random_less_than(q, &mut rng))
+            .collect::<Vec<BigUint>>();
+        let generators = Helper::get_generators(&vote_id, p, size);
+        let commitments = ShuffleProof::generate_permutation_commitment(
+            params,
+            &permutation,
+            commitment_randoms,
+            generators,
+        )
+        .commitments;
+
+        // TEST: challenge value generation
+        let challenges = ShuffleProof::get_challenges_rows(
+            size,
+            rows.clone(),
+            shuffled_rows.clone(),
+            commitments.clone(),
+            &pk,
+            &context,
+        );
+        assert_eq!(challenges.len(), 3);
+        assert!(challenges.iter().all(|value| value < q));
+
+        // the challenges depend on the whole rows
+        let mut other_shuffled_rows = shuffled_rows;
+        other_shuffled_rows[0].swap(0, 1);
+        let other_challenges = ShuffleProof::get_challenges_rows(
+            size,
+            rows,
+            other_shuffled_rows,
+            commitments,
+            &pk,
+            &context,
+        );
+        assert_ne!(challenges, other_challenges);
+    }
+
     #[test]
     #[should_panic(expected = "challenges and randoms need to have the same length!")]
     fn it_should_panic_generate_commitment_chain_different_size_challenges_randoms() {
@@ -514,6 +778,7 @@ mod tests {
 
     #[test]
     fn it_should_panic_generate_commitment_chain() {
+        let mut rng = StdRng::seed_from_u64(42);
         // SETUP
         let (params, _, _) = Helper::setup_md_system();
 
@@ -524,13 +789,13 @@ mod tests {
         // fake challenge values
         let mut challenges: Vec<BigUint> = Vec::new();
         for _ in 0..size {
-            challenges.push(Random::get_random_less_than(q));
+            challenges.push(Random::get_random_less_than(q, &mut rng));
         }
 
         // generate {size} random values
         let mut randoms: Vec<BigUint> = Vec::new();
         for _ in 0..size {
-            randoms.push(Random::get_random_less_than(q));
+            randoms.push(Random::get_random_less_than(q, &mut rng));
         }
 
         // TEST
@@ -1,36 +1,43 @@
 use crate::{
     encryption::ElGamal,
-    types::{Cipher, PublicKey},
+    fixed_base::FixedBaseTables,
+    types::{Cipher, PublicKey, Secret},
 };
 use alloc::vec::Vec;
 use core::ops::{AddAssign, Sub};
 use num_bigint::{BigUint, RandBigInt};
 use num_traits::{One, Zero};
-use rand::Rng;
-use std::boxed::Box;
-use std::panic;
+use rand::{CryptoRng, Rng, RngCore};
+#[cfg(any(feature = "std", test))]
+use std::{boxed::Box, panic};
 
+/// All randomized functions take the random number generator as argument,
+/// e.g. `rand::thread_rng()` (std), a `ChaChaRng` seeded from the offchain worker's seed (pallet)
+/// or a `StdRng` seeded from a fixed value for reproducible runs (tests and benchmarks).
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub struct Random;
 
 impl Random {
-    pub fn generate_random_encryptions_encoded(
+    pub fn generate_random_encryptions_encoded<R: RngCore + CryptoRng>(
         pk: &PublicKey,
         q: &BigUint,
         number: usize,
+        rng: &mut R,
     ) -> Vec<Cipher> {
         let mut encryptions: Vec<Cipher> = Vec::new();
+        let tables = FixedBaseTables::for_public_key(pk);
 
         for i in 0..number {
             let nr = BigUint::from(i);
-            let r = Random::get_random_less_than(q);
-            let enc = ElGamal::encrypt_encode(&nr, &r, pk);
+            let r = Random::get_random_less_than(q, rng);
+            let enc = ElGamal::encrypt_encode_with(&nr, &r, pk, &tables);
             encryptions.push(enc);
         }
         encryptions
     }
 
     /// used to suppress the panic message when using panic::catch_unwind
+    #[cfg(any(feature = "std", test))]
     fn catch_unwind_silent<F: FnOnce() -> R + panic::UnwindSafe, R>(
         f: F,
     ) -> std::thread::Result<R> {
@@ -41,14 +48,17 @@ impl Random {
         result
     }
 
-    pub fn generate_encryptions(
+    #[cfg(any(feature = "std", test))]
+    pub fn generate_encryptions<R: RngCore + CryptoRng>(
         pk: &PublicKey,
         q: &BigUint,
         number: usize,
         votes: Vec<u32>,
+        rng: &mut R,
     ) -> Vec<Cipher> {
         assert!(!votes.is_empty(), "there must be at least one value!");
         let mut encryptions: Vec<Cipher> = Vec::new();
+        let tables = FixedBaseTables::for_public_key(pk);
 
         'outer: loop {
             for vote in votes.iter() {
@@ -57,9 +67,10 @@ impl Random {
                 if encryptions.len() == number {
                     break 'outer;
                 }
-                let r = Random::get_random_less_than(q);
+                let r = Random::get_random_less_than(q, rng);
                 let nr = BigUint::from(*vote);
-                let result = Self::catch_unwind_silent(|| ElGamal::encrypt(&nr, &r, pk));
+                let result =
+                    Self::catch_unwind_silent(|| ElGamal::encrypt_with(&nr, &r, pk, &tables));
                 if result.is_ok() {
                     let enc = result.unwrap();
                     encryptions.push(enc.clone());
@@ -69,15 +80,22 @@ impl Random {
         encryptions
     }
 
-    pub fn generate_random_encryptions(pk: &PublicKey, q: &BigUint, number: usize) -> Vec<Cipher> {
+    #[cfg(any(feature = "std", test))]
+    pub fn generate_random_encryptions<R: RngCore + CryptoRng>(
+        pk: &PublicKey,
+        q: &BigUint,
+        number: usize,
+        rng: &mut R,
+    ) -> Vec<Cipher> {
         let mut encryptions: Vec<Cipher> = Vec::new();
+        let tables = FixedBaseTables::for_public_key(pk);
         let mut i: u32 = 0;
 
         while encryptions.len() != number {
             let nr = BigUint::from(i);
 
-            let r = Random::get_random_less_than(q);
-            let result = Self::catch_unwind_silent(|| ElGamal::encrypt(&nr, &r, pk));
+            let r = Random::get_random_less_than(q, rng);
+            let result = Self::catch_unwind_silent(|| ElGamal::encrypt_with(&nr, &r, pk, &tables));
             if result.is_ok() {
                 let enc = result.unwrap();
                 encryptions.push(enc.clone());
@@ -93,20 +111,22 @@ impl Random {
     /// * `pk` - public key
     /// * `q` - the group modulus
     /// * `encryptions` - a vector of encrypted votes
-    pub fn generate_shuffle(
+    /// * `rng` - the random number generator
+    pub fn generate_shuffle<R: RngCore + CryptoRng>(
         pk: &PublicKey,
         q: &BigUint,
         encryptions: Vec<Cipher>,
-    ) -> Vec<(Cipher, BigUint, usize)> {
+        rng: &mut R,
+    ) -> Vec<(Cipher, Secret, usize)> {
         // create a permutation of size
         let size = encryptions.len();
-        let permutation = Random::generate_permutation(&size);
+        let permutation = Random::generate_permutation(&size, rng);
 
         // create {size} random values < q
-        let mut randoms: Vec<BigUint> = Vec::new();
+        let mut randoms: Vec<Secret> = Vec::new();
 
         for _ in 0..size {
-            randoms.push(Random::get_random_less_than(&q));
+            randoms.push(Secret::new(Random::get_random_less_than(q, rng)));
         }
 
         // shuffle (permute + re-encrypt) the encryptions
@@ -117,10 +137,10 @@ impl Random {
     ///
     /// Arguments
     /// * `size` - size of the permuatation
-    pub fn generate_permutation(size: &usize) -> Vec<usize> {
+    /// * `rng` - the random number generator
+    pub fn generate_permutation<R: RngCore + CryptoRng>(size: &usize, rng: &mut R) -> Vec<usize> {
         assert!(*size > 0, "size must be greater than zero!");
 
-        let mut rng = rand::thread_rng();
         let mut permutation: Vec<usize> = Vec::new();
 
         // vector containing the range of values from 0 up to the size of the vector - 1
@@ -154,13 +174,13 @@ impl Random {
     ///
     /// Arguments
     /// * `number` - upper limit
-    pub fn get_random_less_than(number: &BigUint) -> BigUint {
+    /// * `rng` - the random number generator
+    pub fn get_random_less_than<R: RngCore + CryptoRng>(number: &BigUint, rng: &mut R) -> BigUint {
         assert!(*number > BigUint::zero(), "q must be greater than zero!");
         let one = BigUint::one();
         let upper_bound = number.clone().sub(one);
         let bit_size: u64 = upper_bound.bits();
 
-        let mut rng = rand::thread_rng();
         rng.gen_biguint(bit_size) % number
     }
 
@@ -168,8 +188,8 @@ impl Random {
     ///
     /// Arguments
     /// * `bit_size` - size of prime
-    pub fn generate_random_prime(bit_size: u64) -> BigUint {
-        let mut rng = rand::thread_rng();
+    /// * `rng` - the random number generator
+    pub fn generate_random_prime<R: RngCore + CryptoRng>(bit_size: u64, rng: &mut R) -> BigUint {
         let mut candidate = rng.gen_biguint(bit_size);
         let two = BigUint::from(2u32);
 
@@ -177,7 +197,7 @@ impl Random {
             candidate.add_assign(BigUint::one())
         }
 
-        while !Self::is_prime(&candidate, 128) {
+        while !Self::is_prime(&candidate, 128, rng) {
             candidate.add_assign(two.clone());
         }
         candidate
@@ -186,7 +206,7 @@ impl Random {
     /// Miller-Rabin Primality Test
     ///
     /// https://en.wikipedia.org/wiki/Miller-Rabin_primality_test
-    pub fn is_prime(num: &BigUint, certainty: u32) -> bool {
+    pub fn is_prime<R: RngCore + CryptoRng>(num: &BigUint, certainty: u32, rng: &mut R) -> bool {
         let zero: BigUint = BigUint::zero();
         let one: BigUint = BigUint::one();
         let two = one.clone() + one.clone();
@@ -211,7 +231,6 @@ impl Random {
         }
 
         let mut k = 0;
-        let mut rng = rand::thread_rng();
 
         // test for probable prime
         while k < certainty {
@@ -241,51 +260,77 @@ mod tests {
     use crate::helper::Helper;
     use crate::random::Random;
     use num_bigint::BigUint;
+    use rand::{rngs::StdRng, SeedableRng};
     use std::vec::Vec;
 
     #[test]
     fn it_should_generate_random_number() {
+        let mut rng = StdRng::seed_from_u64(42);
         let number = BigUint::parse_bytes(b"123", 10).unwrap();
         for _ in 0..20 {
-            let random = Random::get_random_less_than(&number);
+            let random = Random::get_random_less_than(&number, &mut rng);
             assert!(random < number);
         }
     }
 
+    #[test]
+    fn it_should_generate_the_same_values_from_the_same_seed() {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = params.q();
+        let encryptions =
+            Random::generate_random_encryptions(&pk, &q, 3, &mut StdRng::seed_from_u64(7));
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let random = Random::get_random_less_than(&q, &mut rng);
+        let shuffle = Random::generate_shuffle(&pk, &q, encryptions.clone(), &mut rng);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(Random::get_random_less_than(&q, &mut rng), random);
+        assert_eq!(
+            Random::generate_shuffle(&pk, &q, encryptions, &mut rng),
+            shuffle
+        );
+    }
+
     #[test]
     fn check_that_2_is_prime() {
+        let mut rng = StdRng::seed_from_u64(42);
         let number = BigUint::parse_bytes(b"2", 10).unwrap();
-        let is_prime = Random::is_prime(&number, 20);
+        let is_prime = Random::is_prime(&number, 20, &mut rng);
         assert!(is_prime);
     }
 
     #[test]
     fn check_that_11_is_prime() {
+        let mut rng = StdRng::seed_from_u64(42);
         let number = BigUint::from(11u32);
-        let is_prime = Random::is_prime(&number, 20);
+        let is_prime = Random::is_prime(&number, 20, &mut rng);
         assert!(is_prime);
     }
 
     #[test]
     fn check_that_84532559_is_prime() {
+        let mut rng = StdRng::seed_from_u64(42);
         let number = BigUint::parse_bytes(b"84532559", 10).unwrap();
-        let is_prime = Random::is_prime(&number, 20);
+        let is_prime = Random::is_prime(&number, 20, &mut rng);
         assert!(is_prime);
     }
 
     #[test]
     fn check_that_84532560_is_not_prime() {
+        let mut rng = StdRng::seed_from_u64(42);
         let number = BigUint::parse_bytes(b"84532560", 10).unwrap();
-        let is_prime = Random::is_prime(&number, 20);
+        let is_prime = Random::is_prime(&number, 20, &mut rng);
         assert!(!is_prime);
     }
 
     #[test]
     fn it_should_generate_a_random_prime() {
+        let mut rng = StdRng::seed_from_u64(42);
         let bit_size = 256;
         let byte_size = 32;
 
-        let prime = Random::generate_random_prime(bit_size);
+        let prime = Random::generate_random_prime(bit_size, &mut rng);
 
         // check that the prime is in range bit_size - 8 <= prime <= bit_size
         assert!(prime.bits().le(&bit_size));
@@ -294,21 +339,23 @@ mod tests {
         // check that the prime has the same number of bytes as requested
         assert!(prime.to_bytes_le().len() == byte_size);
 
-        let is_prime = Random::is_prime(&prime, 128);
+        let is_prime = Random::is_prime(&prime, 128, &mut rng);
         assert!(is_prime);
     }
 
     #[test]
     #[should_panic(expected = "size must be greater than zero!")]
     fn permutation_size_zero_should_panic() {
+        let mut rng = StdRng::seed_from_u64(42);
         let size = 0;
-        Random::generate_permutation(&size);
+        Random::generate_permutation(&size, &mut rng);
     }
 
     #[test]
     fn it_should_generate_a_permutation_for_three_numbers() {
+        let mut rng = StdRng::seed_from_u64(42);
         let size = 3;
-        let permutation = Random::generate_permutation(&size);
+        let permutation = Random::generate_permutation(&size, &mut rng);
 
         // check that the permutation has the expected size
         assert!(permutation.len() == (size as usize));
@@ -321,29 +368,32 @@ mod tests {
 
     #[test]
     fn it_should_generate_a_random_encryptions_encoded() {
+        let mut rng = StdRng::seed_from_u64(42);
         let number = 2usize;
         let (params, _, pk) = Helper::setup_sm_system();
         let q = params.q();
-        let encryptions = Random::generate_random_encryptions_encoded(&pk, &q, number);
+        let encryptions = Random::generate_random_encryptions_encoded(&pk, &q, number, &mut rng);
         assert_eq!(encryptions.len(), number);
     }
 
     #[test]
     fn it_should_generate_a_random_encryptions() {
+        let mut rng = StdRng::seed_from_u64(42);
         let number = 2usize;
         let (params, _, pk) = Helper::setup_sm_system();
         let q = params.q();
-        let encryptions = Random::generate_random_encryptions(&pk, &q, number);
+        let encryptions = Random::generate_random_encryptions(&pk, &q, number, &mut rng);
         assert_eq!(encryptions.len(), number);
     }
 
     #[test]
     fn it_should_generate_an_encryption_of_one_and_two() {
+        let mut rng = StdRng::seed_from_u64(42);
         let votes = vec![1u32, 2u32];
         let number = 2usize;
         let (params, sk, pk) = Helper::setup_sm_system();
         let q = params.q();
-        let encryptions = Random::generate_encryptions(&pk, &q, number, votes.clone());
+        let encryptions = Random::generate_encryptions(&pk, &q, number, votes.clone(), &mut rng);
         let decryptions = encryptions
             .iter()
             .map(|cipher| ElGamal::decrypt(cipher, &sk))
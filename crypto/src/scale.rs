@@ -0,0 +1,405 @@
+use crate::{
+    proofs::{
+        decryption::DecryptionProof, keygen::KeyGenerationProof, re_encryption::ReEncryptionProof,
+    },
+    types::{BigRowS, BigRowShuffleProof, BigS, BigShuffleProof, Cipher, ElGamalParams, PublicKey},
+};
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_traits::Zero;
+use parity_scale_codec::{Compact, Decode, Encode, EncodeLike, Error, Input, Output};
+
+/// Returns the value of its canonical encoding: the big-endian bytes without leading zeros,
+/// i.e. the output of `BigUint::to_bytes_be` (zero is encoded as `[0]`).
+/// Any other byte form of a value (empty, leading zeros) is rejected.
+/// Only the modulus p is encoded this way, it defines the width of all other values.
+pub fn decode_biguint(bytes: &[u8]) -> Result<BigUint, Error> {
+    match bytes {
+        [] => Err("empty encoding of a BigUint".into()),
+        [0, _, ..] => Err("non-canonical encoding of a BigUint: leading zeros".into()),
+        _ => Ok(BigUint::from_bytes_be(bytes)),
+    }
+}
+
+/// Encodes a type using the fixed width of the parameters of the vote.
+///
+/// Every group element and exponent is encoded as SCALE `Vec<u8>` of its canonical
+/// encoding (see `ElGamalParams::encode_value`), the fields one after the other (in declaration order).
+/// Therefore, the encoding of a type is the same as the encoding of a struct with
+/// `Vec<u8>` fields which hold the output of `ElGamalParams::encode_value`.
+pub trait EncodeWith {
+    fn encode_with_to<W: Output>(&self, params: &ElGamalParams, dest: &mut W);
+
+    fn encode_with(&self, params: &ElGamalParams) -> Vec<u8> {
+        let mut dest: Vec<u8> = Vec::new();
+        self.encode_with_to(params, &mut dest);
+        dest
+    }
+}
+
+/// Decodes a type which has been encoded using the parameters of the vote (see `EncodeWith`).
+///
+/// Values which are not ⌈|p|/8⌉ bytes wide, group elements ≥ p and exponents ≥ q are rejected.
+/// Therefore, every value has exactly one encoding.
+pub trait DecodeWith: Sized {
+    fn decode_with<I: Input>(input: &mut I, params: &ElGamalParams) -> Result<Self, Error>;
+}
+
+/// The encoding of a field of the crypto types (a number, a list or a nested type).
+trait Field {
+    type Value;
+
+    fn encode_field<W: Output>(value: &Self::Value, params: &ElGamalParams, dest: &mut W);
+
+    fn decode_field<I: Input>(input: &mut I, params: &ElGamalParams) -> Result<Self::Value, Error>;
+}
+
+/// A group element: a value < p.
+struct Element;
+
+/// An exponent: a value < q (e.g. a challenge or a response).
+struct Exponent;
+
+impl Field for Element {
+    type Value = BigUint;
+
+    fn encode_field<W: Output>(value: &BigUint, params: &ElGamalParams, dest: &mut W) {
+        params.encode_value(value).encode_to(dest);
+    }
+
+    fn decode_field<I: Input>(input: &mut I, params: &ElGamalParams) -> Result<BigUint, Error> {
+        params
+            .decode_less_than(&Vec::<u8>::decode(input)?, &params.p)
+            .ok_or_else(|| "invalid encoding of a group element".into())
+    }
+}
+
+impl Field for Exponent {
+    type Value = BigUint;
+
+    fn encode_field<W: Output>(value: &BigUint, params: &ElGamalParams, dest: &mut W) {
+        params.encode_value(value).encode_to(dest);
+    }
+
+    fn decode_field<I: Input>(input: &mut I, params: &ElGamalParams) -> Result<BigUint, Error> {
+        params
+            .decode_scalar(&Vec::<u8>::decode(input)?)
+            .ok_or_else(|| "invalid encoding of an exponent".into())
+    }
+}
+
+impl<T: Field> Field for Vec<T> {
+    type Value = Vec<T::Value>;
+
+    fn encode_field<W: Output>(values: &Vec<T::Value>, params: &ElGamalParams, dest: &mut W) {
+        Compact(values.len() as u32).encode_to(dest);
+        values
+            .iter()
+            .for_each(|value| T::encode_field(value, params, dest));
+    }
+
+    fn decode_field<I: Input>(
+        input: &mut I,
+        params: &ElGamalParams,
+    ) -> Result<Vec<T::Value>, Error> {
+        // the length is not trusted: no allocation upfront
+        let length = <Compact<u32>>::decode(input)?.0;
+        (0..length)
+            .map(|_| T::decode_field(input, params))
+            .collect()
+    }
+}
+
+/// Implements `EncodeWith`, `DecodeWith` (and `Field`) for a struct by encoding its fields.
+/// The type of a field defines its encoding: `Element`, `Exponent`, a list or a nested type.
+macro_rules! impl_codec {
+    ($($type:ident { $($field:ident: $kind:ty),+ $(,)? }),+ $(,)?) => {$(
+        impl EncodeWith for $type {
+            fn encode_with_to<W: Output>(&self, params: &ElGamalParams, dest: &mut W) {
+                $(<$kind as Field>::encode_field(&self.$field, params, dest);)+
+            }
+        }
+
+        impl DecodeWith for $type {
+            fn decode_with<I: Input>(input: &mut I, params: &ElGamalParams) -> Result<Self, Error> {
+                // the fields of a struct expression are evaluated in order
+                Ok($type {
+                    $($field: <$kind as Field>::decode_field(input, params)?,)+
+                })
+            }
+        }
+
+        impl Field for $type {
+            type Value = Self;
+
+            fn encode_field<W: Output>(value: &Self, params: &ElGamalParams, dest: &mut W) {
+                value.encode_with_to(params, dest);
+            }
+
+            fn decode_field<I: Input>(input: &mut I, params: &ElGamalParams) -> Result<Self, Error> {
+                Self::decode_with(input, params)
+            }
+        }
+    )+};
+}
+
+impl_codec! {
+    Cipher { a: Element, b: Element },
+    KeyGenerationProof { challenge: Exponent, response: Exponent },
+    DecryptionProof { challenge: Exponent, response: Exponent },
+    ReEncryptionProof {
+        c_one_prime: Cipher,
+        challenge: Exponent,
+        h1: Exponent,
+        h2: Exponent,
+        s2: Exponent,
+        t2: Element,
+    },
+    BigS {
+        s1: Exponent,
+        s2: Exponent,
+        s3: Exponent,
+        s4: Exponent,
+        vec_s_hat: Vec<Exponent>,
+        vec_s_tilde: Vec<Exponent>,
+    },
+    BigShuffleProof {
+        challenge: Exponent,
+        S: BigS,
+        permutation_commitments: Vec<Element>,
+        permutation_chain_commitments: Vec<Element>,
+    },
+    BigRowS {
+        s1: Exponent,
+        s2: Exponent,
+        s3: Exponent,
+        vec_s4: Vec<Exponent>,
+        vec_s_hat: Vec<Exponent>,
+        vec_s_tilde: Vec<Exponent>,
+    },
+    BigRowShuffleProof {
+        challenge: Exponent,
+        S: BigRowS,
+        permutation_commitments: Vec<Element>,
+        permutation_chain_commitments: Vec<Element>,
+    },
+}
+
+/// The parameters define the width of their generators: p is encoded canonically
+/// (see `decode_biguint`), g and h as group elements with the width of p.
+impl Encode for ElGamalParams {
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        self.p.to_bytes_be().encode_to(dest);
+        Element::encode_field(&self.g, self, dest);
+        Element::encode_field(&self.h, self, dest);
+    }
+}
+
+impl EncodeLike for ElGamalParams {}
+
+impl Decode for ElGamalParams {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let mut params = ElGamalParams {
+            p: decode_biguint(&Vec::<u8>::decode(input)?)?,
+            g: BigUint::zero(),
+            h: BigUint::zero(),
+        };
+        params.g = Element::decode_field(input, &params)?;
+        params.h = Element::decode_field(input, &params)?;
+        Ok(params)
+    }
+}
+
+/// A public key is encoded with its parameters, h as group element with the width of p.
+impl Encode for PublicKey {
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        self.params.encode_to(dest);
+        Element::encode_field(&self.h, &self.params, dest);
+    }
+}
+
+impl EncodeLike for PublicKey {}
+
+impl Decode for PublicKey {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let params = ElGamalParams::decode(input)?;
+        let h = Element::decode_field(input, &params)?;
+        Ok(PublicKey { params, h })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        encryption::ElGamal,
+        helper::{Helper, ProofContext},
+        proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof},
+        random::Random,
+        scale::{decode_biguint, DecodeWith, EncodeWith},
+        types::{BigS, BigShuffleProof, Cipher, ElGamalParams, PublicKey, Secret},
+    };
+    use alloc::{vec, vec::Vec};
+    use num_bigint::BigUint;
+    use num_traits::Zero;
+    use parity_scale_codec::{Decode, Encode};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn it_should_decode_canonical_biguints() {
+        assert_eq!(decode_biguint(&[0]), Ok(BigUint::zero()));
+        assert_eq!(decode_biguint(&[1, 0]), Ok(BigUint::from(256u32)));
+        assert!(decode_biguint(&[]).is_err());
+        assert!(decode_biguint(&[0, 1]).is_err());
+        assert!(decode_biguint(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn it_should_encode_and_decode_ciphers_and_keys() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, _, pk) = Helper::setup_sm_system();
+        let r = Random::get_random_less_than(&params.q(), &mut rng);
+        let cipher = ElGamal::encrypt(&BigUint::from(3u32), &r, &pk);
+
+        // same encoding as the fixed width bytes of the values
+        let encoded = cipher.encode_with(&params);
+        assert_eq!(
+            encoded,
+            (
+                params.encode_value(&cipher.a),
+                params.encode_value(&cipher.b)
+            )
+                .encode()
+        );
+        assert_eq!(Cipher::decode_with(&mut &encoded[..], &params), Ok(cipher));
+        assert_eq!(PublicKey::decode(&mut &pk.encode()[..]), Ok(pk.clone()));
+        assert_eq!(
+            ElGamalParams::decode(&mut &params.encode()[..]),
+            Ok(params.clone())
+        );
+
+        // the generators of the parameters are encoded with the width of p
+        let encoded = params.encode();
+        let bytes = (
+            params.p.to_bytes_be(),
+            params.encode_value(&params.g),
+            params.encode_value(&params.h),
+        );
+        assert_eq!(encoded, bytes.encode());
+    }
+
+    #[test]
+    fn it_should_reject_values_of_another_width_or_out_of_range() {
+        let (params, _, _) = Helper::setup_tiny_system();
+        let decode = |a: Vec<u8>, b: Vec<u8>| {
+            let encoded = (a, b).encode();
+            Cipher::decode_with(&mut &encoded[..], &params)
+        };
+        assert_eq!(
+            decode(vec![2u8], vec![3u8]),
+            Ok(Cipher {
+                a: BigUint::from(2u32),
+                b: BigUint::from(3u32),
+            })
+        );
+
+        // the values of the tiny system are 1 byte wide
+        assert!(decode(vec![0u8, 2u8], vec![3u8]).is_err());
+        assert!(decode(vec![2u8], vec![]).is_err());
+
+        // group elements ≥ p (47) are rejected
+        assert!(decode(vec![47u8], vec![3u8]).is_err());
+        assert!(decode(vec![2u8], vec![255u8]).is_err());
+
+        // exponents ≥ q (23) are rejected
+        let decode = |challenge: u8, response: u8| {
+            let encoded = (vec![challenge], vec![response]).encode();
+            DecryptionProof::decode_with(&mut &encoded[..], &params)
+        };
+        assert!(decode(22u8, 0u8).is_ok());
+        assert!(decode(23u8, 0u8).is_err());
+        assert!(decode(0u8, 46u8).is_err());
+
+        // the generators of the parameters are checked against p
+        let bytes = (
+            params.p.to_bytes_be(),
+            vec![47u8],
+            params.encode_value(&params.h),
+        );
+        assert!(ElGamalParams::decode(&mut &bytes.encode()[..]).is_err());
+        let bytes = (params.p.to_bytes_be(), vec![0u8, 2u8], vec![3u8]);
+        assert!(ElGamalParams::decode(&mut &bytes.encode()[..]).is_err());
+    }
+
+    #[test]
+    fn it_should_encode_and_decode_proofs() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+        let context = ProofContext::new(b"2020-12-12_01");
+
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, b"Bob", &context);
+        let decoded =
+            KeyGenerationProof::decode_with(&mut &proof.encode_with(&params)[..], &params);
+        assert_eq!(decoded, Ok(proof));
+
+        let encryptions = Random::generate_random_encryptions(&pk, q, 3, &mut rng);
+        let decryptions = encryptions
+            .iter()
+            .map(|cipher| ElGamal::partial_decrypt_a(cipher, &sk))
+            .collect::<Vec<BigUint>>();
+        let r = Secret::new(Random::get_random_less_than(q, &mut rng));
+        let proof = DecryptionProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            encryptions,
+            decryptions,
+            b"Bob",
+            &context,
+        );
+        let encoded = proof.encode_with(&params);
+        let bytes = (
+            params.encode_value(&proof.challenge),
+            params.encode_value(&proof.response),
+        );
+        assert_eq!(encoded, bytes.encode());
+        assert_eq!(
+            DecryptionProof::decode_with(&mut &encoded[..], &params),
+            Ok(proof)
+        );
+    }
+
+    #[test]
+    fn it_should_encode_and_decode_shuffle_proofs() {
+        let (params, _, _) = Helper::setup_sm_system();
+        let values = |n: u32| (1..=n).map(BigUint::from).collect::<Vec<BigUint>>();
+        let proof = BigShuffleProof {
+            challenge: BigUint::from(7u32),
+            S: BigS {
+                s1: BigUint::from(1u32),
+                s2: BigUint::from(2u32),
+                s3: BigUint::zero(),
+                s4: BigUint::from(4u32),
+                vec_s_hat: values(3),
+                vec_s_tilde: values(3),
+            },
+            permutation_commitments: values(3),
+            permutation_chain_commitments: Vec::new(),
+        };
+        let encoded = proof.encode_with(&params);
+        assert_eq!(
+            BigShuffleProof::decode_with(&mut &encoded[..], &params),
+            Ok(proof)
+        );
+
+        // a truncated encoding is rejected
+        let truncated = &encoded[..encoded.len() - 1];
+        assert!(BigShuffleProof::decode_with(&mut &truncated[..], &params).is_err());
+
+        // the encoding depends on the width of the parameters
+        let (tiny_params, _, _) = Helper::setup_tiny_system();
+        assert!(BigShuffleProof::decode_with(&mut &encoded[..], &tiny_params).is_err());
+    }
+}
@@ -1,13 +1,19 @@
-use alloc::vec::Vec;
-use core::ops::{Add, Div, Mul, Sub};
+use alloc::{vec, vec::Vec};
+use core::{
+    cmp, fmt,
+    ops::{Add, Div, Mul, Sub},
+};
 use num_bigint::{BigInt, BigUint};
 use num_traits::{One, Zero};
+use zeroize::Zeroizing;
 
-#[cfg(feature = "std")]
+#[cfg(feature = "constant-time")]
+use crate::constant_time::ConstantTime;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ElGamalParams {
     // modulus: p
     pub p: BigUint,
@@ -25,10 +31,75 @@ impl ElGamalParams {
     pub fn q(&self) -> BigUint {
         (self.p.clone().sub(BigUint::one())).div(BigUint::from(2u32))
     }
+
+    /// Returns the width of the canonical encoding of a value: ⌈|p|/8⌉ bytes.
+    pub fn byte_width(&self) -> usize {
+        self.p.to_bytes_be().len()
+    }
+
+    /// Returns the canonical encoding of a group element or an exponent (i.e. a value < p):
+    /// its big-endian bytes padded with leading zeros to the fixed width of ⌈|p|/8⌉ bytes.
+    /// Therefore, every value has exactly one encoding.
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The value to encode (BigUint), must be less than p
+    pub fn encode_value(&self, value: &BigUint) -> Vec<u8> {
+        assert!(value < &self.p, "only values less than p can be encoded!");
+        let bytes = value.to_bytes_be();
+        let mut encoded = vec![0u8; self.byte_width() - bytes.len()];
+        encoded.extend(bytes);
+        encoded
+    }
+
+    /// Returns true if the value is an element of the group G_q: 0 < x < p and x^q ≡ 1 (mod p).
+    ///
+    /// ## Arguments
+    ///
+    /// * `value` - The value to check (BigUint)
+    pub fn is_element(&self, value: &BigUint) -> bool {
+        !value.is_zero() && value < &self.p && value.modpow(&self.q(), &self.p) == BigUint::one()
+    }
+
+    /// Decodes a group element from its canonical encoding (see `encode_value`).
+    /// Returns `None` if the encoding is not ⌈|p|/8⌉ bytes wide or the value is not an element of G_q
+    /// (see `is_element`), i.e. it is 0, not less than p or not a quadratic residue.
+    ///
+    /// ## Arguments
+    ///
+    /// * `bytes` - The canonical encoding of the group element
+    pub fn decode_element(&self, bytes: &[u8]) -> Option<BigUint> {
+        self.decode_less_than(bytes, &self.p)
+            .filter(|value| self.is_element(value))
+    }
+
+    /// Decodes an exponent from its canonical encoding (see `encode_value`).
+    /// Returns `None` if the encoding is not ⌈|p|/8⌉ bytes wide or the value is not less than q.
+    ///
+    /// ## Arguments
+    ///
+    /// * `bytes` - The canonical encoding of the exponent
+    pub fn decode_scalar(&self, bytes: &[u8]) -> Option<BigUint> {
+        self.decode_less_than(bytes, &self.q())
+    }
+
+    /// Decodes a value from its canonical encoding (see `encode_value`).
+    /// Returns `None` if the encoding is not ⌈|p|/8⌉ bytes wide or the value is not less than `upper`.
+    pub(crate) fn decode_less_than(&self, bytes: &[u8], upper: &BigUint) -> Option<BigUint> {
+        if bytes.len() != self.byte_width() {
+            return None;
+        }
+        let value = BigUint::from_bytes_be(bytes);
+        if &value < upper {
+            Some(value)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PublicKey {
     // system parameters (p, g)
     pub params: ElGamalParams,
@@ -63,18 +134,72 @@ impl PublicKey {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct PrivateKey {
     // system parameters (p, g)
     pub params: ElGamalParams,
 
     // private key: x
     // - x: a random value (x ∈ Zq)
-    pub x: BigUint,
+    pub x: Secret,
+}
+
+/// A secret value, e.g. a private key, a nonce of a proof or a re-encryption random.
+///
+/// The value is kept in a buffer owned by the secret (little-endian bytes) which is
+/// overwritten with zeros when it is dropped (see `zeroize::Zeroizing`), also for clones.
+/// Two values are compared in constant time and `Debug` never prints the value.
+/// The value is only converted into a `BigUint` at the point of use (see `expose`),
+/// these temporary values are not overwritten.
+#[derive(Clone)]
+pub struct Secret(Zeroizing<Vec<u8>>);
+
+impl Secret {
+    /// Moves the value into the buffer of the secret.
+    /// The digits of the consumed `BigUint` are not overwritten.
+    pub fn new(value: BigUint) -> Self {
+        Secret(Zeroizing::new(value.to_bytes_le()))
+    }
+
+    /// Returns the secret value as a `BigUint` for the operation which uses it.
+    pub fn expose(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.0)
+    }
+}
+
+impl Default for Secret {
+    fn default() -> Self {
+        Secret::new(BigUint::zero())
+    }
+}
+
+impl PartialEq for Secret {
+    /// Compares all bytes of both values, the running time only depends on their lengths.
+    fn eq(&self, other: &Self) -> bool {
+        let mut difference: u8 = 0;
+        for i in 0..cmp::max(self.0.len(), other.0.len()) {
+            difference |= self.0.get(i).unwrap_or(&0) ^ other.0.get(i).unwrap_or(&0);
+        }
+        difference == 0
+    }
+}
+
+impl Eq for Secret {}
+
+impl From<BigUint> for Secret {
+    fn from(value: BigUint) -> Self {
+        Secret::new(value)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
 }
 
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cipher {
     // a = g^r mod p
     // - g: generator
@@ -112,6 +237,71 @@ pub type BigT = (
     Vec<BigUint>, // vec_t_hat
 );
 
+/// Algorithm 8.47: The s value of the ShuffleProof
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BigS {
+    pub s1: BigUint,               // s1
+    pub s2: BigUint,               // s2
+    pub s3: BigUint,               // s3
+    pub s4: BigUint,               // s4
+    pub vec_s_hat: Vec<BigUint>,   // vec_s_hat
+    pub vec_s_tilde: Vec<BigUint>, // vec_s_tilde
+}
+
+/// Algorithm 8.47: The ShuffleProof
+#[allow(non_snake_case)]
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BigShuffleProof {
+    pub challenge: BigUint,                          // challenge
+    pub S: BigS,                                     // S
+    pub permutation_commitments: Vec<BigUint>,       // permutation_commitments
+    pub permutation_chain_commitments: Vec<BigUint>, // permutation_chain_commitments
+}
+
+/// Algorithm 8.47: The public value Y of a shuffle of rows (width w)
+pub type BigYRows<'a> = (
+    Vec<Vec<Cipher>>, // e
+    Vec<Vec<Cipher>>, // e_tilde
+    Vec<BigUint>,     // vec_c
+    Vec<BigUint>,     // vec_c_hat
+    &'a BigUint,      // public key: the value h of pk
+);
+
+/// Algorithm 8.47: The public commitment t of a shuffle of rows (width w)
+pub type BigTRows = (
+    BigUint,                 // t1
+    BigUint,                 // t2
+    BigUint,                 // t3
+    Vec<(BigUint, BigUint)>, // (t4_1, t4_2) per column
+    Vec<BigUint>,            // vec_t_hat
+);
+
+/// Algorithm 8.47: The s value of the ShuffleProof of rows (width w).
+/// the value s4 is computed per column.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BigRowS {
+    pub s1: BigUint,               // s1
+    pub s2: BigUint,               // s2
+    pub s3: BigUint,               // s3
+    pub vec_s4: Vec<BigUint>,      // vec_s4
+    pub vec_s_hat: Vec<BigUint>,   // vec_s_hat
+    pub vec_s_tilde: Vec<BigUint>, // vec_s_tilde
+}
+
+/// Algorithm 8.47: The ShuffleProof of rows (width w)
+#[allow(non_snake_case)]
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BigRowShuffleProof {
+    pub challenge: BigUint,                          // challenge
+    pub S: BigRowS,                                  // S
+    pub permutation_commitments: Vec<BigUint>,       // permutation_commitments
+    pub permutation_chain_commitments: Vec<BigUint>, // permutation_chain_commitments
+}
+
 pub trait ModuloOperations {
     /// Calculates the modular multiplicative of a BigUint: result = self * rhs % modulus.
     fn modmul(&self, rhs: &Self, modulus: &Self) -> Self;
@@ -129,8 +319,27 @@ pub trait ModuloOperations {
     /// Alternative formulation: a^-1 (mod m)
     fn invmod(&self, modulus: &Self) -> Option<BigUint>;
     // fn extended_gcd(a: &BigUint, b: &BigUint) -> (BigUint, BigUint, BigUint);
+
+    /// Calculates the modular exponentiation with a secret exponent (e.g. a private key or a nonce):
+    /// result = self^exponent % modulus.
+    /// With the feature `constant-time`, the running time does not depend on the values.
+    fn modpow_secret(&self, exponent: &Self, modulus: &Self) -> Self;
+
+    /// Calculates `modmul` of secret values, in constant time with the feature `constant-time`.
+    fn modmul_secret(&self, rhs: &Self, modulus: &Self) -> Self;
+
+    /// Calculates `modadd` of secret values, in constant time with the feature `constant-time`.
+    fn modadd_secret(&self, other: &Self, modulus: &Self) -> Self;
+
+    /// Calculates `modsub` of secret values, in constant time with the feature `constant-time`.
+    fn modsub_secret(&self, other: &Self, modulus: &Self) -> Self;
 }
 
+/// The secret operations fail closed: they never fall back to the variable-time operations.
+#[cfg(feature = "constant-time")]
+const CONSTANT_TIME_UNSUPPORTED: &str =
+    "the constant-time backend does not support the modulus or the operands!";
+
 impl ModuloOperations for BigUint {
     fn modmul(&self, multiplier: &Self, modulus: &Self) -> Self {
         assert!(
@@ -193,6 +402,46 @@ impl ModuloOperations for BigUint {
             result.to_biguint()
         }
     }
+
+    #[cfg(feature = "constant-time")]
+    fn modpow_secret(&self, exponent: &Self, modulus: &Self) -> Self {
+        ConstantTime::modpow(self, exponent, modulus).expect(CONSTANT_TIME_UNSUPPORTED)
+    }
+
+    #[cfg(not(feature = "constant-time"))]
+    fn modpow_secret(&self, exponent: &Self, modulus: &Self) -> Self {
+        self.modpow(exponent, modulus)
+    }
+
+    #[cfg(feature = "constant-time")]
+    fn modmul_secret(&self, rhs: &Self, modulus: &Self) -> Self {
+        ConstantTime::modmul(self, rhs, modulus).expect(CONSTANT_TIME_UNSUPPORTED)
+    }
+
+    #[cfg(not(feature = "constant-time"))]
+    fn modmul_secret(&self, rhs: &Self, modulus: &Self) -> Self {
+        self.modmul(rhs, modulus)
+    }
+
+    #[cfg(feature = "constant-time")]
+    fn modadd_secret(&self, other: &Self, modulus: &Self) -> Self {
+        ConstantTime::modadd(self, other, modulus).expect(CONSTANT_TIME_UNSUPPORTED)
+    }
+
+    #[cfg(not(feature = "constant-time"))]
+    fn modadd_secret(&self, other: &Self, modulus: &Self) -> Self {
+        self.modadd(other, modulus)
+    }
+
+    #[cfg(feature = "constant-time")]
+    fn modsub_secret(&self, other: &Self, modulus: &Self) -> Self {
+        ConstantTime::modsub(self, other, modulus).expect(CONSTANT_TIME_UNSUPPORTED)
+    }
+
+    #[cfg(not(feature = "constant-time"))]
+    fn modsub_secret(&self, other: &Self, modulus: &Self) -> Self {
+        self.modsub(other, modulus)
+    }
 }
 
 fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
@@ -209,9 +458,9 @@ fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
 mod tests {
     use crate::{
         helper::Helper,
-        types::{ElGamalParams, ModuloOperations, PrivateKey, PublicKey},
+        types::{ElGamalParams, ModuloOperations, PrivateKey, PublicKey, Secret},
     };
-    use alloc::vec::Vec;
+    use alloc::{format, vec::Vec};
     use num_bigint::BigUint;
     use num_traits::Zero;
 
@@ -229,6 +478,82 @@ mod tests {
         assert_eq!(expected_q, q);
     }
 
+    #[test]
+    fn it_should_encode_values_with_a_fixed_width() {
+        // p = 263 -> two bytes, q = 131
+        let params = ElGamalParams {
+            p: BigUint::from(263u32),
+            g: BigUint::from(4u32),
+            h: BigUint::from(9u32),
+        };
+        assert_eq!(params.byte_width(), 2);
+        assert_eq!(params.encode_value(&BigUint::zero()), [0u8, 0u8]);
+        assert_eq!(params.encode_value(&BigUint::from(7u32)), [0u8, 7u8]);
+        assert_eq!(params.encode_value(&BigUint::from(262u32)), [1u8, 6u8]);
+
+        let value = BigUint::from(200u32);
+        let encoded = params.encode_value(&value);
+        assert_eq!(params.decode_element(&encoded), Some(value));
+    }
+
+    #[test]
+    fn it_should_check_group_membership() {
+        let params = ElGamalParams {
+            p: BigUint::from(263u32),
+            g: BigUint::from(4u32),
+            h: BigUint::from(9u32),
+        };
+        assert!(params.is_element(&BigUint::from(1u32)));
+        assert!(params.is_element(&BigUint::from(4u32)));
+        assert!(params.is_element(&BigUint::from(9u32)));
+
+        // 0, p - 1 (order 2) and values ≥ p are not elements of G_q
+        assert!(!params.is_element(&BigUint::zero()));
+        assert!(!params.is_element(&BigUint::from(262u32)));
+        assert!(!params.is_element(&BigUint::from(263u32)));
+        assert!(!params.is_element(&BigUint::from(267u32)));
+    }
+
+    #[test]
+    fn it_should_reject_non_canonical_encodings() {
+        let params = ElGamalParams {
+            p: BigUint::from(263u32),
+            g: BigUint::from(4u32),
+            h: BigUint::from(9u32),
+        };
+        // variable length: without or with additional leading zeros
+        assert_eq!(params.decode_element(&[7u8]), None);
+        assert_eq!(params.decode_element(&[0u8, 0u8, 7u8]), None);
+        assert_eq!(params.decode_element(&[]), None);
+
+        // out of range: values ≥ p (elements) or ≥ q (exponents)
+        assert_eq!(params.decode_element(&[1u8, 7u8]), None);
+        assert_eq!(params.decode_scalar(&[0u8, 131u8]), None);
+
+        // in range but not an element of G_q: 0 and p - 1
+        assert_eq!(params.decode_element(&[0u8, 0u8]), None);
+        assert_eq!(params.decode_element(&[1u8, 6u8]), None);
+        assert_eq!(
+            params.decode_element(&[0u8, 9u8]),
+            Some(BigUint::from(9u32))
+        );
+        assert_eq!(
+            params.decode_scalar(&[0u8, 130u8]),
+            Some(BigUint::from(130u32))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only values less than p can be encoded!")]
+    fn it_should_not_encode_values_greater_than_p() {
+        let params = ElGamalParams {
+            p: BigUint::from(263u32),
+            g: BigUint::from(4u32),
+            h: BigUint::from(9u32),
+        };
+        params.encode_value(&BigUint::from(263u32));
+    }
+
     #[test]
     fn it_should_create_a_public_key() {
         let params = ElGamalParams {
@@ -269,14 +594,45 @@ mod tests {
         let sk = PrivateKey {
             params: params.clone(),
             // x: a random value
-            x: r.clone(),
+            x: Secret::new(r.clone()),
         };
 
-        assert_eq!(sk.x, BigUint::from(2u32));
+        assert_eq!(sk.x.expose(), BigUint::from(2u32));
         assert_eq!(sk.params.g, BigUint::from(2u32));
         assert_eq!(sk.params.p, BigUint::from(7u32));
     }
 
+    #[test]
+    fn it_should_expose_a_secret() {
+        let value: BigUint = (BigUint::from(0x1234_5678u32) << 100usize) + 42u32;
+        let secret = Secret::new(value.clone());
+        assert_eq!(secret.expose(), value);
+        assert_eq!(secret.clone().expose(), value);
+        assert!(Secret::default().expose().is_zero());
+    }
+
+    #[test]
+    fn it_should_compare_secrets() {
+        let value: BigUint = (BigUint::from(7u32) << 130usize) + 3u32;
+        assert_eq!(Secret::new(value.clone()), Secret::new(value.clone()));
+        assert_ne!(Secret::new(value.clone()), Secret::new(&value + 1u32));
+        assert_ne!(Secret::new(value), Secret::new(BigUint::from(3u32)));
+        assert_eq!(Secret::default(), Secret::new(BigUint::zero()));
+    }
+
+    #[test]
+    fn it_should_not_print_a_secret() {
+        let secret = Secret::new(BigUint::from(1234567u32));
+        let sk = PrivateKey {
+            params: Helper::setup_tiny_system().0,
+            x: secret.clone(),
+        };
+
+        assert_eq!(secret.expose(), BigUint::from(1234567u32));
+        assert_eq!(format!("{:?}", secret), "Secret(<redacted>)");
+        assert!(!format!("{:?}", sk).contains("1234567"));
+    }
+
     #[test]
     #[should_panic(expected = "there must be at least another key!")]
     fn it_should_combine_public_keys_bigunits_not_same_params() {
@@ -1,3 +1,4 @@
+use crate::election::{ExportElectionCmd, VerifyElectionCmd};
 use sc_cli::RunCmd;
 use structopt::StructOpt;
 
@@ -33,6 +34,12 @@ pub enum Subcommand {
     /// Revert the chain to a previous state.
     Revert(sc_cli::RevertCmd),
 
+    /// Export the election record of a vote from the local database.
+    ExportElection(ExportElectionCmd),
+
+    /// Verify the election record of a vote from the local database offline.
+    VerifyElection(VerifyElectionCmd),
+
     /// The custom benchmark subcommmand benchmarking runtime pallets.
     #[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
     Benchmark(frame_benchmarking_cli::BenchmarkCmd),
@@ -0,0 +1,188 @@
+//! Export and offline verification of the election record of a vote.
+//!
+//! The record is read from the pallet storage in the node's database at a given block
+//! (see `pallet_mixnet::Module::election_record`), no RPC connection is required.
+
+use crate::service;
+use codec::Encode;
+use provotum_runtime::{pallet_mixnet::types::VoteId, Block, PalletMixnet};
+use provotum_verifier::{election_guard, verify_record, Record};
+use sc_cli::{
+    BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams,
+};
+use sc_client_api::Backend;
+use sc_service::{Configuration, PartialComponents};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+    generic::BlockId,
+    traits::{BlakeTwo256, NumberFor},
+};
+use sp_state_machine::{Ext, OverlayedChanges, StorageTransactionCache};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// The `export-election` command: writes the election record of a vote to disk.
+#[derive(Debug, StructOpt)]
+pub struct ExportElectionCmd {
+    /// The id of the vote
+    #[structopt(long)]
+    pub vote: String,
+
+    /// The block at which the record is read (default: the best block)
+    #[structopt(long, value_name = "HASH or NUMBER")]
+    pub block: Option<BlockNumberOrHash>,
+
+    /// The file to which the SCALE encoded record is written (default: <vote>.record)
+    #[structopt(long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+
+    /// Writes the record as ElectionGuard compatible JSON to this file as well
+    #[structopt(long, parse(from_os_str))]
+    pub election_guard: Option<PathBuf>,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub pruning_params: PruningParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub database_params: DatabaseParams,
+}
+
+/// The `verify-election` command: verifies the election record of a vote end to end.
+#[derive(Debug, StructOpt)]
+pub struct VerifyElectionCmd {
+    /// The id of the vote
+    #[structopt(long)]
+    pub vote: String,
+
+    /// The block at which the record is read (default: the best block)
+    #[structopt(long, value_name = "HASH or NUMBER")]
+    pub block: Option<BlockNumberOrHash>,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub pruning_params: PruningParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub database_params: DatabaseParams,
+}
+
+impl ExportElectionCmd {
+    /// Run the export-election command
+    pub fn run(&self, config: Configuration) -> sc_cli::Result<()> {
+        let record: Record = read_election_record(&config, &self.vote, &self.block)?;
+
+        let output: PathBuf = self
+            .output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("{}.record", self.vote)));
+        fs::write(&output, record.encode())?;
+        println!("exported the election record to {}", output.display());
+
+        if let Some(path) = self.election_guard.as_ref() {
+            let json: String = election_guard::to_json(&record)
+                .map_err(|error| sc_cli::Error::Input(error.to_string()))?;
+            fs::write(path, json)?;
+            println!("exported the ElectionGuard record to {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+impl VerifyElectionCmd {
+    /// Run the verify-election command
+    pub fn run(&self, config: Configuration) -> sc_cli::Result<()> {
+        let record: Record = read_election_record(&config, &self.vote, &self.block)?;
+
+        println!(
+            "verifying vote {} ({} topics, {} ballots)",
+            self.vote,
+            record.topics.len(),
+            record.transcript.ballots.len()
+        );
+        verify_record(&record, |step, result| match result {
+            Ok(()) => println!("  {:<16} ok", step),
+            Err(error) => println!("  {:<16} FAILED: {:?}", step, error),
+        })
+        .map_err(|error| {
+            sc_cli::Error::Other(format!("the election record is invalid: {:?}", error))
+        })?;
+        println!("the election record is valid");
+        Ok(())
+    }
+}
+
+/// reads the election record of a vote from the state of the given block (or the best block)
+fn read_election_record(
+    config: &Configuration,
+    vote: &str,
+    block: &Option<BlockNumberOrHash>,
+) -> sc_cli::Result<Record> {
+    let PartialComponents {
+        client, backend, ..
+    } = service::new_partial(config)?;
+    let block_id: BlockId<Block> = match block {
+        Some(block) => block.parse::<Block>().map_err(sc_cli::Error::Input)?,
+        None => BlockId::Hash(client.info().best_hash),
+    };
+    let state = backend.state_at(block_id)?;
+
+    // run the (native) pallet code on top of the state of the block
+    let mut overlay = OverlayedChanges::default();
+    let mut cache = StorageTransactionCache::default();
+    let mut ext = Ext::<BlakeTwo256, NumberFor<Block>, _>::new(
+        &mut overlay,
+        &mut cache,
+        &state,
+        None,
+        None,
+    );
+    let vote_id: VoteId = vote.as_bytes().to_vec();
+    sp_externalities::set_and_run_with_externalities(&mut ext, || {
+        if !PalletMixnet::vote_ids().contains(&vote_id) {
+            return Err(sc_cli::Error::Input(format!(
+                "the vote {} does not exist at block {}",
+                vote, block_id
+            )));
+        }
+        Ok(PalletMixnet::election_record(&vote_id))
+    })
+}
+
+impl CliConfiguration for ExportElectionCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
+
+impl CliConfiguration for VerifyElectionCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    dkg::{create::combine_shares, tally::combine_shares_and_tally_topic},
+    helpers::{
+        assertions::{ensure_vote_exists, ensure_vote_phase, is_vote_in_progress},
+        phase::set_phase,
+    },
+    types::{
+        AuthorityAction, NrOfShuffles, Proposal, PublicKey as SubstratePK, TopicId,
+        TopicResult, VoteId, VotePhase,
+    },
+    Error, Module, RawEvent, Trait, VoteEndedAt,
+};
+use frame_support::{
+    debug, ensure,
+    storage::{with_transaction, StorageMap},
+};
+use sp_runtime::TransactionOutcome;
+
+impl<T: Trait> Module<T> {
+    /// the number of approvals required to execute an authority action.
+    /// a threshold of 0 or 1 means that a single voting authority is enough.
+    pub fn required_approvals() -> u32 {
+        Self::approval_threshold().max(1)
+    }
+
+    /// critical actions can only be called directly
+    /// if the M-of-N approval flow is disabled.
+    pub fn ensure_no_approval_required() -> Result<(), Error<T>> {
+        ensure!(
+            Self::required_approvals() == 1,
+            Error::<T>::ApprovalRequired
+        );
+        Ok(())
+    }
+
+    /// adds the approval of the voting authority `who` to the proposal.
+    /// once enough approvals have been collected, the action is executed.
+    ///
+    /// the action is executed in a storage transaction before the proposal is stored,
+    /// so that a failing action neither leaves partial changes nor an approval behind
+    /// and can be approved again.
+    pub fn approve_proposal(
+        who: T::AccountId,
+        mut proposal: Proposal<T::AccountId>,
+    ) -> Result<Proposal<T::AccountId>, Error<T>> {
+        ensure!(!proposal.executed, Error::<T>::ProposalAlreadyExecuted);
+        ensure!(
+            !proposal.approvals.contains(&who),
+            Error::<T>::ProposalAlreadyApproved
+        );
+        proposal.approvals.push(who.clone());
+
+        if Self::nr_of_valid_approvals(&proposal) >= Self::required_approvals() {
+            let action = proposal.action.clone();
+            with_transaction(|| match Self::execute_action(who, action) {
+                Ok(()) => TransactionOutcome::Commit(Ok(())),
+                Err(error) => TransactionOutcome::Rollback(Err(error)),
+            })?;
+            proposal.executed = true;
+        }
+        Ok(proposal)
+    }
+
+    /// the number of approvals given by accounts which are still voting authorities.
+    /// approvals of removed voting authorities do not count towards the threshold.
+    pub fn nr_of_valid_approvals(proposal: &Proposal<T::AccountId>) -> u32 {
+        let voting_authorities = Self::voting_authorities();
+        proposal
+            .approvals
+            .iter()
+            .filter(|account| voting_authorities.contains(account))
+            .count() as u32
+    }
+
+    /// the vote an authority action refers to
+    pub fn action_vote_id(action: &AuthorityAction) -> &VoteId {
+        match action {
+            AuthorityAction::SetVotePhase(vote_id, _) => vote_id,
+            AuthorityAction::CombinePublicKeyShares(vote_id) => vote_id,
+            AuthorityAction::CombineDecryptedShares(vote_id, _, _, _) => vote_id,
+        }
+    }
+
+    pub fn execute_action(
+        who: T::AccountId,
+        action: AuthorityAction,
+    ) -> Result<(), Error<T>> {
+        match action {
+            AuthorityAction::SetVotePhase(vote_id, phase) => {
+                Self::do_set_vote_phase(who, vote_id, phase)
+            }
+            AuthorityAction::CombinePublicKeyShares(vote_id) => {
+                Self::do_combine_public_key_shares(who, vote_id)
+            }
+            AuthorityAction::CombineDecryptedShares(
+                vote_id,
+                topic_id,
+                encoded,
+                nr_of_shuffles,
+            ) => Self::do_combine_decrypted_shares(
+                vote_id,
+                topic_id,
+                encoded,
+                nr_of_shuffles,
+            ),
+        }
+    }
+
+    pub fn do_set_vote_phase(
+        who: T::AccountId,
+        vote_id: VoteId,
+        phase: VotePhase,
+    ) -> Result<(), Error<T>> {
+        // set the new phase
+        set_phase::<T>(&who, &vote_id, phase.clone())?;
+
+        // notify that the vote phase has been changed
+        debug::info!("updated vote phase: {:?}, {:?}", vote_id, phase);
+        Self::deposit_event(RawEvent::VotePhaseChanged(vote_id, phase));
+        Ok(())
+    }
+
+    pub fn do_combine_public_key_shares(
+        who: T::AccountId,
+        vote_id: VoteId,
+    ) -> Result<(), Error<T>> {
+        ensure_vote_exists::<T>(&vote_id)?;
+
+        // create the system's public key
+        let pk: SubstratePK = combine_shares::<T>(who, &vote_id)?;
+
+        debug::info!("combined public key shares for vote: {:?}", vote_id);
+        Self::deposit_event(RawEvent::PublicKeyCreated(vote_id, pk));
+        Ok(())
+    }
+
+    pub fn do_combine_decrypted_shares(
+        vote_id: VoteId,
+        topic_id: TopicId,
+        encoded: bool,
+        nr_of_shuffles: NrOfShuffles,
+    ) -> Result<(), Error<T>> {
+        ensure_vote_exists::<T>(&vote_id)?;
+        ensure_vote_phase::<T>(&vote_id, VotePhase::Tallying)?;
+
+        // combine the decrypted shares
+        // tally the topic
+        let result: TopicResult = combine_shares_and_tally_topic::<T>(
+            &vote_id,
+            &topic_id,
+            encoded,
+            &nr_of_shuffles,
+        )?;
+
+        // notify that the decrypted shares have been combined
+        // and that the result has been tallied!
+        debug::info!(
+            "result for vote: {:?} and topic: {:?} is: {:?}",
+            vote_id,
+            topic_id,
+            result
+        );
+        // the vote ends once all topics have been tallied
+        if !is_vote_in_progress::<T>(&vote_id) {
+            let now: T::BlockNumber = <frame_system::Module<T>>::block_number();
+            VoteEndedAt::<T>::insert(&vote_id, now);
+        }
+        Self::deposit_event(RawEvent::TopicTallied(vote_id, topic_id, result));
+        Ok(())
+    }
+}
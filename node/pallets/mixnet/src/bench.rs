@@ -1,18 +1,21 @@
 #![cfg(feature = "runtime-benchmarks")]
 
 use crate::types::{
-    Ballot, Cipher, PublicKey as SubstratePK, PublicKeyShare, PublicParameters,
-    ShuffleProof as Proof, Topic, TopicId, Vote, VoteId, VotePhase, Wrapper,
+    Ballot, Canonical, Cipher, DecryptedShareProof, PublicKey as SubstratePK,
+    PublicKeyShare, PublicParameters, ShuffleProof as Proof, Topic, TopicId, Vote,
+    VoteId, VotePhase,
 };
 use crate::{Ballots, Module, Trait};
 use alloc::vec::Vec;
 use codec::Decode;
 use crypto::{
     encryption::ElGamal,
-    helper::Helper,
+    helper::{Helper, ProofContext},
     proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof},
     types::Cipher as BigCipher,
-    types::{ElGamalParams, ModuloOperations, PrivateKey, PublicKey as ElGamalPK},
+    types::{
+        ElGamalParams, ModuloOperations, PrivateKey, PublicKey as ElGamalPK, Secret,
+    },
 };
 use frame_benchmarking::{benchmarks, whitelisted_caller};
 use frame_support::{ensure, storage::StorageDoubleMap, traits::Box};
@@ -25,6 +28,7 @@ use sp_std::vec;
 use crate::Module as PalletMixnet;
 
 const NR_OF_SHUFFLES: u8 = 0;
+const VOTE_ID: &[u8] = b"20201212";
 
 fn get_voting_authority<T: Trait>() -> RawOrigin<T::AccountId> {
     // use Alice as VotingAuthority
@@ -70,7 +74,7 @@ fn setup_vote<T: Trait>(
     let who = get_voting_authority::<T>();
 
     // create the vote
-    let vote_id = "20201212".as_bytes().to_vec();
+    let vote_id = VOTE_ID.to_vec();
     let vote_title = "Popular Vote of 12.12.2020".as_bytes().to_vec();
 
     let topic_id = "20201212-01".as_bytes().to_vec();
@@ -78,6 +82,9 @@ fn setup_vote<T: Trait>(
     let topic: Topic = (topic_id.clone(), topic_question);
     let topics = vec![topic];
 
+    // use all registered sealers as sealers of the vote
+    let sealers: Vec<T::AccountId> = PalletMixnet::<T>::sealers();
+
     PalletMixnet::<T>::create_vote(
         who.into(),
         vote_id.clone(),
@@ -85,6 +92,7 @@ fn setup_vote<T: Trait>(
         params,
         topics,
         30,
+        sealers,
     )?;
     set_vote_phase::<T>(vote_id.clone(), VotePhase::Voting)?;
 
@@ -111,7 +119,7 @@ fn generate_random_encryptions_encoded<T: Trait>(
         let nr = BigUint::from(i);
         let r = PalletMixnet::<T>::get_random_biguint_less_than(q)?;
         let enc = ElGamal::encrypt_encode(&nr, &r, pk);
-        encryptions.push(enc.into());
+        encryptions.push(Cipher::encode_canonical(&enc, &pk.params));
     }
     Ok(encryptions)
 }
@@ -131,7 +139,7 @@ fn generate_random_encryptions<T: Trait>(
         if nr.modpow(q, p) == one {
             let r = PalletMixnet::<T>::get_random_biguint_less_than(q)?;
             let enc = ElGamal::encrypt(&nr, &r, pk);
-            encryptions.push(enc.into());
+            encryptions.push(Cipher::encode_canonical(&enc, &pk.params));
         }
         i += 1u32;
     }
@@ -172,7 +180,9 @@ fn setup_shuffle<T: Trait>(
     }
 
     // type conversion
-    let encryptions: Vec<BigCipher> = Wrapper(ciphers).into();
+    let encryptions: Vec<BigCipher> = ciphers
+        .decode_canonical(&pk.params)
+        .ok_or("the ciphers are not canonically encoded")?;
     ensure!(
         encryptions.len() == size,
         "# of votes on chain is not correct"
@@ -189,7 +199,7 @@ fn setup_shuffle_proof<T: Trait>(
         Vec<u8>,
         Vec<BigCipher>,
         Vec<BigCipher>,
-        Vec<BigUint>,
+        Vec<Secret>,
         Vec<usize>,
         ElGamalPK,
     ),
@@ -200,7 +210,7 @@ fn setup_shuffle_proof<T: Trait>(
 
     // shuffle the votes
     let result = PalletMixnet::<T>::shuffle_ciphers(&pk, e.clone());
-    let s: (Vec<BigCipher>, Vec<BigUint>, Vec<usize>) = result.unwrap();
+    let s: (Vec<BigCipher>, Vec<Secret>, Vec<usize>) = result.unwrap();
     let e_hat = s.0; // the shuffled votes
     let r = s.1; // the re-encryption randoms
     let permutation = s.2;
@@ -217,11 +227,18 @@ fn setup_sealer<T: Trait>(
 ) -> Result<(PublicKeyShare, KeyGenerationProof), &'static str> {
     // create public key share + proof
     let q = &pk.params.q();
-    let r = PalletMixnet::<T>::get_random_biguint_less_than(q)?;
-    let proof = KeyGenerationProof::generate(params, &sk.x, &pk.h, &r, sealer_id);
+    let r = Secret::new(PalletMixnet::<T>::get_random_biguint_less_than(q)?);
+    let proof = KeyGenerationProof::generate(
+        params,
+        &sk.x,
+        &pk.h,
+        &r,
+        sealer_id,
+        &ProofContext::new(VOTE_ID),
+    );
     let pk_share = PublicKeyShare {
-        proof: proof.clone().into(),
-        pk: pk.h.to_bytes_be(),
+        proof: Canonical::encode_canonical(&proof, &pk.params),
+        pk: pk.params.encode_value(&pk.h),
     };
 
     // submit the public key share
@@ -330,12 +347,13 @@ fn create_decrypted_shares_and_proof<T: Trait>(
     sealer_pk: &ElGamalPK,
     sealer_sk: &PrivateKey,
     sealer_id: [u8; 32],
-) -> Result<(DecryptionProof, Vec<Vec<u8>>), &'static str> {
+) -> Result<(DecryptedShareProof, Vec<Vec<u8>>, Vec<Vec<u8>>), &'static str> {
     let q = &params.q();
 
     // fetch the encrypted votes from chain
-    let encryptions: Vec<BigCipher> =
-        Wrapper(PalletMixnet::<T>::ciphers(topic_id, NR_OF_SHUFFLES)).into();
+    let encryptions: Vec<BigCipher> = PalletMixnet::<T>::ciphers(topic_id, NR_OF_SHUFFLES)
+        .decode_canonical(params)
+        .ok_or("the ciphers are not canonically encoded")?;
     ensure!(
         encryptions.len() > 0,
         "the number of encryptions is too low"
@@ -350,12 +368,12 @@ fn create_decrypted_shares_and_proof<T: Trait>(
     // convert the decrypted shares: Vec<BigUint> to Vec<Vec<u8>>
     let decrypted_shares: Vec<Vec<u8>> = partial_decrytpions
         .iter()
-        .map(|c| c.to_bytes_be())
+        .map(|c| params.encode_value(c))
         .collect::<Vec<Vec<u8>>>();
 
     // create sealer's proof using sealer's public and private key share
-    let r = PalletMixnet::<T>::get_random_biguint_less_than(q)?;
-    let decryption_proof = DecryptionProof::generate(
+    let r = Secret::new(PalletMixnet::<T>::get_random_biguint_less_than(q)?);
+    let (decryption_proof, commitments) = DecryptionProof::generate_with_commitments(
         params,
         &sealer_sk.x,
         &sealer_pk.h.clone().into(),
@@ -363,8 +381,14 @@ fn create_decrypted_shares_and_proof<T: Trait>(
         encryptions,
         partial_decrytpions,
         &sealer_id,
+        &ProofContext::new(VOTE_ID),
     );
-    Ok((decryption_proof, decrypted_shares))
+    let commitments: Vec<Vec<u8>> = commitments
+        .iter()
+        .map(|t| params.encode_value(t))
+        .collect::<Vec<Vec<u8>>>();
+    let decryption_proof = Canonical::encode_canonical(&decryption_proof, params);
+    Ok((decryption_proof, commitments, decrypted_shares))
 }
 
 fn submit_decrypted_shares_and_proofs<T: Trait>(
@@ -379,7 +403,7 @@ fn submit_decrypted_shares_and_proofs<T: Trait>(
     let (bob, bob_id) = get_sealer_bob::<T>();
 
     // create bob's decrypted shares + proof using bob's public and private key share
-    let (bob_proof, bob_shares) = create_decrypted_shares_and_proof::<T>(
+    let (bob_proof, bob_commitments, bob_shares) = create_decrypted_shares_and_proof::<T>(
         &topic_id,
         &bob_pk.params,
         &bob_pk,
@@ -393,7 +417,8 @@ fn submit_decrypted_shares_and_proofs<T: Trait>(
         vote_id.clone(),
         topic_id.clone(),
         bob_shares,
-        bob_proof.into(),
+        bob_proof,
+        bob_commitments,
         NR_OF_SHUFFLES,
     )?;
 
@@ -401,13 +426,14 @@ fn submit_decrypted_shares_and_proofs<T: Trait>(
     let (charlie, charlie_id) = get_sealer_charlie::<T>();
 
     // create charlie's decrypted shares + proof using charlie's public and private key share
-    let (charlie_proof, charlie_shares) = create_decrypted_shares_and_proof::<T>(
-        &topic_id,
-        &charlie_pk.params,
-        &charlie_pk,
-        &charlie_sk,
-        charlie_id,
-    )?;
+    let (charlie_proof, charlie_commitments, charlie_shares) =
+        create_decrypted_shares_and_proof::<T>(
+            &topic_id,
+            &charlie_pk.params,
+            &charlie_pk,
+            &charlie_sk,
+            charlie_id,
+        )?;
 
     // submit charlie's proof + shares
     PalletMixnet::<T>::submit_decrypted_shares(
@@ -415,7 +441,8 @@ fn submit_decrypted_shares_and_proofs<T: Trait>(
         vote_id.clone(),
         topic_id.clone(),
         charlie_shares,
-        charlie_proof.into(),
+        charlie_proof,
+        charlie_commitments,
         NR_OF_SHUFFLES,
     )?;
     Ok((topic_id, vote_id))
@@ -445,11 +472,11 @@ benchmarks! {
 
         // create public key share + proof
         let q = &params.clone().q();
-        let random = PalletMixnet::<T>::get_random_biguint_less_than(q)?;
-        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &random, &bob_id);
+        let random = Secret::new(PalletMixnet::<T>::get_random_biguint_less_than(q)?);
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &random, &bob_id, &ProofContext::new(VOTE_ID));
         let pk_share = PublicKeyShare {
-            proof: proof.clone().into(),
-            pk: pk.h.to_bytes_be(),
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
         };
     }: {
         // store created public key and public parameters
@@ -464,22 +491,22 @@ benchmarks! {
 
         // create public key share + proof for bob
         let (bob, bob_id) = get_sealer_bob::<T>();
-        let random = PalletMixnet::<T>::get_random_biguint_less_than(q)?;
-        let proof_bob = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &random, &bob_id);
+        let random = Secret::new(PalletMixnet::<T>::get_random_biguint_less_than(q)?);
+        let proof_bob = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &random, &bob_id, &ProofContext::new(VOTE_ID));
         let pk_share_bob = PublicKeyShare {
-            proof: proof_bob.clone().into(),
-            pk: pk.h.to_bytes_be(),
+            proof: Canonical::encode_canonical(&proof_bob, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
         };
         // store created public key and public parameters
         let result_ = PalletMixnet::<T>::store_public_key_share(bob.into(), vote_id.clone(), pk_share_bob.clone().into());
 
         // create public key share + proof for charlie
         let (charlie, charlie_id) = get_sealer_charlie::<T>();
-        let random = PalletMixnet::<T>::get_random_biguint_less_than(q)?;
-        let proof_charlie = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &random, &charlie_id);
+        let random = Secret::new(PalletMixnet::<T>::get_random_biguint_less_than(q)?);
+        let proof_charlie = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &random, &charlie_id, &ProofContext::new(VOTE_ID));
         let pk_share_charlie = PublicKeyShare {
-            proof: proof_charlie.clone().into(),
-            pk: pk.h.to_bytes_be(),
+            proof: Canonical::encode_canonical(&proof_charlie, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
         };
         // store created public key and public parameters
         let result_ = PalletMixnet::<T>::store_public_key_share(charlie.into(), vote_id.clone(), pk_share_charlie.clone().into());
@@ -504,9 +531,10 @@ benchmarks! {
         // store created public key
         let (params, _, pk) = Helper::setup_lg_system();
         PalletMixnet::<T>::store_public_key(who.clone().into(), vote_id.clone(), pk.into())?;
+        let sealers: Vec<T::AccountId> = PalletMixnet::<T>::sealers();
 
     }: {
-        let _result = PalletMixnet::<T>::create_vote(who.into(), vote_id.clone(), vote_title.clone(), params.into(), topics, 30)?;
+        let _result = PalletMixnet::<T>::create_vote(who.into(), vote_id.clone(), vote_title.clone(), params.into(), topics, 30, sealers)?;
     } verify {
         let vote: Vote<T::AccountId> = PalletMixnet::<T>::votes(vote_id);
         ensure!(vote_title == vote.title, "title are not the same!");
@@ -548,7 +576,8 @@ benchmarks! {
 
         // transform the ballot into a from that the blockchain can handle
         // i.e. a Substrate representation { a: Vec<u8>, b: Vec<u8> }
-        let cipher: Cipher = ElGamal::encrypt_encode(&message, &random, &pk).into();
+        let cipher = ElGamal::encrypt_encode(&message, &random, &pk);
+        let cipher: Cipher = Cipher::encode_canonical(&cipher, &pk.params);
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id, cipher)];
         let ballot: Ballot = Ballot { answers };
     }: {
@@ -571,11 +600,11 @@ benchmarks! {
         let sealer = RawOrigin::Signed(sealer_account_id.into());
 
         // create public key share + proof
-        let r = PalletMixnet::<T>::get_random_biguint_less_than(&q)?;
-        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, &sealer_id);
+        let r = Secret::new(PalletMixnet::<T>::get_random_biguint_less_than(&q)?);
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, &sealer_id, &ProofContext::new(VOTE_ID));
         let pk_share = PublicKeyShare {
-            proof: proof.clone().into(),
-            pk: pk.h.to_bytes_be(),
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
         };
 
     }: {
@@ -645,140 +674,140 @@ benchmarks! {
     shuffle_proof_3 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(3, false)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_10 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(10, false)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_30 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(30, false)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_100 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(100, false)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_1000 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(1000, false)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_3_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(3, true)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_10_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(10, true)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_30_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(30, true)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_100_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(100, true)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     shuffle_proof_1000_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(1000, true)?;
     }: {
-        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk);
+        let _result = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e, e_hat, r, &permutation, &pk, &ProofContext::new(VOTE_ID));
     }
 
     verify_shuffle_proof_3 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(3, false)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_10 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(10, false)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_30 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(30, false)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_100 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(100, false)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_1000 {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(1000, false)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_3_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(3, true)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_10_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(10, true)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_30_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(30, true)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_100_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(100, true)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
     verify_shuffle_proof_1000_encoded {
         let (topic_id, e, e_hat, r, permutation, pk) = setup_shuffle_proof::<T>(1000, true)?;
-        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk)?;
+        let proof: Proof = PalletMixnet::<T>::generate_shuffle_proof(&topic_id, e.clone(), e_hat.clone(), r, &permutation, &pk, &ProofContext::new(VOTE_ID))?;
     }: {
-        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk)?;
+        let success = PalletMixnet::<T>::verify_shuffle_proof(&topic_id, proof, e, e_hat, &pk, &ProofContext::new(VOTE_ID))?;
         ensure!(success, "proof did not verify!");
     }
 
@@ -790,14 +819,14 @@ benchmarks! {
         let (bob, bob_id) = get_sealer_bob::<T>();
 
         // create bob's decrypted shares + proof using bob's public and private key share
-        let (bob_proof, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
+        let (bob_proof, bob_commitments, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
     }: {
         let _success = PalletMixnet::<T>::submit_decrypted_shares(
             bob.into(),
             vote_id,
             topic_id,
             bob_shares,
-            bob_proof.into(), NR_OF_SHUFFLES
+            bob_proof, bob_commitments, NR_OF_SHUFFLES
         )?;
     }
 
@@ -809,14 +838,14 @@ benchmarks! {
         let (bob, bob_id) = get_sealer_bob::<T>();
 
         // create bob's decrypted shares + proof using bob's public and private key share
-        let (bob_proof, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
+        let (bob_proof, bob_commitments, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
     }: {
         let _success = PalletMixnet::<T>::submit_decrypted_shares(
             bob.into(),
             vote_id,
             topic_id,
             bob_shares,
-            bob_proof.into(), NR_OF_SHUFFLES
+            bob_proof, bob_commitments, NR_OF_SHUFFLES
         )?;
     }
 
@@ -828,14 +857,14 @@ benchmarks! {
         let (bob, bob_id) = get_sealer_bob::<T>();
 
         // create bob's decrypted shares + proof using bob's public and private key share
-        let (bob_proof, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
+        let (bob_proof, bob_commitments, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
     }: {
         let _success = PalletMixnet::<T>::submit_decrypted_shares(
             bob.into(),
             vote_id,
             topic_id,
             bob_shares,
-            bob_proof.into(), NR_OF_SHUFFLES
+            bob_proof, bob_commitments, NR_OF_SHUFFLES
         )?;
     }
 
@@ -847,14 +876,14 @@ benchmarks! {
         let (bob, bob_id) = get_sealer_bob::<T>();
 
         // create bob's decrypted shares + proof using bob's public and private key share
-        let (bob_proof, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
+        let (bob_proof, bob_commitments, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
     }: {
         let _success = PalletMixnet::<T>::submit_decrypted_shares(
             bob.into(),
             vote_id,
             topic_id,
             bob_shares,
-            bob_proof.into(), NR_OF_SHUFFLES
+            bob_proof, bob_commitments, NR_OF_SHUFFLES
         )?;
     }
 
@@ -866,14 +895,14 @@ benchmarks! {
         let (bob, bob_id) = get_sealer_bob::<T>();
 
         // create bob's decrypted shares + proof using bob's public and private key share
-        let (bob_proof, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
+        let (bob_proof, bob_commitments, bob_shares) = create_decrypted_shares_and_proof::<T>(&topic_id, &bob_pk.params, &bob_pk, &bob_sk, bob_id)?;
     }: {
         let _success = PalletMixnet::<T>::submit_decrypted_shares(
             bob.into(),
             vote_id,
             topic_id,
             bob_shares,
-            bob_proof.into(), NR_OF_SHUFFLES
+            bob_proof, bob_commitments, NR_OF_SHUFFLES
         )?;
     }
 
@@ -1,14 +1,12 @@
 use crate::{
     helpers::params::get_public_params,
     helpers::phase::set_phase,
-    types::{
-        PublicKey as SubstratePK, PublicKeyShare, PublicParameters, VoteId, VotePhase,
-    },
-    Error, PublicKey, PublicKeyShares, Trait,
+    types::{PublicKey as SubstratePK, PublicKeyShare, VoteId, VotePhase},
+    Error, PublicKey, PublicKeyShareBySealer, Trait, VoteSealers,
 };
 use alloc::borrow::ToOwned;
 use alloc::vec::Vec;
-use crypto::types::PublicKey as ElGamalPK;
+use crypto::types::{ElGamalParams, PublicKey as ElGamalPK};
 use frame_support::{debug, ensure, storage::StorageMap};
 use num_bigint::BigUint;
 use num_traits::One;
@@ -19,11 +17,16 @@ pub fn combine_shares<T: Trait>(
     vote_id: &VoteId,
 ) -> Result<SubstratePK, Error<T>> {
     // get the public parameters
-    let params: PublicParameters = get_public_params::<T>(&vote_id)?;
-    let shares: Vec<PublicKeyShare> = PublicKeyShares::get(&vote_id);
-
-    // check that there are at least two shares
-    ensure!(shares.len() > 1, Error::<T>::NotEnoughPublicKeyShares);
+    let params: ElGamalParams = get_public_params::<T>(&vote_id)?.into();
+    // every sealer of the vote must have submitted a (verified) public key share,
+    // otherwise the sealers without a share could not decrypt the votes
+    let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(&vote_id);
+    let shares: Vec<PublicKeyShare> = sealers
+        .iter()
+        .map(|sealer| PublicKeyShareBySealer::<T>::get((vote_id, sealer)))
+        .collect::<Option<Vec<PublicKeyShare>>>()
+        .ok_or(Error::<T>::NotEnoughPublicKeyShares)?;
+    ensure!(!shares.is_empty(), Error::<T>::NotEnoughPublicKeyShares);
 
     let pk_shares_bytes: Vec<Vec<u8>> = shares
         .iter()
@@ -31,12 +34,13 @@ pub fn combine_shares<T: Trait>(
         .collect::<Vec<Vec<u8>>>();
     let pk_shares_biguint: Vec<BigUint> = pk_shares_bytes
         .iter()
-        .map(|share| BigUint::from_bytes_be(share))
-        .collect::<Vec<BigUint>>();
+        .map(|share| params.decode_element(share))
+        .collect::<Option<Vec<BigUint>>>()
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
 
     let base: ElGamalPK = ElGamalPK {
         h: BigUint::one(),
-        params: params.into(),
+        params,
     };
 
     // combine the shares into a single key
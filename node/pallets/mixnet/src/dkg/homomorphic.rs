@@ -0,0 +1,141 @@
+use super::tally::get_partial_decryptions;
+use crate::helpers::params::get_public_params;
+use crate::types::{
+    Canonical, Cipher, OptionEncoding, PublicParameters, QuestionType, TopicDefinition,
+    TopicId, TopicResult, VoteId, VoterWeight,
+};
+use crate::{EncryptedSums, Error, SummedWeights, Tally, TopicDefinitions, Trait};
+use crypto::encryption::ElGamal;
+use crypto::types::{Cipher as BigCipher, ElGamalParams, ModuloOperations};
+use frame_support::{ensure, storage::StorageMap};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+/// the sum of zero votes: an encryption of 0 using the randomness 0 -> (1, 1)
+pub fn empty_sum(params: &PublicParameters) -> Cipher {
+    let params: ElGamalParams = params.clone().into();
+    let sum = BigCipher {
+        a: BigUint::one(),
+        b: BigUint::one(),
+    };
+    Cipher::encode_canonical(&sum, &params)
+}
+
+/// checks that a declared topic can be tallied homomorphically.
+/// the sum of the votes only counts the votes for option 1,
+/// therefore, only a single choice between two index encoded options is supported.
+pub fn is_summable_definition(definition: &TopicDefinition) -> bool {
+    definition.question_type == QuestionType::SingleChoice
+        && definition.encoding == OptionEncoding::Index
+        && definition.options.len() == 2
+}
+
+/// adds an encrypted (and encoded) vote multiplied by the weight of the voter
+/// to the encrypted sum of a topic: Enc(sum) * Enc(m)^w = Enc(sum + w * m)
+pub fn add_to_encrypted_sum<T: Trait>(
+    vote_id: &VoteId,
+    topic_id: &TopicId,
+    cipher: &Cipher,
+    weight: VoterWeight,
+) -> Result<(), Error<T>> {
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
+    let big_p: &BigUint = &params.p;
+
+    let sum: BigCipher = EncryptedSums::get(topic_id)
+        .ok_or(Error::<T>::HomomorphicTallyNotEnabled)?
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+    let big_cipher: BigCipher = cipher
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+    let weighted: BigCipher =
+        ElGamal::homomorphic_multiply(&big_cipher, &BigUint::from(weight), big_p);
+    let sum: BigCipher = ElGamal::homomorphic_addition(&sum, &weighted, big_p);
+    let sum: Cipher = Cipher::encode_canonical(&sum, &params);
+
+    EncryptedSums::insert(topic_id, sum);
+    SummedWeights::mutate(topic_id, |summed| *summed = summed.saturating_add(weight));
+    Ok(())
+}
+
+/// decrypts the encrypted sum of a topic using the decrypted shares of all sealers.
+/// each vote is expected to be an encoded 0 or 1, the sum is decoded by brute forcing
+/// the discrete logarithm up to the summed weight of the cast votes.
+pub fn tally_encrypted_sum<T: Trait>(
+    vote_id: &VoteId,
+    topic_id: &TopicId,
+) -> Result<TopicResult, Error<T>> {
+    // check that topic has not been tallied yet
+    ensure!(
+        Tally::get::<&TopicId>(topic_id).is_none(),
+        Error::<T>::TopicHasAlreadyBeenTallied
+    );
+
+    // get the public parameters and the encrypted sum
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
+    let big_p: BigUint = params.p.clone();
+    let big_g: BigUint = params.g.clone();
+    let sum: BigCipher = EncryptedSums::get(topic_id)
+        .ok_or(Error::<T>::HomomorphicTallyNotEnabled)?
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+
+    // combine the partial decryptions of the sum by all sealers
+    let partial_decryptions: Vec<Vec<BigUint>> =
+        get_partial_decryptions::<T>(vote_id, topic_id)?;
+    let decrypted_a: BigUint =
+        ElGamal::combine_partial_decrypted_as(partial_decryptions, &big_p)
+            .pop()
+            .ok_or(Error::<T>::NotEnoughDecryptedShares)?;
+    let encoded_sum: BigUint = ElGamal::partial_decrypt_b(&sum.b, &decrypted_a, &big_p);
+
+    // the (weighted) votes for option 1 cannot exceed the summed weight of all votes
+    let summed_weight: u64 = SummedWeights::get(topic_id);
+    let yes: u64 = decode_sum(&encoded_sum, &big_g, &big_p, summed_weight)
+        .ok_or(Error::<T>::HomomorphicSumOutOfRange)?;
+
+    let definition: Option<TopicDefinition> = TopicDefinitions::get(topic_id);
+    let results: TopicResult = sum_results(definition.as_ref(), summed_weight, yes);
+
+    // store the results on chain
+    Tally::insert::<&TopicId, TopicResult>(topic_id, results.clone());
+    Ok(results)
+}
+
+/// the results of a homomorphically tallied topic: the (weighted) votes for option 0 and 1.
+/// if the topic declares its options, the counts are stored per option name.
+pub fn sum_results(
+    definition: Option<&TopicDefinition>,
+    summed_weight: u64,
+    yes: u64,
+) -> TopicResult {
+    let no: u64 = summed_weight - yes;
+    let (name_no, name_yes): (Vec<u8>, Vec<u8>) = match definition {
+        Some(definition) if is_summable_definition(definition) => {
+            (definition.options[0].clone(), definition.options[1].clone())
+        }
+        _ => (BigUint::zero().to_bytes_be(), BigUint::one().to_bytes_be()),
+    };
+    let mut results: TopicResult = BTreeMap::new();
+    results.insert(name_no, BigUint::from(no).to_bytes_be());
+    results.insert(name_yes, BigUint::from(yes).to_bytes_be());
+    results
+}
+
+/// returns m such that g^m = encoded_sum (mod p) and m <= max
+pub fn decode_sum(
+    encoded_sum: &BigUint,
+    g: &BigUint,
+    p: &BigUint,
+    max: u64,
+) -> Option<u64> {
+    let mut encoded: BigUint = BigUint::one();
+    for m in 0..=max {
+        if encoded == *encoded_sum {
+            return Some(m);
+        }
+        encoded = encoded.modmul(g, p);
+    }
+    None
+}
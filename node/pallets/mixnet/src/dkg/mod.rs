@@ -1,3 +1,8 @@
 pub mod create;
+pub mod homomorphic;
+pub mod options;
+pub mod ranked;
 pub mod tally;
 pub mod verify;
+pub mod weights;
+pub mod write_in;
@@ -0,0 +1,126 @@
+use super::{ranked::MAX_RANKED_OPTIONS, write_in::MAX_WRITE_IN_LENGTH};
+use crate::types::{
+    OptionEncoding, QuestionType, RejectedVotes, TopicDefinition, TopicResult,
+};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use sp_std::{collections::btree_map::BTreeMap, vec, vec::Vec};
+
+/// checks that the declared options fit the question type and the encoding
+pub fn is_valid_definition(definition: &TopicDefinition) -> bool {
+    let nr_of_options = definition.options.len();
+
+    // write-ins are free-text answers without declared options
+    match (&definition.question_type, &definition.encoding) {
+        (QuestionType::WriteIn { max_length }, OptionEncoding::Text) => {
+            return nr_of_options == 0
+                && 0 < *max_length
+                && *max_length <= MAX_WRITE_IN_LENGTH;
+        }
+        (QuestionType::WriteIn { .. }, _) | (_, OptionEncoding::Text) => return false,
+        _ => (),
+    }
+
+    // there need to be at least two options and the option names must be unique
+    let mut names = definition.options.clone();
+    names.sort();
+    names.dedup();
+    if nr_of_options < 2 || names.len() != nr_of_options {
+        return false;
+    }
+
+    match (&definition.question_type, &definition.encoding) {
+        (QuestionType::Ranked(_), OptionEncoding::Ranking) => {
+            nr_of_options <= MAX_RANKED_OPTIONS
+        }
+        // rankings are only used by ranked questions and vice versa
+        (QuestionType::Ranked(_), _) | (_, OptionEncoding::Ranking) => false,
+        (QuestionType::SingleChoice, _) => true,
+        (QuestionType::YesNoAbstain, _) => nr_of_options == 3,
+        (QuestionType::MultiChoice { min, max }, OptionEncoding::Bitmask) => {
+            min <= max && *max as usize <= nr_of_options
+        }
+        // multiple selections cannot be encoded as a single index
+        (QuestionType::MultiChoice { .. }, OptionEncoding::Index) => false,
+        // write-ins have been checked above
+        (QuestionType::WriteIn { .. }, _) | (_, OptionEncoding::Text) => false,
+    }
+}
+
+/// returns the indices of the options selected in a plaintext
+/// or None if the plaintext does not map to the declared options
+fn selected_options(
+    plaintext: &BigUint,
+    encoding: &OptionEncoding,
+    nr_of_options: usize,
+) -> Option<Vec<usize>> {
+    match encoding {
+        OptionEncoding::Index => plaintext
+            .to_usize()
+            .filter(|index| *index < nr_of_options)
+            .map(|index| vec![index]),
+        OptionEncoding::Bitmask => {
+            if plaintext.bits() > nr_of_options as u64 {
+                return None;
+            }
+            let bytes: Vec<u8> = plaintext.to_bytes_le();
+            let selected = (0..nr_of_options)
+                .filter(|i| {
+                    bytes
+                        .get(i / 8)
+                        .map_or(false, |byte| (byte >> (i % 8)) & 1 == 1)
+                })
+                .collect::<Vec<usize>>();
+            Some(selected)
+        }
+        // rankings are counted by `ranked::tally_ranking`
+        // and write-ins by `write_in::count_write_ins`
+        OptionEncoding::Ranking | OptionEncoding::Text => None,
+    }
+}
+
+/// checks that the number of selected options is allowed by the question type
+fn is_valid_selection(question_type: &QuestionType, nr_of_selections: usize) -> bool {
+    match question_type {
+        QuestionType::SingleChoice | QuestionType::YesNoAbstain => nr_of_selections == 1,
+        QuestionType::MultiChoice { min, max } => {
+            *min as usize <= nr_of_selections && nr_of_selections <= *max as usize
+        }
+        QuestionType::Ranked(_) | QuestionType::WriteIn { .. } => false,
+    }
+}
+
+/// counts the plaintexts per declared option, each plaintext counts with its weight.
+/// the result maps each option name to its count (including options without votes).
+/// plaintexts which do not map to the options or which do not respect
+/// the question type are counted separately.
+pub fn count_options(
+    definition: &TopicDefinition,
+    plaintexts: &[BigUint],
+    weights: &[u64],
+) -> (TopicResult, RejectedVotes) {
+    let nr_of_options = definition.options.len();
+    let mut counts: Vec<u64> = vec![0; nr_of_options];
+    let mut rejected = RejectedVotes::default();
+
+    for (plaintext, weight) in plaintexts.iter().zip(weights.iter()) {
+        match selected_options(plaintext, &definition.encoding, nr_of_options) {
+            None => rejected.unknown += 1,
+            Some(selected)
+                if !is_valid_selection(&definition.question_type, selected.len()) =>
+            {
+                rejected.invalid += 1
+            }
+            Some(selected) => selected.iter().for_each(|index| {
+                counts[*index] = counts[*index].saturating_add(*weight)
+            }),
+        }
+    }
+
+    // type conversion: count (u64) to Vec<u8> to match the raw tally
+    let mut results: TopicResult = BTreeMap::new();
+    for (name, count) in definition.options.iter().zip(counts.into_iter()) {
+        results.insert(name.clone(), BigUint::from(count).to_bytes_be());
+    }
+    (results, rejected)
+}
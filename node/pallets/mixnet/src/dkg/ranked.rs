@@ -0,0 +1,204 @@
+use crate::types::{
+    RankedRound, RankingMethod, RejectedVotes, TopicDefinition, TopicResult, VoteId,
+};
+use crate::{BallotShuffleStateStore, Error, Trait};
+use core::cmp::Reverse;
+use crypto::types::ModuloOperations;
+use frame_support::{ensure, storage::StorageMap};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive};
+use sp_std::{collections::btree_map::BTreeMap, vec, vec::Vec};
+
+/// the maximal number of options of a ranked topic.
+/// bounds the width of a ballot and the discrete logarithms computed during the tally.
+pub const MAX_RANKED_OPTIONS: usize = 16;
+
+/// checks that the vote can mix rankings.
+/// a ranking consists of one cipher per rank which need to stay together,
+/// therefore, the ballots must be shuffled as rows.
+pub fn ensure_rankings_supported<T: Trait>(vote_id: &VoteId) -> Result<(), Error<T>> {
+    ensure!(
+        BallotShuffleStateStore::contains_key(vote_id),
+        Error::<T>::RankingNotSupported
+    );
+    Ok(())
+}
+
+/// returns the plaintexts of a ranking (option indices ordered by preference),
+/// one per rank. see `OptionEncoding::Ranking` for the encoding.
+pub fn encode_ranking(ranking: &[usize], nr_of_options: usize) -> Vec<BigUint> {
+    (0..nr_of_options)
+        .map(|rank| ranking.get(rank).map_or(0, |index| index + 1))
+        .map(BigUint::from)
+        .collect()
+}
+
+/// decodes the exponentially encoded plaintexts g^d (mod p) of the ranks with d <= n.
+/// a plaintext which cannot be decoded is mapped to n + 1, which is never a valid rank.
+/// at most n + 1 modular multiplications are required, independent of the plaintexts.
+pub fn decode_ranks(
+    plaintexts: &[BigUint],
+    nr_of_options: usize,
+    g: &BigUint,
+    p: &BigUint,
+) -> Vec<BigUint> {
+    let mut encoded: Vec<BigUint> = Vec::with_capacity(nr_of_options + 1);
+    let mut power: BigUint = BigUint::one();
+    for _ in 0..=nr_of_options {
+        encoded.push(power.clone());
+        power = power.modmul(g, p);
+    }
+
+    plaintexts
+        .iter()
+        .map(|plaintext| {
+            let digit = encoded
+                .iter()
+                .position(|power| power == plaintext)
+                .unwrap_or(nr_of_options + 1);
+            BigUint::from(digit)
+        })
+        .collect()
+}
+
+/// turns the plaintexts of the ranks into a ranking (option indices ordered by preference).
+/// returns None for empty rankings, gaps and options which are ranked twice.
+pub fn decode_ranking(ranks: &[BigUint], nr_of_options: usize) -> Option<Vec<usize>> {
+    let mut ranking: Vec<usize> = Vec::new();
+    let mut ended = false;
+
+    for rank in ranks.iter() {
+        let digit = rank.to_usize()?;
+        if digit == 0 {
+            ended = true;
+            continue;
+        }
+
+        // a preference after the end of the ranking is a gap
+        if ended || digit > nr_of_options || ranking.contains(&(digit - 1)) {
+            return None;
+        }
+        ranking.push(digit - 1);
+    }
+
+    if ranking.is_empty() {
+        None
+    } else {
+        Some(ranking)
+    }
+}
+
+/// counts the (weighted) rankings using instant-runoff voting.
+/// each round counts the first preference among the remaining options. if no option
+/// has a majority of the counted ballots, the option with the fewest votes is
+/// eliminated (ties: the option declared last) and the next round starts.
+/// returns the counts, the # of exhausted ballots and the eliminated option per round.
+fn instant_runoff(
+    rankings: &[(Vec<usize>, u64)],
+    nr_of_options: usize,
+) -> Vec<(Vec<(usize, u64)>, u64, Option<usize>)> {
+    let mut remaining: Vec<usize> = (0..nr_of_options).collect();
+    let mut rounds = Vec::new();
+
+    loop {
+        let mut counts: Vec<u64> = vec![0; nr_of_options];
+        let mut exhausted: u64 = 0;
+        for (ranking, weight) in rankings.iter() {
+            match ranking.iter().find(|index| remaining.contains(index)) {
+                Some(index) => counts[*index] = counts[*index].saturating_add(*weight),
+                None => exhausted = exhausted.saturating_add(*weight),
+            }
+        }
+
+        let round_counts: Vec<(usize, u64)> = remaining
+            .iter()
+            .map(|index| (*index, counts[*index]))
+            .collect();
+        let total: u64 = round_counts.iter().map(|(_, count)| count).sum();
+        let max: u64 = round_counts
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0);
+
+        // stop if an option has a majority or only one option remains
+        if remaining.len() <= 1 || 2 * max > total {
+            rounds.push((round_counts, exhausted, None));
+            return rounds;
+        }
+
+        let eliminated: usize = *remaining
+            .iter()
+            .min_by_key(|index| (counts[**index], Reverse(**index)))
+            .expect("at least two options remain");
+        remaining.retain(|index| *index != eliminated);
+        rounds.push((round_counts, exhausted, Some(eliminated)));
+    }
+}
+
+/// counts the (weighted) rankings using the borda count (a single round).
+fn borda(
+    rankings: &[(Vec<usize>, u64)],
+    nr_of_options: usize,
+) -> Vec<(Vec<(usize, u64)>, u64, Option<usize>)> {
+    let mut points: Vec<u64> = vec![0; nr_of_options];
+    for (ranking, weight) in rankings.iter() {
+        for (position, index) in ranking.iter().enumerate() {
+            let ranking_points = (nr_of_options - 1 - position) as u64;
+            points[*index] =
+                points[*index].saturating_add(ranking_points.saturating_mul(*weight));
+        }
+    }
+    let round_counts: Vec<(usize, u64)> = points.into_iter().enumerate().collect();
+    vec![(round_counts, 0, None)]
+}
+
+/// tallies the (decoded) plaintexts of a ranked topic, each ranking counts with its weight.
+/// the plaintexts of a ranking are consecutive, one plaintext per rank (see `decode_ranks`).
+/// returns the result of the final round (option name -> count or points),
+/// the # of invalid rankings and all rounds of the tally.
+pub fn tally_ranking(
+    definition: &TopicDefinition,
+    method: &RankingMethod,
+    plaintexts: &[BigUint],
+    weights: &[u64],
+) -> (TopicResult, RejectedVotes, Vec<RankedRound>) {
+    let nr_of_options = definition.options.len();
+    let mut rejected = RejectedVotes::default();
+
+    let mut rankings: Vec<(Vec<usize>, u64)> = Vec::with_capacity(weights.len());
+    for (ranks, weight) in plaintexts.chunks(nr_of_options).zip(weights.iter()) {
+        match decode_ranking(ranks, nr_of_options) {
+            Some(ranking) => rankings.push((ranking, *weight)),
+            None => rejected.invalid += 1,
+        }
+    }
+
+    let rounds = match method {
+        RankingMethod::InstantRunoff => instant_runoff(&rankings, nr_of_options),
+        RankingMethod::Borda => borda(&rankings, nr_of_options),
+    };
+
+    // type conversion: option index to option name
+    let name = |index: usize| definition.options[index].clone();
+    let rounds: Vec<RankedRound> = rounds
+        .into_iter()
+        .map(|(counts, exhausted, eliminated)| RankedRound {
+            counts: counts
+                .into_iter()
+                .map(|(index, count)| (name(index), count))
+                .collect(),
+            exhausted,
+            eliminated: eliminated.map(name),
+        })
+        .collect();
+
+    // the result of the tally is the final round
+    let mut results: TopicResult = BTreeMap::new();
+    if let Some(last) = rounds.last() {
+        for (option, count) in last.counts.iter() {
+            results.insert(option.clone(), BigUint::from(*count).to_bytes_be());
+        }
+    }
+    (results, rejected, rounds)
+}
@@ -1,41 +1,35 @@
+use super::{
+    homomorphic::tally_encrypted_sum,
+    options::count_options,
+    ranked::{decode_ranks, tally_ranking},
+    weights::decrypt_weights,
+    write_in::{answer_width, count_write_ins, write_in_length},
+};
 use crate::types::{
-    Cipher, DecryptedShare, NrOfShuffles, PublicParameters, TopicId, TopicResult, VoteId,
-    Wrapper,
+    Canonical, Cipher, DecryptedShare, NrOfShuffles, QuestionType, RankedRound,
+    RejectedVotes, TopicDefinition, TopicId, TopicResult, VoteId, VoterWeight,
 };
 use crate::{
-    helpers::params::get_public_params, Ciphers, DecryptedShares, Error, Sealers, Tally,
-    Trait,
+    helpers::params::get_public_params, Ciphers, DecryptedShares, EncryptedSums, Error,
+    RankedRounds, RejectedVotesByTopic, Tally, TopicDefinitions, Trait, VoteSealers,
+    WeightedVotes,
 };
 use crypto::encryption::ElGamal;
-use crypto::types::Cipher as BigCipher;
+use crypto::types::{Cipher as BigCipher, ElGamalParams};
 use frame_support::{
     ensure,
-    storage::{StorageDoubleMap, StorageMap, StorageValue},
+    storage::{StorageDoubleMap, StorageMap},
 };
 use num_bigint::BigUint;
-use num_traits::One;
-use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+use sp_std::{collections::btree_map::BTreeMap, vec, vec::Vec};
 
-pub fn combine_shares_and_tally_topic<T: Trait>(
+/// returns the decrypted shares of all sealers of the vote for a topic
+pub fn get_partial_decryptions<T: Trait>(
     vote_id: &VoteId,
     topic_id: &TopicId,
-    encoded: bool,
-    nr_of_shuffles: &NrOfShuffles,
-) -> Result<TopicResult, Error<T>> {
-    // get the public parameters and the system public key
-    let params: PublicParameters = get_public_params::<T>(vote_id)?;
-    let big_p: BigUint = BigUint::from_bytes_be(&params.p);
-    let big_g: BigUint = BigUint::from_bytes_be(&params.g);
-
-    // get all encrypted votes (ciphers)
-    // for the topic with id: topic_id and the # of shuffles (nr_of_shuffles)
-    let ciphers: Vec<Cipher> = Ciphers::get(topic_id, nr_of_shuffles);
-
-    // type conversion: Vec<Cipher> (Vec<Vec<u8>>) to Vec<BigCipher> (Vec<BigUint>)
-    let big_ciphers: Vec<BigCipher> = Wrapper(ciphers).into();
-
-    // retrieve the decrypted shares of all sealers
-    let sealers: Vec<T::AccountId> = Sealers::<T>::get();
+) -> Result<Vec<Vec<BigUint>>, Error<T>> {
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
+    let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(vote_id);
     let mut partial_decryptions: Vec<Vec<BigUint>> = Vec::with_capacity(sealers.len());
 
     for sealer in sealers.iter() {
@@ -49,45 +43,172 @@ pub fn combine_shares_and_tally_topic<T: Trait>(
         // type conversion: DecryptedShare (Vec<u8>) to BigUint
         let big_shares: Vec<BigUint> = shares
             .iter()
-            .map(|s| BigUint::from_bytes_be(s))
-            .collect::<Vec<BigUint>>();
+            .map(|s| params.decode_element(s))
+            .collect::<Option<Vec<BigUint>>>()
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
         partial_decryptions.push(big_shares);
     }
+    Ok(partial_decryptions)
+}
 
-    // combine all partial decryptions by all sealers
-    let combined_partial_decryptions =
-        ElGamal::combine_partial_decrypted_as(partial_decryptions, &big_p);
+pub fn combine_shares_and_tally_topic<T: Trait>(
+    vote_id: &VoteId,
+    topic_id: &TopicId,
+    encoded: bool,
+    nr_of_shuffles: &NrOfShuffles,
+) -> Result<TopicResult, Error<T>> {
+    // the votes of a homomorphically tallied topic are not shuffled, only their sum is decrypted
+    if EncryptedSums::contains_key(topic_id) {
+        return tally_encrypted_sum::<T>(vote_id, topic_id);
+    }
+
+    // get the public parameters and the system public key
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
+    let big_p: BigUint = params.p.clone();
+    let big_g: BigUint = params.g.clone();
+
+    // get all encrypted votes (ciphers)
+    // for the topic with id: topic_id and the # of shuffles (nr_of_shuffles)
+    let ciphers: Vec<Cipher> = Ciphers::get(topic_id, nr_of_shuffles);
+
+    // type conversion: Vec<Cipher> (Vec<Vec<u8>>) to Vec<BigCipher> (Vec<BigUint>)
+    let big_ciphers: Vec<BigCipher> = ciphers
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+
+    // retrieve the decrypted shares of all sealers of the vote
+    let partial_decryptions: Vec<Vec<BigUint>> =
+        get_partial_decryptions::<T>(vote_id, topic_id)?;
 
     // retrieve the plaintext votes
-    // by combining the decrypted components a with their decrypted components b
-    let iterator = big_ciphers.iter().zip(combined_partial_decryptions.iter());
-    let mut plaintexts = iterator
-        .map(|(cipher, decrypted_a)| {
-            ElGamal::partial_decrypt_b(&cipher.b, decrypted_a, &big_p)
-        })
-        .collect::<Vec<BigUint>>();
+    let mut plaintexts: Vec<BigUint> =
+        decrypt_plaintexts(&big_ciphers, partial_decryptions, &big_p);
+
+    // write-in answers are never exponentially encoded, they are decoded when counted
+    let definition: Option<TopicDefinition> = TopicDefinitions::get(topic_id);
+    let is_write_in: bool = definition.as_ref().and_then(write_in_length).is_some();
+    let ranked_options: Option<usize> = definition
+        .as_ref()
+        .filter(|definition| matches!(definition.question_type, QuestionType::Ranked(_)))
+        .map(|definition| definition.options.len());
 
-    // if the votes were encoded, we need to decoded them (brute force dlog)
-    if encoded {
+    // the ranks of a ranking are always exponentially encoded,
+    // their dlog is bounded by the number of options.
+    // otherwise, if the votes were encoded, we need to decoded them (brute force dlog)
+    if let Some(nr_of_options) = ranked_options {
+        plaintexts = decode_ranks(&plaintexts, nr_of_options, &big_g, &big_p);
+    } else if encoded && !is_write_in {
         plaintexts = plaintexts
             .iter()
             .map(|encoded| ElGamal::decode_message(encoded, &big_g, &big_p))
             .collect::<Vec<BigUint>>();
     }
 
+    // each answer (a single plaintext or `width` plaintexts of a write-in)
+    // is counted with the weight of its voter
+    let width: usize = answer_width::<T>(vote_id, topic_id)?;
+    ensure!(
+        plaintexts.len() % width == 0,
+        Error::<T>::WrongNumberOfDecryptedShares
+    );
+    let nr_of_answers: usize = plaintexts.len() / width;
+
+    // the decrypted weights are at the same position as the answers (ballot rows)
+    let weights: Vec<VoterWeight> = if WeightedVotes::get(vote_id) {
+        decrypt_weights::<T>(vote_id, nr_of_shuffles)?
+    } else {
+        vec![1; nr_of_answers]
+    };
+    ensure!(
+        weights.len() == nr_of_answers,
+        Error::<T>::UnknownVoterWeight
+    );
+
     // get the tally for the vote with topic id: topic_id
     let tally: Option<TopicResult> = Tally::get::<&TopicId>(topic_id);
 
     // check that topic has not been tallied yet
     ensure!(tally.is_none(), Error::<T>::TopicHasAlreadyBeenTallied);
 
-    // count the number of votes per voting option
+    // count the (weighted) votes of the topic
+    let (results, rejected, rounds): (TopicResult, RejectedVotes, Vec<RankedRound>) =
+        count_plaintexts(definition.as_ref(), &plaintexts, &weights, width, &big_p);
+
+    // if the topic declares its options, the plaintexts which do not map
+    // to a valid selection are counted separately
+    // and ranked topics publish the results of every round
+    if let Some(definition) = definition {
+        if let QuestionType::Ranked(_) = definition.question_type {
+            RankedRounds::insert(topic_id, rounds);
+        }
+        RejectedVotesByTopic::insert(topic_id, rejected);
+    }
+
+    // store the results on chain
+    Tally::insert::<&TopicId, TopicResult>(topic_id, results.clone());
+    Ok(results)
+}
+
+/// combines the partial decryptions of all sealers and decrypts the ciphers
+pub fn decrypt_plaintexts(
+    ciphers: &[BigCipher],
+    partial_decryptions: Vec<Vec<BigUint>>,
+    p: &BigUint,
+) -> Vec<BigUint> {
+    // combine all partial decryptions by all sealers
+    let combined_partial_decryptions =
+        ElGamal::combine_partial_decrypted_as(partial_decryptions, p);
+
+    // retrieve the plaintext votes
+    // by combining the decrypted components a with their decrypted components b
+    ciphers
+        .iter()
+        .zip(combined_partial_decryptions.iter())
+        .map(|(cipher, decrypted_a)| {
+            ElGamal::partial_decrypt_b(&cipher.b, decrypted_a, p)
+        })
+        .collect::<Vec<BigUint>>()
+}
+
+/// counts the (weighted) plaintexts of a topic.
+/// if the topic declares its options, the votes are counted per named option,
+/// ranking or write-in and the plaintexts which cannot be counted are rejected.
+/// otherwise, the votes are counted per plaintext.
+pub fn count_plaintexts(
+    definition: Option<&TopicDefinition>,
+    plaintexts: &[BigUint],
+    weights: &[VoterWeight],
+    width: usize,
+    p: &BigUint,
+) -> (TopicResult, RejectedVotes, Vec<RankedRound>) {
+    if let Some(definition) = definition {
+        return match &definition.question_type {
+            // write-ins are counted per decoded text
+            QuestionType::WriteIn { max_length } => {
+                let (results, rejected) =
+                    count_write_ins(*max_length, plaintexts, weights, width, p);
+                (results, rejected, Vec::new())
+            }
+            // ranked topics are counted in rounds
+            QuestionType::Ranked(method) => {
+                tally_ranking(definition, method, plaintexts, weights)
+            }
+            _ => {
+                let (results, rejected) = count_options(definition, plaintexts, weights);
+                (results, rejected, Vec::new())
+            }
+        };
+    }
+
+    // count the (weighted) number of votes per voting option
     // store result as a map -> key: voting option, value: count
-    let one = BigUint::one();
     let mut big_results: BTreeMap<BigUint, BigUint> = BTreeMap::new();
     plaintexts
-        .into_iter()
-        .for_each(|item| *big_results.entry(item).or_default() += &one);
+        .iter()
+        .zip(weights.iter())
+        .for_each(|(item, weight)| {
+            *big_results.entry(item.clone()).or_default() += *weight
+        });
 
     // type conversion: BTreeMap<BigUint, BigUint> to BTreeMap<Vec<u8>, Vec<u8>>
     // to be able to store the results on chain
@@ -95,8 +216,5 @@ pub fn combine_shares_and_tally_topic<T: Trait>(
     for (key, value) in big_results.iter() {
         results.insert(key.to_bytes_be(), value.to_bytes_be());
     }
-
-    // store the results on chain
-    Tally::insert::<&TopicId, TopicResult>(topic_id, results.clone());
-    Ok(results)
+    (results, RejectedVotes::default(), Vec::new())
 }
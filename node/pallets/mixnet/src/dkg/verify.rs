@@ -1,20 +1,25 @@
-use crate::helpers::params::get_public_params;
+use crate::helpers::params::{get_proof_version, get_public_params};
 use crate::types::{
-    Cipher, DecryptedShare, DecryptedShareProof, NrOfShuffles, PublicKeyShare,
-    PublicKeyShareProof, PublicParameters, TopicId, VoteId, Wrapper,
+    Canonical, Cipher, DecryptedShare, DecryptedShareCommitment, DecryptedShareProof,
+    NrOfShuffles, PublicKeyShare, TopicId, VoteId,
 };
 use crate::{
-    Ciphers, DecryptedShares, Error, PublicKeyShareBySealer, PublicKeyShares, Trait,
+    Ciphers, DecryptedShareCommitments, DecryptedShareProofs, DecryptedShares,
+    EncryptedSums, Error, PublicKeyShareBySealer, PublicKeyShares, Trait,
 };
 use codec::Encode;
-use crypto::proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof};
-use crypto::types::Cipher as BigCipher;
+use crypto::helper::ProofContext;
+use crypto::proofs::{
+    decryption::{DecryptionProof, DecryptionStatement},
+    keygen::KeyGenerationProof,
+};
+use crypto::types::{Cipher as BigCipher, ElGamalParams};
 use frame_support::{
     debug, ensure,
     storage::{StorageDoubleMap, StorageMap},
 };
 use num_bigint::BigUint;
-use sp_std::vec::Vec;
+use sp_std::{vec, vec::Vec};
 
 fn get_public_keyshare<T: Trait>(
     vote_id: &VoteId,
@@ -30,14 +35,20 @@ pub fn verify_proof_and_store_keygen_share<T: Trait>(
     pk_share: PublicKeyShare,
 ) -> Result<(), Error<T>> {
     // get the public parameters
-    let params: PublicParameters = get_public_params::<T>(&vote_id)?;
+    let params: ElGamalParams = get_public_params::<T>(&vote_id)?.into();
 
     // verify the public key share proof
     let sealer_id = who.encode();
-    let proof: PublicKeyShareProof = pk_share.proof.clone();
-    let pk: BigUint = BigUint::from_bytes_be(&pk_share.pk);
+    let context = ProofContext::with_version(vote_id, get_proof_version(vote_id));
+    let proof: KeyGenerationProof = pk_share
+        .proof
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+    let pk: BigUint = params
+        .decode_element(&pk_share.pk)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
     let proof_valid =
-        KeyGenerationProof::verify(&params.into(), &pk, &proof.into(), &sealer_id);
+        KeyGenerationProof::verify(&params, &pk, &proof, &sealer_id, &context);
     ensure!(proof_valid, Error::<T>::PublicKeyShareProofError);
 
     // store the public key share
@@ -55,36 +66,70 @@ pub fn verify_proof_and_store_decrypted_share<T: Trait>(
     topic_id: &TopicId,
     shares: Vec<DecryptedShare>,
     proof: DecryptedShareProof,
+    commitments: Vec<DecryptedShareCommitment>,
     nr_of_shuffles: &NrOfShuffles,
 ) -> Result<(), Error<T>> {
     // get the public parameters and the public key share of the sealer
     let sealer_id: &[u8] = &who.encode();
-    let params: PublicParameters = get_public_params::<T>(vote_id)?;
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
     let sealer_pk_share: PublicKeyShare = get_public_keyshare::<T>(vote_id, &who)?;
-    let sealer_pk: BigUint = BigUint::from_bytes_be(&sealer_pk_share.pk);
+    let sealer_pk: BigUint = params
+        .decode_element(&sealer_pk_share.pk)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
 
     // get all encrypted votes (ciphers)
     // for the topic with id: topic_id and the # of shuffles (nr_of_shuffles)
-    let ciphers: Vec<Cipher> = Ciphers::get(topic_id, nr_of_shuffles);
+    // if the topic is tallied homomorphically, only the encrypted sum is decrypted
+    let ciphers: Vec<Cipher> = match EncryptedSums::get(topic_id) {
+        Some(sum) => vec![sum],
+        None => Ciphers::get(topic_id, nr_of_shuffles),
+    };
+    ensure!(
+        !ciphers.is_empty() && ciphers.len() == shares.len(),
+        Error::<T>::WrongNumberOfDecryptedShares
+    );
 
     // type conversion: Vec<Cipher> (Vec<Vec<u8>>) to Vec<BigCipher> (Vec<BigUint>)
-    let big_ciphers: Vec<BigCipher> = Wrapper(ciphers).into();
+    let big_ciphers: Vec<BigCipher> = ciphers
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
 
     // type conversion: DecryptedShare (Vec<u8>) to BigUint
+    // only canonically encoded shares are accepted, i.e. a share has a single encoding
     let decrypted_shares: Vec<BigUint> = shares
         .iter()
-        .map(|s| BigUint::from_bytes_be(s))
-        .collect::<Vec<BigUint>>();
+        .map(|s| params.decode_element(s))
+        .collect::<Option<Vec<BigUint>>>()
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
 
-    // verify the proof using the sealer's public key share
-    let is_valid: bool = DecryptionProof::verify(
-        &params.into(),
-        &sealer_pk,
-        &proof.into(),
-        big_ciphers,
-        decrypted_shares,
-        sealer_id,
+    // type conversion: DecryptedShareProof (Vec<u8>) to DecryptionProof
+    let big_proof: DecryptionProof = proof
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+
+    // the commitments of the proof: t_0 and one commitment per share (or none)
+    ensure!(
+        commitments.is_empty() || commitments.len() == shares.len() + 1,
+        Error::<T>::DecryptedShareProofError
     );
+    let big_commitments: Vec<BigUint> = commitments
+        .iter()
+        .map(|t| params.decode_element(t))
+        .collect::<Option<Vec<BigUint>>>()
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+
+    // verify the proof using the sealer's public key share
+    // the same verification is used for the election record (see `verify_decryptions`)
+    let context = ProofContext::with_version(vote_id, get_proof_version(vote_id));
+    let statement = DecryptionStatement {
+        pk: sealer_pk,
+        proof: big_proof,
+        commitments: big_commitments,
+        encryptions: big_ciphers,
+        partial_decryptions: decrypted_shares,
+        id: sealer_id.to_vec(),
+    };
+    let is_valid: bool = DecryptionProof::verify_batch(&params, &[statement], &context);
     ensure!(is_valid, Error::<T>::DecryptedShareProofError);
 
     // store the decrypted shares
@@ -99,6 +144,9 @@ pub fn verify_proof_and_store_decrypted_share<T: Trait>(
     }
 
     // store the decrypted shares per topic and sealer
+    // and the proof for the verification of the election record
     DecryptedShares::<T>::insert(topic_id, &who, stored);
+    DecryptedShareProofs::<T>::insert(topic_id, &who, (*nr_of_shuffles, proof));
+    DecryptedShareCommitments::<T>::insert(topic_id, &who, commitments);
     Ok(())
 }
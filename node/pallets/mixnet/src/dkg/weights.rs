@@ -0,0 +1,93 @@
+use super::tally::{decrypt_plaintexts, get_partial_decryptions};
+use crate::helpers::params::get_public_params;
+use crate::types::{
+    Canonical, Cipher, NrOfShuffles, PublicParameters, TopicId, VoteId, VoterWeight,
+};
+use crate::{Ciphers, Error, Trait, VoterWeights};
+use crypto::encryption::ElGamal;
+use crypto::types::{Cipher as BigCipher, ElGamalParams};
+use frame_support::{
+    ensure,
+    storage::{IterableStorageDoubleMap, StorageDoubleMap},
+};
+use num_bigint::BigUint;
+use num_traits::One;
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+pub const DEFAULT_VOTER_WEIGHT: VoterWeight = 1;
+
+/// the topic under which the shuffled (encrypted) weights of a vote are stored
+/// and decrypted by the sealers
+pub fn weight_topic_id(vote_id: &VoteId) -> TopicId {
+    [vote_id.as_slice(), b"#weights"].concat()
+}
+
+/// returns the weight of a voter (1 if no weight has been set)
+pub fn get_voter_weight<T: Trait>(vote_id: &VoteId, voter: &T::AccountId) -> VoterWeight {
+    VoterWeights::<T>::get(vote_id, voter).unwrap_or(DEFAULT_VOTER_WEIGHT)
+}
+
+/// encrypts the encoded weight using the randomness 0: (g^0, h^0 * g^w) = (1, g^w).
+/// the weight of a voter is public, it is hidden by the re-encryption of the first shuffle.
+pub fn encrypted_weight(weight: VoterWeight, params: &PublicParameters) -> Cipher {
+    let params: ElGamalParams = params.clone().into();
+    let cipher = BigCipher {
+        a: BigUint::one(),
+        b: ElGamal::encode_message(&BigUint::from(weight), &params.g, &params.p),
+    };
+    Cipher::encode_canonical(&cipher, &params)
+}
+
+/// decrypts the shuffled weights of a vote using the decrypted shares of all sealers.
+/// the weights are decoded by comparing them to the weights of the voters,
+/// i.e., each decrypted weight must be the weight of a voter.
+pub fn decrypt_weights<T: Trait>(
+    vote_id: &VoteId,
+    nr_of_shuffles: &NrOfShuffles,
+) -> Result<Vec<VoterWeight>, Error<T>> {
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
+    let big_p: BigUint = params.p.clone();
+    let big_g: BigUint = params.g.clone();
+
+    // get the shuffled weights and their decrypted shares
+    let topic_id: TopicId = weight_topic_id(vote_id);
+    let ciphers: Vec<Cipher> = Ciphers::get(&topic_id, nr_of_shuffles);
+    let partial_decryptions: Vec<Vec<BigUint>> =
+        get_partial_decryptions::<T>(vote_id, &topic_id)?;
+    ensure!(
+        partial_decryptions
+            .iter()
+            .all(|shares| shares.len() == ciphers.len()),
+        Error::<T>::WrongNumberOfDecryptedShares
+    );
+    let big_ciphers: Vec<BigCipher> = ciphers
+        .decode_canonical(&params)
+        .ok_or(Error::<T>::NonCanonicalEncoding)?;
+    let encoded: Vec<BigUint> =
+        decrypt_plaintexts(&big_ciphers, partial_decryptions, &big_p);
+
+    let registered = VoterWeights::<T>::iter_prefix_values(vote_id);
+    decode_weights(&encoded, registered, &big_g, &big_p)
+        .ok_or(Error::<T>::UnknownVoterWeight)
+}
+
+/// decodes the encoded weights g^w by comparing them to the weights of the voters,
+/// i.e., each weight must be the weight of a voter or the default weight.
+pub fn decode_weights(
+    encoded: &[BigUint],
+    registered: impl Iterator<Item = VoterWeight>,
+    g: &BigUint,
+    p: &BigUint,
+) -> Option<Vec<VoterWeight>> {
+    // map each encoded weight g^w of a voter to its weight w
+    let mut encoded_weights: BTreeMap<BigUint, VoterWeight> = BTreeMap::new();
+    for weight in registered.chain(Some(DEFAULT_VOTER_WEIGHT).into_iter()) {
+        let encoded = ElGamal::encode_message(&BigUint::from(weight), g, p);
+        encoded_weights.insert(encoded, weight);
+    }
+
+    encoded
+        .iter()
+        .map(|encoded| encoded_weights.get(encoded).copied())
+        .collect()
+}
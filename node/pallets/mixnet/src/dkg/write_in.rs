@@ -0,0 +1,105 @@
+use crate::helpers::params::get_public_params;
+use crate::types::{
+    PublicParameters, QuestionType, RejectedVotes, TopicDefinition, TopicId, TopicResult,
+    VoteId,
+};
+use crate::{BallotShuffleStateStore, Error, TopicDefinitions, Trait};
+use crypto::encoding::Encoding;
+use frame_support::{ensure, storage::StorageMap};
+use num_bigint::BigUint;
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+/// the maximal length of a write-in answer in bytes
+pub const MAX_WRITE_IN_LENGTH: u32 = 1024;
+
+/// returns the maximal length of the answer if the topic asks for a write-in
+pub fn write_in_length(definition: &TopicDefinition) -> Option<u32> {
+    match definition.question_type {
+        QuestionType::WriteIn { max_length } => Some(max_length),
+        _ => None,
+    }
+}
+
+/// checks that the vote can mix write-in answers.
+/// a write-in answer consists of several ciphers which need to stay together,
+/// therefore, the ballots must be shuffled as rows.
+/// in addition, a group element must be able to hold at least one byte.
+pub fn ensure_write_ins_supported<T: Trait>(vote_id: &VoteId) -> Result<(), Error<T>> {
+    ensure!(
+        BallotShuffleStateStore::contains_key(vote_id),
+        Error::<T>::WriteInNotSupported
+    );
+    let params: PublicParameters = get_public_params::<T>(vote_id)?;
+    let big_p: BigUint = BigUint::from_bytes_be(&params.p);
+    ensure!(big_p.bits() > 9, Error::<T>::WriteInNotSupported);
+    Ok(())
+}
+
+/// the number of ciphers which make up the answer of a topic:
+/// the number of group elements of a write-in of maximal length,
+/// one cipher per rank of a ranking, otherwise 1
+pub fn answer_width<T: Trait>(
+    vote_id: &VoteId,
+    topic_id: &TopicId,
+) -> Result<usize, Error<T>> {
+    let definition: Option<TopicDefinition> = TopicDefinitions::get(topic_id);
+    let params: PublicParameters = get_public_params::<T>(vote_id)?;
+    let big_p: BigUint = BigUint::from_bytes_be(&params.p);
+    Ok(answer_width_of(definition.as_ref(), &big_p))
+}
+
+/// the number of ciphers which make up the answer of a topic with the given definition
+pub fn answer_width_of(definition: Option<&TopicDefinition>, p: &BigUint) -> usize {
+    match definition {
+        Some(TopicDefinition {
+            question_type: QuestionType::WriteIn { max_length },
+            ..
+        }) => Encoding::nr_of_elements(*max_length as usize, p),
+        Some(TopicDefinition {
+            question_type: QuestionType::Ranked(_),
+            options,
+            ..
+        }) => options.len(),
+        _ => 1,
+    }
+}
+
+/// counts the write-in answers, each answer counts with its weight.
+/// the plaintexts of an answer are consecutive, `width` plaintexts form one answer.
+/// the result maps each answer (text) to its count.
+/// plaintexts which cannot be decoded are counted as unknown,
+/// empty answers, answers which are too long or not valid UTF-8 as invalid.
+pub fn count_write_ins(
+    max_length: u32,
+    plaintexts: &[BigUint],
+    weights: &[u64],
+    width: usize,
+    p: &BigUint,
+) -> (TopicResult, RejectedVotes) {
+    let mut counts: BTreeMap<Vec<u8>, u64> = BTreeMap::new();
+    let mut rejected = RejectedVotes::default();
+
+    for (answer, weight) in plaintexts.chunks(width).zip(weights.iter()) {
+        match Encoding::decode_bytes(answer, p) {
+            None => rejected.unknown += 1,
+            Some(text)
+                if text.is_empty()
+                    || text.len() > max_length as usize
+                    || core::str::from_utf8(&text).is_err() =>
+            {
+                rejected.invalid += 1
+            }
+            Some(text) => {
+                let count = counts.entry(text).or_default();
+                *count = count.saturating_add(*weight);
+            }
+        }
+    }
+
+    // type conversion: count (u64) to Vec<u8> to match the raw tally
+    let results: TopicResult = counts
+        .into_iter()
+        .map(|(text, count)| (text, BigUint::from(count).to_bytes_be()))
+        .collect();
+    (results, rejected)
+}
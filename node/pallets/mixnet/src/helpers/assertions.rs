@@ -1,8 +1,12 @@
 use crate::{
-    types::{VoteId, VotePhase},
-    Error, Module, Trait, Votes,
+    types::{Topic, Vote, VoteId, VotePhase},
+    Error, Module, Tally, Topics, Trait, VoteIds, VoteSealers, Votes,
 };
-use frame_support::{debug, ensure, storage::StorageMap};
+use frame_support::{
+    debug, ensure,
+    storage::{StorageMap, StorageValue},
+};
+use sp_std::vec::Vec;
 
 pub fn ensure_voting_authority<T: Trait>(
     account_id: &T::AccountId,
@@ -30,17 +34,45 @@ pub fn ensure_not_a_voting_authority<T: Trait>(
     }
 }
 
-pub fn ensure_sealer<T: Trait>(account_id: &T::AccountId) -> Result<(), Error<T>> {
-    let sealers = Module::<T>::sealers();
+pub fn ensure_sealer<T: Trait>(
+    account_id: &T::AccountId,
+    vote_id: &VoteId,
+) -> Result<(), Error<T>> {
+    let sealers = VoteSealers::<T>::get(vote_id);
     match sealers.contains(account_id) {
         true => Ok(()),
         false => {
-            debug::info!("Requester: {:?} is not a sealer!", account_id);
+            debug::info!(
+                "Requester: {:?} is not a sealer of vote: {:?}!",
+                account_id,
+                vote_id
+            );
             Err(Error::<T>::NotASealer)
         }
     }
 }
 
+pub fn ensure_valid_vote_sealers<T: Trait>(
+    sealers: &[T::AccountId],
+) -> Result<(), Error<T>> {
+    // a vote cannot be shuffled or decrypted without any sealers
+    ensure!(!sealers.is_empty(), Error::<T>::NoSealersForVote);
+
+    // each sealer of the vote must be a registered sealer
+    let registered_sealers = Module::<T>::sealers();
+    for (index, sealer) in sealers.iter().enumerate() {
+        if !registered_sealers.contains(sealer) {
+            debug::info!("Account: {:?} is not a registered sealer!", sealer);
+            return Err(Error::<T>::NotASealer);
+        }
+        ensure!(
+            !sealers[..index].contains(sealer),
+            Error::<T>::DuplicateSealer
+        );
+    }
+    Ok(())
+}
+
 pub fn ensure_vote_exists<T: Trait>(vote_id: &VoteId) -> Result<(), Error<T>> {
     // check that the vote_id exists
     ensure!(
@@ -68,3 +100,56 @@ pub fn ensure_vote_phase<T: Trait>(
     ensure!(vote.phase == phase, Error::<T>::WrongVotePhase);
     Ok(())
 }
+
+/// a vote is in progress as long as it is not aborted
+/// and not all of its topics have been tallied.
+/// a vote without topics is only in progress during key generation and voting.
+pub fn is_vote_in_progress<T: Trait>(vote_id: &VoteId) -> bool {
+    let vote: Vote<T::AccountId> = Votes::<T>::get(vote_id);
+    if vote.phase == VotePhase::Aborted {
+        return false;
+    }
+    let topics: Vec<Topic> = Topics::get(vote_id);
+    if topics.is_empty() {
+        return vote.phase == VotePhase::KeyGeneration || vote.phase == VotePhase::Voting;
+    }
+    topics
+        .iter()
+        .any(|(topic_id, _)| Tally::get(topic_id).is_none())
+}
+
+pub fn ensure_no_vote_in_progress_as_voting_authority<T: Trait>(
+    account_id: &T::AccountId,
+) -> Result<(), Error<T>> {
+    let vote_ids: Vec<VoteId> = VoteIds::get();
+    for vote_id in vote_ids.iter() {
+        let vote: Vote<T::AccountId> = Votes::<T>::get(vote_id);
+        if &vote.voting_authority == account_id && is_vote_in_progress::<T>(vote_id) {
+            debug::info!(
+                "Voting authority: {:?} runs vote: {:?} which is in progress!",
+                account_id,
+                vote_id
+            );
+            return Err(Error::<T>::MemberOfVoteInProgress);
+        }
+    }
+    Ok(())
+}
+
+pub fn ensure_no_vote_in_progress_as_sealer<T: Trait>(
+    account_id: &T::AccountId,
+) -> Result<(), Error<T>> {
+    let vote_ids: Vec<VoteId> = VoteIds::get();
+    for vote_id in vote_ids.iter() {
+        let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(vote_id);
+        if sealers.contains(account_id) && is_vote_in_progress::<T>(vote_id) {
+            debug::info!(
+                "Sealer: {:?} takes part in vote: {:?} which is in progress!",
+                account_id,
+                vote_id
+            );
+            return Err(Error::<T>::MemberOfVoteInProgress);
+        }
+    }
+    Ok(())
+}
@@ -1,14 +1,51 @@
-use crate::types::{Ballot, Cipher, VoteId};
-use crate::{Ballots, Ciphers, Trait};
-use frame_support::storage::StorageDoubleMap;
+use super::params::get_public_params;
+use crate::dkg::{
+    homomorphic::add_to_encrypted_sum,
+    weights::{encrypted_weight, get_voter_weight},
+    write_in::answer_width,
+};
+use crate::types::{Ballot, BallotRow, Canonical, Cipher, Topic, VoteId, VoterWeight};
+use crate::{
+    BallotRows, BallotShuffleStateStore, Ballots, Ciphers, EncryptedSums, Error, Topics,
+    Trait, WeightedVotes,
+};
+use crypto::types::ElGamalParams;
+use frame_support::{
+    ensure,
+    storage::{StorageDoubleMap, StorageMap},
+};
 use sp_std::vec::Vec;
 
 const INITIAL_NUMBER_OF_SHUFFLES: u8 = 0;
 
-pub fn store_ballot<T: Trait>(from: &T::AccountId, vote_id: &VoteId, ballot: Ballot) {
+pub fn store_ballot<T: Trait>(
+    from: &T::AccountId,
+    vote_id: &VoteId,
+    ballot: Ballot,
+) -> Result<(), Error<T>> {
     // TODO: perform ballot duplication check
     // TODO: perform voter double vote cast check
 
+    // only canonically encoded ciphers are accepted, i.e. every cipher has a single encoding
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
+    ensure!(
+        ballot
+            .answers
+            .iter()
+            .all(|(_, cipher)| cipher.decode_canonical(&params).is_some()),
+        Error::<T>::NonCanonicalEncoding
+    );
+
+    // the ballot is counted with the weight of the voter
+    let weight: VoterWeight = get_voter_weight::<T>(vote_id, from);
+
+    // if the ballots are shuffled as rows, the ballot must answer every topic
+    let row: Option<BallotRow> = if BallotShuffleStateStore::contains_key(vote_id) {
+        Some(ballot_row::<T>(vote_id, &ballot, weight)?)
+    } else {
+        None
+    };
+
     // store the encrypted ballot
     Ballots::<T>::insert(vote_id, from, ballot.clone());
 
@@ -17,9 +54,74 @@ pub fn store_ballot<T: Trait>(from: &T::AccountId, vote_id: &VoteId, ballot: Bal
         // # of shuffles is always 0 -> since the voter has just submitted the vote
         let mut ciphers: Vec<Cipher> =
             Ciphers::get(&topic_id, INITIAL_NUMBER_OF_SHUFFLES);
-        ciphers.push(cipher);
+        ciphers.push(cipher.clone());
 
         // store the ciphers
         Ciphers::insert(&topic_id, INITIAL_NUMBER_OF_SHUFFLES, ciphers);
+
+        // if the topic is tallied homomorphically, add the cipher to its encrypted sum
+        if EncryptedSums::contains_key(&topic_id) {
+            add_to_encrypted_sum::<T>(vote_id, &topic_id, &cipher, weight)?;
+        }
+    }
+
+    // store the answers as a single row
+    if let Some(row) = row {
+        let mut rows: Vec<BallotRow> =
+            BallotRows::get(vote_id, INITIAL_NUMBER_OF_SHUFFLES);
+        rows.push(row);
+        BallotRows::insert(vote_id, INITIAL_NUMBER_OF_SHUFFLES, rows);
+    }
+    Ok(())
+}
+
+/// the number of ciphers of a ballot row: one per topic (several for a write-in)
+/// and the encrypted weight of the voter if the vote is weighted
+pub fn ballot_row_width<T: Trait>(vote_id: &VoteId) -> Result<usize, Error<T>> {
+    let topics: Vec<Topic> = Topics::get(vote_id);
+    let mut width: usize = WeightedVotes::get(vote_id) as usize;
+    for (topic_id, _) in topics.iter() {
+        width += answer_width::<T>(vote_id, topic_id)?;
+    }
+    Ok(width)
+}
+
+/// orders the answers of a ballot by the topics of the vote.
+/// every topic must be answered exactly once, a write-in answer
+/// consists of consecutive ciphers with the same topic id.
+/// if the vote is weighted, the encrypted weight is appended to the row.
+fn ballot_row<T: Trait>(
+    vote_id: &VoteId,
+    ballot: &Ballot,
+    weight: VoterWeight,
+) -> Result<BallotRow, Error<T>> {
+    let topics: Vec<Topic> = Topics::get(vote_id);
+    ensure!(!topics.is_empty(), Error::<T>::InvalidBallotRow);
+
+    let mut row: BallotRow = Vec::with_capacity(ballot.answers.len());
+    for (topic_id, _) in topics.iter() {
+        let ciphers: Vec<&Cipher> = ballot
+            .answers
+            .iter()
+            .filter(|(id, _)| id == topic_id)
+            .map(|(_, cipher)| cipher)
+            .collect();
+        ensure!(
+            ciphers.len() == answer_width::<T>(vote_id, topic_id)?,
+            Error::<T>::InvalidBallotRow
+        );
+        row.extend(ciphers.into_iter().cloned());
+    }
+
+    // answers to unknown topics are not allowed
+    ensure!(
+        row.len() == ballot.answers.len(),
+        Error::<T>::InvalidBallotRow
+    );
+
+    if WeightedVotes::get(vote_id) {
+        let params = get_public_params::<T>(vote_id)?;
+        row.push(encrypted_weight(weight, &params));
     }
+    Ok(row)
 }
@@ -1,5 +1,5 @@
 use crate::{Module, Trait};
-use crypto::types::ModuloOperations;
+use crypto::{multi_exp::MultiExp, types::ModuloOperations};
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 use sp_std::vec::Vec;
@@ -14,12 +14,24 @@ impl<T: Trait> Module<T> {
         a: &Vec<BigUint>,
         b: &Vec<BigUint>,
         modulus: &BigUint,
+    ) -> BigUint {
+        assert!(a.len() == b.len(), "vectors must have the same length!");
+        // Π(a_i^b_i % modulus) % modulus, all exponentiations at once
+        MultiExp::multi_modpow(a, b, modulus)
+    }
+
+    /// same as `zip_vectors_multiply_a_pow_b` for secret exponents b:
+    /// every exponentiation and multiplication uses the operations for secret values
+    pub fn zip_vectors_multiply_a_pow_b_secret(
+        a: &Vec<BigUint>,
+        b: &Vec<BigUint>,
+        modulus: &BigUint,
     ) -> BigUint {
         assert!(a.len() == b.len(), "vectors must have the same length!");
         let iterator = a.iter().zip(b.iter());
-        iterator.fold(BigUint::one(), |prod, (a_i, b_i)| {
-            // Π(a_i^b_i % modulus) % modulus
-            prod.modmul(&a_i.modpow(b_i, modulus), modulus)
+        // Π(a_i^b_i % modulus) % modulus
+        iterator.fold(BigUint::one(), |product, (a_i, b_i)| {
+            product.modmul_secret(&a_i.modpow_secret(b_i, modulus), modulus)
         })
     }
 
@@ -27,6 +39,8 @@ impl<T: Trait> Module<T> {
     /// performs component-wise operation: x = a_i * b_i % modulus
     /// sums all component-wise operation results
     /// Σ(x) % modulus
+    /// the vectors contain secret values (e.g. randoms of the prover),
+    /// therefore, the operations for secret values are used
     pub fn zip_vectors_sum_products(
         a: &Vec<BigUint>,
         b: &Vec<BigUint>,
@@ -36,7 +50,7 @@ impl<T: Trait> Module<T> {
         let iterator = a.iter().zip(b.iter());
         // Σ(a_i * b_i) % modulus
         iterator.fold(BigUint::zero(), |sum, (a_i, b_i)| {
-            sum.modadd(&a_i.modmul(b_i, modulus), modulus)
+            sum.modadd_secret(&a_i.modmul_secret(b_i, modulus), modulus)
         })
     }
 }
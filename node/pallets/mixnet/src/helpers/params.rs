@@ -1,8 +1,10 @@
 use super::assertions::ensure_vote_exists;
 use crate::{
     types::{PublicKey as SubstratePK, PublicParameters, Vote, VoteId},
-    Error, PublicKey, Trait, Votes,
+    Error, ProofVersions, PublicKey, Trait, Votes,
 };
+use core::convert::TryFrom;
+use crypto::helper::ProofVersion;
 use frame_support::storage::StorageMap;
 
 /// all functions related to key generation and decrypted share operations
@@ -19,3 +21,11 @@ pub fn get_public_params<T: Trait>(
 pub fn get_public_key<T: Trait>(vote_id: &VoteId) -> Result<SubstratePK, Error<T>> {
     PublicKey::get(vote_id).ok_or(Error::<T>::PublicKeyNotExistsError)
 }
+
+/// the version of the Fiat-Shamir transform of the proofs of a vote.
+/// votes which have been created before the proofs were versioned use version 1.
+pub fn get_proof_version(vote_id: &VoteId) -> ProofVersion {
+    ProofVersions::get(vote_id)
+        .and_then(|version| ProofVersion::try_from(version).ok())
+        .unwrap_or(ProofVersion::V1)
+}
@@ -1,7 +1,8 @@
 use super::assertions::{ensure_vote_exists, ensure_voting_authority};
-use crate::types::{Vote, VoteId, VotePhase};
-use crate::{Error, Trait, Votes};
-use frame_support::{debug, storage::StorageMap};
+use crate::types::{Topic, Vote, VoteId, VotePhase};
+use crate::{Error, Module, Topics, Trait, Votes};
+use frame_support::{debug, ensure, storage::StorageMap};
+use sp_std::vec::Vec;
 
 /// all functions related to key generation and decrypted share operations
 pub fn set_phase<T: Trait>(
@@ -14,10 +15,30 @@ pub fn set_phase<T: Trait>(
     // pase can only be changed if the vote exists
     ensure_vote_exists(vote_id)?;
 
-    // set the new phase
+    // an aborted vote cannot be changed anymore
+    // and a vote can only be aborted using abort_vote
     let mut vote: Vote<T::AccountId> = Votes::<T>::get(&vote_id);
+    ensure!(vote.phase != VotePhase::Aborted, Error::<T>::VoteIsAborted);
+    ensure!(phase != VotePhase::Aborted, Error::<T>::VoteIsAborted);
+
+    // only the votes in the tallying phase are checked for missed turns
+    if phase == VotePhase::Tallying {
+        Module::<T>::track_tallying_vote(vote_id)?;
+    } else {
+        Module::<T>::untrack_tallying_vote(vote_id);
+    }
+
+    // set the new phase
     vote.phase = phase.clone();
     Votes::<T>::insert(&vote_id, &vote);
+
+    // the shuffling starts with the tallying phase
+    if phase == VotePhase::Tallying {
+        let topics: Vec<Topic> = Topics::get(vote_id);
+        for (topic_id, _) in topics.iter() {
+            Module::<T>::note_shuffle_activity(vote_id, topic_id);
+        }
+    }
     debug::info!("vote phase updated! new phase: {:?}", phase);
     Ok(())
 }
@@ -16,6 +16,14 @@ mod dkg;
 #[allow(clippy::many_single_char_names)]
 pub mod types;
 
+mod approval;
+
+mod misbehaviour;
+
+mod purge;
+
+mod record;
+
 mod bench;
 
 #[cfg(test)]
@@ -27,29 +35,36 @@ mod tests;
 
 pub mod keys;
 
-use crate::dkg::{
-    create::combine_shares,
-    tally::combine_shares_and_tally_topic,
-    verify::{
-        verify_proof_and_store_decrypted_share, verify_proof_and_store_keygen_share,
-    },
+use crate::dkg::homomorphic::{empty_sum, is_summable_definition};
+use crate::dkg::options::is_valid_definition;
+use crate::dkg::ranked::ensure_rankings_supported;
+use crate::dkg::verify::{
+    verify_proof_and_store_decrypted_share, verify_proof_and_store_keygen_share,
 };
+use crate::dkg::write_in::{ensure_write_ins_supported, write_in_length};
 use crate::helpers::{
     assertions::{
-        ensure_not_a_voting_authority, ensure_sealer, ensure_vote_does_not_exist,
+        ensure_no_vote_in_progress_as_sealer,
+        ensure_no_vote_in_progress_as_voting_authority, ensure_not_a_voting_authority,
+        ensure_sealer, ensure_valid_vote_sealers, ensure_vote_does_not_exist,
         ensure_vote_exists, ensure_vote_phase, ensure_voting_authority,
     },
     ballot::store_ballot,
-    phase::set_phase,
+    params::get_public_params,
 };
 use crate::types::{
-    Ballot, Cipher, DecryptedShare, DecryptedShareProof, NrOfShuffles,
-    PublicKey as SubstratePK, PublicKeyShare, PublicParameters, ShufflePayload,
-    ShuffleState, Title, Topic, TopicId, TopicResult, Vote, VoteId, VotePhase,
+    AuthorityAction, Ballot, BallotRow, Cipher, DecryptedShare, DecryptedShareCommitment,
+    DecryptedShareProof, Misbehaviour, MisbehaviourRecord, NrOfShuffles, Proposal, ProposalId,
+    PublicKey as SubstratePK, PublicKeyShare, PublicParameters, QuestionType,
+    RankedRound, RejectedVotes, RowShufflePayload, ShufflePayload, ShuffleState, Title,
+    Topic, TopicDefinition, TopicId, TopicResult, Vote, VoteId, VotePhase, VoterWeight,
 };
+use crypto::helper::ProofVersion;
 use frame_support::{
     debug, decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult,
-    storage::StorageMap, storage::StorageValue, traits::Get, weights::Pays,
+    ensure, storage::StorageMap, storage::StorageValue, traits::Currency,
+    traits::EnsureOrigin, traits::Get, traits::ReservableCurrency, weights::Pays,
+    weights::Weight,
 };
 use frame_system::{
     ensure_signed,
@@ -71,15 +86,63 @@ pub trait Trait:
     // Wait period between automated fetches. Set to 0 disable this feature.
     //   Then you need to manucally kickoff pricefetch
     type BlockDuration: Get<Self::BlockNumber>;
+
+    /// The origin which is allowed to add and remove voting authorities and sealers.
+    type ManagementOrigin: EnsureOrigin<Self::Origin>;
+
+    /// The currency used to bond and slash sealers.
+    type Currency: ReservableCurrency<Self::AccountId>;
+
+    /// The amount reserved from an account when it is added as a sealer.
+    /// Misbehaving sealers are slashed from their bond.
+    type SealerBond: Get<BalanceOf<Self>>;
+
+    /// The amount slashed from the bond of a sealer per recorded misbehaviour.
+    /// Set to 0 to disable slashing.
+    type MisbehaviourSlash: Get<BalanceOf<Self>>;
+
+    /// Number of blocks after the shuffling of a topic has been completed
+    /// within which the sealers need to submit their decrypted shares.
+    /// Set to 0 to disable this check.
+    type DecryptionPeriod: Get<Self::BlockNumber>;
+
+    /// Number of blocks after a vote has ended (tallied or aborted)
+    /// before its intermediate data can be purged.
+    type RetentionPeriod: Get<Self::BlockNumber>;
+
+    /// Maximum number of votes which can be in the tallying phase at the same time.
+    /// Bounds the work of the missed turn detection in `on_initialize`.
+    type MaxTallyingVotes: Get<u32>;
 }
 
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
 decl_storage! {
     trait Store for Module<T: Trait> as OffchainModule {
         pub VotingAuthorities get(fn voting_authorities) config(): Vec<T::AccountId>;
+
+        /// The set of accounts which can be chosen as sealers when creating a vote
         pub Sealers get(fn sealers) config(): Vec<T::AccountId>;
 
-        CountsBySealer get(fn count_by_sealer): map hasher(blake2_128_concat) T::AccountId => u32;
-        Counts get(fn count): u32;
+        /// Maps a vote to the sealers (trustees) taking part in it
+        pub VoteSealers get(fn vote_sealers): map hasher(blake2_128_concat) VoteId => Vec<T::AccountId>;
+
+        /// The number of misbehaviours recorded for a sealer (over all votes)
+        pub CountsBySealer get(fn count_by_sealer): map hasher(blake2_128_concat) T::AccountId => u32;
+
+        /// The number of misbehaviours recorded for all sealers
+        pub Counts get(fn count): u32;
+
+        /// The votes in the tallying phase which are still being shuffled (at most `T::MaxTallyingVotes`)
+        pub TallyingVotes get(fn tallying_votes): Vec<VoteId>;
+
+        /// Maps a vote and a sealer to the misbehaviour recorded for the sealer
+        pub Misbehaviours get(fn misbehaviours): double_map hasher(blake2_128_concat) VoteId, hasher(blake2_128_concat) T::AccountId => MisbehaviourRecord;
+
+        /// Maps a vote and a topic to the block of the latest shuffle progress
+        /// (or the start of the tallying phase)
+        ShuffleActivity get(fn shuffle_activity): map hasher(blake2_128_concat) (VoteId, TopicId) => Option<T::BlockNumber>;
 
         /// A vector containing the IDs of voters that have submitted their ballots
         Voters get(fn voters): Vec<T::AccountId>;
@@ -105,12 +168,45 @@ decl_storage! {
         /// Maps a voteId and topicid to a shuffle status
         ShuffleStateStore: map hasher(blake2_128_concat) (VoteId, TopicId) => Option<ShuffleState>;
 
+        /// Maps a voteId to the shuffle status of its ballot rows.
+        /// Only exists if the ballots of the vote are shuffled as rows (see enable_ballot_shuffling).
+        BallotShuffleStateStore get(fn ballot_shuffle_state): map hasher(blake2_128_concat) VoteId => Option<ShuffleState>;
+
+        /// Maps a voteId to the ballot rows (answers in the order of the vote's topics) and how many times each row has been shuffled
+        BallotRows get(fn ballot_rows): double_map hasher(blake2_128_concat) VoteId, hasher(blake2_128_concat) NrOfShuffles => Vec<BallotRow>;
+
+        /// Maps a voteId to a list of shuffle proofs of its ballot rows (iteration, rows, proof)
+        BallotShuffleProofs: map hasher(blake2_128_concat) VoteId => Vec<RowShufflePayload>;
+
+        /// Maps a homomorphically tallied topic to the encrypted sum of its votes.
+        /// Only exists if the topic is tallied homomorphically (see enable_homomorphic_tally).
+        EncryptedSums get(fn encrypted_sum): map hasher(blake2_128_concat) TopicId => Option<Cipher>;
+
+        /// Maps a homomorphically tallied topic to the summed weight of its votes (i.e. the number of votes if the vote is not weighted)
+        SummedWeights get(fn summed_weight): map hasher(blake2_128_concat) TopicId => u64;
+
+        /// Maps a vote and a voter to the weight with which the voter's ballot is counted (default: 1)
+        VoterWeights get(fn voter_weight): double_map hasher(blake2_128_concat) VoteId, hasher(blake2_128_concat) T::AccountId => Option<VoterWeight>;
+
+        /// Maps a vote to whether the ballots are counted with the weights of the voters
+        WeightedVotes get(fn is_weighted_vote): map hasher(blake2_128_concat) VoteId => bool;
+
+        /// Maps a vote to the version of the Fiat-Shamir transform used by its proofs (see `crypto::helper::ProofVersion`).
+        /// Votes which have been created before the proofs were versioned have no entry and use version 1.
+        ProofVersions get(fn proof_version): map hasher(blake2_128_concat) VoteId => Option<u8>;
+
         /// Maps a topic to a map of results. [topic_id -> {message/vote: count}]
         Tally get(fn tally): map hasher(blake2_128_concat) TopicId => Option<TopicResult>;
 
         /// Maps a sealer and a topic to a vector of decrypted shares.
         DecryptedShares get(fn decrypted_shares): double_map hasher(blake2_128_concat) TopicId, hasher(blake2_128_concat) T::AccountId  => Vec<Vec<u8>>;
 
+        /// Maps a sealer and a topic to the proof of its latest decrypted shares and the # of shuffles of the decrypted ciphers.
+        DecryptedShareProofs get(fn decrypted_share_proof): double_map hasher(blake2_128_concat) TopicId, hasher(blake2_128_concat) T::AccountId => Option<(NrOfShuffles, DecryptedShareProof)>;
+
+        /// Maps a sealer and a topic to the commitments of the proof of its latest decrypted shares (empty if none were submitted).
+        DecryptedShareCommitments get(fn decrypted_share_commitments): double_map hasher(blake2_128_concat) TopicId, hasher(blake2_128_concat) T::AccountId => Vec<DecryptedShareCommitment>;
+
         /// Stores the public key of a sealer together with its Schnorr proof.
         PublicKeyShares get(fn key_shares): map hasher(blake2_128_concat) VoteId => Vec<PublicKeyShare>;
 
@@ -119,6 +215,43 @@ decl_storage! {
 
         /// Maps a vote to a public key (the vote's/system's public key) used to encrypt ballots.
         PublicKey get(fn public_key): map hasher(blake2_128_concat) VoteId => Option<SubstratePK>;
+
+        /// The number of voting authorities which need to approve a critical action.
+        /// A threshold of 0 or 1 disables the approval flow.
+        pub ApprovalThreshold get(fn approval_threshold): u32;
+
+        /// The id of the next proposed authority action.
+        ProposalCount get(fn proposal_count): ProposalId;
+
+        /// Maps a proposal id to the proposed authority action and its approvals.
+        Proposals get(fn proposals): map hasher(blake2_128_concat) ProposalId => Option<Proposal<T::AccountId>>;
+
+        /// Maps a topic to its declared answer options, question type and encoding
+        TopicDefinitions get(fn topic_definition): map hasher(blake2_128_concat) TopicId => Option<TopicDefinition>;
+
+        /// Maps a topic to the number of plaintexts which could not be counted for a declared option
+        RejectedVotesByTopic get(fn rejected_votes): map hasher(blake2_128_concat) TopicId => RejectedVotes;
+
+        /// Maps a ranked topic to the results of each round of its tally
+        RankedRounds get(fn ranked_rounds): map hasher(blake2_128_concat) TopicId => Vec<RankedRound>;
+
+        /// Maps a vote to the block at which it ended (all topics tallied or aborted)
+        VoteEndedAt get(fn vote_ended_at): map hasher(blake2_128_concat) VoteId => Option<T::BlockNumber>;
+
+        /// Maps an aborted vote to the reason why it has been aborted
+        AbortReasons get(fn abort_reason): map hasher(blake2_128_concat) VoteId => Option<Vec<u8>>;
+
+        /// Maps a purged vote to the blake2_256 hash of its removed transcript
+        TranscriptCommitments get(fn transcript_commitment): map hasher(blake2_128_concat) VoteId => Option<[u8; 32]>;
+    }
+    add_extra_genesis {
+        build(|config: &GenesisConfig<T>| {
+            // the sealers of the genesis need to be endowed with the bond
+            for sealer in config.sealers.iter() {
+                T::Currency::reserve(sealer, T::SealerBond::get())
+                    .expect("genesis sealers need to be able to reserve the sealer bond");
+            }
+        });
     }
 }
 
@@ -127,6 +260,7 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
     {
         /// ballot submission event -> [from/who, ballot]
         BallotSubmitted(AccountId, VoteId, Ballot),
@@ -157,6 +291,57 @@ decl_event!(
 
         /// A decrypted share was submitted for a vote. [paritial decryptions with its proof]
         ShuffleProofSubmitted(TopicId, AccountId),
+
+        /// The ballots of a vote are shuffled as rows. [vote_id]
+        BallotShufflingEnabled(VoteId),
+
+        /// A shuffle proof of ballot rows was submitted. [vote_id, sealer]
+        BallotShuffleProofSubmitted(VoteId, AccountId),
+
+        /// A topic is tallied homomorphically instead of being shuffled. [vote_id, topic_id]
+        HomomorphicTallyEnabled(VoteId, TopicId),
+
+        /// The weight of a voter has been set. [vote_id, voter, weight]
+        VoterWeightSet(VoteId, AccountId, VoterWeight),
+
+        /// A voting authority has been added. [who]
+        VotingAuthorityAdded(AccountId),
+
+        /// A voting authority has been removed. [who]
+        VotingAuthorityRemoved(AccountId),
+
+        /// A sealer has been added. [who]
+        SealerAdded(AccountId),
+
+        /// A sealer has been removed. [who]
+        SealerRemoved(AccountId),
+
+        /// A sealer misbehaved in a vote. [sealer, vote_id, misbehaviour]
+        SealerMisbehaved(AccountId, VoteId, Misbehaviour),
+
+        /// A misbehaving sealer has been slashed. [sealer, amount]
+        SealerSlashed(AccountId, Balance),
+
+        /// The answer options of a topic have been declared. [vote_id, topic_id]
+        TopicDefined(VoteId, TopicId),
+
+        /// A vote has been aborted. [vote_id, reason]
+        VoteAborted(VoteId, Vec<u8>),
+
+        /// The intermediate data of a vote has been purged. [vote_id, transcript_commitment]
+        VotePurged(VoteId, [u8; 32]),
+
+        /// The approval threshold for critical actions has been set. [threshold]
+        ApprovalThresholdSet(u32),
+
+        /// A critical action has been proposed. [proposal_id, proposer, action]
+        ActionProposed(ProposalId, AccountId, AuthorityAction),
+
+        /// A proposed action has been approved. [proposal_id, who]
+        ActionApproved(ProposalId, AccountId),
+
+        /// A proposed action has reached the approval threshold and has been executed. [proposal_id]
+        ActionExecuted(ProposalId),
     }
 );
 
@@ -177,6 +362,9 @@ decl_error! {
         // Error returned when requester is not a sealer
         NotASealer,
 
+        // Error returned when a sealer cannot reserve the sealer bond
+        InsufficientSealerBond,
+
         // Error returned when making signed transactions in off-chain worker
         NoLocalAcctForSigning,
         OffchainSignedTxError,
@@ -202,7 +390,7 @@ decl_error! {
         // Error returned when the public key share proof doesn't verify
         PublicKeyShareProofError,
 
-        // Error returned when there are less than two public key shares
+        // Error returned when a sealer of the vote has not submitted a public key share
         NotEnoughPublicKeyShares,
 
         // Error returned when inverse modulo operation fails
@@ -248,7 +436,104 @@ decl_error! {
         ShuffleStateIncorrect,
 
         /// Error returned when shuffle is submitted for (vote_id, topic_id) which is already completed
-        ShuffleAlreadyCompleted
+        ShuffleAlreadyCompleted,
+
+        /// Error returned when a vote is created without any sealers
+        NoSealersForVote,
+
+        /// Error returned when the same sealer is assigned twice to a vote
+        DuplicateSealer,
+
+        /// Error returned when adding an account which already is a voting authority
+        AlreadyAVotingAuthority,
+
+        /// Error returned when adding an account which already is a sealer
+        AlreadyASealer,
+
+        /// Error returned when removing an account which takes part in a vote that is still in progress
+        MemberOfVoteInProgress,
+
+        /// Error returned when a critical action is called directly but requires the approval of multiple voting authorities
+        ApprovalRequired,
+
+        /// Error returned when the approval threshold exceeds the number of voting authorities
+        InvalidApprovalThreshold,
+
+        /// Error returned when a proposal does not exist
+        ProposalDoesNotExist,
+
+        /// Error returned when a proposal has already been executed
+        ProposalAlreadyExecuted,
+
+        /// Error returned when a voting authority approves the same proposal twice
+        ProposalAlreadyApproved,
+
+        /// Error returned when a topic does not belong to a vote
+        TopicDoesNotExist,
+
+        /// Error returned when the declared options do not fit the question type or encoding
+        InvalidTopicDefinition,
+
+        /// Error returned when an aborted vote is changed
+        VoteIsAborted,
+
+        /// Error returned when a vote is aborted after all topics have been tallied
+        VoteAlreadyEnded,
+
+        /// Error returned when a vote is purged before it has been tallied or aborted
+        VoteNotEnded,
+
+        /// Error returned when a vote is purged before the retention period is over
+        RetentionPeriodNotOver,
+
+        /// Error returned when a vote is purged twice
+        VoteAlreadyPurged,
+
+        /// Error returned when the maximum number of votes in the tallying phase is reached
+        TooManyTallyingVotes,
+
+        /// Error returned when a ballot does not answer every topic exactly once
+        /// or when the rows of a shuffle do not have the same width
+        InvalidBallotRow,
+
+        /// Error returned when the ciphers of a topic are shuffled separately but the vote shuffles ballot rows
+        BallotShufflingEnabled,
+
+        /// Error returned when ballot rows are shuffled but the vote shuffles the ciphers of each topic separately
+        BallotShufflingNotEnabled,
+
+        /// Error returned when the ciphers of a topic are shuffled but the topic is tallied homomorphically
+        HomomorphicTallyEnabled,
+
+        /// Error returned when the encrypted sum of a topic is accessed but the topic is not tallied homomorphically
+        HomomorphicTallyNotEnabled,
+
+        /// Error returned when the decrypted sum of a topic exceeds the number of cast votes
+        HomomorphicSumOutOfRange,
+
+        /// Error returned when the number of decrypted shares does not match the number of ciphers
+        WrongNumberOfDecryptedShares,
+
+        /// Error returned when a voter weight of 0 is set
+        InvalidVoterWeight,
+
+        /// Error returned when weights are set for a vote whose topics are shuffled separately
+        WeightedVoteNotSupported,
+
+        /// Error returned when a decrypted weight is not the weight of a voter
+        /// or when the number of weights does not match the number of ciphers
+        UnknownVoterWeight,
+
+        /// Error returned when a write-in topic is defined for a vote whose topics are shuffled separately
+        /// or whose group is too small to encode text
+        WriteInNotSupported,
+
+        /// Error returned when a ranked topic is defined for a vote whose topics are shuffled separately
+        RankingNotSupported,
+
+        /// Error returned when a submitted value (e.g. a cipher or a key) does not have
+        /// the canonical fixed-width encoding of the vote's group or is out of range
+        NonCanonicalEncoding
     }
 }
 
@@ -261,24 +546,15 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Set a vote phase.
+        /// Requires a proposal if the approval threshold is larger than 1.
         #[weight = (10_000, Pays::No)]
         fn set_vote_phase(origin, vote_id: VoteId, phase: VotePhase) -> DispatchResult {
             // only the voting_authority should be able to store the key
             let who: T::AccountId = ensure_signed(origin)?;
             ensure_voting_authority::<T>(&who)?;
+            Self::ensure_no_approval_required()?;
 
-            // check that the vote_id exists
-            ensure_vote_exists::<T>(&vote_id)?;
-
-            // set the new phase
-            let mut vote: Vote<T::AccountId> = Votes::<T>::get(&vote_id);
-            vote.phase = phase.clone();
-            Votes::<T>::insert(&vote_id, &vote);
-            set_phase::<T>(&who, &vote_id, phase.clone())?;
-
-            // notify that the vote phase has been changed
-            debug::info!("updated vote phase: {:?}, {:?}", vote_id, phase);
-            Self::deposit_event(RawEvent::VotePhaseChanged(vote_id, phase));
+            Self::do_set_vote_phase(who, vote_id, phase)?;
             Ok(())
         }
 
@@ -289,6 +565,7 @@ decl_module! {
             // only the voting_authority should be able to store the key
             let who: T::AccountId = ensure_signed(origin)?;
             ensure_voting_authority::<T>(&who)?;
+            ensure!(pk.decode_canonical().is_some(), Error::<T>::NonCanonicalEncoding);
 
             // store the public key
             PublicKey::insert(vote_id.clone(), pk.clone());
@@ -306,11 +583,15 @@ decl_module! {
             // only sealers can store their public key shares
             let who: T::AccountId = ensure_signed(origin)?;
             ensure_not_a_voting_authority::<T>(&who)?;
-            ensure_sealer::<T>(&who)?;
+            ensure_sealer::<T>(&who, &vote_id)?;
 
             // verify key generatin proof
             // and store public key share
-            verify_proof_and_store_keygen_share::<T>(who.clone(), &vote_id, pk_share.clone())?;
+            let result = verify_proof_and_store_keygen_share::<T>(who.clone(), &vote_id, pk_share.clone());
+            if let Err(Error::<T>::PublicKeyShareProofError) = result {
+                Self::report_misbehaviour(&who, &vote_id, Misbehaviour::InvalidProof);
+            }
+            result?;
 
             debug::info!("stored public key share for vote: {:?} (by sealer: {:?})", vote_id, who.clone());
             Self::deposit_event(RawEvent::PublicKeyShareSubmitted(pk_share));
@@ -318,27 +599,27 @@ decl_module! {
         }
 
         /// Combine public key shares into a single public key.
+        /// Requires a proposal if the approval threshold is larger than 1.
         #[weight = (10_000, Pays::No)]
         fn combine_public_key_shares(origin, vote_id: VoteId) -> DispatchResult {
             // only the voting_authority should be able to combine the public key shares
             let who: T::AccountId = ensure_signed(origin)?;
             ensure_voting_authority::<T>(&who)?;
-            ensure_vote_exists::<T>(&vote_id)?;
+            Self::ensure_no_approval_required()?;
 
-            // create the system's public key
-            let pk: SubstratePK = combine_shares::<T>(who, &vote_id)?;
-
-            debug::info!("combined public key shares for vote: {:?}", vote_id);
-            Self::deposit_event(RawEvent::PublicKeyCreated(vote_id, pk));
+            Self::do_combine_public_key_shares(who, vote_id)?;
             Ok(())
         }
 
         /// Create a vote and store public crypto parameters.
+        /// The sealers taking part in the vote must be a subset of the registered sealers.
         /// Can only be called from a voting authority.
         #[weight = (10000, Pays::No)]
-        fn create_vote(origin, vote_id: VoteId, title: Title, params: PublicParameters, topics: Vec<Topic>, batch_size: u64) -> DispatchResult {
+        fn create_vote(origin, vote_id: VoteId, title: Title, params: PublicParameters, topics: Vec<Topic>, batch_size: u64, sealers: Vec<T::AccountId>) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
             ensure_voting_authority::<T>(&who)?;
+            ensure_valid_vote_sealers::<T>(&sealers)?;
+            ensure!(params.decode_canonical().is_some(), Error::<T>::NonCanonicalEncoding);
 
             // create new vote
             let vote = Vote::<T::AccountId> {
@@ -355,6 +636,8 @@ decl_module! {
             vote_ids.push(vote_id.clone());
             VoteIds::put(vote_ids);
             Votes::<T>::insert(&vote_id, vote);
+            VoteSealers::<T>::insert(&vote_id, sealers);
+            ProofVersions::insert(&vote_id, ProofVersion::CURRENT as u8);
 
             // create an empty shuffle state for each topic
             for topic in topics.iter() {
@@ -384,6 +667,9 @@ decl_module! {
             ensure_voting_authority::<T>(&who)?;
             ensure_vote_exists::<T>(&vote_id)?;
 
+            // the weights of a vote whose topics are summed up cannot be applied to a shuffled topic
+            ensure!(!Self::is_weighted_vote(&vote_id) || BallotShuffleStateStore::contains_key(&vote_id), Error::<T>::WeightedVoteNotSupported);
+
             let topic_id = &topic.0;
             let mut topics: Vec<Topic> = Topics::get(&vote_id);
             topics.push(topic.clone());
@@ -404,6 +690,115 @@ decl_module! {
             Ok(())
         }
 
+        /// Declare the answer options, the question type and the encoding of a topic.
+        /// The tally maps the plaintexts of the topic to the declared options.
+        /// Write-in topics (free-text answers) and ranked topics require the ballots to be shuffled as rows.
+        /// Can only be called from a voting authority before the voting starts.
+        #[weight = (10000, Pays::No)]
+        fn define_topic(origin, vote_id: VoteId, topic_id: TopicId, definition: TopicDefinition) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::KeyGeneration)?;
+
+            let topics: Vec<Topic> = Topics::get(&vote_id);
+            ensure!(topics.iter().any(|(id, _)| id == &topic_id), Error::<T>::TopicDoesNotExist);
+            ensure!(is_valid_definition(&definition), Error::<T>::InvalidTopicDefinition);
+            ensure!(!EncryptedSums::contains_key(&topic_id) || is_summable_definition(&definition), Error::<T>::InvalidTopicDefinition);
+            if write_in_length(&definition).is_some() {
+                ensure_write_ins_supported::<T>(&vote_id)?;
+            }
+            if let QuestionType::Ranked(_) = definition.question_type {
+                ensure_rankings_supported::<T>(&vote_id)?;
+            }
+
+            TopicDefinitions::insert(&topic_id, definition);
+
+            debug::info!("defined options of topic: {:?} of vote: {:?}", topic_id, vote_id);
+            Self::deposit_event(RawEvent::TopicDefined(vote_id, topic_id));
+            Ok(())
+        }
+
+        /// Shuffle the ballots of the vote as rows (all answers of a ballot together)
+        /// instead of shuffling the ciphers of each topic separately.
+        /// Every ballot needs to answer all topics of the vote.
+        /// Can only be called from a voting authority before the voting starts.
+        #[weight = (10000, Pays::No)]
+        fn enable_ballot_shuffling(origin, vote_id: VoteId, batch_size: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::KeyGeneration)?;
+
+            // the rows contain the ciphers of all topics
+            let topics: Vec<Topic> = Topics::get(&vote_id);
+            ensure!(!topics.iter().any(|(topic_id, _)| EncryptedSums::contains_key(topic_id)), Error::<T>::HomomorphicTallyEnabled);
+
+            // create an empty shuffle state for the ballot rows
+            BallotShuffleStateStore::insert(&vote_id, ShuffleState {
+                iteration: 0,
+                start_position: 0,
+                batch_size,
+                done: false,
+            });
+
+            debug::info!("enabled ballot shuffling for vote: {:?}", vote_id);
+            Self::deposit_event(RawEvent::BallotShufflingEnabled(vote_id));
+            Ok(())
+        }
+
+        /// Tally a topic homomorphically instead of shuffling its ciphers.
+        /// The votes are summed up as they are cast and the sealers only decrypt the sum
+        /// (submit_decrypted_shares with a single share). Each vote must be an encoded 0 or 1.
+        /// Can only be called from a voting authority before the voting starts.
+        #[weight = (10000, Pays::No)]
+        fn enable_homomorphic_tally(origin, vote_id: VoteId, topic_id: TopicId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::KeyGeneration)?;
+            ensure!(!BallotShuffleStateStore::contains_key(&vote_id), Error::<T>::BallotShufflingEnabled);
+
+            let topics: Vec<Topic> = Topics::get(&vote_id);
+            ensure!(topics.iter().any(|(id, _)| id == &topic_id), Error::<T>::TopicDoesNotExist);
+            if let Some(definition) = TopicDefinitions::get(&topic_id) {
+                ensure!(is_summable_definition(&definition), Error::<T>::InvalidTopicDefinition);
+            }
+
+            // the topic is not shuffled, the votes are summed up instead
+            ShuffleStateStore::remove((&vote_id, &topic_id));
+            let params: PublicParameters = get_public_params::<T>(&vote_id)?;
+            EncryptedSums::insert(&topic_id, empty_sum(&params));
+
+            debug::info!("enabled homomorphic tally for topic: {:?} of vote: {:?}", topic_id, vote_id);
+            Self::deposit_event(RawEvent::HomomorphicTallyEnabled(vote_id, topic_id));
+            Ok(())
+        }
+
+        /// Set the weight with which the ballot of a voter is counted (default: 1).
+        /// Weights require that every topic is tallied homomorphically (the vote is multiplied by the weight)
+        /// or that the ballots are shuffled as rows (the encrypted weight is shuffled together with the answers).
+        /// Can only be called from a voting authority before the voting starts.
+        #[weight = (10000, Pays::No)]
+        fn set_voter_weight(origin, vote_id: VoteId, voter: T::AccountId, weight: VoterWeight) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::KeyGeneration)?;
+            ensure!(weight > 0, Error::<T>::InvalidVoterWeight);
+
+            let topics: Vec<Topic> = Topics::get(&vote_id);
+            let is_summed = !topics.is_empty() && topics.iter().all(|(topic_id, _)| EncryptedSums::contains_key(topic_id));
+            ensure!(is_summed || BallotShuffleStateStore::contains_key(&vote_id), Error::<T>::WeightedVoteNotSupported);
+
+            VoterWeights::<T>::insert(&vote_id, &voter, weight);
+            WeightedVotes::insert(&vote_id, true);
+
+            debug::info!("set weight: {:?} of voter: {:?} for vote: {:?}", weight, voter, vote_id);
+            Self::deposit_event(RawEvent::VoterWeightSet(vote_id, voter, weight));
+            Ok(())
+        }
+
         #[weight = (10000, Pays::No)]
         pub fn cast_ballot(origin, vote_id: VoteId, ballot: Ballot) -> DispatchResult {
           let who = ensure_signed(origin)?;
@@ -413,7 +808,7 @@ decl_module! {
           // TODO: ensure that it is a legit voter -> in some other project where identity management is considered
 
           // store the ballot
-          store_ballot::<T>(&who, &vote_id, ballot.clone());
+          store_ballot::<T>(&who, &vote_id, ballot.clone())?;
 
           // notify that the ballot has been submitted and stored
           debug::info!("stored ballot for vote_id: {:?}", vote_id);
@@ -425,13 +820,20 @@ decl_module! {
         #[weight = (10_000, Pays::No)]
         fn submit_shuffled_votes_and_proof(origin, vote_id: VoteId, topic_id: TopicId, payload: ShufflePayload) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
-            ensure_sealer::<T>(&who)?;
             ensure_vote_exists::<T>(&vote_id)?;
+            ensure_sealer::<T>(&who, &vote_id)?;
 
             // TODO: discuss if shuffling should be allowed earlier
             ensure_vote_phase::<T>(&vote_id, VotePhase::Tallying)?;
+            ensure!(!BallotShuffleStateStore::contains_key(&vote_id), Error::<T>::BallotShufflingEnabled);
+            ensure!(!EncryptedSums::contains_key(&topic_id), Error::<T>::HomomorphicTallyEnabled);
 
-            Self::verify_proof_store_shuffled_ciphers(&vote_id, &topic_id, payload)?;
+            let result = Self::verify_proof_store_shuffled_ciphers(&vote_id, &topic_id, payload);
+            if let Err(Error::<T>::ShuffleProofVerifcationFailed) = result {
+                Self::report_misbehaviour(&who, &vote_id, Misbehaviour::InvalidProof);
+            }
+            result?;
+            Self::note_shuffle_activity(&vote_id, &topic_id);
 
             // notify that the decrypted share has been:
             // submitted, the proof verified and stored
@@ -440,18 +842,56 @@ decl_module! {
             Ok(())
         }
 
+        /// Submit a batch of shuffled ballot rows and the shuffle proof.
+        /// Once all shuffles are done, the columns of the rows become the shuffled ciphers of the topics.
+        #[weight = (10_000, Pays::No)]
+        fn submit_shuffled_ballots_and_proof(origin, vote_id: VoteId, payload: RowShufflePayload) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_sealer::<T>(&who, &vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::Tallying)?;
+
+            let result = Self::verify_proof_store_shuffled_ballots(&vote_id, payload);
+            if let Err(Error::<T>::ShuffleProofVerifcationFailed) = result {
+                Self::report_misbehaviour(&who, &vote_id, Misbehaviour::InvalidProof);
+            }
+            result?;
+
+            // the rows contain the ciphers of all topics
+            let topics: Vec<Topic> = Topics::get(&vote_id);
+            for (topic_id, _) in topics.iter() {
+                Self::note_shuffle_activity(&vote_id, topic_id);
+            }
+
+            debug::info!("verified ballot shuffle proof for vote_id: {:?}", vote_id);
+            Self::deposit_event(RawEvent::BallotShuffleProofSubmitted(vote_id, who));
+            Ok(())
+        }
+
         /// Store a decrypted shares.
+        /// For a homomorphically tallied topic, a single share of the encrypted sum is submitted
+        /// and nr_of_shuffles is ignored.
+        /// The commitments of the proof are optional, without them the proof cannot be batch verified.
         #[weight = (10_000, Pays::No)]
-        fn submit_decrypted_shares(origin, vote_id: VoteId, topic_id: TopicId, shares: Vec<DecryptedShare>, proof: DecryptedShareProof, nr_of_shuffles: NrOfShuffles) -> DispatchResult {
+        fn submit_decrypted_shares(origin, vote_id: VoteId, topic_id: TopicId, shares: Vec<DecryptedShare>, proof: DecryptedShareProof, commitments: Vec<DecryptedShareCommitment>, nr_of_shuffles: NrOfShuffles) -> DispatchResult {
             // only sealers should be able to store their decrypted shares
             let who: T::AccountId = ensure_signed(origin)?;
             ensure_vote_exists::<T>(&vote_id)?;
             ensure_vote_phase::<T>(&vote_id, VotePhase::Tallying)?;
-            ensure_sealer::<T>(&who)?;
+            ensure_sealer::<T>(&who, &vote_id)?;
 
             // verify the decrypted share proof
             // and store the decrypted shares if proof verification is successfull
-            verify_proof_and_store_decrypted_share::<T>(who.clone(), &vote_id, &topic_id, shares, proof.clone(), &nr_of_shuffles)?;
+            let result = verify_proof_and_store_decrypted_share::<T>(who.clone(), &vote_id, &topic_id, shares, proof.clone(), commitments, &nr_of_shuffles);
+            if let Err(Error::<T>::DecryptedShareProofError) = result {
+                Self::report_misbehaviour(&who, &vote_id, Misbehaviour::InvalidProof);
+            }
+            result?;
+
+            // the shares are accepted, but a late submission is recorded
+            if Self::is_decryption_late(&vote_id, &topic_id) {
+                Self::report_misbehaviour(&who, &vote_id, Misbehaviour::LateDecryption);
+            }
 
             // notify that the decrypted share has been:
             // submitted, the proof verified and stored
@@ -461,22 +901,210 @@ decl_module! {
         }
 
         /// Combine decrypted shares into a final plain text tally.
+        /// For a homomorphically tallied topic, encoded and nr_of_shuffles are ignored.
+        /// Requires a proposal if the approval threshold is larger than 1.
         #[weight = (10_000, Pays::No)]
         fn combine_decrypted_shares(origin, vote_id: VoteId, topic_id: TopicId, encoded: bool, nr_of_shuffles: NrOfShuffles) -> DispatchResult {
             // only the voting_authority should be able to create the final tally
             let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            Self::ensure_no_approval_required()?;
+
+            Self::do_combine_decrypted_shares(vote_id, topic_id, encoded, nr_of_shuffles)?;
+            Ok(())
+        }
+
+        /// Propose a critical action (set_vote_phase, combine_public_key_shares or combine_decrypted_shares).
+        /// The action is executed once the approval threshold has been reached.
+        /// The proposal counts as the first approval.
+        /// Can only be called from a voting authority.
+        #[weight = (10_000, Pays::No)]
+        fn propose_action(origin, action: AuthorityAction) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(Self::action_vote_id(&action))?;
+
+            let proposal_id: ProposalId = ProposalCount::get();
+            let proposal = Proposal {
+                action: action.clone(),
+                approvals: Vec::new(),
+                executed: false,
+            };
+            let proposal = Self::approve_proposal(who.clone(), proposal)?;
+            Proposals::<T>::insert(proposal_id, &proposal);
+            ProposalCount::put(proposal_id + 1);
+
+            debug::info!("proposed action: {:?} (proposal: {:?})", action, proposal_id);
+            Self::deposit_event(RawEvent::ActionProposed(proposal_id, who, action));
+            if proposal.executed {
+                Self::deposit_event(RawEvent::ActionExecuted(proposal_id));
+            }
+            Ok(())
+        }
+
+        /// Approve a proposed action.
+        /// The action is executed once the approval threshold has been reached.
+        /// Can only be called from a voting authority.
+        #[weight = (10_000, Pays::No)]
+        fn approve_action(origin, proposal_id: ProposalId) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+
+            let proposal: Proposal<T::AccountId> = Proposals::<T>::get(proposal_id)
+                .ok_or(Error::<T>::ProposalDoesNotExist)?;
+            let proposal = Self::approve_proposal(who.clone(), proposal)?;
+            Proposals::<T>::insert(proposal_id, &proposal);
+
+            debug::info!("approved proposal: {:?} (by: {:?})", proposal_id, who);
+            Self::deposit_event(RawEvent::ActionApproved(proposal_id, who));
+            if proposal.executed {
+                Self::deposit_event(RawEvent::ActionExecuted(proposal_id));
+            }
+            Ok(())
+        }
+
+        /// Abort a vote which has not ended yet.
+        /// Can only be called from a voting authority.
+        #[weight = (10_000, Pays::No)]
+        fn abort_vote(origin, vote_id: VoteId, reason: Vec<u8>) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
             ensure_vote_exists::<T>(&vote_id)?;
-            ensure_vote_phase::<T>(&vote_id, VotePhase::Tallying)?;
+
+            let mut vote: Vote<T::AccountId> = Votes::<T>::get(&vote_id);
+            ensure!(vote.phase != VotePhase::Aborted, Error::<T>::VoteIsAborted);
+            ensure!(VoteEndedAt::<T>::get(&vote_id).is_none(), Error::<T>::VoteAlreadyEnded);
+
+            vote.phase = VotePhase::Aborted;
+            Votes::<T>::insert(&vote_id, &vote);
+            AbortReasons::insert(&vote_id, &reason);
+            Self::untrack_tallying_vote(&vote_id);
+            VoteEndedAt::<T>::insert(&vote_id, <frame_system::Module<T>>::block_number());
+
+            debug::info!("aborted vote: {:?}, reason: {:?}", vote_id, reason);
+            Self::deposit_event(RawEvent::VoteAborted(vote_id, reason));
+            Ok(())
+        }
+
+        /// Remove the intermediate data (ballots, ciphers, shuffle proofs, key and decrypted shares)
+        /// of a vote once the retention period is over. The tally and a hash commitment of the
+        /// removed transcript are kept, so the result can be verified against an archive.
+        /// Can only be called from a voting authority.
+        /// The weight depends on the number of stored items of the vote (see `purge_vote_weight`).
+        #[weight = Module::<T>::purge_vote_weight(vote_id)]
+        fn purge_vote(origin, vote_id: VoteId) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
             ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure!(TranscriptCommitments::get(&vote_id).is_none(), Error::<T>::VoteAlreadyPurged);
+
+            let ended_at: T::BlockNumber = VoteEndedAt::<T>::get(&vote_id).ok_or(Error::<T>::VoteNotEnded)?;
+            let now: T::BlockNumber = <frame_system::Module<T>>::block_number();
+            ensure!(now >= ended_at + T::RetentionPeriod::get(), Error::<T>::RetentionPeriodNotOver);
 
-            // combine the decrypted shares
-            // tally the topic
-            let result: TopicResult = combine_shares_and_tally_topic::<T>(&vote_id, &topic_id, encoded, &nr_of_shuffles)?;
+            // commit to the transcript before it is removed
+            let transcript = Self::vote_transcript(&vote_id);
+            let commitment: [u8; 32] = Self::transcript_commitment_of(&transcript);
+            Self::remove_vote_transcript(&vote_id);
+            TranscriptCommitments::insert(&vote_id, commitment);
 
-            // notify that the decrypted shares have been combined
-            // and that the result has been tallied!
-            debug::info!("result for vote: {:?} and topic: {:?} is: {:?}", vote_id, topic_id, result);
-            Self::deposit_event(RawEvent::TopicTallied(vote_id, topic_id, result));
+            debug::info!("purged vote: {:?}, commitment: {:?}", vote_id, commitment);
+            Self::deposit_event(RawEvent::VotePurged(vote_id, commitment));
+            Ok(())
+        }
+
+        /// Set the number of voting authorities which need to approve a critical action.
+        /// A threshold of 0 or 1 disables the approval flow.
+        /// Can only be called from the management origin (e.g. root/sudo).
+        #[weight = (10_000, Pays::No)]
+        fn set_approval_threshold(origin, threshold: u32) -> DispatchResult {
+            T::ManagementOrigin::ensure_origin(origin)?;
+            let voting_authorities: Vec<T::AccountId> = VotingAuthorities::<T>::get();
+            ensure!(threshold as usize <= voting_authorities.len(), Error::<T>::InvalidApprovalThreshold);
+
+            ApprovalThreshold::put(threshold);
+
+            debug::info!("set approval threshold: {:?}", threshold);
+            Self::deposit_event(RawEvent::ApprovalThresholdSet(threshold));
+            Ok(())
+        }
+
+        /// Add a voting authority.
+        /// Can only be called from the management origin (e.g. root/sudo).
+        #[weight = (10_000, Pays::No)]
+        fn add_voting_authority(origin, who: T::AccountId) -> DispatchResult {
+            T::ManagementOrigin::ensure_origin(origin)?;
+
+            let mut voting_authorities: Vec<T::AccountId> = VotingAuthorities::<T>::get();
+            ensure!(!voting_authorities.contains(&who), Error::<T>::AlreadyAVotingAuthority);
+            voting_authorities.push(who.clone());
+            VotingAuthorities::<T>::put(voting_authorities);
+
+            debug::info!("added voting authority: {:?}", who);
+            Self::deposit_event(RawEvent::VotingAuthorityAdded(who));
+            Ok(())
+        }
+
+        /// Remove a voting authority.
+        /// Fails if the voting authority still runs a vote which is in progress.
+        /// Can only be called from the management origin (e.g. root/sudo).
+        #[weight = (10_000, Pays::No)]
+        fn remove_voting_authority(origin, who: T::AccountId) -> DispatchResult {
+            T::ManagementOrigin::ensure_origin(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_no_vote_in_progress_as_voting_authority::<T>(&who)?;
+
+            let mut voting_authorities: Vec<T::AccountId> = VotingAuthorities::<T>::get();
+            voting_authorities.retain(|account| account != &who);
+
+            // the approval threshold must remain reachable
+            ensure!(Self::approval_threshold() as usize <= voting_authorities.len(), Error::<T>::InvalidApprovalThreshold);
+            VotingAuthorities::<T>::put(voting_authorities);
+
+            debug::info!("removed voting authority: {:?}", who);
+            Self::deposit_event(RawEvent::VotingAuthorityRemoved(who));
+            Ok(())
+        }
+
+        /// Add a sealer to the set of sealers which can take part in a vote.
+        /// Reserves the sealer bond (`T::SealerBond`) from the account of the sealer.
+        /// Can only be called from the management origin (e.g. root/sudo).
+        #[weight = (10_000, Pays::No)]
+        fn add_sealer(origin, who: T::AccountId) -> DispatchResult {
+            T::ManagementOrigin::ensure_origin(origin)?;
+
+            let mut sealers: Vec<T::AccountId> = Sealers::<T>::get();
+            ensure!(!sealers.contains(&who), Error::<T>::AlreadyASealer);
+            T::Currency::reserve(&who, T::SealerBond::get())
+                .map_err(|_| Error::<T>::InsufficientSealerBond)?;
+            sealers.push(who.clone());
+            Sealers::<T>::put(sealers);
+
+            debug::info!("added sealer: {:?}", who);
+            Self::deposit_event(RawEvent::SealerAdded(who));
+            Ok(())
+        }
+
+        /// Remove a sealer from the set of sealers which can take part in a vote.
+        /// Fails if the sealer takes part in a vote which is in progress.
+        /// The remaining (not slashed) sealer bond is unreserved.
+        /// Can only be called from the management origin (e.g. root/sudo).
+        #[weight = (10_000, Pays::No)]
+        fn remove_sealer(origin, who: T::AccountId) -> DispatchResult {
+            T::ManagementOrigin::ensure_origin(origin)?;
+
+            let mut sealers: Vec<T::AccountId> = Sealers::<T>::get();
+            ensure!(sealers.contains(&who), Error::<T>::NotASealer);
+            ensure_no_vote_in_progress_as_sealer::<T>(&who)?;
+
+            sealers.retain(|account| account != &who);
+            Sealers::<T>::put(sealers);
+
+            // return what is left of the bond
+            T::Currency::unreserve(&who, T::SealerBond::get());
+
+            debug::info!("removed sealer: {:?}", who);
+            Self::deposit_event(RawEvent::SealerRemoved(who));
             Ok(())
         }
 
@@ -489,6 +1117,11 @@ decl_module! {
             Ok(())
         }
 
+        fn on_initialize(block_number: T::BlockNumber) -> Weight {
+            // record the sealers which missed their turn to shuffle
+            Self::record_missed_turns(block_number)
+        }
+
         fn offchain_worker(block_number: T::BlockNumber) {
             debug::info!("off-chain worker: entering...");
 
@@ -0,0 +1,204 @@
+use crate::{
+    types::{
+        Misbehaviour, MisbehaviourRecord, ShuffleState, Topic, TopicId, Vote, VoteId,
+        VotePhase,
+    },
+    BalanceOf, Counts, CountsBySealer, Error, Misbehaviours, Module, RawEvent,
+    ShuffleActivity, ShuffleStateStore, TallyingVotes, Topics, Trait, VoteSealers, Votes,
+};
+use frame_support::{
+    debug, ensure,
+    storage::{StorageDoubleMap, StorageMap, StorageValue},
+    traits::{Get, Imbalance, ReservableCurrency},
+    weights::Weight,
+};
+use sp_runtime::traits::Zero;
+use sp_std::vec::Vec;
+
+impl<T: Trait> Module<T> {
+    /// records the misbehaviour of a sealer for a vote and,
+    /// if enabled, slashes the bond of the sealer by `T::MisbehaviourSlash`.
+    /// returns the weight of the storage (and currency) accesses.
+    ///
+    /// dispatchables are not executed transactionally, therefore, the record
+    /// is kept even if the calling extrinsic returns an error afterwards.
+    pub fn report_misbehaviour(
+        who: &T::AccountId,
+        vote_id: &VoteId,
+        misbehaviour: Misbehaviour,
+    ) -> Weight {
+        Misbehaviours::<T>::mutate(vote_id, who, |record: &mut MisbehaviourRecord| {
+            match misbehaviour {
+                Misbehaviour::InvalidProof => {
+                    record.invalid_proofs = record.invalid_proofs.saturating_add(1)
+                }
+                Misbehaviour::MissedTurn => {
+                    record.missed_turns = record.missed_turns.saturating_add(1)
+                }
+                Misbehaviour::LateDecryption => {
+                    record.late_decryptions = record.late_decryptions.saturating_add(1)
+                }
+            }
+        });
+        CountsBySealer::<T>::mutate(who, |count| *count = count.saturating_add(1));
+        Counts::mutate(|count| *count = count.saturating_add(1));
+
+        debug::info!(
+            "sealer: {:?} misbehaved in vote: {:?} -> {:?}",
+            who,
+            vote_id,
+            misbehaviour
+        );
+        Self::deposit_event(RawEvent::SealerMisbehaved(
+            who.clone(),
+            vote_id.clone(),
+            misbehaviour,
+        ));
+
+        // the misbehaviour record and both counts
+        let weight: Weight = T::DbWeight::get().reads_writes(3, 3);
+
+        // slashing is disabled if the slash amount is zero
+        let amount: BalanceOf<T> = T::MisbehaviourSlash::get();
+        if amount.is_zero() {
+            return weight;
+        }
+
+        // the slashed amount is burned: the account and the total issuance are updated
+        let (imbalance, _) = T::Currency::slash_reserved(who, amount);
+        let slashed: BalanceOf<T> = imbalance.peek();
+        debug::info!("slashed sealer: {:?} by: {:?}", who, slashed);
+        Self::deposit_event(RawEvent::SealerSlashed(who.clone(), slashed));
+        weight.saturating_add(T::DbWeight::get().reads_writes(2, 2))
+    }
+
+    /// stores the block of the latest shuffle progress of a topic.
+    /// used to detect missed turns and late decryptions.
+    pub fn note_shuffle_activity(vote_id: &VoteId, topic_id: &TopicId) {
+        let now: T::BlockNumber = <frame_system::Module<T>>::block_number();
+        ShuffleActivity::<T>::insert((vote_id, topic_id), now);
+    }
+
+    /// returns true if the decrypted shares of a shuffled topic
+    /// are submitted after the decryption period has passed.
+    pub fn is_decryption_late(vote_id: &VoteId, topic_id: &TopicId) -> bool {
+        let period: T::BlockNumber = T::DecryptionPeriod::get();
+        if period.is_zero() {
+            return false;
+        }
+
+        let shuffle_state: Option<ShuffleState> =
+            ShuffleStateStore::get((vote_id, topic_id));
+        let shuffled_at: Option<T::BlockNumber> =
+            ShuffleActivity::<T>::get((vote_id, topic_id));
+        match (shuffle_state, shuffled_at) {
+            (Some(state), Some(shuffled_at)) if state.done => {
+                let now: T::BlockNumber = <frame_system::Module<T>>::block_number();
+                now > shuffled_at + period
+            }
+            _ => false,
+        }
+    }
+
+    /// adds a vote to the votes which are checked for missed turns.
+    /// fails if `T::MaxTallyingVotes` votes are already in the tallying phase.
+    pub fn track_tallying_vote(vote_id: &VoteId) -> Result<(), Error<T>> {
+        let mut vote_ids: Vec<VoteId> = TallyingVotes::get();
+        if vote_ids.contains(vote_id) {
+            return Ok(());
+        }
+        ensure!(
+            (vote_ids.len() as u32) < T::MaxTallyingVotes::get(),
+            Error::<T>::TooManyTallyingVotes
+        );
+        vote_ids.push(vote_id.clone());
+        TallyingVotes::put(vote_ids);
+        Ok(())
+    }
+
+    /// removes a vote from the votes which are checked for missed turns.
+    pub fn untrack_tallying_vote(vote_id: &VoteId) {
+        TallyingVotes::mutate(|vote_ids| vote_ids.retain(|id| id != vote_id));
+    }
+
+    /// records a missed turn for every sealer which did not shuffle during its turn.
+    ///
+    /// a turn starts at every block which is a multiple of `T::BlockDuration`
+    /// (see `offchain_shuffling`). the shuffle of a turn is included in one of
+    /// the following `T::BlockDuration` blocks. therefore, the turn which started
+    /// at block `turn` is evaluated at block `turn + T::BlockDuration + 1`.
+    ///
+    /// only the votes in `TallyingVotes` are checked, i.e. at most `T::MaxTallyingVotes`.
+    /// votes whose topics are all shuffled are removed from `TallyingVotes`.
+    pub fn record_missed_turns(block_number: T::BlockNumber) -> Weight {
+        let duration: T::BlockNumber = T::BlockDuration::get();
+        let one: T::BlockNumber = T::BlockNumber::from(1u32);
+        if duration.is_zero() || block_number <= duration + one {
+            return 0;
+        }
+        let turn: T::BlockNumber = block_number - duration - one;
+        if !(turn % duration).is_zero() {
+            return 0;
+        }
+
+        let mut reads: Weight = 1;
+        let mut writes: Weight = 0;
+        let mut reported: Weight = 0;
+        let vote_ids: Vec<VoteId> = TallyingVotes::get();
+        let mut shuffled: Vec<VoteId> = Vec::new();
+        for vote_id in vote_ids.iter() {
+            reads += 1;
+            let vote: Vote<T::AccountId> = Votes::<T>::get(vote_id);
+            if vote.phase != VotePhase::Tallying {
+                shuffled.push(vote_id.clone());
+                continue;
+            }
+
+            reads += 2;
+            let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(vote_id);
+            let topics: Vec<Topic> = Topics::get(vote_id);
+            if sealers.is_empty() {
+                continue;
+            }
+
+            let mut all_done = true;
+            for (topic_id, _) in topics.iter() {
+                reads += 2;
+                let done = ShuffleStateStore::get((vote_id, topic_id))
+                    .map_or(true, |state| state.done);
+                let last_activity: Option<T::BlockNumber> =
+                    ShuffleActivity::<T>::get((vote_id, topic_id));
+                all_done &= done;
+
+                // the shuffle did not progress since the start of the turn
+                match last_activity {
+                    Some(last_activity) if !done && last_activity <= turn => {
+                        let sealer = Self::get_current_sealer(turn, sealers.clone());
+                        reported = reported.saturating_add(Self::report_misbehaviour(
+                            &sealer,
+                            vote_id,
+                            Misbehaviour::MissedTurn,
+                        ));
+                        // a sealer misses at most one turn per vote and turn
+                        break;
+                    }
+                    _ => continue,
+                }
+            }
+            if all_done {
+                shuffled.push(vote_id.clone());
+            }
+        }
+
+        // the votes which are shuffled completely do not have turns anymore
+        if !shuffled.is_empty() {
+            TallyingVotes::mutate(|vote_ids| {
+                vote_ids.retain(|id| !shuffled.contains(id))
+            });
+            writes += 1;
+        }
+        T::DbWeight::get()
+            .reads_writes(reads, writes)
+            .saturating_add(reported)
+    }
+}
@@ -33,6 +33,7 @@ impl_outer_event! {
     pub enum TestEvent for TestRuntime {
         // events of crate: pallet_mixnet
         frame_system<T>,
+        pallet_balances<T>,
         pallet_mixnet<T>,
     }
 }
@@ -70,7 +71,7 @@ impl frame_system::Trait for TestRuntime {
     type AvailableBlockRatio = AvailableBlockRatio;
     type Version = ();
     type PalletInfo = ();
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
@@ -88,6 +89,23 @@ impl pallet_timestamp::Trait for TestRuntime {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Trait for TestRuntime {
+    type MaxLocks = MaxLocks;
+    type Balance = u64;
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+pub type Balances = pallet_balances::Module<TestRuntime>;
+
 // --- mocking offchain-worker trait
 
 pub type TestExtrinsic = TestXt<Call<TestRuntime>, ()>;
@@ -130,6 +148,11 @@ pub type System = frame_system::Module<TestRuntime>;
 // Mock Implementation of pallet_mixnet
 parameter_types! {
     pub const TestBlockDuration: u64 = 1;
+    pub const TestSealerBond: u64 = 500;
+    pub const TestMisbehaviourSlash: u64 = 100;
+    pub const TestDecryptionPeriod: u64 = 10;
+    pub const TestRetentionPeriod: u64 = 10;
+    pub const TestMaxTallyingVotes: u32 = 2;
 }
 
 impl pallet_mixnet::Trait for TestRuntime {
@@ -137,6 +160,13 @@ impl pallet_mixnet::Trait for TestRuntime {
     type Event = TestEvent;
     type AuthorityId = pallet_mixnet::keys::TestAuthId;
     type BlockDuration = TestBlockDuration;
+    type ManagementOrigin = frame_system::EnsureRoot<sr25519::Public>;
+    type Currency = Balances;
+    type SealerBond = TestSealerBond;
+    type MisbehaviourSlash = TestMisbehaviourSlash;
+    type DecryptionPeriod = TestDecryptionPeriod;
+    type RetentionPeriod = TestRetentionPeriod;
+    type MaxTallyingVotes = TestMaxTallyingVotes;
 }
 
 pub type OffchainModule = pallet_mixnet::Module<TestRuntime>;
@@ -219,6 +249,13 @@ impl ExternalityBuilder {
 
         let (voting_authorities, sealers) = Self::initialize_test_authorities();
 
+        // endow the sealers, so that they can reserve the sealer bond
+        pallet_balances::GenesisConfig::<TestRuntime> {
+            balances: sealers.iter().map(|sealer| (*sealer, 1_000)).collect(),
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
         super::GenesisConfig::<TestRuntime> {
             voting_authorities,
             sealers,
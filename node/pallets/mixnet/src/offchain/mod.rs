@@ -1,19 +1,25 @@
 mod send;
 
 use crate::{
-    helpers::{array::get_slice, assertions::ensure_vote_exists, params::get_public_key},
+    helpers::{
+        array::get_slice,
+        assertions::ensure_vote_exists,
+        params::{get_proof_version, get_public_key},
+    },
     types::{
-        Ballot, Cipher, PublicKey as SubstratePK, ShufflePayload, ShuffleProof,
-        ShuffleState, Topic, TopicId, Vote, VoteId, VotePhase, Wrapper,
+        Ballot, BallotRow, Canonical, Cipher, PublicKey as SubstratePK,
+        RowShufflePayload, RowShuffleProof, ShufflePayload, ShuffleProof, ShuffleState,
+        Topic, TopicId, Vote, VoteId, VotePhase,
     },
 };
 use crate::{
-    Call, Ciphers, Error, Module, Sealers, ShuffleStateStore, Topics, Trait, VoteIds,
-    Votes,
+    BallotRows, BallotShuffleStateStore, Call, Ciphers, Error, Module, ShuffleStateStore,
+    Topics, Trait, VoteIds, VoteSealers, Votes,
 };
 use core::convert::TryInto;
 use crypto::{
-    encryption::ElGamal, types::Cipher as BigCipher, types::PublicKey as ElGamalPK,
+    encryption::ElGamal, helper::ProofContext, types::Cipher as BigCipher,
+    types::PublicKey as ElGamalPK, types::Secret,
 };
 use frame_support::{
     debug,
@@ -51,7 +57,8 @@ impl<T: Trait> Module<T> {
         let r = Self::get_random_biguint_less_than(q)?;
 
         // encrypt the current block number
-        let cipher: Cipher = ElGamal::encrypt_encode(&number_as_biguint, &r, &pk).into();
+        let cipher = ElGamal::encrypt_encode(&number_as_biguint, &r, &pk);
+        let cipher: Cipher = Cipher::encode_canonical(&cipher, &pk.params);
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id, cipher)];
         let ballot: Ballot = Ballot { answers };
 
@@ -98,12 +105,31 @@ impl<T: Trait> Module<T> {
             let pk: SubstratePK = get_public_key::<T>(&vote_id)?;
             let pk: ElGamalPK = pk.into();
 
+            // get the sealers taking part in this vote
+            let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(vote_id);
+
+            // if the ballots are shuffled as rows, the topics are not shuffled separately
+            if let Some(shuffle_state) = BallotShuffleStateStore::get(vote_id) {
+                if !shuffle_state.done {
+                    Self::offchain_ballot_shuffling(
+                        block_number,
+                        vote_id,
+                        &pk,
+                        sealers,
+                        shuffle_state,
+                    )?;
+                }
+                continue;
+            }
+
             for (topic_id, _) in topics.iter() {
                 // get shuffle state
-                let shuffle_state: ShuffleState = ShuffleStateStore::get((
-                    vote_id, topic_id,
-                ))
-                .expect("shuffle state should exist for all existing votes & topics!");
+                // homomorphically tallied topics are not shuffled -> skip to next topic
+                let shuffle_state: ShuffleState =
+                    match ShuffleStateStore::get((vote_id, topic_id)) {
+                        Some(shuffle_state) => shuffle_state,
+                        None => continue,
+                    };
                 debug::info!("shuffle_state: {:?}", shuffle_state);
 
                 // if the shuffling has been completed -> skip to next topic
@@ -112,8 +138,8 @@ impl<T: Trait> Module<T> {
                 }
 
                 // check who's turn it is
-                let sealers: Vec<T::AccountId> = Sealers::<T>::get();
-                let current_sealer = Self::get_current_sealer(block_number, sealers);
+                let current_sealer =
+                    Self::get_current_sealer(block_number, sealers.clone());
 
                 // get the signer for the transaction
                 let signer = Signer::<T, T::AuthorityId>::any_account();
@@ -127,6 +153,7 @@ impl<T: Trait> Module<T> {
                         debug::info!("my turn!");
                         // shuffle ciphers + create proof
                         let payload_response = Self::offchain_shuffle_and_proof(
+                            &vote_id,
                             &topic_id,
                             shuffle_state.iteration,
                             &pk,
@@ -156,6 +183,7 @@ impl<T: Trait> Module<T> {
     }
 
     pub fn offchain_shuffle_and_proof(
+        vote_id: &VoteId,
         topic_id: &TopicId,
         iteration: u8,
         pk: &ElGamalPK,
@@ -168,7 +196,9 @@ impl<T: Trait> Module<T> {
         let ciphers: Vec<Cipher> = Ciphers::get(&topic_id, iteration);
 
         // type conversion: Cipher (Vec<u8>) to BigCipher (BigUint)
-        let encryptions: Vec<BigCipher> = Wrapper(ciphers).into();
+        let encryptions: Vec<BigCipher> = ciphers
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
 
         // retrieve the ciphers for the computed range
         let slice =
@@ -178,11 +208,12 @@ impl<T: Trait> Module<T> {
         // shuffle the votes
         let (shuffled_slice, re_encryption_randoms, permutation): (
             Vec<BigCipher>,
-            Vec<BigUint>,
+            Vec<Secret>,
             Vec<usize>,
         ) = Self::shuffle_ciphers(&pk, slice.to_vec())?;
 
         // generate the shuffle proof
+        let context = ProofContext::with_version(vote_id, get_proof_version(vote_id));
         let proof: ShuffleProof = Self::generate_shuffle_proof(
             &topic_id,
             slice,
@@ -190,12 +221,99 @@ impl<T: Trait> Module<T> {
             re_encryption_randoms,
             &permutation,
             &pk,
+            &context,
         )?;
 
         // create transaction payload
         let payload = ShufflePayload {
-            ciphers: Wrapper(shuffled_slice).into(),
-            proof: proof.into(),
+            ciphers: Canonical::encode_canonical(&shuffled_slice, &pk.params),
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            iteration,
+            start_position,
+            batch_size,
+        };
+        Ok(payload)
+    }
+
+    fn offchain_ballot_shuffling(
+        block_number: T::BlockNumber,
+        vote_id: &VoteId,
+        pk: &ElGamalPK,
+        sealers: Vec<T::AccountId>,
+        shuffle_state: ShuffleState,
+    ) -> Result<(), Error<T>> {
+        debug::info!("ballot shuffle_state: {:?}", shuffle_state);
+
+        // check who's turn it is
+        let current_sealer = Self::get_current_sealer(block_number, sealers);
+
+        // get the signer for the transaction
+        let signer = Signer::<T, T::AuthorityId>::any_account();
+
+        // if it's the current_sealer's turn, then shuffle + submit rows + proof
+        // else, submit empty transaction
+        let transaction_response = signer.send_signed_transaction(|_acct| {
+            if current_sealer.eq(&_acct.id) {
+                debug::info!("my turn!");
+                let payload: RowShufflePayload =
+                    Self::offchain_shuffle_ballots_and_proof(
+                        vote_id,
+                        shuffle_state.iteration,
+                        pk,
+                        shuffle_state.start_position,
+                        shuffle_state.batch_size,
+                    )
+                    .unwrap();
+                Call::submit_shuffled_ballots_and_proof(vote_id.to_vec(), payload)
+            } else {
+                debug::info!("not my turn!");
+                Call::do_nothing_when_its_not_your_turn()
+            }
+        });
+        Self::handle_transaction_response(vote_id, &current_sealer, transaction_response)
+    }
+
+    pub fn offchain_shuffle_ballots_and_proof(
+        vote_id: &VoteId,
+        iteration: u8,
+        pk: &ElGamalPK,
+        start_position: u64,
+        batch_size: u64,
+    ) -> Result<RowShufflePayload, Error<T>> {
+        // get all ballot rows for the # of shuffles (iteration)
+        let rows: Vec<BallotRow> = BallotRows::get(vote_id, iteration);
+
+        // retrieve the rows for the computed range
+        // type conversion: BallotRow (Vec<u8>) to Vec<BigCipher> (BigUint)
+        let slice: Vec<BallotRow> =
+            get_slice::<T, BallotRow>(rows, start_position, batch_size);
+        let slice: Vec<Vec<BigCipher>> = slice
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
+
+        // shuffle the rows
+        let (shuffled_slice, re_encryption_randoms, permutation): (
+            Vec<Vec<BigCipher>>,
+            Vec<Vec<Secret>>,
+            Vec<usize>,
+        ) = Self::shuffle_rows(pk, slice.clone())?;
+
+        // generate the shuffle proof
+        let context = ProofContext::with_version(vote_id, get_proof_version(vote_id));
+        let proof: RowShuffleProof = Self::generate_row_shuffle_proof(
+            vote_id,
+            slice,
+            shuffled_slice.clone(),
+            re_encryption_randoms,
+            &permutation,
+            pk,
+            &context,
+        )?;
+
+        // create transaction payload
+        let payload = RowShufflePayload {
+            rows: Canonical::encode_canonical(&shuffled_slice, &pk.params),
+            proof: Canonical::encode_canonical(&proof, &pk.params),
             iteration,
             start_position,
             batch_size,
@@ -203,8 +321,8 @@ impl<T: Trait> Module<T> {
         Ok(payload)
     }
 
-    /// retrieves the current sealer, depends on the block number
-    fn get_current_sealer(
+    /// retrieves the current sealer of a vote, depends on the block number
+    pub fn get_current_sealer(
         block_number: T::BlockNumber,
         sealers: Vec<T::AccountId>,
     ) -> T::AccountId {
@@ -0,0 +1,167 @@
+use crate::{
+    dkg::weights::weight_topic_id,
+    types::{
+        Ballot, BallotRow, Cipher, DecryptedShare, DecryptedShareCommitment,
+        DecryptedShareProof, NrOfShuffles, ShufflePayload, Topic, TopicId,
+        TopicTranscript, VoteId, VoteTranscript,
+    },
+    BallotRows, BallotShuffleProofs, BallotShuffleStateStore, Ballots, Ciphers,
+    DecryptedShareCommitments, DecryptedShareProofs, DecryptedShares, EncryptedSums,
+    Module, PublicKeyShareBySealer, PublicKeyShares, ShuffleActivity, ShuffleProofs,
+    Topics, Trait, VoteSealers, WeightedVotes,
+};
+use codec::Encode;
+use frame_support::{
+    storage::{IterableStorageDoubleMap, StorageDoubleMap, StorageMap},
+    traits::Get,
+    weights::Weight,
+};
+use sp_std::vec::Vec;
+
+/// the base weight of `purge_vote` (the checks, the commitment and the event)
+const PURGE_VOTE_BASE_WEIGHT: Weight = 10_000;
+
+/// the weight of `purge_vote` per stored item (encoding + hashing it into the commitment)
+const PURGE_VOTE_ITEM_WEIGHT: Weight = 1_000;
+
+impl<T: Trait> Module<T> {
+    /// collects all intermediate data of a vote: the public key shares,
+    /// the ballots, the ciphers (and ballot rows) of all shuffle iterations,
+    /// the shuffle proofs, the encrypted sums and the decrypted shares.
+    pub fn vote_transcript(vote_id: &VoteId) -> VoteTranscript<T::AccountId> {
+        let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(vote_id);
+
+        // sort the ballots by voter, since the storage order depends on the hasher
+        let mut ballots: Vec<(T::AccountId, Ballot)> =
+            Ballots::<T>::iter_prefix(vote_id).collect();
+        ballots.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let topics: Vec<TopicTranscript<T::AccountId>> =
+            Self::transcript_topic_ids(vote_id)
+                .iter()
+                .map(|topic_id| Self::topic_transcript(vote_id, topic_id, &sealers))
+                .collect();
+
+        let mut ballot_rows: Vec<(NrOfShuffles, Vec<BallotRow>)> =
+            BallotRows::iter_prefix(vote_id).collect();
+        ballot_rows.sort_by_key(|(iteration, _)| *iteration);
+
+        VoteTranscript {
+            vote_id: vote_id.clone(),
+            public_key_shares: PublicKeyShares::get(vote_id),
+            ballots,
+            topics,
+            ballot_rows,
+            ballot_shuffle_proofs: BallotShuffleProofs::get(vote_id),
+        }
+    }
+
+    /// the topics of a vote and, if the vote is weighted and its ballots are shuffled as rows,
+    /// the topic of the shuffled weights (see `weight_topic_id`)
+    fn transcript_topic_ids(vote_id: &VoteId) -> Vec<TopicId> {
+        let topics: Vec<Topic> = Topics::get(vote_id);
+        let mut topic_ids: Vec<TopicId> =
+            topics.into_iter().map(|(topic_id, _)| topic_id).collect();
+        if WeightedVotes::get(vote_id) && BallotShuffleStateStore::contains_key(vote_id) {
+            topic_ids.push(weight_topic_id(vote_id));
+        }
+        topic_ids
+    }
+
+    fn topic_transcript(
+        vote_id: &VoteId,
+        topic_id: &TopicId,
+        sealers: &[T::AccountId],
+    ) -> TopicTranscript<T::AccountId> {
+        let mut ciphers: Vec<(NrOfShuffles, Vec<Cipher>)> =
+            Ciphers::iter_prefix(topic_id).collect();
+        ciphers.sort_by_key(|(iteration, _)| *iteration);
+
+        let shuffle_proofs: Vec<ShufflePayload> = ShuffleProofs::get((vote_id, topic_id));
+        let decrypted_shares: Vec<(T::AccountId, Vec<DecryptedShare>)> = sealers
+            .iter()
+            .map(|sealer| (sealer.clone(), DecryptedShares::<T>::get(topic_id, sealer)))
+            .collect();
+        let decryption_proofs: Vec<(T::AccountId, NrOfShuffles, DecryptedShareProof)> =
+            sealers
+                .iter()
+                .filter_map(|sealer| {
+                    DecryptedShareProofs::<T>::get(topic_id, sealer)
+                        .map(|(iteration, proof)| (sealer.clone(), iteration, proof))
+                })
+                .collect();
+        let decryption_commitments: Vec<(T::AccountId, Vec<DecryptedShareCommitment>)> =
+            sealers
+                .iter()
+                .map(|sealer| {
+                    (
+                        sealer.clone(),
+                        DecryptedShareCommitments::<T>::get(topic_id, sealer),
+                    )
+                })
+                .filter(|(_, commitments)| !commitments.is_empty())
+                .collect();
+
+        TopicTranscript {
+            topic_id: topic_id.clone(),
+            ciphers,
+            shuffle_proofs,
+            encrypted_sum: EncryptedSums::get(topic_id),
+            decrypted_shares,
+            decryption_proofs,
+            decryption_commitments,
+        }
+    }
+
+    /// the commitment to the transcript of a vote: blake2_256(SCALE(transcript))
+    pub fn transcript_commitment_of(
+        transcript: &VoteTranscript<T::AccountId>,
+    ) -> [u8; 32] {
+        sp_io::hashing::blake2_256(&transcript.encode())
+    }
+
+    /// the weight of `purge_vote` computed from the number of stored items of the vote:
+    /// every item of the transcript is read, encoded, hashed and removed.
+    pub fn purge_vote_weight(vote_id: &VoteId) -> Weight {
+        let nr_of_sealers = VoteSealers::<T>::get(vote_id).len() as Weight;
+        let mut items: Weight = 3 + nr_of_sealers;
+        items += Ballots::<T>::iter_prefix(vote_id).count() as Weight;
+        items += BallotRows::iter_prefix(vote_id)
+            .map(|(_, rows)| rows.len() as Weight)
+            .sum::<Weight>();
+        for topic_id in Self::transcript_topic_ids(vote_id).iter() {
+            items += 4 + 3 * nr_of_sealers;
+            items += Ciphers::iter_prefix(topic_id)
+                .map(|(_, ciphers)| ciphers.len() as Weight)
+                .sum::<Weight>();
+        }
+
+        // the commitment is written on top of the removed items
+        PURGE_VOTE_BASE_WEIGHT
+            .saturating_add(PURGE_VOTE_ITEM_WEIGHT.saturating_mul(items))
+            .saturating_add(T::DbWeight::get().reads_writes(items, items + 1))
+    }
+
+    /// removes all intermediate data of a vote (see `vote_transcript`).
+    /// the vote, its topics, the public key and the tally are kept.
+    pub fn remove_vote_transcript(vote_id: &VoteId) {
+        let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(vote_id);
+
+        for topic_id in Self::transcript_topic_ids(vote_id).iter() {
+            Ciphers::remove_prefix(topic_id);
+            DecryptedShares::<T>::remove_prefix(topic_id);
+            DecryptedShareProofs::<T>::remove_prefix(topic_id);
+            DecryptedShareCommitments::<T>::remove_prefix(topic_id);
+            ShuffleProofs::remove((vote_id, topic_id));
+            ShuffleActivity::<T>::remove((vote_id, topic_id));
+            EncryptedSums::remove(topic_id);
+        }
+        for sealer in sealers.iter() {
+            PublicKeyShareBySealer::<T>::remove((vote_id, sealer));
+        }
+        PublicKeyShares::remove(vote_id);
+        Ballots::<T>::remove_prefix(vote_id);
+        BallotRows::remove_prefix(vote_id);
+        BallotShuffleProofs::remove(vote_id);
+    }
+}
@@ -0,0 +1,67 @@
+pub mod verify;
+
+use crate::helpers::params::{get_proof_version, get_public_key};
+use crate::types::{
+    ElectionRecord, PublicKeyShare, Topic, TopicDefinition, TopicId, TopicResult, VoteId,
+    VoterWeight,
+};
+use crate::{
+    BallotShuffleStateStore, Module, PublicKeyShareBySealer, Tally, TopicDefinitions,
+    Topics, Trait, VoteSealers, VoterWeights, Votes, WeightedVotes,
+};
+use frame_support::storage::{IterableStorageDoubleMap, StorageMap};
+use sp_std::vec::Vec;
+
+impl<T: Trait> Module<T> {
+    /// collects the public record of a vote: the vote, its topics and their definitions,
+    /// the public key shares of the sealers, the public key, the weights of the voters,
+    /// the tally of every topic, the transcript of the vote (see `vote_transcript`)
+    /// and the version of its proofs.
+    /// the record must be collected before the vote is purged.
+    pub fn election_record(vote_id: &VoteId) -> ElectionRecord<T::AccountId> {
+        let topics: Vec<Topic> = Topics::get(vote_id);
+        let sealers: Vec<T::AccountId> = VoteSealers::<T>::get(vote_id);
+
+        let definitions: Vec<(TopicId, TopicDefinition)> = topics
+            .iter()
+            .filter_map(|(topic_id, _)| {
+                TopicDefinitions::get(topic_id)
+                    .map(|definition| (topic_id.clone(), definition))
+            })
+            .collect();
+        let public_key_shares: Vec<(T::AccountId, PublicKeyShare)> = sealers
+            .iter()
+            .filter_map(|sealer| {
+                PublicKeyShareBySealer::<T>::get((vote_id, sealer))
+                    .map(|share| (sealer.clone(), share))
+            })
+            .collect();
+
+        // sort the weights by voter, since the storage order depends on the hasher
+        let mut voter_weights: Vec<(T::AccountId, VoterWeight)> =
+            VoterWeights::<T>::iter_prefix(vote_id).collect();
+        voter_weights.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let tallies: Vec<(TopicId, TopicResult)> = topics
+            .iter()
+            .filter_map(|(topic_id, _)| {
+                Tally::get(topic_id).map(|result| (topic_id.clone(), result))
+            })
+            .collect();
+
+        ElectionRecord {
+            vote: Votes::<T>::get(vote_id),
+            topics,
+            definitions,
+            sealers,
+            public_key_shares,
+            public_key: get_public_key::<T>(vote_id).ok(),
+            ballot_shuffling: BallotShuffleStateStore::contains_key(vote_id),
+            weighted: WeightedVotes::get(vote_id),
+            voter_weights,
+            tallies,
+            transcript: Self::vote_transcript(vote_id),
+            proof_version: get_proof_version(vote_id) as u8,
+        }
+    }
+}
@@ -0,0 +1,835 @@
+use crate::dkg::{
+    homomorphic::{decode_sum, sum_results},
+    tally::{count_plaintexts, decrypt_plaintexts},
+    weights::{decode_weights, encrypted_weight, weight_topic_id, DEFAULT_VOTER_WEIGHT},
+    write_in::{answer_width_of, write_in_length},
+};
+use crate::types::{
+    BallotRow, Canonical, Cipher, ElectionRecord, NrOfShuffles, OptionEncoding,
+    PublicKey as SubstratePK, PublicKeyShare, RowShuffleProof, ShuffleProof,
+    TopicDefinition, TopicId, TopicResult, TopicTranscript, VerificationError,
+    VoterWeight,
+};
+use crate::{Module, Trait};
+use codec::Encode;
+use core::convert::TryFrom;
+use crypto::encryption::ElGamal;
+use crypto::helper::{ProofContext, ProofVersion};
+use crypto::proofs::{
+    decryption::{DecryptionProof, DecryptionStatement},
+    keygen::KeyGenerationProof,
+};
+use crypto::types::{
+    Cipher as BigCipher, ElGamalParams, ModuloOperations, PublicKey as ElGamalPK,
+};
+use frame_support::ensure;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive};
+use sp_std::{
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+    vec,
+    vec::Vec,
+};
+
+type VerificationResult<T> =
+    Result<(), VerificationError<<T as frame_system::Trait>::AccountId>>;
+
+/// the verification of an election record (see `Module::election_record`).
+/// the record is verified in steps, each step only relies on the steps before it:
+/// 1. the key generation proofs and the public key
+/// 2. the ballots: the submitted ciphers, ballot rows and encrypted sums
+/// 3. every shuffle proof (in the order in which they were submitted)
+/// 4. every decryption proof
+/// 5. the tally of every topic
+impl<T: Trait> Module<T> {
+    /// verifies an election record end to end,
+    /// returns the first step at which the verification failed
+    pub fn verify_election_record(
+        record: &ElectionRecord<T::AccountId>,
+    ) -> VerificationResult<T> {
+        Self::verify_key_generation(record)?;
+        Self::verify_ballots(record)?;
+        Self::verify_shuffles(record)?;
+        Self::verify_decryptions(record)?;
+        Self::verify_tallies(record)
+    }
+
+    /// verifies the key generation proof of every public key share
+    /// and that the public key is the combination of all public key shares
+    pub fn verify_key_generation(
+        record: &ElectionRecord<T::AccountId>,
+    ) -> VerificationResult<T> {
+        let pk: ElGamalPK = record_public_key(record)?;
+        let params: ElGamalParams = pk.params.clone();
+        let context: ProofContext = proof_context(record)?;
+
+        let mut combined: BigUint = BigUint::one();
+        for (sealer, share) in record.public_key_shares.iter() {
+            let invalid = VerificationError::InvalidKeyGenerationProof(sealer.clone());
+            let share_pk: BigUint = params
+                .decode_element(&share.pk)
+                .ok_or_else(|| invalid.clone())?;
+            let proof: KeyGenerationProof = share
+                .proof
+                .decode_canonical(&params)
+                .ok_or_else(|| invalid.clone())?;
+            let is_valid: bool = KeyGenerationProof::verify(
+                &params,
+                &share_pk,
+                &proof,
+                &sealer.encode(),
+                &context,
+            );
+            ensure!(is_valid, invalid);
+            combined = combined.modmul(&share_pk, &params.p);
+        }
+        ensure!(
+            record.public_key_shares.len() > 1 && combined == pk.h,
+            VerificationError::PublicKeyMismatch
+        );
+        Ok(())
+    }
+
+    /// verifies that the submitted ciphers of every topic are the answers of the ballots,
+    /// that the encrypted sums are the (weighted) sums of the answers and,
+    /// if the ballots are shuffled as rows, that the submitted rows are the ballots
+    pub fn verify_ballots(
+        record: &ElectionRecord<T::AccountId>,
+    ) -> VerificationResult<T> {
+        let params = &record.vote.params;
+        let big_params: ElGamalParams = params.clone().into();
+        let ballots = &record.transcript.ballots;
+
+        for (topic_id, _) in record.topics.iter() {
+            let topic = topic_transcript(record, topic_id)
+                .ok_or_else(|| VerificationError::BallotsMismatch(topic_id.clone()))?;
+
+            // the ciphers are stored in the order in which the ballots were cast,
+            // therefore, they are compared as multisets
+            let answers: Vec<(VoterWeight, &Cipher)> = ballots
+                .iter()
+                .flat_map(|(voter, ballot)| {
+                    let weight: VoterWeight = voter_weight(record, voter);
+                    ballot
+                        .answers
+                        .iter()
+                        .filter(move |(id, _)| id == topic_id)
+                        .map(move |(_, cipher)| (weight, cipher))
+                })
+                .collect();
+            let submitted: Vec<Cipher> = ciphers_at(topic, 0);
+            ensure!(
+                sorted_encodings(answers.iter().map(|(_, cipher)| *cipher))
+                    == sorted_encodings(submitted.iter()),
+                VerificationError::BallotsMismatch(topic_id.clone())
+            );
+
+            // the encrypted sum: Enc(sum) = Prod(Enc(m)^w)
+            if let Some(encrypted_sum) = &topic.encrypted_sum {
+                let mut sum: BigCipher = BigCipher {
+                    a: BigUint::one(),
+                    b: BigUint::one(),
+                };
+                for (weight, cipher) in answers.into_iter() {
+                    let big_cipher: BigCipher = cipher
+                        .decode_canonical(&big_params)
+                        .ok_or_else(|| VerificationError::BallotsMismatch(topic_id.clone()))?;
+                    let weighted: BigCipher = ElGamal::homomorphic_multiply(
+                        &big_cipher,
+                        &BigUint::from(weight),
+                        &big_params.p,
+                    );
+                    sum = ElGamal::homomorphic_addition(&sum, &weighted, &big_params.p);
+                }
+                let sum: Cipher = Cipher::encode_canonical(&sum, &big_params);
+                ensure!(
+                    sum == *encrypted_sum,
+                    VerificationError::EncryptedSumMismatch(topic_id.clone())
+                );
+            }
+        }
+
+        if record.ballot_shuffling {
+            // each ballot: its answers in the order of the topics (+ the encrypted weight)
+            let rows: Vec<BallotRow> = ballots
+                .iter()
+                .map(|(voter, ballot)| {
+                    let mut row: BallotRow = Vec::with_capacity(ballot.answers.len() + 1);
+                    for (topic_id, _) in record.topics.iter() {
+                        row.extend(
+                            ballot
+                                .answers
+                                .iter()
+                                .filter(|(id, _)| id == topic_id)
+                                .map(|(_, cipher)| cipher.clone()),
+                        );
+                    }
+                    if record.weighted {
+                        row.push(encrypted_weight(voter_weight(record, voter), params));
+                    }
+                    row
+                })
+                .collect();
+            let submitted: Vec<BallotRow> = rows_at(record, 0);
+            ensure!(
+                sorted_encodings(rows.iter()) == sorted_encodings(submitted.iter()),
+                VerificationError::BallotRowsMismatch
+            );
+        }
+        Ok(())
+    }
+
+    /// replays every shuffle proof in the order in which it was submitted
+    /// and verifies that the shuffled ciphers (and rows) of every iteration
+    /// are exactly the outputs of the shuffle proofs
+    pub fn verify_shuffles(
+        record: &ElectionRecord<T::AccountId>,
+    ) -> VerificationResult<T> {
+        let pk: ElGamalPK = record_public_key(record)?;
+        let context: ProofContext = proof_context(record)?;
+
+        // the ciphers of the topics are either shuffled as part of the ballot rows
+        // or each topic is shuffled on its own
+        if record.ballot_shuffling {
+            return Self::verify_row_shuffles(record, &pk, &context);
+        }
+        for topic in record.transcript.topics.iter() {
+            Self::verify_topic_shuffles(topic, &pk, &context)?;
+        }
+        Ok(())
+    }
+
+    fn verify_topic_shuffles(
+        topic: &TopicTranscript<T::AccountId>,
+        pk: &ElGamalPK,
+        context: &ProofContext,
+    ) -> VerificationResult<T> {
+        let topic_id: &TopicId = &topic.topic_id;
+        let mut outputs: BTreeMap<NrOfShuffles, Vec<Cipher>> = BTreeMap::new();
+        let (mut iteration, mut start_position): (NrOfShuffles, u64) = (0, 0);
+
+        for (index, payload) in topic.shuffle_proofs.iter().enumerate() {
+            let invalid =
+                VerificationError::InvalidShuffleProof(topic_id.clone(), index as u32);
+
+            // the shuffles must be submitted batch by batch, iteration by iteration
+            let ciphers: Vec<Cipher> = ciphers_at(topic, payload.iteration);
+            ensure!(
+                payload.iteration == iteration
+                    && payload.start_position == start_position
+                    && (payload.start_position as usize) < ciphers.len(),
+                invalid
+            );
+            let slice: Vec<Cipher> =
+                get_range(ciphers.clone(), payload.start_position, payload.batch_size);
+            let proof: ShuffleProof = payload
+                .proof
+                .decode_canonical(&pk.params)
+                .ok_or_else(|| invalid.clone())?;
+            ensure!(
+                slice.len() == payload.ciphers.len()
+                    && has_shuffle_length(&proof, slice.len()),
+                invalid
+            );
+
+            let big_slice: Vec<BigCipher> = slice
+                .decode_canonical(&pk.params)
+                .ok_or_else(|| invalid.clone())?;
+            let big_shuffled: Vec<BigCipher> = payload
+                .ciphers
+                .decode_canonical(&pk.params)
+                .ok_or_else(|| invalid.clone())?;
+            let is_valid: bool = Self::verify_shuffle_proof(
+                topic_id,
+                proof,
+                big_slice,
+                big_shuffled,
+                pk,
+                context,
+            )
+            .unwrap_or(false);
+            ensure!(is_valid, invalid);
+
+            outputs
+                .entry(iteration + 1)
+                .or_default()
+                .extend(payload.ciphers.iter().cloned());
+            let next = Self::compute_next_shuffle_state(
+                start_position,
+                payload.batch_size,
+                ciphers.len(),
+                iteration,
+            );
+            iteration = next.iteration;
+            start_position = next.start_position;
+        }
+
+        for (iteration, ciphers) in topic.ciphers.iter().filter(|(i, _)| *i > 0) {
+            ensure!(
+                outputs.remove(iteration).as_ref() == Some(ciphers),
+                VerificationError::ShuffledCiphersMismatch(topic_id.clone(), *iteration)
+            );
+        }
+        if let Some((iteration, _)) = outputs.into_iter().next() {
+            return Err(VerificationError::ShuffledCiphersMismatch(
+                topic_id.clone(),
+                iteration,
+            ));
+        }
+        Ok(())
+    }
+
+    fn verify_row_shuffles(
+        record: &ElectionRecord<T::AccountId>,
+        pk: &ElGamalPK,
+        context: &ProofContext,
+    ) -> VerificationResult<T> {
+        let vote_id = &record.transcript.vote_id;
+        let mut outputs: BTreeMap<NrOfShuffles, Vec<BallotRow>> = BTreeMap::new();
+        let (mut iteration, mut start_position): (NrOfShuffles, u64) = (0, 0);
+        let mut done: bool = false;
+
+        for (index, payload) in record.transcript.ballot_shuffle_proofs.iter().enumerate()
+        {
+            let invalid = VerificationError::InvalidRowShuffleProof(index as u32);
+
+            let rows: Vec<BallotRow> = rows_at(record, payload.iteration);
+            ensure!(
+                !done
+                    && payload.iteration == iteration
+                    && payload.start_position == start_position
+                    && (payload.start_position as usize) < rows.len(),
+                invalid
+            );
+            let slice: Vec<BallotRow> =
+                get_range(rows.clone(), payload.start_position, payload.batch_size);
+            let width: usize = slice[0].len();
+            ensure!(
+                slice.len() == payload.rows.len()
+                    && slice
+                        .iter()
+                        .chain(payload.rows.iter())
+                        .all(|row| row.len() == width),
+                invalid
+            );
+
+            let big_slice: Vec<Vec<BigCipher>> = slice
+                .decode_canonical(&pk.params)
+                .ok_or_else(|| invalid.clone())?;
+            let big_shuffled: Vec<Vec<BigCipher>> = payload
+                .rows
+                .decode_canonical(&pk.params)
+                .ok_or_else(|| invalid.clone())?;
+            let proof: RowShuffleProof = payload
+                .proof
+                .decode_canonical(&pk.params)
+                .ok_or_else(|| invalid.clone())?;
+            let is_valid: bool = Self::verify_row_shuffle_proof(
+                vote_id,
+                proof,
+                big_slice,
+                big_shuffled,
+                pk,
+                context,
+            )
+            .unwrap_or(false);
+            ensure!(is_valid, invalid);
+
+            outputs
+                .entry(iteration + 1)
+                .or_default()
+                .extend(payload.rows.iter().cloned());
+            let next = Self::compute_next_shuffle_state(
+                start_position,
+                payload.batch_size,
+                rows.len(),
+                iteration,
+            );
+            iteration = next.iteration;
+            start_position = next.start_position;
+            done = next.done;
+        }
+
+        for (iteration, rows) in
+            record.transcript.ballot_rows.iter().filter(|(i, _)| *i > 0)
+        {
+            ensure!(
+                outputs.remove(iteration).as_ref() == Some(rows),
+                VerificationError::ShuffledBallotRowsMismatch(*iteration)
+            );
+        }
+        if let Some((iteration, _)) = outputs.into_iter().next() {
+            return Err(VerificationError::ShuffledBallotRowsMismatch(iteration));
+        }
+
+        // once the shuffle is done, the columns of the rows are the shuffled ciphers of the topics
+        let shuffled: Option<NrOfShuffles> = if done { Some(iteration) } else { None };
+        Self::verify_ballot_columns(record, shuffled)
+    }
+
+    /// verifies that the shuffled ciphers of every topic (and of the weights)
+    /// are the columns of the shuffled ballot rows (see `store_shuffled_ballot_columns`).
+    /// before the rows have been shuffled completely, there must not be any shuffled ciphers.
+    fn verify_ballot_columns(
+        record: &ElectionRecord<T::AccountId>,
+        shuffled: Option<NrOfShuffles>,
+    ) -> VerificationResult<T> {
+        let big_p: BigUint = BigUint::from_bytes_be(&record.vote.params.p);
+        let rows: Vec<BallotRow> = shuffled
+            .map(|iteration| rows_at(record, iteration))
+            .unwrap_or_default();
+
+        let mut columns: Vec<(TopicId, usize)> = record
+            .topics
+            .iter()
+            .map(|(topic_id, _)| {
+                let definition = definition_of(record, topic_id);
+                (topic_id.clone(), answer_width_of(definition, &big_p))
+            })
+            .collect();
+        if record.weighted {
+            columns.push((weight_topic_id(&record.transcript.vote_id), 1));
+        }
+
+        let mut column: usize = 0;
+        for (topic_id, width) in columns.into_iter() {
+            let topic = topic_transcript(record, &topic_id).ok_or_else(|| {
+                VerificationError::ShuffledCiphersMismatch(topic_id.clone(), 0)
+            })?;
+            let mut submitted = topic.ciphers.iter().filter(|(i, _)| *i > 0);
+
+            if let Some(iteration) = shuffled {
+                let mismatch = VerificationError::ShuffledCiphersMismatch(
+                    topic_id.clone(),
+                    iteration,
+                );
+                ensure!(rows.iter().all(|row| row.len() >= column + width), mismatch);
+                let expected: Vec<Cipher> = rows
+                    .iter()
+                    .flat_map(|row| row[column..column + width].iter().cloned())
+                    .collect();
+                ensure!(submitted.next() == Some(&(iteration, expected)), mismatch);
+            }
+            if let Some((iteration, _)) = submitted.next() {
+                return Err(VerificationError::ShuffledCiphersMismatch(
+                    topic_id.clone(),
+                    *iteration,
+                ));
+            }
+            column += width;
+        }
+        Ok(())
+    }
+
+    /// verifies the decryption proof of every sealer which submitted decrypted shares.
+    /// all proofs are verified at once (see `DecryptionProof::verify_batch`),
+    /// only if the batch is invalid, the proofs are verified one by one to find the invalid proof.
+    pub fn verify_decryptions(
+        record: &ElectionRecord<T::AccountId>,
+    ) -> VerificationResult<T> {
+        let params: ElGamalParams = record.vote.params.clone().into();
+        let context: ProofContext = proof_context(record)?;
+        let mut statements: Vec<DecryptionStatement> = Vec::new();
+        let mut invalid_proofs: Vec<VerificationError<T::AccountId>> = Vec::new();
+
+        for topic in record.transcript.topics.iter() {
+            let topic_id: &TopicId = &topic.topic_id;
+
+            for (sealer, shares) in topic.decrypted_shares.iter() {
+                if shares.is_empty() {
+                    continue;
+                }
+                let invalid = VerificationError::InvalidDecryptionProof(
+                    topic_id.clone(),
+                    sealer.clone(),
+                );
+                let (nr_of_shuffles, proof) = topic
+                    .decryption_proofs
+                    .iter()
+                    .find(|(account, _, _)| account == sealer)
+                    .map(|(_, nr_of_shuffles, proof)| (*nr_of_shuffles, proof))
+                    .ok_or_else(|| {
+                        VerificationError::MissingDecryptionProof(
+                            topic_id.clone(),
+                            sealer.clone(),
+                        )
+                    })?;
+                let share: &PublicKeyShare =
+                    public_key_share(record, sealer).ok_or_else(|| invalid.clone())?;
+
+                // the decrypted ciphers: the encrypted sum or the (shuffled) ciphers
+                let ciphers: Vec<Cipher> = match &topic.encrypted_sum {
+                    Some(sum) => vec![sum.clone()],
+                    None => ciphers_at(topic, nr_of_shuffles),
+                };
+                ensure!(ciphers.len() == shares.len(), invalid);
+
+                // the commitments of the proof (none if they were not submitted)
+                let commitments: &[Vec<u8>] = topic
+                    .decryption_commitments
+                    .iter()
+                    .find(|(account, _)| account == sealer)
+                    .map(|(_, commitments)| commitments.as_slice())
+                    .unwrap_or_default();
+
+                // only canonically encoded keys, ciphers, shares and commitments are accepted
+                let pk: BigUint = params
+                    .decode_element(&share.pk)
+                    .ok_or_else(|| invalid.clone())?;
+                let big_ciphers: Vec<BigCipher> = ciphers
+                    .decode_canonical(&params)
+                    .ok_or_else(|| invalid.clone())?;
+                let big_shares: Vec<BigUint> = shares
+                    .iter()
+                    .map(|s| params.decode_element(s))
+                    .collect::<Option<Vec<BigUint>>>()
+                    .ok_or_else(|| invalid.clone())?;
+                let big_commitments: Vec<BigUint> = commitments
+                    .iter()
+                    .map(|t| params.decode_element(t))
+                    .collect::<Option<Vec<BigUint>>>()
+                    .ok_or_else(|| invalid.clone())?;
+                let proof: DecryptionProof = proof
+                    .decode_canonical(&params)
+                    .ok_or_else(|| invalid.clone())?;
+                statements.push(DecryptionStatement {
+                    pk,
+                    proof,
+                    commitments: big_commitments,
+                    encryptions: big_ciphers,
+                    partial_decryptions: big_shares,
+                    id: sealer.encode(),
+                });
+                invalid_proofs.push(invalid);
+            }
+        }
+
+        if DecryptionProof::verify_batch(&params, &statements, &context) {
+            return Ok(());
+        }
+        for (statement, invalid) in statements.iter().zip(invalid_proofs.into_iter()) {
+            let is_valid: bool = DecryptionProof::verify_batch(
+                &params,
+                core::slice::from_ref(statement),
+                &context,
+            );
+            ensure!(is_valid, invalid);
+        }
+        Ok(())
+    }
+
+    /// recomputes the tally of every tallied topic from the decrypted shares of all sealers
+    pub fn verify_tallies(
+        record: &ElectionRecord<T::AccountId>,
+    ) -> VerificationResult<T> {
+        for (topic_id, tally) in record.tallies.iter() {
+            let mismatch = VerificationError::TallyMismatch(topic_id.clone());
+            let topic =
+                topic_transcript(record, topic_id).ok_or_else(|| mismatch.clone())?;
+            let results: TopicResult = match &topic.encrypted_sum {
+                Some(sum) => recompute_sum(record, topic, sum),
+                None => recompute_tally(record, topic, tally),
+            }
+            .ok_or_else(|| mismatch.clone())?;
+            ensure!(results == *tally, mismatch);
+        }
+        Ok(())
+    }
+}
+
+/// the public key of the vote, which must use the parameters of the vote
+fn record_public_key<A>(
+    record: &ElectionRecord<A>,
+) -> Result<ElGamalPK, VerificationError<A>> {
+    let pk: SubstratePK = record
+        .public_key
+        .clone()
+        .ok_or(VerificationError::MissingPublicKey)?;
+    ensure!(
+        pk.params == record.vote.params,
+        VerificationError::PublicKeyMismatch
+    );
+    pk.decode_canonical()
+        .ok_or(VerificationError::PublicKeyMismatch)
+}
+
+/// the context of the proofs of the vote (see `get_proof_version`)
+fn proof_context<A>(
+    record: &ElectionRecord<A>,
+) -> Result<ProofContext, VerificationError<A>> {
+    let version: ProofVersion = ProofVersion::try_from(record.proof_version)
+        .map_err(|_| VerificationError::UnsupportedProofVersion(record.proof_version))?;
+    Ok(ProofContext::with_version(
+        &record.transcript.vote_id,
+        version,
+    ))
+}
+
+fn topic_transcript<'a, A>(
+    record: &'a ElectionRecord<A>,
+    topic_id: &TopicId,
+) -> Option<&'a TopicTranscript<A>> {
+    record
+        .transcript
+        .topics
+        .iter()
+        .find(|topic| topic.topic_id == *topic_id)
+}
+
+fn definition_of<'a, A>(
+    record: &'a ElectionRecord<A>,
+    topic_id: &TopicId,
+) -> Option<&'a TopicDefinition> {
+    record
+        .definitions
+        .iter()
+        .find(|(id, _)| id == topic_id)
+        .map(|(_, definition)| definition)
+}
+
+fn public_key_share<'a, A: PartialEq>(
+    record: &'a ElectionRecord<A>,
+    sealer: &A,
+) -> Option<&'a PublicKeyShare> {
+    record
+        .public_key_shares
+        .iter()
+        .find(|(account, _)| account == sealer)
+        .map(|(_, share)| share)
+}
+
+/// the weight of a voter (1 if no weight has been set)
+fn voter_weight<A: PartialEq>(record: &ElectionRecord<A>, voter: &A) -> VoterWeight {
+    record
+        .voter_weights
+        .iter()
+        .find(|(account, _)| account == voter)
+        .map(|(_, weight)| *weight)
+        .unwrap_or(DEFAULT_VOTER_WEIGHT)
+}
+
+/// the ciphers of a topic after the given number of shuffles
+fn ciphers_at<A>(topic: &TopicTranscript<A>, iteration: NrOfShuffles) -> Vec<Cipher> {
+    topic
+        .ciphers
+        .iter()
+        .find(|(i, _)| *i == iteration)
+        .map(|(_, ciphers)| ciphers.clone())
+        .unwrap_or_default()
+}
+
+/// the ballot rows after the given number of shuffles
+fn rows_at<A>(record: &ElectionRecord<A>, iteration: NrOfShuffles) -> Vec<BallotRow> {
+    record
+        .transcript
+        .ballot_rows
+        .iter()
+        .find(|(i, _)| *i == iteration)
+        .map(|(_, rows)| rows.clone())
+        .unwrap_or_default()
+}
+
+/// the items in [start_position, start_position + batch_size) (see `get_slice`)
+fn get_range<B>(mut items: Vec<B>, start_position: u64, batch_size: u64) -> Vec<B> {
+    let start: usize = start_position as usize;
+    let end: usize = start.saturating_add(batch_size as usize).min(items.len());
+    items.truncate(end);
+    items.split_off(start.min(end))
+}
+
+/// checks that the commitments and responses of a shuffle proof match the shuffled ciphers,
+/// `verify_shuffle_proof` expects the lengths to be checked beforehand
+fn has_shuffle_length(proof: &ShuffleProof, size: usize) -> bool {
+    size > 0
+        && proof.permutation_commitments.len() == size
+        && proof.permutation_chain_commitments.len() == size
+        && proof.S.vec_s_hat.len() == size
+        && proof.S.vec_s_tilde.len() == size
+}
+
+/// the SCALE encodings of the items in sorted order (i.e. the items as a multiset)
+fn sorted_encodings<'a, E: Encode + 'a>(
+    items: impl Iterator<Item = &'a E>,
+) -> Vec<Vec<u8>> {
+    let mut encodings: Vec<Vec<u8>> = items.map(|item| item.encode()).collect();
+    encodings.sort();
+    encodings
+}
+
+/// the decrypted shares of every sealer of the vote, if every sealer submitted its shares
+fn partial_decryptions<A: PartialEq>(
+    record: &ElectionRecord<A>,
+    topic: &TopicTranscript<A>,
+    nr_of_ciphers: usize,
+) -> Option<Vec<Vec<BigUint>>> {
+    let params: ElGamalParams = record.vote.params.clone().into();
+    record
+        .sealers
+        .iter()
+        .map(|sealer| {
+            let (_, shares) = topic
+                .decrypted_shares
+                .iter()
+                .find(|(account, _)| account == sealer)?;
+            if shares.is_empty() || shares.len() != nr_of_ciphers {
+                return None;
+            }
+            shares.iter().map(|s| params.decode_element(s)).collect()
+        })
+        .collect()
+}
+
+/// recomputes the results of a homomorphically tallied topic (see `tally_encrypted_sum`)
+fn recompute_sum<A: PartialEq>(
+    record: &ElectionRecord<A>,
+    topic: &TopicTranscript<A>,
+    sum: &Cipher,
+) -> Option<TopicResult> {
+    let params: ElGamalParams = record.vote.params.clone().into();
+    let big_p: BigUint = params.p.clone();
+    let big_g: BigUint = params.g.clone();
+
+    // the summed weight of all answers of the topic
+    let summed_weight: u64 = record
+        .transcript
+        .ballots
+        .iter()
+        .map(|(voter, ballot)| {
+            let answers = ballot
+                .answers
+                .iter()
+                .filter(|(id, _)| *id == topic.topic_id)
+                .count() as u64;
+            answers.saturating_mul(voter_weight(record, voter))
+        })
+        .fold(0u64, |summed, weight| summed.saturating_add(weight));
+
+    let sum: BigCipher = sum.decode_canonical(&params)?;
+    let partials: Vec<Vec<BigUint>> = partial_decryptions(record, topic, 1)?;
+    let encoded_sum: BigUint = decrypt_plaintexts(&[sum], partials, &big_p).pop()?;
+    let yes: u64 = decode_sum(&encoded_sum, &big_g, &big_p, summed_weight)?;
+    Some(sum_results(
+        definition_of(record, &topic.topic_id),
+        summed_weight,
+        yes,
+    ))
+}
+
+/// recomputes the results of a topic from its decrypted ciphers (see `combine_shares_and_tally_topic`).
+/// whether the votes were exponentially encoded is not part of the record,
+/// therefore, the tally must either match the plaintexts or the decoded plaintexts.
+fn recompute_tally<A: PartialEq>(
+    record: &ElectionRecord<A>,
+    topic: &TopicTranscript<A>,
+    tally: &TopicResult,
+) -> Option<TopicResult> {
+    let big_p: BigUint = BigUint::from_bytes_be(&record.vote.params.p);
+    let big_g: BigUint = BigUint::from_bytes_be(&record.vote.params.g);
+    let definition: Option<&TopicDefinition> = definition_of(record, &topic.topic_id);
+
+    // all sealers decrypt the ciphers of the same shuffle iteration
+    let (_, nr_of_shuffles, _) = topic.decryption_proofs.first()?;
+    let plaintexts: Vec<BigUint> = decrypt_topic(record, topic, *nr_of_shuffles)?;
+
+    let width: usize = answer_width_of(definition, &big_p);
+    if plaintexts.len() % width != 0 {
+        return None;
+    }
+    let nr_of_answers: usize = plaintexts.len() / width;
+
+    // the decrypted weights are at the same position as the answers (ballot rows)
+    let weights: Vec<VoterWeight> = if record.weighted {
+        let weight_topic =
+            topic_transcript(record, &weight_topic_id(&record.transcript.vote_id))?;
+        let encoded: Vec<BigUint> = decrypt_topic(record, weight_topic, *nr_of_shuffles)?;
+        let registered = record.voter_weights.iter().map(|(_, weight)| *weight);
+        decode_weights(&encoded, registered, &big_g, &big_p)?
+    } else {
+        vec![1; nr_of_answers]
+    };
+    if weights.len() != nr_of_answers {
+        return None;
+    }
+
+    let (results, _, _) =
+        count_plaintexts(definition, &plaintexts, &weights, width, &big_p);
+    if results == *tally || definition.and_then(write_in_length).is_some() {
+        return Some(results);
+    }
+
+    // the votes were exponentially encoded: decode them up to the largest countable plaintext
+    let max: u64 = max_plaintext(definition, tally);
+    let decoded: Vec<BigUint> = decode_plaintexts(&plaintexts, &big_g, &big_p, max);
+    let (results, _, _) = count_plaintexts(definition, &decoded, &weights, width, &big_p);
+    Some(results)
+}
+
+/// decrypts the ciphers of a topic after the given number of shuffles
+fn decrypt_topic<A: PartialEq>(
+    record: &ElectionRecord<A>,
+    topic: &TopicTranscript<A>,
+    nr_of_shuffles: NrOfShuffles,
+) -> Option<Vec<BigUint>> {
+    let params: ElGamalParams = record.vote.params.clone().into();
+    let ciphers: Vec<Cipher> = ciphers_at(topic, nr_of_shuffles);
+    let partials: Vec<Vec<BigUint>> = partial_decryptions(record, topic, ciphers.len())?;
+    let big_ciphers: Vec<BigCipher> = ciphers.decode_canonical(&params)?;
+    Some(decrypt_plaintexts(&big_ciphers, partials, &params.p))
+}
+
+/// the largest plaintext which can be counted for a topic: the largest valid selection
+/// of a declared topic, otherwise the largest plaintext of the tally
+fn max_plaintext(definition: Option<&TopicDefinition>, tally: &TopicResult) -> u64 {
+    match definition {
+        Some(definition) => {
+            let n: u32 = definition.options.len() as u32;
+            match definition.encoding {
+                OptionEncoding::Index => (n as u64).saturating_sub(1),
+                OptionEncoding::Bitmask => {
+                    1u64.checked_shl(n).map_or(u64::MAX, |max| max - 1)
+                }
+                OptionEncoding::Ranking => n as u64,
+                OptionEncoding::Text => 0,
+            }
+        }
+        None => tally
+            .keys()
+            .filter_map(|plaintext| BigUint::from_bytes_be(plaintext).to_u64())
+            .max()
+            .unwrap_or(0),
+    }
+}
+
+/// decodes exponentially encoded plaintexts g^m (mod p) with m <= max.
+/// a plaintext which cannot be decoded is mapped to max + 1, which is never counted as valid.
+fn decode_plaintexts(
+    plaintexts: &[BigUint],
+    g: &BigUint,
+    p: &BigUint,
+    max: u64,
+) -> Vec<BigUint> {
+    let mut remaining: BTreeSet<&BigUint> = plaintexts.iter().collect();
+    let mut decoded: BTreeMap<&BigUint, u64> = BTreeMap::new();
+
+    let mut encoded: BigUint = BigUint::one();
+    for m in 0..=max {
+        if remaining.is_empty() {
+            break;
+        }
+        if let Some(plaintext) = remaining.take(&encoded) {
+            decoded.insert(plaintext, m);
+        }
+        encoded = encoded.modmul(g, p);
+    }
+
+    let undecodable: BigUint = BigUint::from(max) + 1u32;
+    plaintexts
+        .iter()
+        .map(|plaintext| {
+            decoded
+                .get(plaintext)
+                .map(|m| BigUint::from(*m))
+                .unwrap_or_else(|| undecodable.clone())
+        })
+        .collect()
+}
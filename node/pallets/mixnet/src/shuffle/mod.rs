@@ -3,15 +3,27 @@ pub mod shuffle;
 pub mod verifier;
 
 use crate::{
-    helpers::{array::get_slice, params::get_public_key},
+    dkg::{weights::weight_topic_id, write_in::answer_width},
+    helpers::{
+        array::get_slice,
+        ballot::ballot_row_width,
+        params::{get_proof_version, get_public_key},
+    },
     types::{
-        Cipher, NrOfShuffles, PublicKey as SubstratePK, ShufflePayload, ShuffleProof,
-        ShuffleState, TopicId, VoteId, Wrapper,
+        BallotRow, Canonical, Cipher, NrOfShuffles, PublicKey as SubstratePK,
+        RowShufflePayload, RowShuffleProof, ShufflePayload, ShuffleProof, ShuffleState,
+        Topic, TopicId, VoteId,
     },
 };
-use crate::{Ciphers, Error, Module, ShuffleProofs, ShuffleStateStore, Trait};
+use crate::{
+    BallotRows, BallotShuffleProofs, BallotShuffleStateStore, Ciphers, Error, Module,
+    ShuffleProofs, ShuffleStateStore, Topics, Trait, WeightedVotes,
+};
 use alloc::vec::Vec;
-use crypto::types::{Cipher as BigCipher, PublicKey as ElGamalPK};
+use crypto::{
+    helper::ProofContext,
+    types::{Cipher as BigCipher, PublicKey as ElGamalPK},
+};
 use frame_support::{
     ensure,
     storage::{StorageDoubleMap, StorageMap},
@@ -25,7 +37,6 @@ impl<T: Trait> Module<T> {
         topic_id: &TopicId,
         payload: ShufflePayload,
     ) -> Result<(), Error<T>> {
-        let proof: ShuffleProof = payload.proof.clone().into();
         let shuffled_ciphers: Vec<Cipher> = payload.ciphers.clone();
         let iteration: NrOfShuffles = payload.iteration;
         let start_position: u64 = payload.start_position;
@@ -70,21 +81,31 @@ impl<T: Trait> Module<T> {
         let pk: ElGamalPK = pk.into();
 
         // type conversion: Vec<Cipher> (Vec<Vec<u8>>) to Vec<BigCipher> (Vec<BigUint>)
-        let big_ciphers: Vec<BigCipher> = Wrapper(ciphers).into();
-        let big_shuffled_ciphers: Vec<BigCipher> =
-            Wrapper(shuffled_ciphers.clone()).into();
+        // only canonically encoded shuffled ciphers are accepted
+        let big_ciphers: Vec<BigCipher> = ciphers
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
+        let big_shuffled_ciphers: Vec<BigCipher> = shuffled_ciphers
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
+        let proof: ShuffleProof = payload
+            .proof
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
 
         // get the required range of ciphers
         let slice: Vec<BigCipher> =
             get_slice::<T, BigCipher>(big_ciphers, start_position, batch_size);
 
         // verify the shuffle proof
+        let context = ProofContext::with_version(vote_id, get_proof_version(vote_id));
         let is_proof_valid = Self::verify_shuffle_proof(
             &topic_id,
             proof,
             slice,
             big_shuffled_ciphers,
             &pk,
+            &context,
         )?;
         ensure!(is_proof_valid, Error::<T>::ShuffleProofVerifcationFailed);
 
@@ -113,7 +134,143 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
-    fn compute_next_shuffle_state(
+    pub fn verify_proof_store_shuffled_ballots(
+        vote_id: &VoteId,
+        payload: RowShufflePayload,
+    ) -> Result<(), Error<T>> {
+        let shuffled_rows: Vec<BallotRow> = payload.rows.clone();
+        let iteration: NrOfShuffles = payload.iteration;
+        let start_position: u64 = payload.start_position;
+        let batch_size: u64 = payload.batch_size;
+
+        // get the shuffle state of the ballot rows
+        let shuffle_state: ShuffleState = BallotShuffleStateStore::get(vote_id)
+            .ok_or(Error::<T>::BallotShufflingNotEnabled)?;
+
+        if shuffle_state.done {
+            return Err(Error::<T>::ShuffleAlreadyCompleted);
+        }
+
+        // get all ballot rows for the # of shuffles already performed (iteration)
+        let rows: Vec<BallotRow> = BallotRows::get(vote_id, iteration);
+        let total_rows = rows.len();
+        if rows.is_empty() {
+            return Err(Error::<T>::NrOfShufflesDoesNotExist);
+        }
+
+        // check prerequisites (see verify_proof_store_shuffled_ciphers)
+        if shuffle_state.iteration != iteration
+            || shuffle_state.start_position != start_position
+            || shuffle_state.batch_size != batch_size
+            || shuffled_rows.len() > shuffle_state.batch_size as usize
+        {
+            return Err(Error::<T>::ShuffleStateIncorrect);
+        }
+
+        // get the required range of rows
+        let slice: Vec<BallotRow> =
+            get_slice::<T, BallotRow>(rows, start_position, batch_size);
+
+        // all rows must have the width of the vote's ballots
+        let width = ballot_row_width::<T>(vote_id)?;
+        ensure!(
+            slice.len() == shuffled_rows.len()
+                && slice
+                    .iter()
+                    .chain(shuffled_rows.iter())
+                    .all(|row| row.len() == width),
+            Error::<T>::InvalidBallotRow
+        );
+
+        // get the public key for the vote
+        let pk: SubstratePK = get_public_key::<T>(vote_id)?;
+        let pk: ElGamalPK = pk.into();
+
+        // type conversion: Vec<BallotRow> to Vec<Vec<BigCipher>>
+        // only canonically encoded shuffled rows are accepted
+        let big_rows: Vec<Vec<BigCipher>> = slice
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
+        let big_shuffled_rows: Vec<Vec<BigCipher>> = shuffled_rows
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
+        let proof: RowShuffleProof = payload
+            .proof
+            .decode_canonical(&pk.params)
+            .ok_or(Error::<T>::NonCanonicalEncoding)?;
+
+        // verify the shuffle proof
+        let context = ProofContext::with_version(vote_id, get_proof_version(vote_id));
+        let is_proof_valid = Self::verify_row_shuffle_proof(
+            vote_id,
+            proof,
+            big_rows,
+            big_shuffled_rows,
+            &pk,
+            &context,
+        )?;
+        ensure!(is_proof_valid, Error::<T>::ShuffleProofVerifcationFailed);
+
+        // store the shuffled rows with the new increased shuffle iteration
+        let next_iteration = iteration + 1;
+        let mut already_shuffled: Vec<BallotRow> =
+            BallotRows::get(vote_id, next_iteration);
+        already_shuffled.extend(shuffled_rows.into_iter());
+        BallotRows::insert(vote_id, next_iteration, already_shuffled);
+
+        // store the shuffle proof payload for verification (audit trail)
+        let mut shuffle_proofs: Vec<RowShufflePayload> =
+            BallotShuffleProofs::get(vote_id);
+        shuffle_proofs.push(payload);
+        BallotShuffleProofs::insert(vote_id, shuffle_proofs);
+
+        // compute and update the new shuffle state
+        let new_state: ShuffleState = Self::compute_next_shuffle_state(
+            start_position,
+            batch_size,
+            total_rows,
+            iteration,
+        );
+        if new_state.done {
+            Self::store_shuffled_ballot_columns(vote_id, &new_state)?;
+        }
+        BallotShuffleStateStore::insert(vote_id, new_state);
+        Ok(())
+    }
+
+    /// stores the columns of topic i of the shuffled ballot rows as the shuffled ciphers
+    /// of topic i and completes the shuffle of each topic. the decryption and the tally are
+    /// performed per topic, the ciphers at the same position belong to the same ballot.
+    /// a write-in answer spans several columns, its ciphers are stored consecutively.
+    /// the last column of a weighted vote contains the encrypted weights (see `weight_topic_id`).
+    fn store_shuffled_ballot_columns(
+        vote_id: &VoteId,
+        state: &ShuffleState,
+    ) -> Result<(), Error<T>> {
+        let rows: Vec<BallotRow> = BallotRows::get(vote_id, state.iteration);
+        let topics: Vec<Topic> = Topics::get(vote_id);
+
+        let mut column: usize = 0;
+        for (topic_id, _) in topics.iter() {
+            let width: usize = answer_width::<T>(vote_id, topic_id)?;
+            let ciphers: Vec<Cipher> = rows
+                .iter()
+                .flat_map(|row| row[column..column + width].iter().cloned())
+                .collect();
+            Ciphers::insert(topic_id, state.iteration, ciphers);
+            ShuffleStateStore::insert((vote_id, topic_id), state.clone());
+            column += width;
+        }
+
+        if WeightedVotes::get(vote_id) {
+            let weights: Vec<Cipher> =
+                rows.iter().map(|row| row[column].clone()).collect();
+            Ciphers::insert(weight_topic_id(vote_id), state.iteration, weights);
+        }
+        Ok(())
+    }
+
+    pub fn compute_next_shuffle_state(
         start_position: u64,
         batch_size: u64,
         nr_ciphers: usize,
@@ -1,8 +1,15 @@
-use crate::{types::BigS, types::ShuffleProof as Proof, Error, Module, Trait};
+use crate::{
+    types::{BigRowS, BigS, RowShuffleProof as RowProof, ShuffleProof as Proof},
+    Error, Module, Trait,
+};
 use crypto::{
-    helper::Helper,
+    helper::{Helper, ProofContext},
+    parallel,
     proofs::shuffle::ShuffleProof,
-    types::{BigT, BigY, Cipher as BigCipher, ModuloOperations, PublicKey},
+    types::{
+        BigT, BigTRows, BigY, BigYRows, Cipher as BigCipher, ModuloOperations, PublicKey,
+        Secret,
+    },
 };
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
@@ -20,9 +27,10 @@ impl<T: Trait> Module<T> {
         id: &Vec<u8>, // topicId (vote question)
         encryptions: Vec<BigCipher>,
         shuffled_encryptions: Vec<BigCipher>,
-        re_encryption_randoms: Vec<BigUint>,
+        re_encryption_randoms: Vec<Secret>,
         permutation: &[usize],
         pk: &PublicKey,
+        context: &ProofContext,
     ) -> Result<Proof, Error<T>> {
         // input checks
         assert!(
@@ -70,6 +78,7 @@ impl<T: Trait> Module<T> {
             e_tilde.clone(),
             vec_c.clone(),
             pk,
+            context,
         );
 
         // permute the challenges -> same order as randoms + permuation
@@ -86,41 +95,50 @@ impl<T: Trait> Module<T> {
         let vec_r_hat = commitment_chain.randoms;
 
         // generate t & w values
-        let (t1, t2, t3, (t4_1, t4_2), vec_t_hat, w1, w2, w3, w4, vec_w_hat, vec_w_tilde) =
+        let (t1, t2, t3, vec_t_hat, w1, w2, w3, vec_w_hat, vec_w_tilde) =
             Self::generate_t_and_w_values(
                 vec_r_hat.clone(),
                 u_tilde.clone(),
                 vec_h,
-                e_tilde.clone(),
                 pk,
                 size,
             )?;
+        let w4 = Self::get_random_biguint_less_than(q)?;
+        let (t4_1, t4_2) = Self::generate_t4_values(&w4, &vec_w_tilde, &e_tilde, pk)?;
 
         // generate challenge from (y, t)
         // public value y = ((e, e_tilde, vec_c, vec_c_hat, public_key) -> public_key = component h of pk
         // public commitment t = (t1, t2, t3, (t4_1, t4_2), (t_hat_0, ..., t_hat_(size-1)))
         let public_value: BigY = (e, e_tilde, vec_c.clone(), vec_c_hat.clone(), &pk.h);
         let public_commitment: BigT = (t1, t2, t3, t4_1, t4_2, vec_t_hat);
-        let challenge = ShuffleProof::get_challenge(public_value, public_commitment, q);
+        let challenge =
+            ShuffleProof::get_challenge(public_value, public_commitment, params, context);
 
         // generate s values
         // s = (s1, s2, s3, s4, (s_hat_0, ..., s_hat_(size-1)), (s_tilde_0, ..., s_tilde_(size-1)))
-        let s = Self::generate_s_values(
+        let s4 = Self::generate_s4_value(&challenge, q, &w4, &vec_r_tilde, &vec_u);
+        let (s1, s2, s3, vec_s_hat, vec_s_tilde) = Self::generate_s_values(
             &challenge,
             q,
             vec_r,
             vec_r_hat,
-            vec_r_tilde,
             w1,
             w2,
             w3,
-            w4,
             vec_w_hat,
             vec_w_tilde,
             vec_u,
             u_tilde,
             size,
         );
+        let s = BigS {
+            s1,
+            s2,
+            s3,
+            s4,
+            vec_s_hat,
+            vec_s_tilde,
+        };
         // return (challenge, s, permutation_commitments, chain_commitments)
         let proof: Proof = Proof {
             challenge,
@@ -131,31 +149,185 @@ impl<T: Trait> Module<T> {
         Ok(proof)
     }
 
+    /// GenShuffleProof Algorithm 8.47 (CHVoteSpec 3.1) for rows of ciphers (width w)
+    ///
+    /// Generates a shuffle proof relative to rows of encryptions e and e~.
+    /// All rows are permuted with the same permutation, therefore, the commitment
+    /// to the permutation and the commitment chain are shared by all columns.
+    /// Only the re-encryption part (t4, s4) is computed per column.
+    pub fn generate_row_shuffle_proof(
+        id: &Vec<u8>, // voteId
+        rows: Vec<Vec<BigCipher>>,
+        shuffled_rows: Vec<Vec<BigCipher>>,
+        re_encryption_randoms: Vec<Vec<Secret>>,
+        permutation: &[usize],
+        pk: &PublicKey,
+        context: &ProofContext,
+    ) -> Result<RowProof, Error<T>> {
+        // input checks
+        assert!(
+            rows.len() == shuffled_rows.len(),
+            "rows and shuffled_rows need to have the same length!"
+        );
+        assert!(
+            rows.len() == re_encryption_randoms.len(),
+            "rows and re_encryption_randoms need to have the same length!"
+        );
+        assert!(
+            rows.len() == permutation.len(),
+            "rows and permutation need to have the same length!"
+        );
+        assert!(!rows.is_empty(), "vectors cannot be empty!");
+
+        // the size of the shuffle (# of rows) and the width of the rows
+        let size = rows.len();
+        let width = rows[0].len();
+        let params = &pk.params;
+        let p = &params.p;
+        let q = &params.q();
+
+        // get {size} independent generators: h
+        let vec_h = Helper::get_generators(id, p, size);
+
+        // commit to the given permutation: (vec_c, vec_r)
+        let randoms: Vec<BigUint> = Self::get_random_biguints_less_than(q, size)?;
+        let permutation_commitment = ShuffleProof::generate_permutation_commitment(
+            params,
+            permutation,
+            randoms,
+            vec_h.clone(),
+        );
+        let vec_c = permutation_commitment.commitments;
+        let vec_r = permutation_commitment.randoms;
+
+        // get {size} challenges (one per row)
+        let vec_u = ShuffleProof::get_challenges_rows(
+            size,
+            rows.clone(),
+            shuffled_rows.clone(),
+            vec_c.clone(),
+            pk,
+            context,
+        );
+        let u_tilde = Self::permute_vector(vec_u.clone(), permutation);
+
+        // generate commitment chain: (vec_c_hat, vec_r_hat)
+        let randoms: Vec<BigUint> = Self::get_random_biguints_less_than(q, size)?;
+        let commitment_chain =
+            ShuffleProof::generate_commitment_chain(u_tilde.clone(), randoms, params);
+        let vec_c_hat = commitment_chain.commitments;
+        let vec_r_hat = commitment_chain.randoms;
+
+        // generate t & w values
+        let (t1, t2, t3, vec_t_hat, w1, w2, w3, vec_w_hat, vec_w_tilde) =
+            Self::generate_t_and_w_values(
+                vec_r_hat.clone(),
+                u_tilde.clone(),
+                vec_h,
+                pk,
+                size,
+            )?;
+
+        // generate (t4_1, t4_2) per column
+        let vec_w4: Vec<BigUint> = Self::get_random_biguints_less_than(q, width)?;
+        let mut vec_t4: Vec<(BigUint, BigUint)> = Vec::with_capacity(width);
+        for (column, w4) in vec_w4.iter().enumerate() {
+            let e_tilde_column: Vec<BigCipher> = shuffled_rows
+                .iter()
+                .map(|row| row[column].clone())
+                .collect();
+            vec_t4.push(Self::generate_t4_values(
+                w4,
+                &vec_w_tilde,
+                &e_tilde_column,
+                pk,
+            )?);
+        }
+
+        // generate challenge from (y, t)
+        let public_value: BigYRows =
+            (rows, shuffled_rows, vec_c.clone(), vec_c_hat.clone(), &pk.h);
+        let public_commitment: BigTRows = (t1, t2, t3, vec_t4, vec_t_hat);
+        let challenge = ShuffleProof::get_challenge_rows(
+            public_value,
+            public_commitment,
+            params,
+            context,
+        );
+
+        // generate s4 per column
+        let vec_s4: Vec<BigUint> = vec_w4
+            .iter()
+            .enumerate()
+            .map(|(column, w4)| {
+                let vec_r_tilde_column: Vec<Secret> = re_encryption_randoms
+                    .iter()
+                    .map(|randoms| randoms[column].clone())
+                    .collect();
+                Self::generate_s4_value(&challenge, q, w4, &vec_r_tilde_column, &vec_u)
+            })
+            .collect();
+
+        // generate the remaining s values
+        let (s1, s2, s3, vec_s_hat, vec_s_tilde) = Self::generate_s_values(
+            &challenge,
+            q,
+            vec_r,
+            vec_r_hat,
+            w1,
+            w2,
+            w3,
+            vec_w_hat,
+            vec_w_tilde,
+            vec_u,
+            u_tilde,
+            size,
+        );
+        let s = BigRowS {
+            s1,
+            s2,
+            s3,
+            vec_s4,
+            vec_s_hat,
+            vec_s_tilde,
+        };
+        Ok(RowProof {
+            challenge,
+            S: s,
+            permutation_commitments: vec_c,
+            permutation_chain_commitments: vec_c_hat,
+        })
+    }
+
     fn generate_s_values(
         challenge: &BigUint,
         q: &BigUint,
         vec_r: Vec<BigUint>,
         vec_r_hat: Vec<BigUint>,
-        vec_r_tilde: Vec<BigUint>,
         w1: BigUint,
         w2: BigUint,
         w3: BigUint,
-        w4: BigUint,
         vec_w_hat: Vec<BigUint>,
         vec_w_tilde: Vec<BigUint>,
         vec_u: Vec<BigUint>,
         u_tilde: Vec<BigUint>,
         size: usize,
-    ) -> BigS {
+    ) -> (
+        BigUint,      // s1
+        BigUint,      // s2
+        BigUint,      // s3
+        Vec<BigUint>, // vec_s_hat
+        Vec<BigUint>, // vec_s_tilde
+    ) {
         // get r_flat
         // Σ(r_i) mod q where r_i are the random values from the permutation commitment
         let r_flat = vec_r
             .iter()
-            .fold(BigUint::zero(), |sum, r| sum.modadd(r, q));
+            .fold(BigUint::zero(), |sum, r| sum.modadd_secret(r, q));
 
         // get s1 = (w1 - challenge * r_flat) % q
         // we add q to w1 to ensure the value will always be >0
-        let s1 = w1.modsub(&challenge.modmul(&r_flat, q), q);
+        let s1 = w1.modsub_secret(&challenge.modmul_secret(&r_flat, q), q);
 
         // generate v values from (N-1...0)
         // start with value v_(n-1) = 1
@@ -170,7 +342,7 @@ impl<T: Trait> Module<T> {
             let u_tilde_i = &u_tilde[i + 1];
 
             // v_(i-1) = u_tilde_i * v_i mod q
-            v_i = u_tilde_i.modmul(&v_i, q);
+            v_i = u_tilde_i.modmul_secret(&v_i, q);
             v.push(v_i.clone());
         }
 
@@ -184,7 +356,7 @@ impl<T: Trait> Module<T> {
 
         // we add q to w2 to ensure the value will always be >0
         // s2 = w2 - challenge * r_hat % q
-        let s2 = w2.modsub(&challenge.modmul(&r_hat, q), q);
+        let s2 = w2.modsub_secret(&challenge.modmul_secret(&r_hat, q), q);
 
         // vec_r -> random values of permutation commitment
         // get r = Σ(vec_r_i * u_i) mod q
@@ -192,14 +364,7 @@ impl<T: Trait> Module<T> {
 
         // we add q to w3 to ensure the value will always be >0
         // s3 = w3 - challenge * r % q
-        let s3 = w3.modsub(&challenge.modmul(&r, q), q);
-
-        // vec_r_tilde -> random values of re-encryption
-        // get r_tilde
-        let r_tilde = Self::zip_vectors_sum_products(&vec_r_tilde, &vec_u, q);
-
-        // we add q to w4 to ensure the value will always be >0
-        let s4 = w4.modsub(&challenge.modmul(&r_tilde, q), q);
+        let s3 = w3.modsub_secret(&challenge.modmul_secret(&r, q), q);
 
         // generate vec_s_hat & vec_s_tilde values
         let mut vec_s_hat = Vec::new();
@@ -210,8 +375,8 @@ impl<T: Trait> Module<T> {
 
             // s_hat_i = w_hat_i - challenge * r_hat_i mod q
             // we add q to w_hat_i to ensure the value will always be >0
-            let c_r_hat_i = challenge.modmul(r_hat_i, q);
-            let s_hat_i = w_hat_i.modsub(&c_r_hat_i, q);
+            let c_r_hat_i = challenge.modmul_secret(r_hat_i, q);
+            let s_hat_i = w_hat_i.modsub_secret(&c_r_hat_i, q);
             vec_s_hat.push(s_hat_i);
 
             let w_tilde_i = &vec_w_tilde[i];
@@ -219,44 +384,59 @@ impl<T: Trait> Module<T> {
 
             // s_tilde_i = w_tilde_i - challenge * u_tilde_i mod q
             // we add q to w_tilde_i to ensure the value will always be >0
-            let c_u_tilde_i = challenge.modmul(u_tilde_i, q);
-            let s_tilde_i = w_tilde_i.modsub(&c_u_tilde_i, q);
+            let c_u_tilde_i = challenge.modmul_secret(u_tilde_i, q);
+            let s_tilde_i = w_tilde_i.modsub_secret(&c_u_tilde_i, q);
             vec_s_tilde.push(s_tilde_i);
         }
-        BigS {
-            s1,
-            s2,
-            s3,
-            s4,
-            vec_s_hat,
-            vec_s_tilde,
-        }
+        (s1, s2, s3, vec_s_hat, vec_s_tilde)
+    }
+
+    /// computes the value s4 of the re-encryption randoms
+    /// (of a single column in case of a shuffle of rows)
+    fn generate_s4_value(
+        challenge: &BigUint,
+        q: &BigUint,
+        w4: &BigUint,
+        vec_r_tilde: &[Secret],
+        vec_u: &[BigUint],
+    ) -> BigUint {
+        // vec_r_tilde -> random values of re-encryption
+        // get r_tilde = Σ(r_tilde_i * u_i) % q
+        assert!(
+            vec_r_tilde.len() == vec_u.len(),
+            "vectors must have the same length!"
+        );
+        let r_tilde = vec_r_tilde.iter().zip(vec_u.iter()).fold(
+            BigUint::zero(),
+            |sum, (r_tilde_i, u_i)| {
+                sum.modadd_secret(&r_tilde_i.expose().modmul_secret(u_i, q), q)
+            },
+        );
+
+        // we add q to w4 to ensure the value will always be >0
+        w4.modsub_secret(&challenge.modmul_secret(&r_tilde, q), q)
     }
 
     fn generate_t_and_w_values(
         r_hat: Vec<BigUint>,
         u_tilde: Vec<BigUint>,
         vec_h: Vec<BigUint>,
-        shuffled_encryptions: Vec<BigCipher>,
         public_key: &PublicKey,
         size: usize,
     ) -> Result<
         (
-            BigUint,            // t1
-            BigUint,            // t2
-            BigUint,            // t3
-            (BigUint, BigUint), // (t4_1, t4_2)
-            Vec<BigUint>,       // vec_t_hat
-            BigUint,            // w1
-            BigUint,            // w2
-            BigUint,            // w3
-            BigUint,            // w4
-            Vec<BigUint>,       // vec_w_hat
-            Vec<BigUint>,       // vec_w_tilde
+            BigUint,      // t1
+            BigUint,      // t2
+            BigUint,      // t3
+            Vec<BigUint>, // vec_t_hat
+            BigUint,      // w1
+            BigUint,      // w2
+            BigUint,      // w3
+            Vec<BigUint>, // vec_w_hat
+            Vec<BigUint>, // vec_w_tilde
         ),
         Error<T>,
     > {
-        let pk = &public_key.h;
         let p = &public_key.params.p;
         let q = &public_key.params.q();
         let g = &public_key.params.g;
@@ -266,8 +446,7 @@ impl<T: Trait> Module<T> {
         let mut r_i_dash: BigUint;
         let mut u_i = BigUint::one();
         let mut u_i_dash: BigUint;
-        let mut t_hat_i: BigUint;
-        let mut vec_t_hat: Vec<BigUint> = Vec::new();
+        let mut exponents: Vec<(BigUint, BigUint)> = Vec::with_capacity(size);
 
         // get random values
         let vec_w_tilde: Vec<BigUint> = Self::get_random_biguints_less_than(q, size)?;
@@ -285,81 +464,82 @@ impl<T: Trait> Module<T> {
             let u_tilde_i = &u_tilde[i];
 
             // r_i_dash = w_hat_i + w_tilde_i * r_(i-1) mod q
-            r_i_dash = w_hat_i.modadd(&w_tilde_i.modmul(&r_i, q), q);
+            r_i_dash = w_hat_i.modadd_secret(&w_tilde_i.modmul_secret(&r_i, q), q);
 
             // r_i = r_hat_i + u_tilde_i * r_(i-1) mod q
-            r_i = r_hat_i.modadd(&u_tilde_i.modmul(&r_i, q), q);
+            r_i = r_hat_i.modadd_secret(&u_tilde_i.modmul_secret(&r_i, q), q);
 
             // u_i_dash = w_tilde_i * u_(i-1) mod q
-            u_i_dash = w_tilde_i.modmul(&u_i, q);
+            u_i_dash = w_tilde_i.modmul_secret(&u_i, q);
 
             // u_i = u_tilde_i * u_(i-1) mod q
-            u_i = u_tilde_i.modmul(&u_i, q);
-
-            // t_hat_i = g^r_i_dash * h_u_i_dash mod p
-            let g_r_i_dash = g.modpow(&r_i_dash, p);
-            let h_u_i_dash = h.modpow(&u_i_dash, p);
-            t_hat_i = g_r_i_dash.modmul(&h_u_i_dash, p);
-            vec_t_hat.push(t_hat_i);
+            u_i = u_tilde_i.modmul_secret(&u_i, q);
+            exponents.push((r_i_dash, u_i_dash));
         }
 
-        // part 2: generate t1, t2, t3 & w1, w2, w3, w4 values
+        // t_hat_i = g^r_i_dash * h_u_i_dash mod p
+        // only the exponents form a chain, the values t_hat_i are computed in parallel
+        // if the feature `parallel` is enabled (native only)
+        let vec_t_hat: Vec<BigUint> =
+            parallel::map(&exponents, |(r_i_dash, u_i_dash)| {
+                let g_r_i_dash = g.modpow_secret(r_i_dash, p);
+                let h_u_i_dash = h.modpow_secret(u_i_dash, p);
+                g_r_i_dash.modmul_secret(&h_u_i_dash, p)
+            });
+
+        // part 2: generate t1, t2, t3 & w1, w2, w3 values
         let w1 = Self::get_random_biguint_less_than(q)?;
         let w2 = Self::get_random_biguint_less_than(q)?;
         let w3 = Self::get_random_biguint_less_than(q)?;
-        let w4 = Self::get_random_biguint_less_than(q)?;
 
-        let t1 = g.modpow(&w1, p);
-        let t2 = g.modpow(&w2, p);
+        let t1 = g.modpow_secret(&w1, p);
+        let t2 = g.modpow_secret(&w2, p);
 
         // t3 = g^w3 * Π(h_i^w_tilde_i) % p
-        let g_pow_w3 = g.modpow(&w3, p);
+        let g_pow_w3 = g.modpow_secret(&w3, p);
 
         // prod = Π(h_i^w_tilde_i) % p
-        let prod = Self::zip_vectors_multiply_a_pow_b(&vec_h, &vec_w_tilde, p);
-        let t3 = g_pow_w3.modmul(&prod, p);
+        let prod = Self::zip_vectors_multiply_a_pow_b_secret(&vec_h, &vec_w_tilde, p);
+        let t3 = g_pow_w3.modmul_secret(&prod, p);
 
-        // chain with shuffled encryptions
-        // generate t4_1, t4_2
+        Ok((t1, t2, t3, vec_t_hat, w1, w2, w3, vec_w_hat, vec_w_tilde))
+    }
+
+    /// chain with shuffled encryptions: generates the values t4_1, t4_2
+    /// (of a single column in case of a shuffle of rows)
+    fn generate_t4_values(
+        w4: &BigUint,
+        vec_w_tilde: &Vec<BigUint>,
+        shuffled_encryptions: &[BigCipher],
+        public_key: &PublicKey,
+    ) -> Result<(BigUint, BigUint), Error<T>> {
+        let pk = &public_key.h;
+        let p = &public_key.params.p;
+        let g = &public_key.params.g;
 
         // g is the first public generator
-        // g^-w4 = (g^-1)^w4 = (g^w4)^-1 = invmod(g^w4)
+        // g^-w4 = (g^-1)^w4 = invmod(g)^w4 mod p
+        // the public generator is inverted, such that w4 is only used as secret exponent
         // for an explanation see: Verifiable Re-Encryption Mixnets (Haenni, Locher, Koenig, Dubuis) page 9
-        let g_pow_w4 = g.modpow(&w4, p);
-        let inv_g_pow_w4 = g_pow_w4.invmod(p).ok_or(Error::InvModError)?;
+        let inv_g = g.invmod(p).ok_or(Error::InvModError)?;
+        let inv_g_pow_w4 = inv_g.modpow_secret(w4, p);
 
-        let vec_a_tilde: Vec<BigUint> = shuffled_encryptions
-            .clone()
-            .into_iter()
-            .map(|c| c.a)
-            .collect();
+        let vec_a_tilde: Vec<BigUint> =
+            shuffled_encryptions.iter().map(|c| c.a.clone()).collect();
         let prod_a_tilde_w_tilde =
-            Self::zip_vectors_multiply_a_pow_b(&vec_a_tilde, &vec_w_tilde, p);
-        let t4_1 = inv_g_pow_w4.modmul(&prod_a_tilde_w_tilde, p);
+            Self::zip_vectors_multiply_a_pow_b_secret(&vec_a_tilde, vec_w_tilde, p);
+        let t4_1 = inv_g_pow_w4.modmul_secret(&prod_a_tilde_w_tilde, p);
 
         // pk is the public key
         // pk^-w4 = (pk^-1)^w4 = invmod(pk)^w4 mod p
         // for an explanation see: Verifiable Re-Encryption Mixnets (Haenni, Locher, Koenig, Dubuis) page 9
         let inv_pk = pk.invmod(p).ok_or(Error::InvModError)?;
-        let inv_pk_pow_w4 = inv_pk.modpow(&w4, p);
+        let inv_pk_pow_w4 = inv_pk.modpow_secret(w4, p);
         let vec_b_tilde: Vec<BigUint> =
-            shuffled_encryptions.into_iter().map(|c| c.b).collect();
+            shuffled_encryptions.iter().map(|c| c.b.clone()).collect();
         let prod_b_tilde_w_tilde =
-            Self::zip_vectors_multiply_a_pow_b(&vec_b_tilde, &vec_w_tilde, p);
-        let t4_2 = inv_pk_pow_w4.modmul(&prod_b_tilde_w_tilde, p);
-
-        Ok((
-            t1,
-            t2,
-            t3,
-            (t4_1, t4_2),
-            vec_t_hat,
-            w1,
-            w2,
-            w3,
-            w4,
-            vec_w_hat,
-            vec_w_tilde,
-        ))
+            Self::zip_vectors_multiply_a_pow_b_secret(&vec_b_tilde, vec_w_tilde, p);
+        let t4_2 = inv_pk_pow_w4.modmul_secret(&prod_b_tilde_w_tilde, p);
+        Ok((t4_1, t4_2))
     }
 }
@@ -1,7 +1,6 @@
 use crate::{Error, Module, Trait};
 use crypto::encryption::ElGamal;
-use crypto::types::{Cipher as BigCipher, PublicKey as ElGamalPK};
-use num_bigint::BigUint;
+use crypto::types::{Cipher as BigCipher, PublicKey as ElGamalPK, Secret};
 use sp_std::vec::Vec;
 
 /// all functions related to ballot operations in the offchain worker
@@ -9,7 +8,7 @@ impl<T: Trait> Module<T> {
     pub fn shuffle_ciphers(
         pk: &ElGamalPK,
         ciphers: Vec<BigCipher>,
-    ) -> Result<(Vec<BigCipher>, Vec<BigUint>, Vec<usize>), Error<T>> {
+    ) -> Result<(Vec<BigCipher>, Vec<Secret>, Vec<usize>), Error<T>> {
         let q = pk.params.q();
         let size = ciphers.len();
 
@@ -22,7 +21,10 @@ impl<T: Trait> Module<T> {
         let permutation: Vec<usize> = Self::generate_permutation(size)?;
 
         // get the random values
-        let randoms: Vec<BigUint> = Self::get_random_biguints_less_than(&q, size)?;
+        let randoms: Vec<Secret> = Self::get_random_biguints_less_than(&q, size)?
+            .into_iter()
+            .map(Secret::new)
+            .collect();
 
         // shuffle the ciphers
         let shuffle = ElGamal::shuffle(&ciphers, &permutation, &randoms, &pk);
@@ -32,4 +34,42 @@ impl<T: Trait> Module<T> {
         // return the shuffled ciphers, randoms, permutation as result
         Ok((shuffled_ciphers, randoms, permutation))
     }
+
+    /// shuffles rows of ciphers (e.g. ballots) using a single permutation.
+    /// returns the shuffled rows, the re-encryption randoms (one row per row) and the permutation.
+    pub fn shuffle_rows(
+        pk: &ElGamalPK,
+        rows: Vec<Vec<BigCipher>>,
+    ) -> Result<(Vec<Vec<BigCipher>>, Vec<Vec<Secret>>, Vec<usize>), Error<T>> {
+        let q = pk.params.q();
+        let size = rows.len();
+
+        // check that there are rows to shuffle
+        if size == 0 {
+            return Err(Error::<T>::ShuffleCiphersSizeZeroError);
+        }
+        let width = rows[0].len();
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return Err(Error::<T>::InvalidBallotRow);
+        }
+
+        // get the permuation or else return error
+        let permutation: Vec<usize> = Self::generate_permutation(size)?;
+
+        // get the random values: one per cipher
+        let randoms: Vec<Secret> = Self::get_random_biguints_less_than(&q, size * width)?
+            .into_iter()
+            .map(Secret::new)
+            .collect();
+        let randoms: Vec<Vec<Secret>> =
+            randoms.chunks(width).map(|chunk| chunk.to_vec()).collect();
+
+        // shuffle the rows
+        let shuffle = ElGamal::shuffle_rows(&rows, &permutation, &randoms, &pk);
+        let shuffled_rows: Vec<Vec<BigCipher>> =
+            shuffle.into_iter().map(|item| item.0).collect();
+
+        // return the shuffled rows, randoms, permutation as result
+        Ok((shuffled_rows, randoms, permutation))
+    }
 }
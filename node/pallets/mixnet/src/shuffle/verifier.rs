@@ -1,9 +1,14 @@
-use crate::{types::BigS, types::ShuffleProof as Proof, Error, Module, Trait};
+use crate::{
+    types::{BigRowS, BigS, RowShuffleProof as RowProof, ShuffleProof as Proof},
+    Error, Module, Trait,
+};
 use crypto::{
-    helper::Helper,
+    helper::{Helper, ProofContext},
+    multi_exp::MultiExp,
     proofs::shuffle::ShuffleProof,
     types::{
-        BigT, BigY, Cipher as BigCipher, ElGamalParams, ModuloOperations, PublicKey,
+        BigT, BigTRows, BigY, BigYRows, Cipher as BigCipher, ElGamalParams,
+        ModuloOperations, PublicKey,
     },
 };
 use num_bigint::BigUint;
@@ -23,6 +28,7 @@ impl<T: Trait> Module<T> {
         encryptions: Vec<BigCipher>,
         shuffled_encryptions: Vec<BigCipher>,
         pk: &PublicKey,
+        context: &ProofContext,
     ) -> Result<bool, Error<T>> {
         let e = encryptions;
         let e_tilde = shuffled_encryptions;
@@ -63,9 +69,7 @@ impl<T: Trait> Module<T> {
         // the size of the shuffle (# of encrypted votes)
         let size = e.len();
         let params = &pk.params;
-        let h = &params.h;
         let p = &params.p;
-        let q = &params.q();
 
         // get {size} independent generators: vec_h
         let vec_h = Helper::get_generators(id, p, size);
@@ -78,39 +82,12 @@ impl<T: Trait> Module<T> {
             e_tilde.clone(),
             vec_c.clone(),
             pk,
+            context,
         );
 
-        // get c_hat_0
-        // h = the 2. public generator
-        let c_hat_0 = &params.h;
-
-        // get c_flat = Π(c_i) / Π(vec_h_i) mod p
-        // vec_c = permutation_commitments
-        // vec_h = public generators
-        let prod_vec_c = vec_c
-            .iter()
-            .fold(BigUint::one(), |prod, c| prod.modmul(c, p));
-        let prod_h = vec_h
-            .iter()
-            .fold(BigUint::one(), |prod, gen| prod.modmul(gen, p));
-        let c_flat = prod_vec_c.moddiv(&prod_h, p).ok_or(Error::DivModError)?;
-
-        // get u = Π(vec_u_i) mod q
-        // vec_u = challenges
-        let u = vec_u
-            .iter()
-            .fold(BigUint::one(), |product, u| product.modmul(u, q));
-
-        // get value c_hat = c_hat_n / h^u mod p
-        // vec_c_hat = permutation_chain_commitments
-        let h_pow_u = h.modpow(&u, p);
-        let c_hat_n = vec_c_hat.get(size - 1).ok_or(Error::InvModError)?;
-        let c_hat = c_hat_n.moddiv(&h_pow_u, p).ok_or(Error::DivModError)?;
-
-        // get value c_tilde = Π(c_i^u_i) mod p
-        // vec_c = permutation_commitments
-        // vec_u = challenges
-        let c_tilde = Self::zip_vectors_multiply_a_pow_b(&vec_c, &vec_u, p);
+        let (c_flat, c_hat, c_tilde) = Self::get_commitment_values_verifier(
+            &vec_h, &vec_u, &vec_c, &vec_c_hat, size, params,
+        )?;
 
         // vec_a = vector of all components a (encryption { a, b })
         // vec_b = vector of all components b (encryption { a, b })
@@ -119,6 +96,10 @@ impl<T: Trait> Module<T> {
         let a_tilde = Self::zip_vectors_multiply_a_pow_b(&vec_a, &vec_u, p);
         let b_tilde = Self::zip_vectors_multiply_a_pow_b(&vec_b, &vec_u, p);
 
+        // get c_hat_0
+        // h = the 2. public generator
+        let c_hat_0 = &params.h;
+
         // generate vec_t_hat values
         let vec_t_hat = Self::get_vec_t_hat_verifier(
             &c_hat_0,
@@ -130,19 +111,24 @@ impl<T: Trait> Module<T> {
             params,
         );
 
-        let (t1, t2, t3, (t4_1, t4_2)) = Self::get_t_values_verifier(
+        let (t1, t2, t3) = Self::get_t_values_verifier(
             &c_flat,
             &c_hat,
             &c_tilde,
             &challenge,
-            &a_tilde,
-            &b_tilde,
-            &e_tilde,
             &vec_h,
             &vec_s_tilde,
             &s1,
             &s2,
             &s3,
+            pk,
+        );
+        let (t4_1, t4_2) = Self::get_t4_values_verifier(
+            &challenge,
+            &a_tilde,
+            &b_tilde,
+            &e_tilde,
+            &vec_s_tilde,
             &s4,
             size,
             pk,
@@ -154,48 +140,234 @@ impl<T: Trait> Module<T> {
         let public_value: BigY = (e, e_tilde, vec_c, vec_c_hat, &pk.h);
         let public_commitment: BigT = (t1, t2, t3, t4_1, t4_2, vec_t_hat);
         let recomputed_challenge =
-            ShuffleProof::get_challenge(public_value, public_commitment, q);
+            ShuffleProof::get_challenge(public_value, public_commitment, params, context);
+
+        let is_proof_valid = recomputed_challenge == challenge;
+        Ok(is_proof_valid)
+    }
+
+    /// CheckShuffleProof Algorithm 8.51 (CHVoteSpec 3.1) for rows of ciphers (width w)
+    ///
+    /// Checks the correctness of a shuffle proof generated by `generate_row_shuffle_proof`.
+    /// The public values are the rows of ElGamal encryptions e and e~ and
+    /// the public encryption key pk.
+    pub fn verify_row_shuffle_proof(
+        id: &Vec<u8>, // voteId
+        proof: RowProof,
+        rows: Vec<Vec<BigCipher>>,
+        shuffled_rows: Vec<Vec<BigCipher>>,
+        pk: &PublicKey,
+        context: &ProofContext,
+    ) -> Result<bool, Error<T>> {
+        let challenge: BigUint = proof.challenge;
+        let s: BigRowS = proof.S;
+        let vec_c: Vec<BigUint> = proof.permutation_commitments;
+        let vec_c_hat: Vec<BigUint> = proof.permutation_chain_commitments;
+        let vec_s_hat: Vec<BigUint> = s.vec_s_hat;
+        let vec_s_tilde: Vec<BigUint> = s.vec_s_tilde;
+        let vec_s4: Vec<BigUint> = s.vec_s4;
+
+        // input checks
+        assert!(
+            rows.len() == shuffled_rows.len(),
+            "rows and shuffled_rows need to have the same length!"
+        );
+        assert!(!rows.is_empty(), "vectors cannot be empty!");
+
+        // the size of the shuffle (# of rows) and the width of the rows
+        let size = rows.len();
+        let width = rows[0].len();
+
+        // the proof is submitted by a sealer, a malformed proof is invalid
+        if vec_c.len() != size
+            || vec_c_hat.len() != size
+            || vec_s_hat.len() != size
+            || vec_s_tilde.len() != size
+            || vec_s4.len() != width
+        {
+            return Ok(false);
+        }
+
+        let params = &pk.params;
+        let p = &params.p;
+
+        // get {size} independent generators: vec_h
+        let vec_h = Helper::get_generators(id, p, size);
+
+        // get {size} challenges (one per row)
+        let vec_u = ShuffleProof::get_challenges_rows(
+            size,
+            rows.clone(),
+            shuffled_rows.clone(),
+            vec_c.clone(),
+            pk,
+            context,
+        );
+
+        let (c_flat, c_hat, c_tilde) = Self::get_commitment_values_verifier(
+            &vec_h, &vec_u, &vec_c, &vec_c_hat, size, params,
+        )?;
+
+        // generate vec_t_hat values, c_hat_0 = h (the 2. public generator)
+        let vec_t_hat = Self::get_vec_t_hat_verifier(
+            &params.h,
+            &challenge,
+            &vec_c_hat,
+            &vec_s_hat,
+            &vec_s_tilde,
+            size,
+            params,
+        );
+
+        let (t1, t2, t3) = Self::get_t_values_verifier(
+            &c_flat,
+            &c_hat,
+            &c_tilde,
+            &challenge,
+            &vec_h,
+            &vec_s_tilde,
+            &s.s1,
+            &s.s2,
+            &s.s3,
+            pk,
+        );
+
+        // compute (t4_1, t4_2) per column
+        let mut vec_t4: Vec<(BigUint, BigUint)> = Vec::with_capacity(width);
+        for (column, s4) in vec_s4.iter().enumerate() {
+            let vec_a: Vec<BigUint> =
+                rows.iter().map(|row| row[column].a.clone()).collect();
+            let vec_b: Vec<BigUint> =
+                rows.iter().map(|row| row[column].b.clone()).collect();
+            let a_tilde = Self::zip_vectors_multiply_a_pow_b(&vec_a, &vec_u, p);
+            let b_tilde = Self::zip_vectors_multiply_a_pow_b(&vec_b, &vec_u, p);
+            let e_tilde_column: Vec<BigCipher> = shuffled_rows
+                .iter()
+                .map(|row| row[column].clone())
+                .collect();
+            vec_t4.push(Self::get_t4_values_verifier(
+                &challenge,
+                &a_tilde,
+                &b_tilde,
+                &e_tilde_column,
+                &vec_s_tilde,
+                s4,
+                size,
+                pk,
+            )?);
+        }
+
+        // recompute the challenge from (y, t)
+        let public_value: BigYRows = (rows, shuffled_rows, vec_c, vec_c_hat, &pk.h);
+        let public_commitment: BigTRows = (t1, t2, t3, vec_t4, vec_t_hat);
+        let recomputed_challenge = ShuffleProof::get_challenge_rows(
+            public_value,
+            public_commitment,
+            params,
+            context,
+        );
 
         let is_proof_valid = recomputed_challenge == challenge;
         Ok(is_proof_valid)
     }
 
+    /// computes the values c_flat, c_hat and c_tilde
+    /// from the permutation commitments and the commitment chain
+    fn get_commitment_values_verifier(
+        vec_h: &Vec<BigUint>,
+        vec_u: &Vec<BigUint>,
+        vec_c: &Vec<BigUint>,
+        vec_c_hat: &Vec<BigUint>,
+        size: usize,
+        params: &ElGamalParams,
+    ) -> Result<(BigUint, BigUint, BigUint), Error<T>> {
+        let h = &params.h;
+        let p = &params.p;
+        let q = &params.q();
+
+        // get c_flat = Π(c_i) / Π(vec_h_i) mod p
+        // vec_c = permutation_commitments
+        // vec_h = public generators
+        let prod_vec_c = vec_c
+            .iter()
+            .fold(BigUint::one(), |prod, c| prod.modmul(c, p));
+        let prod_h = vec_h
+            .iter()
+            .fold(BigUint::one(), |prod, gen| prod.modmul(gen, p));
+        let c_flat = prod_vec_c.moddiv(&prod_h, p).ok_or(Error::DivModError)?;
+
+        // get u = Π(vec_u_i) mod q
+        // vec_u = challenges
+        let u = vec_u
+            .iter()
+            .fold(BigUint::one(), |product, u| product.modmul(u, q));
+
+        // get value c_hat = c_hat_n / h^u mod p
+        // vec_c_hat = permutation_chain_commitments
+        let h_pow_u = h.modpow(&u, p);
+        let c_hat_n = vec_c_hat.get(size - 1).ok_or(Error::InvModError)?;
+        let c_hat = c_hat_n.moddiv(&h_pow_u, p).ok_or(Error::DivModError)?;
+
+        // get value c_tilde = Π(c_i^u_i) mod p
+        // vec_c = permutation_commitments
+        // vec_u = challenges
+        let c_tilde = Self::zip_vectors_multiply_a_pow_b(&vec_c, &vec_u, p);
+        Ok((c_flat, c_hat, c_tilde))
+    }
+
     fn get_t_values_verifier(
         c_flat: &BigUint,
         c_hat: &BigUint,
         c_tilde: &BigUint,
         challenge: &BigUint,
-        a_tilde: &BigUint,
-        b_tilde: &BigUint,
-        e_tilde: &Vec<BigCipher>,
         vec_h: &Vec<BigUint>,
         vec_s_tilde: &Vec<BigUint>,
         s1: &BigUint,
         s2: &BigUint,
         s3: &BigUint,
-        s4: &BigUint,
-        size: usize,
         public_key: &PublicKey,
-    ) -> Result<(BigUint, BigUint, BigUint, (BigUint, BigUint)), Error<T>> {
+    ) -> (BigUint, BigUint, BigUint) {
         let g = &public_key.params.g;
         let p = &public_key.params.p;
-        let pk = &public_key.h;
 
         // get t1 = c_flat^challenge * g^s1 mod p
-        let t1 = c_flat.modpow(challenge, p).modmul(&g.modpow(s1, p), p);
+        let t1 = MultiExp::multi_modpow(
+            &[c_flat.clone(), g.clone()],
+            &[challenge.clone(), s1.clone()],
+            p,
+        );
 
         // get t2 = c_hat^challenge * g^s2 mod p
-        let g_pow_s2 = g.modpow(s2, p);
-        let c_hat_pow_challenge = c_hat.modpow(challenge, p);
-        let t2 = c_hat_pow_challenge.modmul(&g_pow_s2, p);
+        let t2 = MultiExp::multi_modpow(
+            &[c_hat.clone(), g.clone()],
+            &[challenge.clone(), s2.clone()],
+            p,
+        );
 
         // get t3 = c_tilde^challenge * g^s3 * Π(h_i^s_tilde_i) mod p
-        let prod_h_s_tilde = Self::zip_vectors_multiply_a_pow_b(&vec_h, &vec_s_tilde, p);
-        let g_pow_s3 = g.modpow(s3, p);
-        let c_tilde_pow_challenge = c_tilde.modpow(challenge, p);
-        let t3 = c_tilde_pow_challenge
-            .modmul(&g_pow_s3, p)
-            .modmul(&prod_h_s_tilde, p);
+        let mut bases = vec![c_tilde.clone(), g.clone()];
+        bases.extend_from_slice(vec_h);
+        let mut exponents = vec![challenge.clone(), s3.clone()];
+        exponents.extend_from_slice(vec_s_tilde);
+        let t3 = MultiExp::multi_modpow(&bases, &exponents, p);
+        (t1, t2, t3)
+    }
+
+    /// computes the values t4_1, t4_2
+    /// (of a single column in case of a shuffle of rows)
+    fn get_t4_values_verifier(
+        challenge: &BigUint,
+        a_tilde: &BigUint,
+        b_tilde: &BigUint,
+        e_tilde: &[BigCipher],
+        vec_s_tilde: &Vec<BigUint>,
+        s4: &BigUint,
+        size: usize,
+        public_key: &PublicKey,
+    ) -> Result<(BigUint, BigUint), Error<T>> {
+        let g = &public_key.params.g;
+        let p = &public_key.params.p;
+        let pk = &public_key.h;
 
         // we need to swap pk and g
         // since our encryption conatins (a,b) with a = g^r
@@ -208,29 +380,19 @@ impl<T: Trait> Module<T> {
         let mut g_pow_minus_s4 = g.modpow(&s4, p);
         g_pow_minus_s4 = g_pow_minus_s4.invmod(p).ok_or(Error::InvModError)?;
 
-        // compute prod_a = Π(vec_a_tilde_i^s_tilde_i)
-        // compute prod_b = Π(vec_b_tilde_i^s_tilde_i)
-        let mut prod_a = BigUint::one();
-        let mut prod_b = BigUint::one();
-
-        for i in 0..size {
-            // a_tilde_i = component a of entry i in shuffled_encryptions
-            let a_tilde_i = &e_tilde[i].a;
-            // b_tilde_i = component b of entry i in shuffled_encryptions
-            let b_tilde_i = &e_tilde[i].b;
-            let s_tilde_i = &vec_s_tilde[i];
-
-            let a_tilde_i_pow_s_tilde_i = a_tilde_i.modpow(s_tilde_i, p);
-            prod_a = prod_a.modmul(&a_tilde_i_pow_s_tilde_i, p);
-
-            let b_tilde_i_pow_s_tilde_i = b_tilde_i.modpow(s_tilde_i, p);
-            prod_b = prod_b.modmul(&b_tilde_i_pow_s_tilde_i, p);
-        }
+        // compute a_tilde^challenge * Π(vec_a_tilde_i^s_tilde_i)
+        // compute b_tilde^challenge * Π(vec_b_tilde_i^s_tilde_i)
+        let mut exponents = vec![challenge.clone()];
+        exponents.extend_from_slice(&vec_s_tilde[..size]);
+        let mut bases_a = vec![a_tilde.clone()];
+        bases_a.extend(e_tilde[..size].iter().map(|cipher| cipher.a.clone()));
+        let mut bases_b = vec![b_tilde.clone()];
+        bases_b.extend(e_tilde[..size].iter().map(|cipher| cipher.b.clone()));
+        let prod_a = MultiExp::multi_modpow(&bases_a, &exponents, p);
+        let prod_b = MultiExp::multi_modpow(&bases_b, &exponents, p);
 
         // compute t4_1
-        let mut t4_1 = a_tilde.modpow(challenge, p);
-        t4_1 = t4_1.modmul(&g_pow_minus_s4, p);
-        t4_1 = t4_1.modmul(&prod_a, p);
+        let t4_1 = prod_a.modmul(&g_pow_minus_s4, p);
 
         // we need to swap pk and g
         // since our encryption conatins (a,b) with a = g^r
@@ -244,11 +406,9 @@ impl<T: Trait> Module<T> {
         let pk_pow_minus_s4 = pk_pow_s4.invmod(p).ok_or(Error::InvModError)?;
 
         // compute t4_2
-        let mut t4_2 = b_tilde.modpow(challenge, p);
-        t4_2 = t4_2.modmul(&pk_pow_minus_s4, p);
-        t4_2 = t4_2.modmul(&prod_b, p);
+        let t4_2 = prod_b.modmul(&pk_pow_minus_s4, p);
 
-        Ok((t1, t2, t3, (t4_1, t4_2)))
+        Ok((t4_1, t4_2))
     }
 
     fn get_vec_t_hat_verifier(
@@ -274,25 +434,20 @@ impl<T: Trait> Module<T> {
 
         let mut vec_t_hat = Vec::new();
         for i in 0..size {
-            // c_hat_i ^ challenge
-            // i + 1 = the original i in vec_c_hat since the vector was extended above
-            let c_hat_i = vec_c_hat_extended[i + 1];
-            let c_hat_i_pow_challenge = c_hat_i.modpow(challenge, p);
-
-            // g ^ s_hat_i
-            let s_hat_i = &vec_s_hat[i];
-            let g_pow_s_hat_i = g.modpow(&s_hat_i, p);
-
-            // c_hat_(i-1) ^ s_tilde_i
-            let s_tilde_i = &vec_s_tilde[i];
-            let c_hat_i_minus_1 = vec_c_hat_extended[i];
-            let c_hat_i_minus_1_pow_s_tilde_i = c_hat_i_minus_1.modpow(&s_tilde_i, p);
-
             // compute t_hat_i =
             // c_hat_i ^ challenge * g ^ s_hat_i * c_hat_(i-1) ^ s_tilde_i % p
-            let t_hat_i = c_hat_i_pow_challenge
-                .modmul(&g_pow_s_hat_i, p)
-                .modmul(&c_hat_i_minus_1_pow_s_tilde_i, p);
+            // i + 1 = the original i in vec_c_hat since the vector was extended above
+            let bases = [
+                vec_c_hat_extended[i + 1].clone(),
+                g.clone(),
+                vec_c_hat_extended[i].clone(),
+            ];
+            let exponents = [
+                challenge.clone(),
+                vec_s_hat[i].clone(),
+                vec_s_tilde[i].clone(),
+            ];
+            let t_hat_i = MultiExp::multi_modpow(&bases, &exponents, p);
             vec_t_hat.push(t_hat_i);
         }
         assert!(
@@ -1,23 +1,31 @@
+use crate::dkg::ranked::{
+    decode_ranking, decode_ranks, encode_ranking, tally_ranking, MAX_RANKED_OPTIONS,
+};
 use crate::mock::*;
 use crate::types::{
-    Ballot, Cipher, PublicKey as SubstratePK, PublicParameters, ShufflePayload,
-    ShuffleProof as Proof, VotePhase, Wrapper,
+    AuthorityAction, Ballot, Canonical, Cipher, ElectionRecord, OptionEncoding,
+    PublicKey as SubstratePK, PublicParameters, QuestionType, RankingMethod,
+    ShufflePayload, ShuffleProof as Proof, VerificationError, VotePhase,
 };
 use crate::*;
-use codec::Decode;
+use codec::{Decode, Encode};
 use crypto::{
+    encoding::Encoding,
     encryption::ElGamal,
-    helper::Helper,
+    helper::{Helper, ProofContext, ProofVersion},
     proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof},
+    random::Random,
     types::{
         Cipher as BigCipher, ElGamalParams, ModuloOperations, PrivateKey,
-        PublicKey as ElGamalPK,
+        PublicKey as ElGamalPK, Secret,
     },
 };
-use frame_support::{assert_err, assert_ok};
+use frame_support::{assert_err, assert_ok, traits::OnInitialize};
 use hex_literal::hex;
 use num_bigint::BigUint;
-use num_traits::Zero;
+use num_traits::{One, Zero};
+use rand_chacha::{rand_core::SeedableRng, ChaChaRng};
+use sp_runtime::DispatchError;
 use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
 
 const NR_OF_SHUFFLES: u8 = 0;
@@ -69,11 +77,18 @@ fn setup_sealer(
     sealer_id: &[u8],
 ) -> (PublicKeyShare, KeyGenerationProof) {
     // create public key share + proof
-    let r = BigUint::parse_bytes(b"1701411834604692317316873", 10).unwrap();
-    let proof = KeyGenerationProof::generate(params, &sk.x, &pk.h, &r, sealer_id);
+    let r = Secret::new(BigUint::parse_bytes(b"1701411834604692317316873", 10).unwrap());
+    let proof = KeyGenerationProof::generate(
+        params,
+        &sk.x,
+        &pk.h,
+        &r,
+        sealer_id,
+        &ProofContext::new(&vote_id),
+    );
     let pk_share = PublicKeyShare {
-        proof: proof.clone().into(),
-        pk: pk.h.to_bytes_be(),
+        proof: Canonical::encode_canonical(&proof, &pk.params),
+        pk: pk.params.encode_value(&pk.h),
     };
 
     // submit the public key share
@@ -107,8 +122,18 @@ fn setup_vote(params: PublicParameters) -> (Vec<u8>, Vec<u8>) {
     let topic: Topic = (topic_id.clone(), topic_question);
     let topics = vec![topic];
 
-    let vote_created =
-        OffchainModule::create_vote(who, vote_id.clone(), vote_title, params, topics, 2);
+    // use all registered sealers (Bob & Charlie) as sealers of the vote
+    let sealers = OffchainModule::sealers();
+
+    let vote_created = OffchainModule::create_vote(
+        who,
+        vote_id.clone(),
+        vote_title,
+        params,
+        topics,
+        2,
+        sealers,
+    );
     assert_ok!(vote_created);
     set_vote_phase(vote_id.clone(), VotePhase::Voting);
     (vote_id, topic_id)
@@ -154,9 +179,11 @@ fn setup_ciphers(vote_id: &VoteId, topic_id: &TopicId, pk: &ElGamalPK, encoded:
         // i.e. a Substrate representation { a: Vec<u8>, b: Vec<u8> }
         let cipher: Cipher;
         if encoded {
-            cipher = ElGamal::encrypt_encode(&messages[index], &random, pk).into();
+            let big_cipher = ElGamal::encrypt_encode(&messages[index], &random, pk);
+            cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         } else {
-            cipher = ElGamal::encrypt(&messages[index], &random, pk).into();
+            let big_cipher = ElGamal::encrypt(&messages[index], &random, pk);
+            cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         }
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher)];
         let ballot: Ballot = Ballot { answers };
@@ -181,7 +208,9 @@ fn shuffle_proof_test(
 
     // get the encrypted votes
     let big_ciphers_from_chain: Vec<BigCipher> =
-        Wrapper(OffchainModule::ciphers(&topic_id, NR_OF_SHUFFLES)).into();
+        OffchainModule::ciphers(&topic_id, NR_OF_SHUFFLES)
+            .decode_canonical(&pk.params)
+            .unwrap();
     assert!(big_ciphers_from_chain.len() > 0);
 
     // shuffle the votes
@@ -201,6 +230,7 @@ fn shuffle_proof_test(
         re_encryption_randoms,
         permutation,
         &pk,
+        &ProofContext::new(&vote_id),
     );
     let proof: Proof = result.unwrap();
 
@@ -211,6 +241,7 @@ fn shuffle_proof_test(
         big_ciphers_from_chain,
         shuffled_ciphers,
         &pk,
+        &ProofContext::new(&vote_id),
     );
     let is_proof_valid = verification.unwrap();
     is_proof_valid
@@ -293,6 +324,25 @@ fn test_store_public_key_not_a_voting_authority() {
     });
 }
 
+#[test]
+fn test_store_public_key_non_canonical_encoding() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let who = get_voting_authority();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let (_, _, pk) = Helper::setup_md_system();
+
+        // the public key without leading zeros is shorter than the modulus
+        let mut public_key: SubstratePK = pk.into();
+        public_key.h = BigUint::one().to_bytes_be();
+        assert_err!(
+            OffchainModule::store_public_key(who, vote_id.clone(), public_key),
+            Error::<TestRuntime>::NonCanonicalEncoding
+        );
+        assert!(OffchainModule::public_key(vote_id).is_none());
+    });
+}
+
 #[test]
 fn test_fetch_public_key_does_not_exist() {
     let (mut t, _, _) = ExternalityBuilder::build();
@@ -330,7 +380,8 @@ fn test_create_vote_not_a_voting_authority() {
                 vote_title,
                 params.into(),
                 topics,
-                2
+                2,
+                OffchainModule::sealers()
             ),
             Error::<TestRuntime>::NotAVotingAuthority
         )
@@ -356,13 +407,151 @@ fn test_create_vote_works() {
 
         let vote_created = OffchainModule::create_vote(
             who,
-            vote_id,
+            vote_id.clone(),
             vote_title,
             params.into(),
             topics,
             2,
+            OffchainModule::sealers(),
         );
         assert_ok!(vote_created);
+        assert_eq!(
+            OffchainModule::vote_sealers(vote_id),
+            OffchainModule::sealers()
+        );
+    });
+}
+
+#[test]
+fn test_create_vote_with_subset_of_sealers() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let who = get_voting_authority();
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let vote_title = "Popular Vote of 12.12.2020".as_bytes().to_vec();
+        let topic: Topic = (
+            "20201212-01".as_bytes().to_vec(),
+            "Moritz for President?".as_bytes().to_vec(),
+        );
+
+        // only Bob takes part in this vote
+        let (bob, bob_account, bob_sealer_id) = get_sealer_bob();
+        assert_ok!(OffchainModule::create_vote(
+            who,
+            vote_id.clone(),
+            vote_title,
+            params.clone().into(),
+            vec![topic],
+            2,
+            vec![bob_account]
+        ));
+        assert_eq!(OffchainModule::vote_sealers(&vote_id), vec![bob_account]);
+
+        // Bob can submit a public key share
+        setup_sealer(&params, &sk, &pk, bob, &vote_id, &bob_sealer_id);
+
+        // Bob's share is the public key of the vote
+        assert_ok!(OffchainModule::combine_public_key_shares(
+            get_voting_authority(),
+            vote_id.clone()
+        ));
+        let pk_from_chain: ElGamalPK =
+            OffchainModule::public_key(&vote_id).unwrap().into();
+        assert_eq!(pk_from_chain, pk);
+
+        // Charlie is a registered sealer, but not a sealer of this vote
+        let (charlie, _, charlie_sealer_id) = get_sealer_charlie();
+        let r =
+            Secret::new(BigUint::parse_bytes(b"1701411834604692317316873", 10).unwrap());
+        let proof = KeyGenerationProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            &charlie_sealer_id,
+            &ProofContext::new(&vote_id),
+        );
+        let pk_share = PublicKeyShare {
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
+        };
+        assert_err!(
+            OffchainModule::store_public_key_share(charlie, vote_id, pk_share),
+            Error::<TestRuntime>::NotASealer
+        );
+    });
+}
+
+#[test]
+fn test_create_vote_no_sealers() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let who = get_voting_authority();
+        let (params, _, _) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let vote_title = "Popular Vote of 12.12.2020".as_bytes().to_vec();
+        let topic: Topic = (
+            "20201212-01".as_bytes().to_vec(),
+            "Moritz for President?".as_bytes().to_vec(),
+        );
+
+        assert_err!(
+            OffchainModule::create_vote(
+                who,
+                vote_id,
+                vote_title,
+                params.into(),
+                vec![topic],
+                2,
+                Vec::new()
+            ),
+            Error::<TestRuntime>::NoSealersForVote
+        );
+    });
+}
+
+#[test]
+fn test_create_vote_sealer_not_registered() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let who = get_voting_authority();
+        let (params, _, _) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let vote_title = "Popular Vote of 12.12.2020".as_bytes().to_vec();
+        let topic: Topic = (
+            "20201212-01".as_bytes().to_vec(),
+            "Moritz for President?".as_bytes().to_vec(),
+        );
+
+        // the default account is not a registered sealer
+        let account: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
+        let (_, bob, _) = get_sealer_bob();
+
+        assert_err!(
+            OffchainModule::create_vote(
+                who.clone(),
+                vote_id.clone(),
+                vote_title.clone(),
+                params.clone().into(),
+                vec![topic.clone()],
+                2,
+                vec![bob, account]
+            ),
+            Error::<TestRuntime>::NotASealer
+        );
+        assert_err!(
+            OffchainModule::create_vote(
+                who,
+                vote_id,
+                vote_title,
+                params.into(),
+                vec![topic],
+                2,
+                vec![bob, bob]
+            ),
+            Error::<TestRuntime>::DuplicateSealer
+        );
     });
 }
 
@@ -493,7 +682,8 @@ fn test_cast_ballot_works_encoded() {
         let r = OffchainModule::get_random_biguint_less_than(q).unwrap();
 
         // use additive homomorphic encoding for message i.e. g^m
-        let cipher: Cipher = ElGamal::encrypt_encode(&big, &r, &pk).into();
+        let big_cipher: BigCipher = ElGamal::encrypt_encode(&big, &r, &pk);
+        let cipher: Cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         let answers = vec![(topic_id.clone(), cipher.clone())];
         let ballot: Ballot = Ballot { answers };
 
@@ -559,7 +749,8 @@ fn test_cast_ballot_works() {
         let num: u64 = 32;
         let big: BigUint = BigUint::from(num);
         let r = OffchainModule::get_random_biguint_less_than(q).unwrap();
-        let cipher: Cipher = ElGamal::encrypt(&big, &r, &pk).into();
+        let big_cipher: BigCipher = ElGamal::encrypt(&big, &r, &pk);
+        let cipher: Cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         let answers = vec![(topic_id.clone(), cipher.clone())];
         let ballot: Ballot = Ballot { answers };
 
@@ -606,6 +797,50 @@ fn test_cast_ballot_works() {
     });
 }
 
+#[test]
+fn test_cast_ballot_non_canonical_encoding() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.clone().into());
+        setup_public_key(vote_id.clone(), pk.clone().into());
+        let acct: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
+
+        let big_cipher =
+            ElGamal::encrypt_encode(&BigUint::one(), &BigUint::from(7u32), &pk);
+        let cipher = Cipher::encode_canonical(&big_cipher, &params);
+
+        // a leading zero byte changes the width of the encoding
+        let mut padded = cipher.clone();
+        padded.a.insert(0, 0u8);
+
+        // a value which is not reduced modulo p
+        let mut unreduced = cipher.clone();
+        unreduced.b = params.p.to_bytes_be();
+
+        // values of the right width which are not elements of G_q: 0 and p - 1
+        let mut zero = cipher.clone();
+        zero.a = vec![0u8; cipher.a.len()];
+        let mut order_two = cipher;
+        order_two.b = (&params.p - BigUint::one()).to_bytes_be();
+
+        for cipher in [padded, unreduced, zero, order_two].iter() {
+            let ballot = Ballot {
+                answers: vec![(topic_id.clone(), cipher.clone())],
+            };
+            assert_err!(
+                OffchainModule::cast_ballot(
+                    Origin::signed(acct),
+                    vote_id.clone(),
+                    ballot
+                ),
+                Error::<TestRuntime>::NonCanonicalEncoding
+            );
+        }
+        assert!(OffchainModule::ciphers(topic_id, NR_OF_SHUFFLES).is_empty());
+    });
+}
+
 #[test]
 fn test_offchain_signed_tx_encoded() {
     let (mut t, pool_state, _) = ExternalityBuilder::build();
@@ -624,7 +859,8 @@ fn test_offchain_signed_tx_encoded() {
         let r = OffchainModule::get_random_biguint_less_than(q).unwrap();
 
         // use additive homomorphic encoding for message i.e. g^m
-        let cipher: Cipher = ElGamal::encrypt_encode(&big, &r, &pk).into();
+        let big_cipher: BigCipher = ElGamal::encrypt_encode(&big, &r, &pk);
+        let cipher: Cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher)];
         let ballot: Ballot = Ballot { answers };
 
@@ -640,16 +876,6 @@ fn test_offchain_signed_tx_encoded() {
     });
 }
 
-#[test]
-fn test_get_random_bytes() {
-    let (mut t, _, _) = ExternalityBuilder::build();
-    t.execute_with(|| {
-        let size: usize = 32;
-        let random = OffchainModule::get_random_bytes(size).unwrap();
-        assert_eq!(random.len(), size);
-    });
-}
-
 #[test]
 fn test_get_random_number_less_than() {
     let (mut t, _, _) = ExternalityBuilder::build();
@@ -701,77 +927,6 @@ fn test_get_random_numbers_less_than_should_panic_number_is_zero() {
     });
 }
 
-#[test]
-fn test_get_random_bigunint_range() {
-    let (mut t, _, _) = ExternalityBuilder::build();
-    t.execute_with(|| {
-        let lower: BigUint = BigUint::parse_bytes(b"0", 10).unwrap();
-        let upper: BigUint =
-            BigUint::parse_bytes(b"10981023801283012983912312", 10).unwrap();
-        let value = OffchainModule::get_random_bigunint_range(&lower, &upper).unwrap();
-
-        assert!(value < upper);
-        assert!(lower < value);
-    });
-}
-
-#[test]
-fn test_get_random_bigunint_range_upper_is_zero() {
-    let (mut t, _, _) = ExternalityBuilder::build();
-    t.execute_with(|| {
-        let lower: BigUint = BigUint::parse_bytes(b"0", 10).unwrap();
-        let upper: BigUint = BigUint::parse_bytes(b"0", 10).unwrap();
-        OffchainModule::get_random_bigunint_range(&lower, &upper)
-            .expect_err("The returned value should be: '<Error<T>>::RandomRangeError'");
-    });
-}
-
-#[test]
-fn test_get_random_bigunint_range_upper_is_not_larger_than_lower() {
-    let (mut t, _, _) = ExternalityBuilder::build();
-    t.execute_with(|| {
-        let lower: BigUint = BigUint::parse_bytes(b"5", 10).unwrap();
-        let upper: BigUint = BigUint::parse_bytes(b"5", 10).unwrap();
-        OffchainModule::get_random_bigunint_range(&lower, &upper)
-            .expect_err("The returned value should be: '<Error<T>>::RandomRangeError'");
-    });
-}
-
-#[test]
-fn test_get_random_range() {
-    let (mut t, _, _) = ExternalityBuilder::build();
-    t.execute_with(|| {
-        let lower: usize = 0;
-        let upper: usize = 100;
-        let value = OffchainModule::get_random_range(lower, upper).unwrap();
-
-        assert!(value < upper);
-        assert!(lower < value);
-    });
-}
-
-#[test]
-fn test_get_random_range_upper_is_zero_error() {
-    let (mut t, _, _) = ExternalityBuilder::build();
-    t.execute_with(|| {
-        let lower: usize = 0;
-        let upper: usize = 0;
-        OffchainModule::get_random_range(lower, upper)
-            .expect_err("The returned value should be: '<Error<T>>::RandomRangeError'");
-    });
-}
-
-#[test]
-fn test_get_random_range_upper_is_not_larger_than_lower_error() {
-    let (mut t, _, _) = ExternalityBuilder::build();
-    t.execute_with(|| {
-        let lower: usize = 5;
-        let upper: usize = 5;
-        OffchainModule::get_random_range(lower, upper)
-            .expect_err("The returned value should be: '<Error<T>>::RandomRangeError'");
-    });
-}
-
 #[test]
 fn test_generate_permutation_size_zero_error() {
     let (mut t, _, _) = ExternalityBuilder::build();
@@ -830,7 +985,7 @@ fn store_small_dummy_vote_works_encoded() {
 
         // transform the ballot into a from that the blockchain can handle
         // i.e. a Substrate representation { a: Vec<u8>, b: Vec<u8> }
-        let cipher: Cipher = big_cipher.clone().into();
+        let cipher: Cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher.clone())];
         let ballot: Ballot = Ballot { answers };
 
@@ -850,7 +1005,8 @@ fn store_small_dummy_vote_works_encoded() {
         assert_eq!(cipher, cipher_from_chain);
 
         // transform the Ballot -> BigCipher
-        let big_cipher_from_chain: BigCipher = cipher_from_chain.into();
+        let big_cipher_from_chain: BigCipher =
+            cipher_from_chain.decode_canonical(&pk.params).unwrap();
         assert_eq!(big_cipher, big_cipher_from_chain);
 
         let decrypted_vote = ElGamal::decrypt_decode(&big_cipher_from_chain, &sk);
@@ -875,7 +1031,7 @@ fn store_small_dummy_vote_works() {
 
         // transform the ballot into a from that the blockchain can handle
         // i.e. a Substrate representation { a: Vec<u8>, b: Vec<u8> }
-        let cipher: Cipher = big_cipher.clone().into();
+        let cipher: Cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher.clone())];
         let ballot: Ballot = Ballot { answers };
 
@@ -895,7 +1051,8 @@ fn store_small_dummy_vote_works() {
         assert_eq!(cipher, cipher_from_chain);
 
         // transform the Ballot -> BigCipher
-        let big_cipher_from_chain: BigCipher = cipher_from_chain.into();
+        let big_cipher_from_chain: BigCipher =
+            cipher_from_chain.decode_canonical(&pk.params).unwrap();
         assert_eq!(big_cipher, big_cipher_from_chain);
 
         let decrypted_vote = ElGamal::decrypt(&big_cipher_from_chain, &sk);
@@ -920,7 +1077,7 @@ fn store_real_size_vote_works_encoded() {
 
         // transform the ballot into a from that the blockchain can handle
         // i.e. a Substrate representation { a: Vec<u8>, b: Vec<u8> }
-        let cipher: Cipher = big_cipher.clone().into();
+        let cipher: Cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher.clone())];
         let ballot: Ballot = Ballot { answers };
 
@@ -940,7 +1097,8 @@ fn store_real_size_vote_works_encoded() {
         assert_eq!(cipher, cipher_from_chain);
 
         // transform the Ballot -> BigCipher
-        let big_cipher_from_chain: BigCipher = cipher_from_chain.into();
+        let big_cipher_from_chain: BigCipher =
+            cipher_from_chain.decode_canonical(&pk.params).unwrap();
         assert_eq!(big_cipher, big_cipher_from_chain);
 
         let decrypted_vote = ElGamal::decrypt_decode(&big_cipher_from_chain, &sk);
@@ -965,7 +1123,7 @@ fn store_real_size_vote_works() {
 
         // transform the ballot into a from that the blockchain can handle
         // i.e. a Substrate representation { a: Vec<u8>, b: Vec<u8> }
-        let cipher: Cipher = big_cipher.clone().into();
+        let cipher: Cipher = Cipher::encode_canonical(&big_cipher, &pk.params);
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher.clone())];
         let ballot: Ballot = Ballot { answers };
 
@@ -985,7 +1143,8 @@ fn store_real_size_vote_works() {
         assert_eq!(cipher, cipher_from_chain);
 
         // transform the Ballot -> BigCipher
-        let big_cipher_from_chain: BigCipher = cipher_from_chain.into();
+        let big_cipher_from_chain: BigCipher =
+            cipher_from_chain.decode_canonical(&pk.params).unwrap();
         assert_eq!(big_cipher, big_cipher_from_chain);
 
         let decrypted_vote = ElGamal::decrypt(&big_cipher_from_chain, &sk);
@@ -1030,7 +1189,7 @@ fn test_shuffle_ciphers_encoded() {
             let cipher: BigCipher =
                 ElGamal::encrypt_encode(&messages[index], &random, &pk);
             ciphers.push(cipher.clone());
-            let cipher: Cipher = cipher.into();
+            let cipher: Cipher = Cipher::encode_canonical(&cipher, &pk.params);
             let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher)];
             let ballot: Ballot = Ballot { answers };
 
@@ -1045,12 +1204,15 @@ fn test_shuffle_ciphers_encoded() {
         assert!(shuffled_big_ciphers.len() == 3);
 
         // type conversion: BigCipher (BigUint) to Ballot (Vec<u8>)
-        let shuffled_ciphers: Vec<Cipher> = Wrapper(shuffled_big_ciphers).into();
+        let shuffled_ciphers: Vec<Cipher> =
+            Canonical::encode_canonical(&shuffled_big_ciphers, &pk.params);
 
         // transform each ballot into a cipher, decrypt_decode it and finally collect the list of biguints
         let decrypted_votes = shuffled_ciphers
             .iter()
-            .map(|b| ElGamal::decrypt_decode(&(b.clone().into()), &sk))
+            .map(|b| {
+                ElGamal::decrypt_decode(&b.decode_canonical(&pk.params).unwrap(), &sk)
+            })
             .collect::<Vec<BigUint>>();
 
         // check that at least one value is 5, 10, 15
@@ -1102,7 +1264,7 @@ fn test_shuffle_ciphers() {
             // i.e. a Substrate representation { a: Vec<u8>, b: Vec<u8> }
             let cipher: BigCipher = ElGamal::encrypt(&messages[index], &random, &pk);
             ciphers.push(cipher.clone());
-            let cipher: Cipher = cipher.into();
+            let cipher: Cipher = Cipher::encode_canonical(&cipher, &pk.params);
             let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher)];
             let ballot: Ballot = Ballot { answers };
 
@@ -1117,12 +1279,13 @@ fn test_shuffle_ciphers() {
         assert!(shuffled_big_ciphers.len() == 3);
 
         // type conversion: BigCipher (BigUint) to Ballot (Vec<u8>)
-        let shuffled_ciphers: Vec<Cipher> = Wrapper(shuffled_big_ciphers).into();
+        let shuffled_ciphers: Vec<Cipher> =
+            Canonical::encode_canonical(&shuffled_big_ciphers, &pk.params);
 
         // transform each ballot into a cipher, decrypt_decode it and finally collect the list of biguints
         let decrypted_votes = shuffled_ciphers
             .iter()
-            .map(|b| ElGamal::decrypt(&(b.clone().into()), &sk))
+            .map(|b| ElGamal::decrypt(&b.decode_canonical(&pk.params).unwrap(), &sk))
             .collect::<Vec<BigUint>>();
 
         // check that at least one value is 5, 10, 15
@@ -1183,10 +1346,9 @@ fn test_shuffle_proof_small_system_encoded() {
     t.execute_with(|| {
         let (params, _, pk) = Helper::setup_sm_system();
         let (vote_id, topic_id) = setup_vote(params.into());
-        let is_p_prime = OffchainModule::is_prime(&pk.params.p, 10).unwrap();
-        assert!(is_p_prime);
-        let is_q_prime = OffchainModule::is_prime(&pk.params.q(), 10).unwrap();
-        assert!(is_q_prime);
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        assert!(Random::is_prime(&pk.params.p, 10, &mut rng));
+        assert!(Random::is_prime(&pk.params.q(), 10, &mut rng));
 
         let is_proof_valid = shuffle_proof_test(vote_id, topic_id, pk, true);
         assert!(is_proof_valid);
@@ -1204,10 +1366,9 @@ fn test_shuffle_proof_small_system() {
     t.execute_with(|| {
         let (params, _, pk) = Helper::setup_sm_system();
         let (vote_id, topic_id) = setup_vote(params.into());
-        let is_p_prime = OffchainModule::is_prime(&pk.params.p, 10).unwrap();
-        assert!(is_p_prime);
-        let is_q_prime = OffchainModule::is_prime(&pk.params.q(), 10).unwrap();
-        assert!(is_q_prime);
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        assert!(Random::is_prime(&pk.params.p, 10, &mut rng));
+        assert!(Random::is_prime(&pk.params.q(), 10, &mut rng));
 
         let is_proof_valid = shuffle_proof_test(vote_id, topic_id, pk, false);
         assert!(is_proof_valid);
@@ -1220,10 +1381,9 @@ fn test_shuffle_proof_tiny_system_encoded() {
     t.execute_with(|| {
         let (params, _, pk) = Helper::setup_tiny_system();
         let (vote_id, topic_id) = setup_vote(params.into());
-        let is_p_prime = OffchainModule::is_prime(&pk.params.p, 10).unwrap();
-        assert!(is_p_prime);
-        let is_q_prime = OffchainModule::is_prime(&pk.params.q(), 10).unwrap();
-        assert!(is_q_prime);
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        assert!(Random::is_prime(&pk.params.p, 10, &mut rng));
+        assert!(Random::is_prime(&pk.params.q(), 10, &mut rng));
 
         let is_proof_valid = shuffle_proof_test(vote_id, topic_id, pk, true);
         assert!(is_proof_valid);
@@ -1236,10 +1396,9 @@ fn test_shuffle_proof_tiny_system() {
     t.execute_with(|| {
         let (params, _, pk) = Helper::setup_tiny_system();
         let (vote_id, topic_id) = setup_vote(params.into());
-        let is_p_prime = OffchainModule::is_prime(&pk.params.p, 10).unwrap();
-        assert!(is_p_prime);
-        let is_q_prime = OffchainModule::is_prime(&pk.params.q(), 10).unwrap();
-        assert!(is_q_prime);
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        assert!(Random::is_prime(&pk.params.p, 10, &mut rng));
+        assert!(Random::is_prime(&pk.params.q(), 10, &mut rng));
 
         let is_proof_valid = shuffle_proof_test(vote_id, topic_id, pk, false);
         assert!(is_proof_valid);
@@ -1253,10 +1412,9 @@ fn test_shuffle_proof_medium_system() {
     t.execute_with(|| {
         let (params, _, pk) = Helper::setup_md_system();
         let (vote_id, topic_id) = setup_vote(params.into());
-        let is_p_prime = OffchainModule::is_prime(&pk.params.p, 10).unwrap();
-        assert!(is_p_prime);
-        let is_q_prime = OffchainModule::is_prime(&pk.params.q(), 10).unwrap();
-        assert!(is_q_prime);
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        assert!(Random::is_prime(&pk.params.p, 10, &mut rng));
+        assert!(Random::is_prime(&pk.params.q(), 10, &mut rng));
 
         let is_proof_valid = shuffle_proof_test(vote_id, topic_id, pk, false);
         assert!(is_proof_valid);
@@ -1270,10 +1428,9 @@ fn test_shuffle_proof_large_system() {
     t.execute_with(|| {
         let (params, _, pk) = Helper::setup_lg_system();
         let (vote_id, topic_id) = setup_vote(params.into());
-        let is_p_prime = OffchainModule::is_prime(&pk.params.p, 10).unwrap();
-        assert!(is_p_prime);
-        let is_q_prime = OffchainModule::is_prime(&pk.params.q(), 10).unwrap();
-        assert!(is_q_prime);
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        assert!(Random::is_prime(&pk.params.p, 10, &mut rng));
+        assert!(Random::is_prime(&pk.params.q(), 10, &mut rng));
 
         let is_proof_valid = shuffle_proof_test(vote_id, topic_id, pk, false);
         assert!(is_proof_valid);
@@ -1287,10 +1444,9 @@ fn test_shuffle_proof_xl_system() {
     t.execute_with(|| {
         let (params, _, pk) = Helper::setup_xl_system();
         let (vote_id, topic_id) = setup_vote(params.into());
-        let is_p_prime = OffchainModule::is_prime(&pk.params.p, 10).unwrap();
-        assert!(is_p_prime);
-        let is_q_prime = OffchainModule::is_prime(&pk.params.q(), 10).unwrap();
-        assert!(is_q_prime);
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        assert!(Random::is_prime(&pk.params.p, 10, &mut rng));
+        assert!(Random::is_prime(&pk.params.q(), 10, &mut rng));
 
         let is_proof_valid = shuffle_proof_test(vote_id, topic_id, pk, false);
         assert!(is_proof_valid);
@@ -1397,11 +1553,20 @@ fn test_store_public_key_share_fail_is_voting_authority() {
 
         // create public key share + proof
         let sealer_id = "Bob".as_bytes();
-        let r = BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap();
-        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id);
+        let r = Secret::new(
+            BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap(),
+        );
+        let proof = KeyGenerationProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            sealer_id,
+            &ProofContext::new(&vote_id),
+        );
         let pk_share = PublicKeyShare {
-            proof: proof.into(),
-            pk: pk.h.to_bytes_be(),
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
         };
 
         // submit the public key share
@@ -1427,11 +1592,20 @@ fn test_store_public_key_share_fail_no_sealers() {
         let sealer_id = "Bob".as_bytes();
 
         // create public key share + proof
-        let r = BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap();
-        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, sealer_id);
+        let r = Secret::new(
+            BigUint::parse_bytes(b"170141183460469231731687303715884", 10).unwrap(),
+        );
+        let proof = KeyGenerationProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            sealer_id,
+            &ProofContext::new(&vote_id),
+        );
         let pk_share = PublicKeyShare {
-            proof: proof.into(),
-            pk: pk.h.to_bytes_be(),
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
         };
 
         // submit the public key share
@@ -1456,18 +1630,52 @@ fn test_store_public_key_share() {
 
         // verify the public key share submission + proof verification
         let shares: Vec<PublicKeyShare> = OffchainModule::key_shares(vote_id.clone());
-        assert_eq!(shares[0].pk, pk.h.to_bytes_be());
-        assert_eq!(shares[0].proof.challenge, proof.challenge.to_bytes_be());
-        assert_eq!(shares[0].proof.response, proof.response.to_bytes_be());
+        assert_eq!(shares[0].pk, pk.params.encode_value(&pk.h));
+        assert_eq!(
+            shares[0].proof.decode_canonical(&params),
+            Some(proof.clone())
+        );
 
         let share_by_sealer: PublicKeyShare =
             OffchainModule::key_share_by_sealer((vote_id, account_id)).unwrap();
-        assert_eq!(share_by_sealer.pk, pk.h.to_bytes_be());
-        assert_eq!(
-            share_by_sealer.proof.challenge,
-            proof.challenge.to_bytes_be()
+        assert_eq!(share_by_sealer.pk, pk.params.encode_value(&pk.h));
+        assert_eq!(share_by_sealer.proof.decode_canonical(&params), Some(proof));
+    });
+}
+
+#[test]
+fn test_store_public_key_share_proof_of_another_width() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // Setup
+        let (params, sk, pk) = Helper::setup_md_system();
+        let (vote_id, _) = setup_vote(params.clone().into());
+
+        // use sealer bob
+        let (who, _, sealer_id) = get_sealer_bob();
+        let r =
+            Secret::new(BigUint::parse_bytes(b"1701411834604692317316873", 10).unwrap());
+        let proof = KeyGenerationProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            &sealer_id,
+            &ProofContext::new(&vote_id),
+        );
+
+        // the proof is encoded at the width of larger parameters
+        let (lg_params, _, _) = Helper::setup_lg_system();
+        let pk_share = PublicKeyShare {
+            proof: Canonical::encode_canonical(&proof, &lg_params),
+            pk: pk.params.encode_value(&pk.h),
+        };
+
+        // submit the public key share
+        assert_err!(
+            OffchainModule::store_public_key_share(who, vote_id, pk_share.into()),
+            Error::<TestRuntime>::NonCanonicalEncoding
         );
-        assert_eq!(share_by_sealer.proof.response, proof.response.to_bytes_be());
     });
 }
 
@@ -1540,6 +1748,38 @@ fn test_combine_public_key_shares() {
     });
 }
 
+#[test]
+fn test_combine_public_key_shares_missing_sealer_share() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_md_system();
+        let (vote_id, _) = setup_vote(params.clone().into());
+
+        // only Bob submits a public key share, Charlie is a sealer of the vote as well
+        let (bob, _, bob_sealer_id) = get_sealer_bob();
+        setup_sealer(&params, &sk, &pk, bob, &vote_id, &bob_sealer_id);
+
+        let voting_authority = get_voting_authority();
+        assert_err!(
+            OffchainModule::combine_public_key_shares(
+                voting_authority.clone(),
+                vote_id.clone()
+            ),
+            Error::<TestRuntime>::NotEnoughPublicKeyShares
+        );
+        assert!(OffchainModule::public_key(&vote_id).is_none());
+
+        // the key can be combined once Charlie submitted a share
+        let (charlie, _, charlie_sealer_id) = get_sealer_charlie();
+        setup_sealer(&params, &sk, &pk, charlie, &vote_id, &charlie_sealer_id);
+        assert_ok!(OffchainModule::combine_public_key_shares(
+            voting_authority,
+            vote_id.clone()
+        ));
+        assert!(OffchainModule::public_key(&vote_id).is_some());
+    });
+}
+
 #[test]
 fn test_submit_decrypted_share_vote_does_not_exist() {
     let (mut t, _, _) = ExternalityBuilder::build();
@@ -1551,10 +1791,12 @@ fn test_submit_decrypted_share_vote_does_not_exist() {
         let vote_id = "20201212".as_bytes().to_vec();
         let topic_id = "vote1".as_bytes().to_vec();
         let shares: Vec<Vec<u8>> = Vec::new();
-        let proof = DecryptedShareProof {
-            challenge: Vec::new(),
-            response: Vec::new(),
+        let fake_proof = DecryptionProof {
+            challenge: BigUint::zero(),
+            response: BigUint::zero(),
         };
+        let proof: DecryptedShareProof =
+            Canonical::encode_canonical(&fake_proof, &pk.params);
 
         // Setup Public Key
         setup_public_key(vote_id.clone(), pk.clone().into());
@@ -1570,6 +1812,7 @@ fn test_submit_decrypted_share_vote_does_not_exist() {
                 topic_id,
                 shares,
                 proof,
+                vec![],
                 NR_OF_SHUFFLES
             ),
             Error::<TestRuntime>::VoteDoesNotExist
@@ -1590,10 +1833,12 @@ fn test_submit_decrypted_share_wrong_vote_phase() {
 
         // fake proof + fake decrypted shares
         let shares: Vec<Vec<u8>> = Vec::new();
-        let proof = DecryptedShareProof {
-            challenge: Vec::new(),
-            response: Vec::new(),
+        let fake_proof = DecryptionProof {
+            challenge: BigUint::zero(),
+            response: BigUint::zero(),
         };
+        let proof: DecryptedShareProof =
+            Canonical::encode_canonical(&fake_proof, &pk.params);
 
         // create the submitter (i.e. the voting_authority)
         // use Alice as VotingAuthority
@@ -1606,6 +1851,7 @@ fn test_submit_decrypted_share_wrong_vote_phase() {
                 topic_id,
                 shares,
                 proof,
+                vec![],
                 NR_OF_SHUFFLES
             ),
             Error::<TestRuntime>::WrongVotePhase
@@ -1626,10 +1872,12 @@ fn test_submit_decrypted_share_not_a_sealer() {
 
         // fake proof + fake decrypted shares
         let shares: Vec<Vec<u8>> = Vec::new();
-        let proof = DecryptedShareProof {
-            challenge: Vec::new(),
-            response: Vec::new(),
+        let fake_proof = DecryptionProof {
+            challenge: BigUint::zero(),
+            response: BigUint::zero(),
         };
+        let proof: DecryptedShareProof =
+            Canonical::encode_canonical(&fake_proof, &pk.params);
 
         // change the VotePhase to Tallying
         set_vote_phase(vote_id.clone(), VotePhase::Tallying);
@@ -1643,6 +1891,7 @@ fn test_submit_decrypted_share_not_a_sealer() {
                 topic_id,
                 shares,
                 proof,
+                vec![],
                 NR_OF_SHUFFLES
             ),
             Error::<TestRuntime>::NotASealer
@@ -1659,7 +1908,7 @@ fn test_submit_decrypted_share() {
         let (vote_id, topic_id) = setup_vote(params.clone().into());
 
         // Use 1. Sealer: Bob
-        let (bob, _, bob_sealer_id) = get_sealer_bob();
+        let (bob, bob_account, bob_sealer_id) = get_sealer_bob();
         let bob_sk_x = BigUint::parse_bytes(b"12345678", 10).unwrap();
         let (bob_pk, bob_sk) = Helper::generate_key_pair(&params, &bob_sk_x);
         let (_, _) = setup_sealer(
@@ -1706,7 +1955,9 @@ fn test_submit_decrypted_share() {
 
         // fetch the encrypted votes from chain
         let encryptions: Vec<BigCipher> =
-            Wrapper(OffchainModule::ciphers(&topic_id, NR_OF_SHUFFLES)).into();
+            OffchainModule::ciphers(&topic_id, NR_OF_SHUFFLES)
+                .decode_canonical(&params)
+                .unwrap();
         assert!(encryptions.len() > 0);
 
         // get bob's partial decryptions
@@ -1718,12 +1969,12 @@ fn test_submit_decrypted_share() {
         // convert the decrypted shares: Vec<BigUint> to Vec<Vec<u8>>
         let bob_shares: Vec<Vec<u8>> = bob_partial_decrytpions
             .iter()
-            .map(|c| c.to_bytes_be())
+            .map(|c| params.encode_value(c))
             .collect::<Vec<Vec<u8>>>();
 
         // create bob's proof using bob's public and private key share
-        let r = BigUint::parse_bytes(b"1234123123", 10).unwrap();
-        let bob_proof = DecryptionProof::generate(
+        let r = Secret::new(BigUint::parse_bytes(b"1234123123", 10).unwrap());
+        let (bob_proof, bob_commitments) = DecryptionProof::generate_with_commitments(
             &params,
             &bob_sk.x,
             &bob_pk.h.into(),
@@ -1731,19 +1982,60 @@ fn test_submit_decrypted_share() {
             encryptions,
             bob_partial_decrytpions,
             &bob_sealer_id,
+            &ProofContext::new(&vote_id),
+        );
+        let bob_proof: DecryptedShareProof =
+            Canonical::encode_canonical(&bob_proof, &params);
+        let bob_commitments: Vec<Vec<u8>> = bob_commitments
+            .iter()
+            .map(|t| params.encode_value(t))
+            .collect();
+
+        // commitments which do not belong to the proof are rejected
+        let mut invalid_commitments = bob_commitments.clone();
+        invalid_commitments.swap(0, 1);
+        assert_err!(
+            OffchainModule::submit_decrypted_shares(
+                bob.clone(),
+                vote_id.clone(),
+                topic_id.clone(),
+                bob_shares.clone(),
+                bob_proof.clone(),
+                invalid_commitments,
+                NR_OF_SHUFFLES
+            ),
+            Error::<TestRuntime>::DecryptedShareProofError
+        );
+        assert_err!(
+            OffchainModule::submit_decrypted_shares(
+                bob.clone(),
+                vote_id.clone(),
+                topic_id.clone(),
+                bob_shares.clone(),
+                bob_proof.clone(),
+                bob_commitments[1..].to_vec(),
+                NR_OF_SHUFFLES
+            ),
+            Error::<TestRuntime>::DecryptedShareProofError
         );
 
         // check that:
         // 1. the decrypted share is submitted and
         // 2. the proof is successfully verified
+        // 3. the commitments are stored next to the proof
         assert_ok!(OffchainModule::submit_decrypted_shares(
             bob.clone(),
             vote_id,
-            topic_id,
+            topic_id.clone(),
             bob_shares,
-            bob_proof.into(),
+            bob_proof,
+            bob_commitments.clone(),
             NR_OF_SHUFFLES
         ));
+        assert_eq!(
+            OffchainModule::decrypted_share_commitments(&topic_id, &bob_account),
+            bob_commitments
+        );
     });
 }
 
@@ -1870,7 +2162,9 @@ fn test_combine_decrypted_shares() {
 
         // fetch the encrypted votes from chain
         let encryptions: Vec<BigCipher> =
-            Wrapper(OffchainModule::ciphers(&topic_id, NR_OF_SHUFFLES)).into();
+            OffchainModule::ciphers(&topic_id, NR_OF_SHUFFLES)
+                .decode_canonical(&params)
+                .unwrap();
         assert!(encryptions.len() > 0);
 
         // get bob's partial decryptions
@@ -1882,11 +2176,11 @@ fn test_combine_decrypted_shares() {
         // convert the decrypted shares: Vec<BigUint> to Vec<Vec<u8>>
         let bob_shares: Vec<Vec<u8>> = bob_partial_decrytpions
             .iter()
-            .map(|c| c.to_bytes_be())
+            .map(|c| params.encode_value(c))
             .collect::<Vec<Vec<u8>>>();
 
         // create bob's proof using bob's public and private key share
-        let r = BigUint::parse_bytes(b"1234123123", 10).unwrap();
+        let r = Secret::new(BigUint::parse_bytes(b"1234123123", 10).unwrap());
         let bob_proof = DecryptionProof::generate(
             &params,
             &bob_sk.x,
@@ -1895,6 +2189,7 @@ fn test_combine_decrypted_shares() {
             encryptions.clone(),
             bob_partial_decrytpions,
             &bob_sealer_id,
+            &ProofContext::new(&vote_id),
         );
 
         // check that:
@@ -1905,7 +2200,8 @@ fn test_combine_decrypted_shares() {
             vote_id.clone(),
             topic_id.clone(),
             bob_shares,
-            bob_proof.into(),
+            Canonical::encode_canonical(&bob_proof, &params),
+            vec![],
             NR_OF_SHUFFLES
         ));
 
@@ -1918,11 +2214,11 @@ fn test_combine_decrypted_shares() {
         // convert the decrypted shares: Vec<BigUint> to Vec<Vec<u8>>
         let charlie_shares: Vec<Vec<u8>> = charlie_paritial_decryptions
             .iter()
-            .map(|c| c.to_bytes_be())
+            .map(|c| params.encode_value(c))
             .collect::<Vec<Vec<u8>>>();
 
         // create charlie's proof using charlie's public and private key share
-        let r = BigUint::parse_bytes(b"80981238129912392", 10).unwrap();
+        let r = Secret::new(BigUint::parse_bytes(b"80981238129912392", 10).unwrap());
         let charlie_proof = DecryptionProof::generate(
             &params,
             &charlie_sk.x,
@@ -1931,6 +2227,7 @@ fn test_combine_decrypted_shares() {
             encryptions,
             charlie_paritial_decryptions,
             &charlie_sealer_id,
+            &ProofContext::new(&vote_id),
         );
 
         // check that:
@@ -1941,7 +2238,8 @@ fn test_combine_decrypted_shares() {
             vote_id.clone(),
             topic_id.clone(),
             charlie_shares,
-            charlie_proof.into(),
+            Canonical::encode_canonical(&charlie_proof, &params),
+            vec![],
             NR_OF_SHUFFLES
         ));
 
@@ -2027,7 +2325,9 @@ fn test_submit_shuffled_votes_and_proof() {
 
         // get the encrypted votes
         let big_ciphers_from_chain: Vec<BigCipher> =
-            Wrapper(OffchainModule::ciphers(&topic_id, nr_of_shuffles)).into();
+            OffchainModule::ciphers(&topic_id, nr_of_shuffles)
+                .decode_canonical(&pk.params)
+                .unwrap();
         assert!(big_ciphers_from_chain.len() > 0);
 
         // change the VotePhase to Voting using the voting authority
@@ -2049,6 +2349,7 @@ fn test_submit_shuffled_votes_and_proof() {
 
         // shuffle the votes + create proof
         let payload: ShufflePayload = OffchainModule::offchain_shuffle_and_proof(
+            &vote_id,
             &topic_id,
             shuffle_state.iteration,
             &pk,
@@ -2099,6 +2400,7 @@ fn test_submit_shuffled_votes_and_proof() {
 
         // shuffle the votes + create proof
         let payload: ShufflePayload = OffchainModule::offchain_shuffle_and_proof(
+            &vote_id,
             &topic_id,
             shuffle_state.iteration,
             &pk,
@@ -2137,6 +2439,7 @@ fn test_submit_shuffled_votes_and_proof() {
 
         // shuffle the votes + create proof
         let payload: ShufflePayload = OffchainModule::offchain_shuffle_and_proof(
+            &vote_id,
             &topic_id,
             shuffle_state.iteration,
             &pk,
@@ -2193,3 +2496,2223 @@ fn test_setup_ciphers_nr_of_shuffles_not_correct() {
         assert!(from_chain.is_empty());
     });
 }
+
+#[test]
+fn test_add_and_remove_voting_authority() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (_, bob, _) = get_sealer_bob();
+
+        // only the management origin (root) can add a voting authority
+        assert_err!(
+            OffchainModule::add_voting_authority(get_voting_authority(), bob),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(OffchainModule::add_voting_authority(Origin::root(), bob));
+        assert!(OffchainModule::voting_authorities().contains(&bob));
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::VotingAuthorityAdded(bob))));
+
+        // cannot be added twice
+        assert_err!(
+            OffchainModule::add_voting_authority(Origin::root(), bob),
+            Error::<TestRuntime>::AlreadyAVotingAuthority
+        );
+
+        assert_ok!(OffchainModule::remove_voting_authority(Origin::root(), bob));
+        assert!(!OffchainModule::voting_authorities().contains(&bob));
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::VotingAuthorityRemoved(bob))));
+
+        // cannot be removed twice
+        assert_err!(
+            OffchainModule::remove_voting_authority(Origin::root(), bob),
+            Error::<TestRuntime>::NotAVotingAuthority
+        );
+    });
+}
+
+#[test]
+fn test_add_and_remove_sealer() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let account: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
+
+        // only the management origin (root) can add a sealer
+        assert_err!(
+            OffchainModule::add_sealer(Origin::signed(account), account),
+            DispatchError::BadOrigin
+        );
+
+        // a sealer needs to be able to reserve the sealer bond
+        assert_err!(
+            OffchainModule::add_sealer(Origin::root(), account),
+            Error::<TestRuntime>::InsufficientSealerBond
+        );
+        Balances::make_free_balance_be(&account, 1_000);
+
+        assert_ok!(OffchainModule::add_sealer(Origin::root(), account));
+        assert_eq!(OffchainModule::sealers().len(), 3);
+        assert_eq!(Balances::reserved_balance(&account), 500);
+        assert!(System::events().iter().any(
+            |er| er.event == TestEvent::pallet_mixnet(RawEvent::SealerAdded(account))
+        ));
+
+        // cannot be added twice
+        assert_err!(
+            OffchainModule::add_sealer(Origin::root(), account),
+            Error::<TestRuntime>::AlreadyASealer
+        );
+
+        assert_ok!(OffchainModule::remove_sealer(Origin::root(), account));
+        assert_eq!(OffchainModule::sealers().len(), 2);
+        assert_eq!(Balances::reserved_balance(&account), 0);
+        assert_eq!(Balances::free_balance(&account), 1_000);
+        assert!(System::events()
+            .iter()
+            .any(|er| er.event
+                == TestEvent::pallet_mixnet(RawEvent::SealerRemoved(account))));
+
+        // cannot be removed twice
+        assert_err!(
+            OffchainModule::remove_sealer(Origin::root(), account),
+            Error::<TestRuntime>::NotASealer
+        );
+    });
+}
+
+#[test]
+fn test_remove_members_of_vote_in_progress() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        let (_, bob, _) = get_sealer_bob();
+        let alice = OffchainModule::votes(&vote_id).voting_authority;
+
+        // the vote has not been tallied yet
+        assert_err!(
+            OffchainModule::remove_sealer(Origin::root(), bob),
+            Error::<TestRuntime>::MemberOfVoteInProgress
+        );
+        assert_err!(
+            OffchainModule::remove_voting_authority(Origin::root(), alice),
+            Error::<TestRuntime>::MemberOfVoteInProgress
+        );
+
+        // once all topics have been tallied, the members can be removed
+        Tally::insert(&topic_id, BTreeMap::new());
+        assert_ok!(OffchainModule::remove_sealer(Origin::root(), bob));
+        assert_ok!(OffchainModule::remove_voting_authority(
+            Origin::root(),
+            alice
+        ));
+    });
+}
+
+#[test]
+fn test_remove_members_of_vote_without_topics() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let vote_title = "Popular Vote of 12.12.2020".as_bytes().to_vec();
+        let sealers = OffchainModule::sealers();
+        assert_ok!(OffchainModule::create_vote(
+            get_voting_authority(),
+            vote_id.clone(),
+            vote_title,
+            params.into(),
+            Vec::new(),
+            2,
+            sealers
+        ));
+        let (_, bob, _) = get_sealer_bob();
+        let alice = OffchainModule::votes(&vote_id).voting_authority;
+
+        // a vote without topics is in progress during key generation and voting
+        assert_err!(
+            OffchainModule::remove_sealer(Origin::root(), bob),
+            Error::<TestRuntime>::MemberOfVoteInProgress
+        );
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+        assert_err!(
+            OffchainModule::remove_voting_authority(Origin::root(), alice),
+            Error::<TestRuntime>::MemberOfVoteInProgress
+        );
+
+        // but not anymore once the voting phase is over
+        set_vote_phase(vote_id, VotePhase::Tallying);
+        assert_ok!(OffchainModule::remove_sealer(Origin::root(), bob));
+        assert_ok!(OffchainModule::remove_voting_authority(
+            Origin::root(),
+            alice
+        ));
+    });
+}
+
+fn setup_second_voting_authority(threshold: u32) -> Origin {
+    let account: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
+    assert_ok!(OffchainModule::add_voting_authority(
+        Origin::root(),
+        account
+    ));
+    assert_ok!(OffchainModule::set_approval_threshold(
+        Origin::root(),
+        threshold
+    ));
+    Origin::signed(account)
+}
+
+#[test]
+fn test_set_approval_threshold_invalid() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // only a single voting authority is registered
+        assert_err!(
+            OffchainModule::set_approval_threshold(Origin::root(), 2),
+            Error::<TestRuntime>::InvalidApprovalThreshold
+        );
+        assert_err!(
+            OffchainModule::set_approval_threshold(get_voting_authority(), 1),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn test_critical_actions_require_approval() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        setup_second_voting_authority(2);
+
+        assert_err!(
+            OffchainModule::set_vote_phase(
+                get_voting_authority(),
+                vote_id.clone(),
+                VotePhase::Tallying
+            ),
+            Error::<TestRuntime>::ApprovalRequired
+        );
+        assert_err!(
+            OffchainModule::combine_public_key_shares(
+                get_voting_authority(),
+                vote_id.clone()
+            ),
+            Error::<TestRuntime>::ApprovalRequired
+        );
+        assert_err!(
+            OffchainModule::combine_decrypted_shares(
+                get_voting_authority(),
+                vote_id,
+                topic_id,
+                false,
+                NR_OF_SHUFFLES
+            ),
+            Error::<TestRuntime>::ApprovalRequired
+        );
+    });
+}
+
+#[test]
+fn test_propose_and_approve_action() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        let second_authority = setup_second_voting_authority(2);
+
+        // the proposal counts as the first approval
+        let action = AuthorityAction::SetVotePhase(vote_id.clone(), VotePhase::Tallying);
+        assert_ok!(OffchainModule::propose_action(
+            get_voting_authority(),
+            action.clone()
+        ));
+        let proposal = OffchainModule::proposals(0).unwrap();
+        assert_eq!(proposal.action, action);
+        assert_eq!(proposal.approvals.len(), 1);
+        assert!(!proposal.executed);
+        assert_eq!(OffchainModule::votes(&vote_id).phase, VotePhase::Voting);
+
+        // the same voting authority cannot approve twice
+        assert_err!(
+            OffchainModule::approve_action(get_voting_authority(), 0),
+            Error::<TestRuntime>::ProposalAlreadyApproved
+        );
+
+        // the second approval reaches the threshold -> action is executed
+        assert_ok!(OffchainModule::approve_action(second_authority.clone(), 0));
+        let proposal = OffchainModule::proposals(0).unwrap();
+        assert_eq!(proposal.approvals.len(), 2);
+        assert!(proposal.executed);
+        assert_eq!(OffchainModule::votes(&vote_id).phase, VotePhase::Tallying);
+        assert!(System::events()
+            .iter()
+            .any(|er| er.event == TestEvent::pallet_mixnet(RawEvent::ActionExecuted(0))));
+
+        // an executed proposal cannot be approved again
+        assert_err!(
+            OffchainModule::approve_action(second_authority, 0),
+            Error::<TestRuntime>::ProposalAlreadyExecuted
+        );
+    });
+}
+
+#[test]
+fn test_approvals_of_removed_voting_authorities_do_not_count() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        let second_authority = setup_second_voting_authority(2);
+        let third_account = get_voter(1);
+        assert_ok!(OffchainModule::add_voting_authority(
+            Origin::root(),
+            third_account
+        ));
+
+        // the second voting authority proposes the action and is removed afterwards
+        let action = AuthorityAction::SetVotePhase(vote_id.clone(), VotePhase::Tallying);
+        assert_ok!(OffchainModule::propose_action(second_authority, action));
+        assert_ok!(OffchainModule::remove_voting_authority(
+            Origin::root(),
+            Default::default()
+        ));
+
+        // two approvals, but only one of them is given by a voting authority
+        assert_ok!(OffchainModule::approve_action(
+            Origin::signed(third_account),
+            0
+        ));
+        let proposal = OffchainModule::proposals(0).unwrap();
+        assert_eq!(proposal.approvals.len(), 2);
+        assert!(!proposal.executed);
+        assert_eq!(OffchainModule::votes(&vote_id).phase, VotePhase::Voting);
+
+        assert_ok!(OffchainModule::approve_action(get_voting_authority(), 0));
+        assert!(OffchainModule::proposals(0).unwrap().executed);
+        assert_eq!(OffchainModule::votes(&vote_id).phase, VotePhase::Tallying);
+    });
+}
+
+#[test]
+fn test_approve_action_proposal_does_not_exist() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        assert_err!(
+            OffchainModule::approve_action(get_voting_authority(), 0),
+            Error::<TestRuntime>::ProposalDoesNotExist
+        );
+    });
+}
+
+#[test]
+fn test_propose_action_not_a_voting_authority() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        let (bob, _, _) = get_sealer_bob();
+
+        assert_err!(
+            OffchainModule::propose_action(
+                bob,
+                AuthorityAction::CombinePublicKeyShares(vote_id)
+            ),
+            Error::<TestRuntime>::NotAVotingAuthority
+        );
+    });
+}
+
+#[test]
+fn test_store_public_key_share_legacy_proof_version() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_md_system();
+        let (vote_id, _) = setup_vote(params.clone().into());
+        assert_eq!(
+            OffchainModule::proof_version(&vote_id),
+            Some(ProofVersion::CURRENT as u8)
+        );
+
+        // a vote which has been created before the proofs were versioned has no entry
+        ProofVersions::remove(&vote_id);
+
+        // a proof using the current version is rejected
+        let (bob, bob_account, bob_sealer_id) = get_sealer_bob();
+        let r =
+            Secret::new(BigUint::parse_bytes(b"1701411834604692317316873", 10).unwrap());
+        let proof = KeyGenerationProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            &bob_sealer_id,
+            &ProofContext::new(&vote_id),
+        );
+        let pk_share = PublicKeyShare {
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
+        };
+        assert_err!(
+            OffchainModule::store_public_key_share(
+                bob.clone(),
+                vote_id.clone(),
+                pk_share
+            ),
+            Error::<TestRuntime>::PublicKeyShareProofError
+        );
+
+        // a proof using version 1 is still verified
+        let context = ProofContext::with_version(&vote_id, ProofVersion::V1);
+        let proof = KeyGenerationProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            &bob_sealer_id,
+            &context,
+        );
+        let pk_share = PublicKeyShare {
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
+        };
+        assert_ok!(OffchainModule::store_public_key_share(
+            bob,
+            vote_id.clone(),
+            pk_share.clone()
+        ));
+        assert_eq!(
+            OffchainModule::key_share_by_sealer((vote_id, bob_account)),
+            Some(pk_share)
+        );
+    });
+}
+
+#[test]
+fn test_store_public_key_share_invalid_proof_is_recorded() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_md_system();
+        let (vote_id, _) = setup_vote(params.clone().into());
+
+        // bob submits a proof generated for charlie's id -> invalid proof
+        let (bob, bob_account, _) = get_sealer_bob();
+        let (_, _, charlie_sealer_id) = get_sealer_charlie();
+        let r =
+            Secret::new(BigUint::parse_bytes(b"1701411834604692317316873", 10).unwrap());
+        let proof = KeyGenerationProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            &charlie_sealer_id,
+            &ProofContext::new(&vote_id),
+        );
+        let pk_share = PublicKeyShare {
+            proof: Canonical::encode_canonical(&proof, &pk.params),
+            pk: pk.params.encode_value(&pk.h),
+        };
+        assert_err!(
+            OffchainModule::store_public_key_share(bob, vote_id.clone(), pk_share),
+            Error::<TestRuntime>::PublicKeyShareProofError
+        );
+
+        // the misbehaviour is recorded and the sealer is slashed
+        let record = OffchainModule::misbehaviours(&vote_id, &bob_account);
+        assert_eq!(record.invalid_proofs, 1);
+        assert_eq!(record.missed_turns, 0);
+        assert_eq!(OffchainModule::count_by_sealer(&bob_account), 1);
+        assert_eq!(OffchainModule::count(), 1);
+        // the slash is taken from the sealer bond, not from the free balance
+        assert_eq!(Balances::free_balance(&bob_account), 500);
+        assert_eq!(Balances::reserved_balance(&bob_account), 400);
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::SealerMisbehaved(
+                bob_account,
+                vote_id.clone(),
+                Misbehaviour::InvalidProof
+            ))));
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::SealerSlashed(bob_account, 100))));
+    });
+}
+
+#[test]
+fn test_record_missed_turns() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        let (_, bob_account, _) = get_sealer_bob();
+        let (_, charlie_account, _) = get_sealer_charlie();
+
+        // the shuffling starts at block 1
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        // the turn of block 1 (charlie) is evaluated at block 3
+        OffchainModule::on_initialize(3);
+        let record = OffchainModule::misbehaviours(&vote_id, &charlie_account);
+        assert_eq!(record.missed_turns, 1);
+        assert_eq!(OffchainModule::count_by_sealer(&charlie_account), 1);
+        assert_eq!(OffchainModule::count_by_sealer(&bob_account), 0);
+
+        // the shuffle progressed during the turn of block 2 (bob)
+        System::set_block_number(3);
+        OffchainModule::note_shuffle_activity(&vote_id, &topic_id);
+        OffchainModule::on_initialize(4);
+        assert_eq!(OffchainModule::count_by_sealer(&bob_account), 0);
+        assert_eq!(OffchainModule::count(), 1);
+
+        // a completely shuffled vote is not checked anymore
+        assert_eq!(OffchainModule::tallying_votes(), vec![vote_id.clone()]);
+        let mut state = ShuffleStateStore::get((&vote_id, &topic_id)).unwrap();
+        state.done = true;
+        ShuffleStateStore::insert((&vote_id, &topic_id), state);
+        OffchainModule::on_initialize(5);
+        assert!(OffchainModule::tallying_votes().is_empty());
+    });
+}
+
+#[test]
+fn test_tallying_votes_are_bounded() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+        assert_eq!(OffchainModule::tallying_votes(), vec![vote_id.clone()]);
+
+        // at most two votes (see mock) are in the tallying phase at the same time
+        let other_vote_id = b"other vote".to_vec();
+        assert_ok!(OffchainModule::track_tallying_vote(&other_vote_id));
+        assert_err!(
+            OffchainModule::track_tallying_vote(&b"third vote".to_vec()),
+            Error::<TestRuntime>::TooManyTallyingVotes
+        );
+
+        // an aborted vote is not checked for missed turns anymore
+        assert_ok!(OffchainModule::abort_vote(
+            get_voting_authority(),
+            vote_id,
+            Vec::new()
+        ));
+        assert_eq!(OffchainModule::tallying_votes(), vec![other_vote_id]);
+    });
+}
+
+#[test]
+fn test_is_decryption_late() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        // the shuffling is not completed yet
+        System::set_block_number(20);
+        assert!(!OffchainModule::is_decryption_late(&vote_id, &topic_id));
+
+        // the shuffling is completed at block 20
+        let mut state = ShuffleStateStore::get((&vote_id, &topic_id)).unwrap();
+        state.done = true;
+        ShuffleStateStore::insert((&vote_id, &topic_id), state);
+        OffchainModule::note_shuffle_activity(&vote_id, &topic_id);
+
+        // the decryption period is 10 blocks
+        System::set_block_number(30);
+        assert!(!OffchainModule::is_decryption_late(&vote_id, &topic_id));
+        System::set_block_number(31);
+        assert!(OffchainModule::is_decryption_late(&vote_id, &topic_id));
+    });
+}
+
+#[test]
+fn test_abort_vote() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        let reason = "wrong question".as_bytes().to_vec();
+
+        // only a voting authority can abort a vote
+        let (bob, _, _) = get_sealer_bob();
+        assert_err!(
+            OffchainModule::abort_vote(bob, vote_id.clone(), reason.clone()),
+            Error::<TestRuntime>::NotAVotingAuthority
+        );
+
+        assert_ok!(OffchainModule::abort_vote(
+            get_voting_authority(),
+            vote_id.clone(),
+            reason.clone()
+        ));
+        assert_eq!(OffchainModule::votes(&vote_id).phase, VotePhase::Aborted);
+        assert_eq!(OffchainModule::abort_reason(&vote_id), Some(reason.clone()));
+        assert_eq!(OffchainModule::vote_ended_at(&vote_id), Some(1));
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::VoteAborted(
+                vote_id.clone(),
+                reason.clone()
+            ))));
+
+        // an aborted vote cannot be changed anymore
+        assert_err!(
+            OffchainModule::set_vote_phase(
+                get_voting_authority(),
+                vote_id.clone(),
+                VotePhase::Tallying
+            ),
+            Error::<TestRuntime>::VoteIsAborted
+        );
+        assert_err!(
+            OffchainModule::abort_vote(get_voting_authority(), vote_id, reason),
+            Error::<TestRuntime>::VoteIsAborted
+        );
+    });
+}
+
+#[test]
+fn test_set_vote_phase_aborted_not_allowed() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        assert_err!(
+            OffchainModule::set_vote_phase(
+                get_voting_authority(),
+                vote_id,
+                VotePhase::Aborted
+            ),
+            Error::<TestRuntime>::VoteIsAborted
+        );
+    });
+}
+
+#[test]
+fn test_purge_vote() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.clone().into());
+        let empty_weight = OffchainModule::purge_vote_weight(&vote_id);
+
+        // cast a ballot
+        let (voter, voter_account, _) = get_sealer_bob();
+        let big_cipher = BigCipher {
+            a: BigUint::one(),
+            b: BigUint::from(2u32),
+        };
+        let cipher = Cipher::encode_canonical(&big_cipher, &params);
+        let ballot = Ballot {
+            answers: vec![(topic_id.clone(), cipher.clone())],
+        };
+        assert_ok!(OffchainModule::cast_ballot(
+            voter,
+            vote_id.clone(),
+            ballot.clone()
+        ));
+
+        // the weight grows with the stored ballots and ciphers
+        assert!(OffchainModule::purge_vote_weight(&vote_id) > empty_weight);
+
+        // the vote has not ended yet
+        assert_err!(
+            OffchainModule::purge_vote(get_voting_authority(), vote_id.clone()),
+            Error::<TestRuntime>::VoteNotEnded
+        );
+
+        assert_ok!(OffchainModule::abort_vote(
+            get_voting_authority(),
+            vote_id.clone(),
+            Vec::new()
+        ));
+
+        // the retention period is 10 blocks
+        System::set_block_number(10);
+        assert_err!(
+            OffchainModule::purge_vote(get_voting_authority(), vote_id.clone()),
+            Error::<TestRuntime>::RetentionPeriodNotOver
+        );
+
+        System::set_block_number(11);
+        let transcript = OffchainModule::vote_transcript(&vote_id);
+        assert_eq!(transcript.ballots, vec![(voter_account, ballot)]);
+        assert_eq!(transcript.topics[0].ciphers, vec![(0, vec![cipher])]);
+        let commitment = OffchainModule::transcript_commitment_of(&transcript);
+        assert_ok!(OffchainModule::purge_vote(
+            get_voting_authority(),
+            vote_id.clone()
+        ));
+
+        // the intermediate data is removed, the vote and the commitment are kept
+        assert_eq!(
+            OffchainModule::transcript_commitment(&vote_id),
+            Some(commitment)
+        );
+        assert_eq!(
+            OffchainModule::ballots(&vote_id, voter_account),
+            Ballot::default()
+        );
+        assert!(OffchainModule::ciphers(&topic_id, 0).is_empty());
+        assert_eq!(OffchainModule::votes(&vote_id).phase, VotePhase::Aborted);
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::VotePurged(
+                vote_id.clone(),
+                commitment
+            ))));
+
+        // a vote can only be purged once
+        assert_err!(
+            OffchainModule::purge_vote(get_voting_authority(), vote_id),
+            Error::<TestRuntime>::VoteAlreadyPurged
+        );
+    });
+}
+
+fn yes_no_abstain() -> TopicDefinition {
+    TopicDefinition {
+        question_type: QuestionType::YesNoAbstain,
+        options: vec![b"yes".to_vec(), b"no".to_vec(), b"abstain".to_vec()],
+        encoding: OptionEncoding::Index,
+    }
+}
+
+#[test]
+fn test_define_topic() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+
+        // options can only be declared before the voting starts
+        assert_err!(
+            OffchainModule::define_topic(
+                get_voting_authority(),
+                vote_id.clone(),
+                topic_id.clone(),
+                yes_no_abstain()
+            ),
+            Error::<TestRuntime>::WrongVotePhase
+        );
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+
+        assert_err!(
+            OffchainModule::define_topic(
+                get_voting_authority(),
+                vote_id.clone(),
+                b"unknown".to_vec(),
+                yes_no_abstain()
+            ),
+            Error::<TestRuntime>::TopicDoesNotExist
+        );
+
+        assert_ok!(OffchainModule::define_topic(
+            get_voting_authority(),
+            vote_id.clone(),
+            topic_id.clone(),
+            yes_no_abstain()
+        ));
+        assert_eq!(
+            OffchainModule::topic_definition(&topic_id),
+            Some(yes_no_abstain())
+        );
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::TopicDefined(
+                vote_id.clone(),
+                topic_id.clone()
+            ))));
+    });
+}
+
+#[test]
+fn test_define_topic_invalid_definition() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+
+        let invalid_definitions = vec![
+            // yes/no/abstain requires exactly three options
+            TopicDefinition {
+                question_type: QuestionType::YesNoAbstain,
+                options: vec![b"yes".to_vec(), b"no".to_vec()],
+                encoding: OptionEncoding::Index,
+            },
+            // multiple selections cannot be encoded as index
+            TopicDefinition {
+                question_type: QuestionType::MultiChoice { min: 1, max: 2 },
+                options: vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+                encoding: OptionEncoding::Index,
+            },
+            // more selections than options
+            TopicDefinition {
+                question_type: QuestionType::MultiChoice { min: 1, max: 4 },
+                options: vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+                encoding: OptionEncoding::Bitmask,
+            },
+            // duplicate option names
+            TopicDefinition {
+                question_type: QuestionType::SingleChoice,
+                options: vec![b"a".to_vec(), b"a".to_vec()],
+                encoding: OptionEncoding::Index,
+            },
+        ];
+        for definition in invalid_definitions.into_iter() {
+            assert_err!(
+                OffchainModule::define_topic(
+                    get_voting_authority(),
+                    vote_id.clone(),
+                    topic_id.clone(),
+                    definition
+                ),
+                Error::<TestRuntime>::InvalidTopicDefinition
+            );
+        }
+    });
+}
+
+#[test]
+fn test_count_options_single_choice() {
+    let plaintexts: Vec<BigUint> = vec![0u32, 1, 0, 2, 3, 0]
+        .into_iter()
+        .map(BigUint::from)
+        .collect();
+    let (results, rejected) =
+        crate::dkg::options::count_options(&yes_no_abstain(), &plaintexts, &[1; 6]);
+
+    assert_eq!(results.get(&b"yes".to_vec()), Some(&vec![3u8]));
+    assert_eq!(results.get(&b"no".to_vec()), Some(&vec![1u8]));
+    assert_eq!(results.get(&b"abstain".to_vec()), Some(&vec![1u8]));
+    assert_eq!(
+        rejected,
+        RejectedVotes {
+            invalid: 0,
+            unknown: 1
+        }
+    );
+}
+
+#[test]
+fn test_count_options_multi_choice() {
+    let definition = TopicDefinition {
+        question_type: QuestionType::MultiChoice { min: 1, max: 2 },
+        options: vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+        encoding: OptionEncoding::Bitmask,
+    };
+    // 0b011: a + b, 0b100: c, 0b111: too many, 0b000: none, 0b1000: unknown option
+    let plaintexts: Vec<BigUint> = vec![0b011u32, 0b100, 0b111, 0b000, 0b1000]
+        .into_iter()
+        .map(BigUint::from)
+        .collect();
+    let (results, rejected) =
+        crate::dkg::options::count_options(&definition, &plaintexts, &[1; 5]);
+
+    assert_eq!(results.get(&b"a".to_vec()), Some(&vec![1u8]));
+    assert_eq!(results.get(&b"b".to_vec()), Some(&vec![1u8]));
+    assert_eq!(results.get(&b"c".to_vec()), Some(&vec![1u8]));
+    assert_eq!(
+        rejected,
+        RejectedVotes {
+            invalid: 2,
+            unknown: 1
+        }
+    );
+}
+
+#[test]
+fn test_count_options_weighted() {
+    let plaintexts: Vec<BigUint> =
+        vec![0u32, 1, 0, 3].into_iter().map(BigUint::from).collect();
+    let (results, rejected) = crate::dkg::options::count_options(
+        &yes_no_abstain(),
+        &plaintexts,
+        &[10, 5, 1, 7],
+    );
+
+    assert_eq!(results.get(&b"yes".to_vec()), Some(&vec![11u8]));
+    assert_eq!(results.get(&b"no".to_vec()), Some(&vec![5u8]));
+    assert_eq!(results.get(&b"abstain".to_vec()), Some(&vec![0u8]));
+    assert_eq!(rejected.unknown, 1);
+}
+
+#[test]
+fn test_encode_decode_ranking() {
+    let ranks = encode_ranking(&[2, 0, 1], 3);
+    assert_eq!(ranks.len(), 3);
+    assert_eq!(decode_ranking(&ranks, 3), Some(vec![2, 0, 1]));
+    let ranks = encode_ranking(&[1], 3);
+    assert_eq!(
+        ranks,
+        vec![BigUint::from(2u32), BigUint::zero(), BigUint::zero()]
+    );
+    assert_eq!(decode_ranking(&ranks, 3), Some(vec![1]));
+
+    // empty ranking, gap, duplicate preference and unknown option
+    let invalid_ranks: Vec<[u32; 3]> = vec![[0, 0, 0], [1, 0, 3], [1, 2, 1], [1, 4, 0]];
+    for ranks in invalid_ranks.iter() {
+        let ranks: Vec<BigUint> = ranks.iter().map(|rank| BigUint::from(*rank)).collect();
+        assert_eq!(decode_ranking(&ranks, 3), None);
+    }
+}
+
+#[test]
+fn test_decode_ranks() {
+    let (params, _, _) = Helper::setup_sm_system();
+    let g = &params.g;
+    let p = &params.p;
+    let encoded: Vec<BigUint> = vec![
+        g.modpow(&BigUint::from(3u32), p),
+        BigUint::one(),
+        g.modpow(&BigUint::from(1u32), p),
+        // the exponent of a rank is at most the number of options
+        g.modpow(&BigUint::from(4u32), p),
+    ];
+
+    let ranks = decode_ranks(&encoded, 3, g, p);
+    let expected: Vec<BigUint> =
+        vec![3u32, 0, 1, 4].into_iter().map(BigUint::from).collect();
+    assert_eq!(ranks, expected);
+    assert_eq!(decode_ranking(&ranks[..3], 3), None);
+}
+
+fn ranked_topic(method: RankingMethod) -> TopicDefinition {
+    TopicDefinition {
+        question_type: QuestionType::Ranked(method),
+        options: vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+        encoding: OptionEncoding::Ranking,
+    }
+}
+
+fn ranked_plaintexts() -> Vec<BigUint> {
+    vec![
+        encode_ranking(&[0], 3),
+        encode_ranking(&[0, 1], 3),
+        encode_ranking(&[1, 0], 3),
+        encode_ranking(&[1], 3),
+        encode_ranking(&[2, 1], 3),
+        encode_ranking(&[], 3),
+    ]
+    .concat()
+}
+
+#[test]
+fn test_tally_ranking_instant_runoff() {
+    let definition = ranked_topic(RankingMethod::InstantRunoff);
+    let (results, rejected, rounds) = tally_ranking(
+        &definition,
+        &RankingMethod::InstantRunoff,
+        &ranked_plaintexts(),
+        &[1; 6],
+    );
+
+    // round 1: a: 2, b: 2, c: 1 -> c is eliminated and its vote goes to b
+    assert_eq!(rounds.len(), 2);
+    assert_eq!(
+        rounds[0].counts,
+        vec![(b"a".to_vec(), 2), (b"b".to_vec(), 2), (b"c".to_vec(), 1)]
+    );
+    assert_eq!(rounds[0].eliminated, Some(b"c".to_vec()));
+    assert_eq!(
+        rounds[1].counts,
+        vec![(b"a".to_vec(), 2), (b"b".to_vec(), 3)]
+    );
+    assert_eq!(rounds[1].eliminated, None);
+
+    assert_eq!(results.get(&b"b".to_vec()), Some(&vec![3u8]));
+    assert_eq!(rejected.invalid, 1);
+}
+
+#[test]
+fn test_tally_ranking_borda() {
+    let definition = ranked_topic(RankingMethod::Borda);
+    let (results, rejected, rounds) = tally_ranking(
+        &definition,
+        &RankingMethod::Borda,
+        &ranked_plaintexts(),
+        &[1; 6],
+    );
+
+    assert_eq!(rounds.len(), 1);
+    assert_eq!(results.get(&b"a".to_vec()), Some(&vec![5u8]));
+    assert_eq!(results.get(&b"b".to_vec()), Some(&vec![6u8]));
+    assert_eq!(results.get(&b"c".to_vec()), Some(&vec![2u8]));
+    assert_eq!(rejected.invalid, 1);
+}
+
+#[test]
+fn test_tally_ranking_weighted() {
+    let definition = ranked_topic(RankingMethod::InstantRunoff);
+    let (results, _, rounds) = tally_ranking(
+        &definition,
+        &RankingMethod::InstantRunoff,
+        &ranked_plaintexts(),
+        &[3, 1, 1, 1, 1, 1],
+    );
+
+    // round 1: a: 4, b: 2, c: 1 -> a has a majority
+    assert_eq!(rounds.len(), 1);
+    assert_eq!(
+        rounds[0].counts,
+        vec![(b"a".to_vec(), 4), (b"b".to_vec(), 2), (b"c".to_vec(), 1)]
+    );
+    assert_eq!(results.get(&b"a".to_vec()), Some(&vec![4u8]));
+}
+
+#[test]
+fn test_define_ranked_topic() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, pk) = Helper::setup_sm_system();
+
+        // the ciphers of a ranking must be shuffled together
+        let (vote_id, topic_id) = setup_vote(params.clone().into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_err!(
+            OffchainModule::define_topic(
+                get_voting_authority(),
+                vote_id.clone(),
+                topic_id.clone(),
+                ranked_topic(RankingMethod::Borda)
+            ),
+            Error::<TestRuntime>::RankingNotSupported
+        );
+
+        // a ranked topic requires the ranking encoding and a limited number of options
+        let (vote_id, topic_ids) = setup_ballot_shuffling_vote(params.into(), &pk, 2);
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        let mut with_index = ranked_topic(RankingMethod::Borda);
+        with_index.encoding = OptionEncoding::Index;
+        let mut too_many_options = ranked_topic(RankingMethod::Borda);
+        too_many_options.options =
+            (0..=MAX_RANKED_OPTIONS as u8).map(|i| vec![i]).collect();
+        for definition in vec![with_index, too_many_options].into_iter() {
+            assert_err!(
+                OffchainModule::define_topic(
+                    get_voting_authority(),
+                    vote_id.clone(),
+                    topic_ids[0].clone(),
+                    definition
+                ),
+                Error::<TestRuntime>::InvalidTopicDefinition
+            );
+        }
+
+        assert_ok!(OffchainModule::define_topic(
+            get_voting_authority(),
+            vote_id,
+            topic_ids[0].clone(),
+            ranked_topic(RankingMethod::Borda)
+        ));
+    });
+}
+
+fn setup_ballot_shuffling_vote(
+    params: PublicParameters,
+    pk: &ElGamalPK,
+    batch_size: u64,
+) -> (VoteId, Vec<TopicId>) {
+    let (vote_id, topic_id) = setup_vote(params);
+    let second_topic_id = "20201212-02".as_bytes().to_vec();
+    let second_topic: Topic = (second_topic_id.clone(), "Count Q1?".as_bytes().to_vec());
+    set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+    assert_ok!(OffchainModule::store_question(
+        get_voting_authority(),
+        vote_id.clone(),
+        second_topic,
+        batch_size
+    ));
+    assert_ok!(OffchainModule::enable_ballot_shuffling(
+        get_voting_authority(),
+        vote_id.clone(),
+        batch_size
+    ));
+    setup_public_key(vote_id.clone(), pk.clone().into());
+    set_vote_phase(vote_id.clone(), VotePhase::Voting);
+    (vote_id, vec![topic_id, second_topic_id])
+}
+
+fn encrypted_ballot_row(messages: &[u32], pk: &ElGamalPK) -> Vec<BigCipher> {
+    let q = pk.params.q();
+    messages
+        .iter()
+        .map(|m| {
+            let r = OffchainModule::get_random_biguint_less_than(&q).unwrap();
+            ElGamal::encrypt_encode(&BigUint::from(*m), &r, pk)
+        })
+        .collect()
+}
+
+#[test]
+fn test_row_shuffle_proof() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (_, _, pk) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let rows: Vec<Vec<BigCipher>> = vec![
+            encrypted_ballot_row(&[1, 0], &pk),
+            encrypted_ballot_row(&[0, 1], &pk),
+            encrypted_ballot_row(&[1, 1], &pk),
+        ];
+
+        let (shuffled_rows, randoms, permutation) =
+            OffchainModule::shuffle_rows(&pk, rows.clone()).unwrap();
+        let proof = OffchainModule::generate_row_shuffle_proof(
+            &vote_id,
+            rows.clone(),
+            shuffled_rows.clone(),
+            randoms,
+            &permutation,
+            &pk,
+            &ProofContext::new(&vote_id),
+        )
+        .unwrap();
+        assert_eq!(proof.S.vec_s4.len(), 2);
+
+        let is_valid = OffchainModule::verify_row_shuffle_proof(
+            &vote_id,
+            proof.clone(),
+            rows.clone(),
+            shuffled_rows.clone(),
+            &pk,
+            &ProofContext::new(&vote_id),
+        )
+        .unwrap();
+        assert!(is_valid);
+
+        // swapping the answers of two ballots breaks the proof
+        let mut tampered_rows = shuffled_rows.clone();
+        let cipher = tampered_rows[0][1].clone();
+        tampered_rows[0][1] = tampered_rows[1][1].clone();
+        tampered_rows[1][1] = cipher;
+        let is_valid = OffchainModule::verify_row_shuffle_proof(
+            &vote_id,
+            proof.clone(),
+            rows.clone(),
+            tampered_rows,
+            &pk,
+            &ProofContext::new(&vote_id),
+        )
+        .unwrap();
+        assert!(!is_valid);
+
+        // a malformed proof is invalid
+        let mut malformed = proof;
+        malformed.S.vec_s4.pop();
+        let is_valid = OffchainModule::verify_row_shuffle_proof(
+            &vote_id,
+            malformed,
+            rows,
+            shuffled_rows,
+            &pk,
+            &ProofContext::new(&vote_id),
+        )
+        .unwrap();
+        assert!(!is_valid);
+    });
+}
+
+#[test]
+fn test_cast_ballot_incomplete_ballot_row() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_ids) = setup_ballot_shuffling_vote(params.into(), &pk, 2);
+        let (voter, _, _) = get_sealer_bob();
+
+        // the ballot only answers the first topic
+        let cipher: Cipher =
+            Cipher::encode_canonical(&encrypted_ballot_row(&[1], &pk)[0], &pk.params);
+        let ballot = Ballot {
+            answers: vec![(topic_ids[0].clone(), cipher.clone())],
+        };
+        assert_err!(
+            OffchainModule::cast_ballot(voter.clone(), vote_id.clone(), ballot),
+            Error::<TestRuntime>::InvalidBallotRow
+        );
+
+        // the ballot answers the first topic twice
+        let ballot = Ballot {
+            answers: vec![
+                (topic_ids[0].clone(), cipher.clone()),
+                (topic_ids[0].clone(), cipher),
+            ],
+        };
+        assert_err!(
+            OffchainModule::cast_ballot(voter, vote_id.clone(), ballot),
+            Error::<TestRuntime>::InvalidBallotRow
+        );
+        assert!(OffchainModule::ballot_rows(&vote_id, 0).is_empty());
+        assert!(OffchainModule::ciphers(&topic_ids[0], 0).is_empty());
+    });
+}
+
+#[test]
+fn test_submit_shuffled_ballots_and_proof() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_ids) = setup_ballot_shuffling_vote(params.into(), &pk, 2);
+        let (bob, _, _) = get_sealer_bob();
+
+        // cast three ballots, the answers are listed in reverse topic order
+        let messages: Vec<Vec<u32>> = vec![vec![1, 0], vec![0, 1], vec![1, 1]];
+        for (index, message) in messages.iter().enumerate() {
+            let row: Vec<Cipher> = Canonical::encode_canonical(
+                &encrypted_ballot_row(message, &pk),
+                &pk.params,
+            );
+            let ballot = Ballot {
+                answers: vec![
+                    (topic_ids[1].clone(), row[1].clone()),
+                    (topic_ids[0].clone(), row[0].clone()),
+                ],
+            };
+            let account: <TestRuntime as frame_system::Trait>::AccountId =
+                sp_core::sr25519::Public::from_raw([index as u8; 32]);
+            assert_ok!(OffchainModule::cast_ballot(
+                Origin::signed(account),
+                vote_id.clone(),
+                ballot
+            ));
+        }
+        assert_eq!(OffchainModule::ballot_rows(&vote_id, 0).len(), 3);
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        // the topics cannot be shuffled separately
+        let state = ShuffleStateStore::get((&vote_id, &topic_ids[0])).unwrap();
+        let payload: ShufflePayload = OffchainModule::offchain_shuffle_and_proof(
+            &vote_id,
+            &topic_ids[0],
+            state.iteration,
+            &pk,
+            state.start_position,
+            state.batch_size,
+        )
+        .unwrap();
+        assert_err!(
+            OffchainModule::submit_shuffled_votes_and_proof(
+                bob.clone(),
+                vote_id.clone(),
+                topic_ids[0].clone(),
+                payload
+            ),
+            Error::<TestRuntime>::BallotShufflingEnabled
+        );
+
+        // 3 ballots, batch size 2 -> 2 batches per iteration, 3 iterations
+        for _ in 0..6 {
+            let state: ShuffleState =
+                OffchainModule::ballot_shuffle_state(&vote_id).unwrap();
+            assert!(!state.done);
+            let payload = OffchainModule::offchain_shuffle_ballots_and_proof(
+                &vote_id,
+                state.iteration,
+                &pk,
+                state.start_position,
+                state.batch_size,
+            )
+            .unwrap();
+            assert_ok!(OffchainModule::submit_shuffled_ballots_and_proof(
+                bob.clone(),
+                vote_id.clone(),
+                payload
+            ));
+        }
+        let state: ShuffleState = OffchainModule::ballot_shuffle_state(&vote_id).unwrap();
+        assert!(state.done);
+        assert_eq!(state.iteration, 3);
+        assert!(System::events().iter().any(|er| er.event
+            == TestEvent::pallet_mixnet(RawEvent::BallotShuffleProofSubmitted(
+                vote_id.clone(),
+                get_sealer_bob().1
+            ))));
+
+        // the columns are the shuffled ciphers of the topics
+        // and the ciphers at the same position belong to the same ballot
+        let decrypt = |topic_id: &TopicId| -> Vec<BigUint> {
+            let ciphers: Vec<BigCipher> = OffchainModule::ciphers(topic_id, 3)
+                .decode_canonical(&pk.params)
+                .unwrap();
+            ciphers
+                .iter()
+                .map(|cipher| ElGamal::decrypt_decode(cipher, &sk))
+                .collect()
+        };
+        let first = decrypt(&topic_ids[0]);
+        let second = decrypt(&topic_ids[1]);
+        let mut shuffled_messages: Vec<(BigUint, BigUint)> =
+            first.into_iter().zip(second.into_iter()).collect();
+        shuffled_messages.sort();
+        let mut expected: Vec<(BigUint, BigUint)> = messages
+            .iter()
+            .map(|m| (BigUint::from(m[0]), BigUint::from(m[1])))
+            .collect();
+        expected.sort();
+        assert_eq!(shuffled_messages, expected);
+        for topic_id in topic_ids.iter() {
+            let state = ShuffleStateStore::get((&vote_id, topic_id)).unwrap();
+            assert!(state.done);
+        }
+
+        // the shuffle is completed
+        let payload =
+            OffchainModule::offchain_shuffle_ballots_and_proof(&vote_id, 2, &pk, 0, 2)
+                .unwrap();
+        assert_err!(
+            OffchainModule::submit_shuffled_ballots_and_proof(
+                bob,
+                vote_id.clone(),
+                payload
+            ),
+            Error::<TestRuntime>::ShuffleAlreadyCompleted
+        );
+
+        // the rows are part of the transcript
+        let transcript = OffchainModule::vote_transcript(&vote_id);
+        assert_eq!(transcript.ballot_rows.len(), 4);
+        assert_eq!(transcript.ballot_shuffle_proofs.len(), 6);
+    });
+}
+
+#[test]
+fn test_homomorphic_tally() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_md_system();
+        let (vote_id, topic_id) = setup_vote(params.clone().into());
+        let voting_authority = get_voting_authority();
+
+        // the topic can only be tallied homomorphically before the voting starts
+        assert_err!(
+            OffchainModule::enable_homomorphic_tally(
+                voting_authority.clone(),
+                vote_id.clone(),
+                topic_id.clone()
+            ),
+            Error::<TestRuntime>::WrongVotePhase
+        );
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_err!(
+            OffchainModule::enable_homomorphic_tally(
+                voting_authority.clone(),
+                vote_id.clone(),
+                "unknown".as_bytes().to_vec()
+            ),
+            Error::<TestRuntime>::TopicDoesNotExist
+        );
+        assert_ok!(OffchainModule::enable_homomorphic_tally(
+            voting_authority.clone(),
+            vote_id.clone(),
+            topic_id.clone()
+        ));
+
+        // the topic is not shuffled and cannot be part of a ballot row
+        assert!(ShuffleStateStore::get((&vote_id, &topic_id)).is_none());
+        assert_err!(
+            OffchainModule::enable_ballot_shuffling(
+                voting_authority.clone(),
+                vote_id.clone(),
+                2
+            ),
+            Error::<TestRuntime>::HomomorphicTallyEnabled
+        );
+        let expected_event = TestEvent::pallet_mixnet(RawEvent::HomomorphicTallyEnabled(
+            vote_id.clone(),
+            topic_id.clone(),
+        ));
+        assert!(System::events().iter().any(|er| er.event == expected_event));
+
+        // Distributed Key Generation: Bob & Charlie
+        let (bob, _, bob_sealer_id) = get_sealer_bob();
+        let bob_sk_x = BigUint::parse_bytes(b"12345678", 10).unwrap();
+        let (bob_pk, bob_sk) = Helper::generate_key_pair(&params, &bob_sk_x);
+        setup_sealer(
+            &params,
+            &bob_sk,
+            &bob_pk,
+            bob.clone(),
+            &vote_id,
+            &bob_sealer_id,
+        );
+        let (charlie, _, charlie_sealer_id) = get_sealer_charlie();
+        let charlie_sk_x = BigUint::parse_bytes(b"87654321", 10).unwrap();
+        let (charlie_pk, charlie_sk) = Helper::generate_key_pair(&params, &charlie_sk_x);
+        setup_sealer(
+            &params,
+            &charlie_sk,
+            &charlie_pk,
+            charlie.clone(),
+            &vote_id,
+            &charlie_sealer_id,
+        );
+        assert_ok!(OffchainModule::combine_public_key_shares(
+            voting_authority.clone(),
+            vote_id.clone()
+        ));
+        let system_pk: ElGamalPK =
+            OffchainModule::public_key(vote_id.clone()).unwrap().into();
+
+        // cast the votes: 3x yes (1), 2x no (0)
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+        let voter = Origin::signed(Default::default());
+        let q = params.q();
+        for message in [1u32, 0, 1, 1, 0].iter() {
+            let r = OffchainModule::get_random_biguint_less_than(&q).unwrap();
+            let cipher: BigCipher =
+                ElGamal::encrypt_encode(&BigUint::from(*message), &r, &system_pk);
+            let cipher = Cipher::encode_canonical(&cipher, &params);
+            let ballot = Ballot {
+                answers: vec![(topic_id.clone(), cipher)],
+            };
+            assert_ok!(OffchainModule::cast_ballot(
+                voter.clone(),
+                vote_id.clone(),
+                ballot
+            ));
+        }
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        // the sum is decrypted by each sealer using a single decrypted share
+        let sum: BigCipher = OffchainModule::encrypted_sum(&topic_id)
+            .unwrap()
+            .decode_canonical(&params)
+            .unwrap();
+        let r = Secret::new(BigUint::parse_bytes(b"1234123123", 10).unwrap());
+        for (who, sk, pk, sealer_id) in [
+            (bob, bob_sk, bob_pk, bob_sealer_id),
+            (charlie, charlie_sk, charlie_pk, charlie_sealer_id),
+        ]
+        .iter()
+        {
+            let share: BigUint = ElGamal::partial_decrypt_a(&sum, sk);
+            let proof = DecryptionProof::generate(
+                &params,
+                &sk.x,
+                &pk.h,
+                &r,
+                vec![sum.clone()],
+                vec![share.clone()],
+                sealer_id,
+                &ProofContext::new(&vote_id),
+            );
+            let proof: DecryptedShareProof = Canonical::encode_canonical(&proof, &params);
+            assert_err!(
+                OffchainModule::submit_decrypted_shares(
+                    who.clone(),
+                    vote_id.clone(),
+                    topic_id.clone(),
+                    vec![params.encode_value(&share); 2],
+                    proof.clone(),
+                    vec![],
+                    NR_OF_SHUFFLES
+                ),
+                Error::<TestRuntime>::WrongNumberOfDecryptedShares
+            );
+            assert_ok!(OffchainModule::submit_decrypted_shares(
+                who.clone(),
+                vote_id.clone(),
+                topic_id.clone(),
+                vec![params.encode_value(&share)],
+                proof,
+                vec![],
+                NR_OF_SHUFFLES
+            ));
+        }
+
+        // combine the decrypted shares of the sum + tally the topic
+        assert_ok!(OffchainModule::combine_decrypted_shares(
+            voting_authority,
+            vote_id,
+            topic_id.clone(),
+            true,
+            NR_OF_SHUFFLES
+        ));
+        let result: TopicResult = OffchainModule::tally(topic_id).unwrap();
+        let mut expected: TopicResult = BTreeMap::new();
+        expected.insert(
+            BigUint::zero().to_bytes_be(),
+            BigUint::from(2u32).to_bytes_be(),
+        );
+        expected.insert(
+            BigUint::from(1u32).to_bytes_be(),
+            BigUint::from(3u32).to_bytes_be(),
+        );
+        assert_eq!(result, expected);
+    });
+}
+
+#[test]
+fn test_homomorphic_tally_definition() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_ok!(OffchainModule::enable_homomorphic_tally(
+            get_voting_authority(),
+            vote_id.clone(),
+            topic_id.clone()
+        ));
+
+        // the sum can only count a choice between two options
+        assert_err!(
+            OffchainModule::define_topic(
+                get_voting_authority(),
+                vote_id.clone(),
+                topic_id.clone(),
+                yes_no_abstain()
+            ),
+            Error::<TestRuntime>::InvalidTopicDefinition
+        );
+        assert_ok!(OffchainModule::define_topic(
+            get_voting_authority(),
+            vote_id,
+            topic_id,
+            TopicDefinition {
+                question_type: QuestionType::SingleChoice,
+                options: vec!["no".as_bytes().to_vec(), "yes".as_bytes().to_vec()],
+                encoding: OptionEncoding::Index,
+            }
+        ));
+    });
+}
+
+/// stores the decrypted shares of a topic without proofs:
+/// bob decrypts using the private key, charlie's shares are neutral (1)
+fn insert_decrypted_shares(topic_id: &TopicId, ciphers: &[BigCipher], sk: &PrivateKey) {
+    let (_, bob, _) = get_sealer_bob();
+    let (_, charlie, _) = get_sealer_charlie();
+    let bob_shares: Vec<DecryptedShare> = ciphers
+        .iter()
+        .map(|cipher| {
+            sk.params
+                .encode_value(&ElGamal::partial_decrypt_a(cipher, sk))
+        })
+        .collect();
+    let charlie_shares: Vec<DecryptedShare> =
+        vec![sk.params.encode_value(&BigUint::one()); ciphers.len()];
+    DecryptedShares::<TestRuntime>::insert(topic_id, bob, bob_shares);
+    DecryptedShares::<TestRuntime>::insert(topic_id, charlie, charlie_shares);
+}
+
+fn get_voter(index: u8) -> <TestRuntime as frame_system::Trait>::AccountId {
+    sp_core::sr25519::Public::from_raw([index; 32])
+}
+
+#[test]
+fn test_set_voter_weight() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        let voting_authority = get_voting_authority();
+
+        // weights can only be set before the voting starts
+        assert_err!(
+            OffchainModule::set_voter_weight(
+                voting_authority.clone(),
+                vote_id.clone(),
+                get_voter(1),
+                5
+            ),
+            Error::<TestRuntime>::WrongVotePhase
+        );
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+
+        // the weights cannot be applied to topics which are shuffled separately
+        assert_err!(
+            OffchainModule::set_voter_weight(
+                voting_authority.clone(),
+                vote_id.clone(),
+                get_voter(1),
+                5
+            ),
+            Error::<TestRuntime>::WeightedVoteNotSupported
+        );
+        assert_ok!(OffchainModule::enable_homomorphic_tally(
+            voting_authority.clone(),
+            vote_id.clone(),
+            topic_id
+        ));
+        assert_err!(
+            OffchainModule::set_voter_weight(
+                voting_authority.clone(),
+                vote_id.clone(),
+                get_voter(1),
+                0
+            ),
+            Error::<TestRuntime>::InvalidVoterWeight
+        );
+        assert_ok!(OffchainModule::set_voter_weight(
+            voting_authority.clone(),
+            vote_id.clone(),
+            get_voter(1),
+            5
+        ));
+        assert_eq!(
+            OffchainModule::voter_weight(&vote_id, get_voter(1)),
+            Some(5)
+        );
+        assert!(OffchainModule::is_weighted_vote(&vote_id));
+        let expected_event = TestEvent::pallet_mixnet(RawEvent::VoterWeightSet(
+            vote_id.clone(),
+            get_voter(1),
+            5,
+        ));
+        assert!(System::events().iter().any(|er| er.event == expected_event));
+
+        // a topic which is shuffled cannot be added to the weighted vote
+        let topic: Topic = ("20201212-02".as_bytes().to_vec(), b"Q2?".to_vec());
+        assert_err!(
+            OffchainModule::store_question(voting_authority, vote_id, topic, 2),
+            Error::<TestRuntime>::WeightedVoteNotSupported
+        );
+    });
+}
+
+#[test]
+fn test_weighted_homomorphic_tally() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        let voting_authority = get_voting_authority();
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_ok!(OffchainModule::enable_homomorphic_tally(
+            voting_authority.clone(),
+            vote_id.clone(),
+            topic_id.clone()
+        ));
+        for (index, weight) in [(0u8, 10u64), (2, 3)].iter() {
+            assert_ok!(OffchainModule::set_voter_weight(
+                voting_authority.clone(),
+                vote_id.clone(),
+                get_voter(*index),
+                *weight
+            ));
+        }
+        setup_public_key(vote_id.clone(), pk.clone().into());
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+
+        // weights: 10, 1 (default), 3 -> yes: 10 + 3, no: 1
+        for (index, message) in [1u32, 0, 1].iter().enumerate() {
+            let row: Vec<Cipher> = Canonical::encode_canonical(
+                &encrypted_ballot_row(&[*message], &pk),
+                &pk.params,
+            );
+            let ballot = Ballot {
+                answers: vec![(topic_id.clone(), row[0].clone())],
+            };
+            assert_ok!(OffchainModule::cast_ballot(
+                Origin::signed(get_voter(index as u8)),
+                vote_id.clone(),
+                ballot
+            ));
+        }
+        assert_eq!(OffchainModule::summed_weight(&topic_id), 14);
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        let sum: BigCipher = OffchainModule::encrypted_sum(&topic_id)
+            .unwrap()
+            .decode_canonical(&pk.params)
+            .unwrap();
+        insert_decrypted_shares(&topic_id, &[sum], &sk);
+        assert_ok!(OffchainModule::combine_decrypted_shares(
+            voting_authority,
+            vote_id,
+            topic_id.clone(),
+            true,
+            NR_OF_SHUFFLES
+        ));
+
+        let result: TopicResult = OffchainModule::tally(topic_id).unwrap();
+        let mut expected: TopicResult = BTreeMap::new();
+        expected.insert(
+            BigUint::zero().to_bytes_be(),
+            BigUint::from(1u32).to_bytes_be(),
+        );
+        expected.insert(
+            BigUint::from(1u32).to_bytes_be(),
+            BigUint::from(13u32).to_bytes_be(),
+        );
+        assert_eq!(result, expected);
+    });
+}
+
+#[test]
+fn test_weighted_ballot_shuffling() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_ids) = setup_ballot_shuffling_vote(params.into(), &pk, 2);
+        let voting_authority = get_voting_authority();
+        let (bob, _, _) = get_sealer_bob();
+
+        // voter 0 has the weight 5, voter 1 the default weight 1 and voter 2 the weight 2
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        for (index, weight) in [(0u8, 5u64), (2, 2)].iter() {
+            assert_ok!(OffchainModule::set_voter_weight(
+                voting_authority.clone(),
+                vote_id.clone(),
+                get_voter(*index),
+                *weight
+            ));
+        }
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+
+        // the encrypted weight is appended to the ballot row
+        let messages: Vec<Vec<u32>> = vec![vec![1, 0], vec![0, 1], vec![1, 1]];
+        for (index, message) in messages.iter().enumerate() {
+            let row: Vec<Cipher> = Canonical::encode_canonical(
+                &encrypted_ballot_row(message, &pk),
+                &pk.params,
+            );
+            let ballot = Ballot {
+                answers: vec![
+                    (topic_ids[0].clone(), row[0].clone()),
+                    (topic_ids[1].clone(), row[1].clone()),
+                ],
+            };
+            assert_ok!(OffchainModule::cast_ballot(
+                Origin::signed(get_voter(index as u8)),
+                vote_id.clone(),
+                ballot
+            ));
+        }
+        let rows = OffchainModule::ballot_rows(&vote_id, 0);
+        assert!(rows.iter().all(|row| row.len() == 3));
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        // 3 ballots, batch size 2 -> 2 batches per iteration, 3 iterations
+        for _ in 0..6 {
+            let state: ShuffleState =
+                OffchainModule::ballot_shuffle_state(&vote_id).unwrap();
+            let payload = OffchainModule::offchain_shuffle_ballots_and_proof(
+                &vote_id,
+                state.iteration,
+                &pk,
+                state.start_position,
+                state.batch_size,
+            )
+            .unwrap();
+            assert_ok!(OffchainModule::submit_shuffled_ballots_and_proof(
+                bob.clone(),
+                vote_id.clone(),
+                payload
+            ));
+        }
+        assert!(OffchainModule::ballot_shuffle_state(&vote_id).unwrap().done);
+
+        // the weights are shuffled together with the answers
+        let weight_topic_id = crate::dkg::weights::weight_topic_id(&vote_id);
+        let weights: Vec<BigCipher> = OffchainModule::ciphers(&weight_topic_id, 3)
+            .decode_canonical(&pk.params)
+            .unwrap();
+        let answers: Vec<BigCipher> = OffchainModule::ciphers(&topic_ids[0], 3)
+            .decode_canonical(&pk.params)
+            .unwrap();
+        insert_decrypted_shares(&weight_topic_id, &weights, &sk);
+        insert_decrypted_shares(&topic_ids[0], &answers, &sk);
+        let mut decrypted_weights =
+            crate::dkg::weights::decrypt_weights::<TestRuntime>(&vote_id, &3).unwrap();
+        decrypted_weights.sort();
+        assert_eq!(decrypted_weights, vec![1, 2, 5]);
+
+        // topic 1: 1 (weight 5), 0 (weight 1), 1 (weight 2)
+        assert_ok!(OffchainModule::combine_decrypted_shares(
+            voting_authority,
+            vote_id.clone(),
+            topic_ids[0].clone(),
+            true,
+            3
+        ));
+        let result: TopicResult = OffchainModule::tally(&topic_ids[0]).unwrap();
+        let mut expected: TopicResult = BTreeMap::new();
+        expected.insert(
+            BigUint::zero().to_bytes_be(),
+            BigUint::from(1u32).to_bytes_be(),
+        );
+        expected.insert(
+            BigUint::from(1u32).to_bytes_be(),
+            BigUint::from(7u32).to_bytes_be(),
+        );
+        assert_eq!(result, expected);
+
+        // the shuffled weights are part of the transcript
+        let transcript = OffchainModule::vote_transcript(&vote_id);
+        assert!(transcript
+            .topics
+            .iter()
+            .any(|topic| topic.topic_id == weight_topic_id));
+    });
+}
+
+fn write_in_topic(max_length: u32) -> TopicDefinition {
+    TopicDefinition {
+        question_type: QuestionType::WriteIn { max_length },
+        options: Vec::new(),
+        encoding: OptionEncoding::Text,
+    }
+}
+
+fn encrypted_write_in(
+    text: &[u8],
+    nr_of_elements: usize,
+    pk: &ElGamalPK,
+) -> Vec<BigCipher> {
+    let q = pk.params.q();
+    Encoding::encode_bytes_padded(text, nr_of_elements, &pk.params.p)
+        .unwrap()
+        .iter()
+        .map(|m| {
+            let r = OffchainModule::get_random_biguint_less_than(&q).unwrap();
+            ElGamal::encrypt(m, &r, pk)
+        })
+        .collect()
+}
+
+#[test]
+fn test_count_write_ins() {
+    let (params, _, _) = Helper::setup_sm_system();
+    let p = params.p;
+
+    // 48bit p -> 5 bytes per element -> 2 elements per answer
+    let answers: Vec<&[u8]> = vec![b"alice", b"bob", b"alice", b"", &[0xff, 0xfe]];
+    let mut plaintexts: Vec<BigUint> = answers
+        .iter()
+        .flat_map(|text| Encoding::encode_bytes_padded(text, 2, &p).unwrap())
+        .collect();
+    // an answer without padding cannot be decoded
+    plaintexts.extend(vec![BigUint::from(42u32), BigUint::from(42u32)]);
+
+    let (results, rejected) =
+        crate::dkg::write_in::count_write_ins(8, &plaintexts, &[1, 1, 2, 1, 1, 1], 2, &p);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.get(&b"alice".to_vec()), Some(&vec![3u8]));
+    assert_eq!(results.get(&b"bob".to_vec()), Some(&vec![1u8]));
+    assert_eq!(rejected.invalid, 2);
+    assert_eq!(rejected.unknown, 1);
+}
+
+#[test]
+fn test_define_write_in_topic() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, pk) = Helper::setup_sm_system();
+
+        // the ciphers of a write-in must be shuffled together
+        let (vote_id, topic_id) = setup_vote(params.clone().into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_err!(
+            OffchainModule::define_topic(
+                get_voting_authority(),
+                vote_id.clone(),
+                topic_id.clone(),
+                write_in_topic(8)
+            ),
+            Error::<TestRuntime>::WriteInNotSupported
+        );
+
+        // write-ins have no options and a limited length
+        let (vote_id, topic_ids) = setup_ballot_shuffling_vote(params.into(), &pk, 2);
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        let mut with_options = write_in_topic(8);
+        with_options.options = vec![b"a".to_vec(), b"b".to_vec()];
+        let invalid_definitions = vec![
+            write_in_topic(0),
+            write_in_topic(crate::dkg::write_in::MAX_WRITE_IN_LENGTH + 1),
+            with_options,
+            TopicDefinition {
+                question_type: QuestionType::WriteIn { max_length: 8 },
+                options: Vec::new(),
+                encoding: OptionEncoding::Index,
+            },
+        ];
+        for definition in invalid_definitions.into_iter() {
+            assert_err!(
+                OffchainModule::define_topic(
+                    get_voting_authority(),
+                    vote_id.clone(),
+                    topic_ids[1].clone(),
+                    definition
+                ),
+                Error::<TestRuntime>::InvalidTopicDefinition
+            );
+        }
+        assert_ok!(OffchainModule::define_topic(
+            get_voting_authority(),
+            vote_id.clone(),
+            topic_ids[1].clone(),
+            write_in_topic(8)
+        ));
+    });
+}
+
+#[test]
+fn test_write_in_ballot_shuffling() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_ids) = setup_ballot_shuffling_vote(params.into(), &pk, 2);
+        let voting_authority = get_voting_authority();
+        let (bob, _, _) = get_sealer_bob();
+
+        // 8 bytes + padding -> 2 elements of 5 bytes per write-in
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_ok!(OffchainModule::define_topic(
+            voting_authority.clone(),
+            vote_id.clone(),
+            topic_ids[1].clone(),
+            write_in_topic(8)
+        ));
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+
+        // a write-in must consist of exactly 2 ciphers
+        let answer: Vec<Cipher> =
+            Canonical::encode_canonical(&encrypted_ballot_row(&[1], &pk), &pk.params);
+        let write_in: Vec<Cipher> =
+            Canonical::encode_canonical(&encrypted_write_in(b"bob", 1, &pk), &pk.params);
+        let ballot = Ballot {
+            answers: vec![
+                (topic_ids[0].clone(), answer[0].clone()),
+                (topic_ids[1].clone(), write_in[0].clone()),
+            ],
+        };
+        assert_err!(
+            OffchainModule::cast_ballot(
+                Origin::signed(get_voter(0)),
+                vote_id.clone(),
+                ballot
+            ),
+            Error::<TestRuntime>::InvalidBallotRow
+        );
+
+        let texts: Vec<&[u8]> = vec![b"alice", b"bob", b"alice"];
+        for (index, text) in texts.iter().enumerate() {
+            let answer: Vec<Cipher> =
+                Canonical::encode_canonical(&encrypted_ballot_row(&[1], &pk), &pk.params);
+            let write_in: Vec<Cipher> = Canonical::encode_canonical(
+                &encrypted_write_in(text, 2, &pk),
+                &pk.params,
+            );
+            let mut answers = vec![(topic_ids[0].clone(), answer[0].clone())];
+            answers.extend(write_in.into_iter().map(|c| (topic_ids[1].clone(), c)));
+            assert_ok!(OffchainModule::cast_ballot(
+                Origin::signed(get_voter(index as u8)),
+                vote_id.clone(),
+                Ballot { answers }
+            ));
+        }
+        let rows = OffchainModule::ballot_rows(&vote_id, 0);
+        assert!(rows.iter().all(|row| row.len() == 3));
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        // 3 ballots, batch size 2 -> 2 batches per iteration, 3 iterations
+        for _ in 0..6 {
+            let state: ShuffleState =
+                OffchainModule::ballot_shuffle_state(&vote_id).unwrap();
+            let payload = OffchainModule::offchain_shuffle_ballots_and_proof(
+                &vote_id,
+                state.iteration,
+                &pk,
+                state.start_position,
+                state.batch_size,
+            )
+            .unwrap();
+            assert_ok!(OffchainModule::submit_shuffled_ballots_and_proof(
+                bob.clone(),
+                vote_id.clone(),
+                payload
+            ));
+        }
+        assert!(OffchainModule::ballot_shuffle_state(&vote_id).unwrap().done);
+
+        // the ciphers of each write-in are stored consecutively
+        let write_ins: Vec<BigCipher> = OffchainModule::ciphers(&topic_ids[1], 3)
+            .decode_canonical(&pk.params)
+            .unwrap();
+        assert_eq!(write_ins.len(), 6);
+        insert_decrypted_shares(&topic_ids[1], &write_ins, &sk);
+
+        // write-ins are not exponentially encoded, even if the tally asks for it
+        assert_ok!(OffchainModule::combine_decrypted_shares(
+            voting_authority,
+            vote_id.clone(),
+            topic_ids[1].clone(),
+            true,
+            3
+        ));
+        let result: TopicResult = OffchainModule::tally(&topic_ids[1]).unwrap();
+        let mut expected: TopicResult = BTreeMap::new();
+        expected.insert(b"alice".to_vec(), vec![2u8]);
+        expected.insert(b"bob".to_vec(), vec![1u8]);
+        assert_eq!(result, expected);
+    });
+}
+
+fn submit_decryption(
+    sealer: (Origin, &[u8]),
+    key_pair: (&ElGamalPK, &PrivateKey),
+    vote_id: &VoteId,
+    topic_id: &TopicId,
+    nr_of_shuffles: NrOfShuffles,
+) {
+    let (who, sealer_id) = sealer;
+    let (pk, sk) = key_pair;
+    let ciphers: Vec<BigCipher> = OffchainModule::ciphers(topic_id, nr_of_shuffles)
+        .decode_canonical(&pk.params)
+        .unwrap();
+    let partial_decryptions: Vec<BigUint> = ciphers
+        .iter()
+        .map(|cipher| ElGamal::partial_decrypt_a(cipher, sk))
+        .collect();
+    let shares: Vec<DecryptedShare> = partial_decryptions
+        .iter()
+        .map(|c| pk.params.encode_value(c))
+        .collect();
+    let r = Secret::new(BigUint::parse_bytes(b"1234123123", 10).unwrap());
+    let (proof, commitments) = DecryptionProof::generate_with_commitments(
+        &pk.params,
+        &sk.x,
+        &pk.h,
+        &r,
+        ciphers,
+        partial_decryptions,
+        sealer_id,
+        &ProofContext::new(&vote_id),
+    );
+    let commitments: Vec<Vec<u8>> = commitments
+        .iter()
+        .map(|t| pk.params.encode_value(t))
+        .collect();
+    assert_ok!(OffchainModule::submit_decrypted_shares(
+        who,
+        vote_id.clone(),
+        topic_id.clone(),
+        shares,
+        Canonical::encode_canonical(&proof, &pk.params),
+        commitments,
+        nr_of_shuffles
+    ));
+}
+
+/// a vote with two sealers, four ballots, one shuffle iteration and a tally
+fn setup_tallied_election() -> (
+    VoteId,
+    TopicId,
+    ElectionRecord<<TestRuntime as frame_system::Trait>::AccountId>,
+) {
+    let (params, _, _) = Helper::setup_sm_system();
+    let (vote_id, topic_id) = setup_vote(params.clone().into());
+
+    // distributed key generation
+    let (bob, _, bob_sealer_id) = get_sealer_bob();
+    let bob_sk_x = BigUint::parse_bytes(b"12345678", 10).unwrap();
+    let (bob_pk, bob_sk) = Helper::generate_key_pair(&params, &bob_sk_x);
+    setup_sealer(
+        &params,
+        &bob_sk,
+        &bob_pk,
+        bob.clone(),
+        &vote_id,
+        &bob_sealer_id,
+    );
+    let (charlie, _, charlie_sealer_id) = get_sealer_charlie();
+    let charlie_sk_x = BigUint::parse_bytes(b"87654321", 10).unwrap();
+    let (charlie_pk, charlie_sk) = Helper::generate_key_pair(&params, &charlie_sk_x);
+    setup_sealer(
+        &params,
+        &charlie_sk,
+        &charlie_pk,
+        charlie.clone(),
+        &vote_id,
+        &charlie_sealer_id,
+    );
+    let voting_authority = get_voting_authority();
+    assert_ok!(OffchainModule::combine_public_key_shares(
+        voting_authority.clone(),
+        vote_id.clone()
+    ));
+    let pk: ElGamalPK = OffchainModule::public_key(&vote_id).unwrap().into();
+
+    // four voters
+    set_vote_phase(vote_id.clone(), VotePhase::Voting);
+    for (index, message) in [1u32, 3, 4, 1].iter().enumerate() {
+        let r = BigUint::from(1000u32 + index as u32);
+        let cipher = ElGamal::encrypt(&BigUint::from(*message), &r, &pk);
+        let cipher: Cipher = Cipher::encode_canonical(&cipher, &pk.params);
+        assert_ok!(OffchainModule::cast_ballot(
+            Origin::signed(get_voter(index as u8)),
+            vote_id.clone(),
+            Ballot {
+                answers: vec![(topic_id.clone(), cipher)]
+            }
+        ));
+    }
+    set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+    // 4 ciphers, batch size 2 -> 2 batches for the first shuffle iteration
+    for _ in 0..2 {
+        let state: ShuffleState = ShuffleStateStore::get((&vote_id, &topic_id)).unwrap();
+        let payload: ShufflePayload = OffchainModule::offchain_shuffle_and_proof(
+            &vote_id,
+            &topic_id,
+            state.iteration,
+            &pk,
+            state.start_position,
+            state.batch_size,
+        )
+        .unwrap();
+        assert_ok!(OffchainModule::submit_shuffled_votes_and_proof(
+            bob.clone(),
+            vote_id.clone(),
+            topic_id.clone(),
+            payload
+        ));
+    }
+
+    // both sealers decrypt the shuffled ciphers
+    submit_decryption(
+        (bob, &bob_sealer_id),
+        (&bob_pk, &bob_sk),
+        &vote_id,
+        &topic_id,
+        1,
+    );
+    submit_decryption(
+        (charlie, &charlie_sealer_id),
+        (&charlie_pk, &charlie_sk),
+        &vote_id,
+        &topic_id,
+        1,
+    );
+    assert_ok!(OffchainModule::combine_decrypted_shares(
+        voting_authority,
+        vote_id.clone(),
+        topic_id.clone(),
+        false,
+        1
+    ));
+
+    let record = OffchainModule::election_record(&vote_id);
+    (vote_id, topic_id, record)
+}
+
+#[test]
+fn test_verify_election_record() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (_, topic_id, record) = setup_tallied_election();
+        assert_eq!(record.transcript.topics[0].shuffle_proofs.len(), 2);
+        assert_eq!(record.transcript.topics[0].decryption_proofs.len(), 2);
+        assert_eq!(record.transcript.topics[0].decryption_commitments.len(), 2);
+        assert_eq!(record.tallies[0].0, topic_id);
+        assert_eq!(OffchainModule::verify_election_record(&record), Ok(()));
+
+        // the record survives an encoding round trip (i.e. it can be exported)
+        let decoded = ElectionRecord::decode(&mut &record.encode()[..])
+            .expect("decodes the record");
+        assert_eq!(OffchainModule::verify_election_record(&decoded), Ok(()));
+    });
+}
+
+#[test]
+fn test_verify_election_record_reports_failing_step() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (_, topic_id, record) = setup_tallied_election();
+        let (_, bob, _) = get_sealer_bob();
+
+        // a public key share which does not match its key generation proof
+        let mut tampered = record.clone();
+        let share = tampered
+            .public_key_shares
+            .iter_mut()
+            .find(|(sealer, _)| *sealer == bob)
+            .unwrap();
+        share.1.pk = vec![4u8];
+        assert_eq!(
+            OffchainModule::verify_election_record(&tampered),
+            Err(VerificationError::InvalidKeyGenerationProof(bob))
+        );
+
+        // a submitted cipher which is not part of any ballot
+        let mut tampered = record.clone();
+        tampered.transcript.topics[0].ciphers[0].1[0] = Cipher {
+            a: vec![1u8],
+            b: vec![2u8],
+        };
+        assert_eq!(
+            OffchainModule::verify_election_record(&tampered),
+            Err(VerificationError::BallotsMismatch(topic_id.clone()))
+        );
+
+        // an invalid shuffle proof
+        let mut tampered = record.clone();
+        let params: ElGamalParams = record.vote.params.clone().into();
+        let payload = &mut tampered.transcript.topics[0].shuffle_proofs[1];
+        let mut proof: Proof = payload.proof.decode_canonical(&params).unwrap();
+        proof.challenge = BigUint::one();
+        payload.proof = Canonical::encode_canonical(&proof, &params);
+        assert_eq!(
+            OffchainModule::verify_election_record(&tampered),
+            Err(VerificationError::InvalidShuffleProof(topic_id.clone(), 1))
+        );
+
+        // shuffled ciphers which are not the output of the shuffle proofs
+        let mut tampered = record.clone();
+        tampered.transcript.topics[0].ciphers[1].1.swap(0, 1);
+        assert_eq!(
+            OffchainModule::verify_election_record(&tampered),
+            Err(VerificationError::ShuffledCiphersMismatch(
+                topic_id.clone(),
+                1
+            ))
+        );
+
+        // a decrypted share which does not match the decryption proof
+        let mut tampered = record.clone();
+        let shares = tampered.transcript.topics[0]
+            .decrypted_shares
+            .iter_mut()
+            .find(|(sealer, _)| *sealer == bob)
+            .unwrap();
+        shares.1[0] = vec![1u8];
+        assert_eq!(
+            OffchainModule::verify_election_record(&tampered),
+            Err(VerificationError::InvalidDecryptionProof(
+                topic_id.clone(),
+                bob.clone()
+            ))
+        );
+
+        // commitments which do not belong to the decryption proof
+        let mut tampered = record.clone();
+        let commitments = tampered.transcript.topics[0]
+            .decryption_commitments
+            .iter_mut()
+            .find(|(sealer, _)| *sealer == bob)
+            .unwrap();
+        commitments.1.swap(0, 1);
+        assert_eq!(
+            OffchainModule::verify_election_record(&tampered),
+            Err(VerificationError::InvalidDecryptionProof(
+                topic_id.clone(),
+                bob
+            ))
+        );
+
+        // a tally which does not match the decrypted ciphers
+        let mut tampered = record;
+        tampered.tallies[0].1.insert(vec![2u8], vec![1u8]);
+        assert_eq!(
+            OffchainModule::verify_election_record(&tampered),
+            Err(VerificationError::TallyMismatch(topic_id))
+        );
+    });
+}
@@ -1,12 +1,13 @@
 use alloc::str::FromStr;
-use codec::{Decode, Encode};
+use codec::{Decode, Encode, EncodeLike, Error as CodecError, Input, Output};
 use crypto::proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof};
+use crypto::scale::{DecodeWith, EncodeWith};
 use crypto::types::{Cipher as BigCipher, ElGamalParams, PublicKey as ElGamalPK};
 use frame_system::offchain::{SignedPayload, SigningTypes};
 use num_bigint::BigUint;
-use num_traits::One;
+use num_traits::{One, Zero};
 use sp_runtime::RuntimeDebug;
-use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, vec::Vec};
 
 /// the BigCipher from the crypto crate.
 /// different types which the blockchain can handle.
@@ -16,46 +17,121 @@ pub struct Cipher {
     pub b: Vec<u8>,
 }
 
-impl Into<Cipher> for BigCipher {
-    fn into(self) -> Cipher {
+/// the canonical encoding of the values of a vote (see `ElGamalParams::encode_value`).
+/// every value is encoded with the fixed width of ⌈|p|/8⌉ bytes and decoding rejects
+/// any other encoding and values ≥ p, i.e. every cipher has exactly one encoding.
+pub trait Canonical: Sized {
+    type Value;
+
+    fn encode_canonical(value: &Self::Value, params: &ElGamalParams) -> Self;
+
+    /// returns None if the value is not canonically encoded
+    fn decode_canonical(&self, params: &ElGamalParams) -> Option<Self::Value>;
+}
+
+impl Canonical for Cipher {
+    type Value = BigCipher;
+
+    fn encode_canonical(cipher: &BigCipher, params: &ElGamalParams) -> Self {
         Cipher {
-            a: self.a.to_bytes_be(),
-            b: self.b.to_bytes_be(),
+            a: params.encode_value(&cipher.a),
+            b: params.encode_value(&cipher.b),
         }
     }
+
+    fn decode_canonical(&self, params: &ElGamalParams) -> Option<BigCipher> {
+        Some(BigCipher {
+            a: params.decode_element(&self.a)?,
+            b: params.decode_element(&self.b)?,
+        })
+    }
 }
 
-impl Into<BigCipher> for Cipher {
-    fn into(self) -> BigCipher {
-        BigCipher {
-            a: BigUint::from_bytes_be(&self.a),
-            b: BigUint::from_bytes_be(&self.b),
+/// a list of ciphers or ballot rows is encoded value by value,
+/// the decoding fails if any of the values is not canonically encoded
+impl<T: Canonical> Canonical for Vec<T> {
+    type Value = Vec<T::Value>;
+
+    fn encode_canonical(values: &Vec<T::Value>, params: &ElGamalParams) -> Self {
+        values
+            .iter()
+            .map(|value| T::encode_canonical(value, params))
+            .collect::<Vec<T>>()
+    }
+
+    fn decode_canonical(&self, params: &ElGamalParams) -> Option<Vec<T::Value>> {
+        self.iter()
+            .map(|value| value.decode_canonical(params))
+            .collect::<Option<Vec<T::Value>>>()
+    }
+}
+
+/// a value of the crypto crate (e.g. a proof) in its encoding with the fixed width of the
+/// vote's parameters (see `crypto::scale::EncodeWith`). the encoding depends on the parameters,
+/// therefore the value is only decoded at the point of use (see `Canonical`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Encoded<T> {
+    pub bytes: Vec<u8>,
+    value: PhantomData<T>,
+}
+
+impl<T> Default for Encoded<T> {
+    fn default() -> Self {
+        Encoded {
+            bytes: Vec::new(),
+            value: PhantomData,
         }
     }
 }
 
-/// required to perform into() conversion for trait Vec
-/// for Vec<Cipher> is not allowed, since trait Vec is not defined here
-pub struct Wrapper<T>(pub Vec<T>);
+/// the SCALE encoding of an encoded value is the encoding of its bytes
+impl<T> Encode for Encoded<T> {
+    fn size_hint(&self) -> usize {
+        self.bytes.size_hint()
+    }
 
-impl Into<Vec<BigCipher>> for Wrapper<Cipher> {
-    fn into(self) -> Vec<BigCipher> {
-        self.0
-            .into_iter()
-            .map(|v| v.into())
-            .collect::<Vec<BigCipher>>()
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        self.bytes.encode_to(dest)
     }
 }
 
-impl Into<Vec<Cipher>> for Wrapper<BigCipher> {
-    fn into(self) -> Vec<Cipher> {
-        self.0
-            .into_iter()
-            .map(|v| v.into())
-            .collect::<Vec<Cipher>>()
+impl<T> EncodeLike for Encoded<T> {}
+
+impl<T> Decode for Encoded<T> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, CodecError> {
+        Ok(Encoded {
+            bytes: Vec::<u8>::decode(input)?,
+            value: PhantomData,
+        })
     }
 }
 
+/// the decoding fails if the value is not canonically encoded or if bytes are left over
+impl<T: EncodeWith + DecodeWith> Canonical for Encoded<T> {
+    type Value = T;
+
+    fn encode_canonical(value: &T, params: &ElGamalParams) -> Self {
+        Encoded {
+            bytes: value.encode_with(params),
+            value: PhantomData,
+        }
+    }
+
+    fn decode_canonical(&self, params: &ElGamalParams) -> Option<T> {
+        let mut input: &[u8] = &self.bytes;
+        let value = T::decode_with(&mut input, params).ok()?;
+        if input.is_empty() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+/// a row of ciphers which is shuffled as one unit,
+/// i.e. the answers of a ballot in the order of the vote's topics
+pub type BallotRow = Vec<Cipher>;
+
 /// the PublicKey from the crypto crate.
 /// different types which the blockchain can handle.
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
@@ -67,8 +143,8 @@ pub struct PublicKey {
 impl Into<PublicKey> for ElGamalPK {
     fn into(self) -> PublicKey {
         PublicKey {
+            h: self.params.encode_value(&self.h),
             params: self.params.into(),
-            h: self.h.to_bytes_be(),
         }
     }
 }
@@ -82,6 +158,15 @@ impl Into<ElGamalPK> for PublicKey {
     }
 }
 
+impl PublicKey {
+    /// returns None if the parameters or the key are not canonically encoded
+    pub fn decode_canonical(&self) -> Option<ElGamalPK> {
+        let params: ElGamalParams = self.params.decode_canonical()?;
+        let h: BigUint = params.decode_element(&self.h)?;
+        Some(ElGamalPK { params, h })
+    }
+}
+
 /// the ElGamalParams from the crypto crate.
 /// different types which the blockchain can handle.
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
@@ -125,8 +210,8 @@ impl Into<PublicParameters> for ElGamalParams {
     fn into(self) -> PublicParameters {
         PublicParameters {
             p: self.p.to_bytes_be(),
-            g: self.g.to_bytes_be(),
-            h: self.h.to_bytes_be(),
+            g: self.encode_value(&self.g),
+            h: self.encode_value(&self.h),
         }
     }
 }
@@ -141,122 +226,38 @@ impl Into<ElGamalParams> for PublicParameters {
     }
 }
 
-/// Algorithm 8.47: The s value of the ShuffleProof
-#[derive(Default, Clone, PartialEq, Eq, Debug)]
-pub struct BigS {
-    pub s1: BigUint,               // s1
-    pub s2: BigUint,               // s2
-    pub s3: BigUint,               // s3
-    pub s4: BigUint,               // s4
-    pub vec_s_hat: Vec<BigUint>,   // vec_s_hat
-    pub vec_s_tilde: Vec<BigUint>, // vec_s_tilde
-}
-
-#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
-pub struct BigSAsBytes {
-    pub s1: Vec<u8>,               // s1
-    pub s2: Vec<u8>,               // s2
-    pub s3: Vec<u8>,               // s3
-    pub s4: Vec<u8>,               // s4
-    pub vec_s_hat: Vec<Vec<u8>>,   // vec_s_hat
-    pub vec_s_tilde: Vec<Vec<u8>>, // vec_s_tilde
-}
-
-impl Into<BigS> for BigSAsBytes {
-    fn into(self) -> BigS {
-        BigS {
-            s1: BigUint::from_bytes_be(&self.s1),
-            s2: BigUint::from_bytes_be(&self.s2),
-            s3: BigUint::from_bytes_be(&self.s3),
-            s4: BigUint::from_bytes_be(&self.s4),
-            vec_s_hat: self
-                .vec_s_hat
-                .iter()
-                .map(|v| BigUint::from_bytes_be(v))
-                .collect::<Vec<BigUint>>(),
-            vec_s_tilde: self
-                .vec_s_tilde
-                .iter()
-                .map(|v| BigUint::from_bytes_be(v))
-                .collect::<Vec<BigUint>>(),
-        }
-    }
-}
-
-impl Into<BigSAsBytes> for BigS {
-    fn into(self) -> BigSAsBytes {
-        BigSAsBytes {
-            s1: self.s1.to_bytes_be(),
-            s2: self.s2.to_bytes_be(),
-            s3: self.s3.to_bytes_be(),
-            s4: self.s4.to_bytes_be(),
-            vec_s_hat: self
-                .vec_s_hat
-                .into_iter()
-                .map(|v| v.to_bytes_be())
-                .collect::<Vec<Vec<u8>>>(),
-            vec_s_tilde: self
-                .vec_s_tilde
-                .into_iter()
-                .map(|v| v.to_bytes_be())
-                .collect::<Vec<Vec<u8>>>(),
+impl PublicParameters {
+    /// returns None if p has leading zeros or the generators are not canonically encoded
+    pub fn decode_canonical(&self) -> Option<ElGamalParams> {
+        if self.p.first().map_or(true, |byte| *byte == 0) {
+            return None;
         }
+        let mut params = ElGamalParams {
+            p: BigUint::from_bytes_be(&self.p),
+            g: BigUint::zero(),
+            h: BigUint::zero(),
+        };
+        params.g = params.decode_element(&self.g)?;
+        params.h = params.decode_element(&self.h)?;
+        Some(params)
     }
 }
 
-/// Algorithm 8.47: The ShuffleProof
-#[derive(Default, Clone, PartialEq, Eq, Debug)]
-pub struct ShuffleProof {
-    pub challenge: BigUint,                          // challenge
-    pub S: BigS,                                     // S
-    pub permutation_commitments: Vec<BigUint>,       // permutation_commitments
-    pub permutation_chain_commitments: Vec<BigUint>, // permutation_chain_commitments
-}
+/// Algorithm 8.47: the ShuffleProof (of ciphers and of rows) from the crypto crate.
+/// the proofs are submitted and stored with the fixed width of the vote's parameters (see `Encoded`).
+pub use crypto::types::{
+    BigRowS, BigRowShuffleProof as RowShuffleProof, BigS, BigShuffleProof as ShuffleProof,
+};
 
+// the payload submitted after shuffling a batch of ballot rows in an offchain worker
+// contains the shuffle proof and the shuffled rows
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
-pub struct ShuffleProofAsBytes {
-    pub challenge: Vec<u8>,                          // challenge
-    pub S: BigSAsBytes,                              // S
-    pub permutation_commitments: Vec<Vec<u8>>,       // permutation_commitments
-    pub permutation_chain_commitments: Vec<Vec<u8>>, // permutation_chain_commitments
-}
-
-impl Into<ShuffleProof> for ShuffleProofAsBytes {
-    fn into(self) -> ShuffleProof {
-        ShuffleProof {
-            challenge: BigUint::from_bytes_be(&self.challenge),
-            S: self.S.into(),
-            permutation_commitments: self
-                .permutation_commitments
-                .iter()
-                .map(|v| BigUint::from_bytes_be(v))
-                .collect::<Vec<BigUint>>(),
-            permutation_chain_commitments: self
-                .permutation_chain_commitments
-                .iter()
-                .map(|v| BigUint::from_bytes_be(v))
-                .collect::<Vec<BigUint>>(),
-        }
-    }
-}
-
-impl Into<ShuffleProofAsBytes> for ShuffleProof {
-    fn into(self) -> ShuffleProofAsBytes {
-        ShuffleProofAsBytes {
-            challenge: self.challenge.to_bytes_be(),
-            S: self.S.into(),
-            permutation_commitments: self
-                .permutation_commitments
-                .into_iter()
-                .map(|v| v.to_bytes_be())
-                .collect::<Vec<Vec<u8>>>(),
-            permutation_chain_commitments: self
-                .permutation_chain_commitments
-                .into_iter()
-                .map(|v| v.to_bytes_be())
-                .collect::<Vec<Vec<u8>>>(),
-        }
-    }
+pub struct RowShufflePayload {
+    pub iteration: u8,
+    pub rows: Vec<BallotRow>,
+    pub proof: Encoded<RowShuffleProof>,
+    pub start_position: u64,
+    pub batch_size: u64,
 }
 
 // the payload submitted after performing a shuffle proof in an offchain worker
@@ -265,7 +266,7 @@ impl Into<ShuffleProofAsBytes> for ShuffleProof {
 pub struct ShufflePayload {
     pub iteration: u8,
     pub ciphers: Vec<Cipher>,
-    pub proof: ShuffleProofAsBytes,
+    pub proof: Encoded<ShuffleProof>,
     pub start_position: u64,
     pub batch_size: u64,
 }
@@ -295,6 +296,76 @@ pub type TopicResult = BTreeMap<Plaintext, Count>;
 // topicId and question (string as Vec<u8>)
 pub type Topic = (TopicId, TopicQuestion);
 
+/// The name of an answer option (string as Vec<u8>)
+pub type OptionName = Vec<u8>;
+
+/// The type of question a topic asks.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum QuestionType {
+    /// exactly one option can be selected
+    SingleChoice,
+    /// at least `min` and at most `max` options can be selected (k-of-n)
+    MultiChoice { min: u32, max: u32 },
+    /// exactly one of the three options: yes, no or abstain
+    YesNoAbstain,
+    /// the options are ranked by preference and counted using the given method
+    Ranked(RankingMethod),
+    /// a free-text answer of at most `max_length` bytes (no declared options)
+    WriteIn { max_length: u32 },
+}
+
+/// The counting method of a ranked (preferential) question.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum RankingMethod {
+    /// instant-runoff voting: the option with the fewest first preferences
+    /// is eliminated until an option has a majority of the remaining ballots
+    InstantRunoff,
+    /// borda count: an option ranked at position i (0-based) of n options receives n - 1 - i points
+    Borda,
+}
+
+/// How the selected options are encoded in a plaintext.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum OptionEncoding {
+    /// the plaintext is the index of the selected option
+    Index,
+    /// bit i of the plaintext is set if option i is selected
+    Bitmask,
+    /// the answer consists of one exponentially encoded plaintext per rank (n = # of options).
+    /// the plaintext d > 0 ranks option d - 1, the plaintext 0 ends the ranking.
+    Ranking,
+    /// the text is split into several plaintexts, each one a quadratic residue
+    /// (see `crypto::encoding::Encoding`). the plaintexts are not exponentially encoded.
+    Text,
+}
+
+/// The declared answer options of a topic, its question type and encoding.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct TopicDefinition {
+    pub question_type: QuestionType,
+    pub options: Vec<OptionName>,
+    pub encoding: OptionEncoding,
+}
+
+/// A round of a ranked tally: the count (or points) per option,
+/// the number of ballots without any remaining preference
+/// and the option eliminated at the end of the round (instant-runoff only).
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct RankedRound {
+    pub counts: Vec<(OptionName, u64)>,
+    pub exhausted: u64,
+    pub eliminated: Option<OptionName>,
+}
+
+/// The number of plaintexts which could not be counted for an option.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct RejectedVotes {
+    /// plaintexts which do not respect the question type (e.g. too many selections)
+    pub invalid: u64,
+    /// plaintexts which do not map to any declared option
+    pub unknown: u64,
+}
+
 /// A ballot is composed of all answers of a voter
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
 pub struct Ballot {
@@ -306,6 +377,7 @@ pub enum VotePhase {
     KeyGeneration,
     Voting,
     Tallying,
+    Aborted,
 }
 
 // Default defines the starting value when VotePhase is created
@@ -322,6 +394,7 @@ impl FromStr for VotePhase {
             "KeyGeneration" => Ok(VotePhase::KeyGeneration),
             "Voting" => Ok(VotePhase::Voting),
             "Tallying" => Ok(VotePhase::Tallying),
+            "Aborted" => Ok(VotePhase::Aborted),
             _ => Err(()),
         }
     }
@@ -337,31 +410,141 @@ pub struct Vote<AccountId> {
     pub params: PublicParameters,
 }
 
-// the public key generation proof submitted by the sealer -> this prooves knowledge of a secret key that belongs to the submitted public key
+/// The transcript of a topic which is removed when a vote is purged.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct TopicTranscript<AccountId> {
+    pub topic_id: TopicId,
+    /// the ciphers of every shuffle iteration (iteration 0: the submitted ballots)
+    pub ciphers: Vec<(NrOfShuffles, Vec<Cipher>)>,
+    pub shuffle_proofs: Vec<ShufflePayload>,
+    /// the encrypted sum of the votes (only if the topic is tallied homomorphically)
+    pub encrypted_sum: Option<Cipher>,
+    /// the decrypted shares of every sealer (in the order of the vote's sealers)
+    pub decrypted_shares: Vec<(AccountId, Vec<DecryptedShare>)>,
+    /// the proof of the decrypted shares of every sealer which submitted shares
+    pub decryption_proofs: Vec<(AccountId, NrOfShuffles, DecryptedShareProof)>,
+    /// the commitments of the decryption proof of every sealer which submitted them
+    pub decryption_commitments: Vec<(AccountId, Vec<DecryptedShareCommitment>)>,
+}
+
+/// The transcript of a vote which is removed when a vote is purged.
+/// The blake2_256 hash of its SCALE encoding is kept on-chain as commitment.
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
-pub struct PublicKeyShareProof {
-    pub challenge: Vec<u8>,
-    pub response: Vec<u8>,
+pub struct VoteTranscript<AccountId> {
+    pub vote_id: VoteId,
+    pub public_key_shares: Vec<PublicKeyShare>,
+    /// the ballots of all voters (sorted by voter)
+    pub ballots: Vec<(AccountId, Ballot)>,
+    pub topics: Vec<TopicTranscript<AccountId>>,
+    /// the ballot rows of every shuffle iteration (only if ballots are shuffled as rows)
+    pub ballot_rows: Vec<(NrOfShuffles, Vec<BallotRow>)>,
+    pub ballot_shuffle_proofs: Vec<RowShufflePayload>,
+}
+
+/// The public record of a vote: the vote, its key generation, its transcript and its tally.
+/// Everything required to verify a vote end to end (see `Module::verify_election_record`).
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct ElectionRecord<AccountId> {
+    pub vote: Vote<AccountId>,
+    pub topics: Vec<Topic>,
+    /// the declared answer options of the topics which have been defined
+    pub definitions: Vec<(TopicId, TopicDefinition)>,
+    pub sealers: Vec<AccountId>,
+    /// the public key share of every sealer together with its key generation proof
+    pub public_key_shares: Vec<(AccountId, PublicKeyShare)>,
+    pub public_key: Option<PublicKey>,
+    /// whether the ballots are shuffled as rows (see `enable_ballot_shuffling`)
+    pub ballot_shuffling: bool,
+    /// whether the ballots are counted with the weights of the voters
+    pub weighted: bool,
+    pub voter_weights: Vec<(AccountId, VoterWeight)>,
+    pub tallies: Vec<(TopicId, TopicResult)>,
+    pub transcript: VoteTranscript<AccountId>,
+    /// the version of the Fiat-Shamir transform of the proofs (see `crypto::helper::ProofVersion`)
+    pub proof_version: u8,
+}
+
+/// The first step at which the verification of an election record failed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum VerificationError<AccountId> {
+    /// the proofs use an unknown version of the Fiat-Shamir transform. [version]
+    UnsupportedProofVersion(u8),
+    /// the vote has no public key
+    MissingPublicKey,
+    /// the key generation proof of a sealer is invalid. [sealer]
+    InvalidKeyGenerationProof(AccountId),
+    /// the public key is not the combination of the public key shares of all sealers
+    PublicKeyMismatch,
+    /// the submitted ciphers of a topic do not match the ballots. [topic_id]
+    BallotsMismatch(TopicId),
+    /// the submitted ballot rows do not match the ballots
+    BallotRowsMismatch,
+    /// the encrypted sum of a topic does not match the ballots. [topic_id]
+    EncryptedSumMismatch(TopicId),
+    /// a shuffle proof of a topic is invalid. [topic_id, index of the proof]
+    InvalidShuffleProof(TopicId, u32),
+    /// the shuffled ciphers of a topic do not match its shuffle proofs. [topic_id, iteration]
+    ShuffledCiphersMismatch(TopicId, NrOfShuffles),
+    /// a shuffle proof of the ballot rows is invalid. [index of the proof]
+    InvalidRowShuffleProof(u32),
+    /// the shuffled ballot rows do not match their shuffle proofs. [iteration]
+    ShuffledBallotRowsMismatch(NrOfShuffles),
+    /// a sealer submitted decrypted shares without a proof. [topic_id, sealer]
+    MissingDecryptionProof(TopicId, AccountId),
+    /// the decryption proof of a sealer is invalid. [topic_id, sealer]
+    InvalidDecryptionProof(TopicId, AccountId),
+    /// the tally of a topic does not match its decrypted ciphers. [topic_id]
+    TallyMismatch(TopicId),
+}
+
+/// A critical action of a voting authority which can require the approval
+/// of multiple voting authorities before it is executed (M-of-N).
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum AuthorityAction {
+    /// set the phase of a vote. [vote_id, phase]
+    SetVotePhase(VoteId, VotePhase),
+    /// combine the public key shares of a vote. [vote_id]
+    CombinePublicKeyShares(VoteId),
+    /// combine the decrypted shares of a topic. [vote_id, topic_id, encoded, nr_of_shuffles]
+    CombineDecryptedShares(VoteId, TopicId, bool, NrOfShuffles),
 }
 
-impl Into<PublicKeyShareProof> for KeyGenerationProof {
-    fn into(self) -> PublicKeyShareProof {
-        PublicKeyShareProof {
-            challenge: self.challenge.to_bytes_be(),
-            response: self.response.to_bytes_be(),
-        }
-    }
+pub type ProposalId = u64;
+
+/// The weight with which the ballot of a voter is counted.
+pub type VoterWeight = u64;
+
+/// A proposed authority action together with the voting authorities
+/// which approved it and whether it has already been executed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Proposal<AccountId> {
+    pub action: AuthorityAction,
+    pub approvals: Vec<AccountId>,
+    pub executed: bool,
 }
 
-impl Into<KeyGenerationProof> for PublicKeyShareProof {
-    fn into(self) -> KeyGenerationProof {
-        KeyGenerationProof {
-            challenge: BigUint::from_bytes_be(&self.challenge),
-            response: BigUint::from_bytes_be(&self.response),
-        }
-    }
+/// The kinds of sealer misbehaviour which are recorded on-chain.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Misbehaviour {
+    /// a key generation, shuffle or decryption proof failed to verify
+    InvalidProof,
+    /// no shuffle was submitted during the sealer's turn
+    MissedTurn,
+    /// the decrypted shares were submitted after the decryption period
+    LateDecryption,
+}
+
+/// The misbehaviour record of a sealer for a vote.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct MisbehaviourRecord {
+    pub invalid_proofs: u32,
+    pub missed_turns: u32,
+    pub late_decryptions: u32,
 }
 
+// the public key generation proof submitted by the sealer -> this prooves knowledge of a secret key that belongs to the submitted public key
+pub type PublicKeyShareProof = Encoded<KeyGenerationProof>;
+
 // the public key share submitted by each sealer to generated the system's public key
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
 pub struct PublicKeyShare {
@@ -371,29 +554,12 @@ pub struct PublicKeyShare {
 
 pub type DecryptedShare = Vec<u8>;
 
-#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
-pub struct DecryptedShareProof {
-    pub challenge: Vec<u8>,
-    pub response: Vec<u8>,
-}
+// the decryption proof submitted by the sealer together with the decrypted shares
+pub type DecryptedShareProof = Encoded<DecryptionProof>;
 
-impl From<DecryptionProof> for DecryptedShareProof {
-    fn from(source: DecryptionProof) -> Self {
-        DecryptedShareProof {
-            challenge: source.challenge.to_bytes_be(),
-            response: source.response.to_bytes_be(),
-        }
-    }
-}
-
-impl From<DecryptedShareProof> for DecryptionProof {
-    fn from(source: DecryptedShareProof) -> Self {
-        DecryptionProof {
-            challenge: BigUint::from_bytes_be(&source.challenge),
-            response: BigUint::from_bytes_be(&source.response),
-        }
-    }
-}
+// a commitment (t_0, t_1, ..., t_n) of the decryption proof (canonically encoded)
+// the commitments allow to verify the proofs of many sealers at once (see `DecryptionProof::verify_batch`)
+pub type DecryptedShareCommitment = Vec<u8>;
 
 /// the type to sign and send transactions.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
@@ -272,6 +272,16 @@ impl pallet_sudo::Trait for Runtime {
 
 parameter_types! {
     pub const BlockDuration: BlockNumber = 1u64;
+    // sealers do not need to bond, set a non-zero amount together with the slash amount
+    pub const SealerBond: Balance = 0;
+    // slashing is disabled, set a non-zero amount to slash misbehaving sealers
+    pub const MisbehaviourSlash: Balance = 0;
+    // sealers have 100 blocks (~10 minutes) to submit their decrypted shares
+    pub const DecryptionPeriod: BlockNumber = 100u64;
+    // intermediate vote data is kept for 14400 blocks (~1 day) after a vote ended
+    pub const RetentionPeriod: BlockNumber = 14400u64;
+    // at most 10 votes are shuffled at the same time
+    pub const MaxTallyingVotes: u32 = 10;
 }
 
 impl pallet_mixnet::Trait for Runtime {
@@ -279,6 +289,14 @@ impl pallet_mixnet::Trait for Runtime {
     type Call = Call;
     type AuthorityId = pallet_mixnet::keys::TestAuthId;
     type BlockDuration = BlockDuration;
+    // voting authorities and sealers are managed via sudo
+    type ManagementOrigin = frame_system::EnsureRoot<AccountId>;
+    type Currency = Balances;
+    type SealerBond = SealerBond;
+    type MisbehaviourSlash = MisbehaviourSlash;
+    type DecryptionPeriod = DecryptionPeriod;
+    type RetentionPeriod = RetentionPeriod;
+    type MaxTallyingVotes = MaxTallyingVotes;
 }
 
 // Payload data to be signed when making signed transaction from off-chain workers
@@ -0,0 +1,671 @@
+//! Export of an election record in a format compatible with the ElectionGuard election record.
+//!
+//! The record of a vote is written as a single JSON document. Its sections and field names
+//! follow the ElectionGuard election record schema (1.0) as closely as possible:
+//!
+//! | Section              | ElectionGuard                 | Provotum                                |
+//! |----------------------|-------------------------------|-----------------------------------------|
+//! | `constants`          | `constants.json`              | the vote's group parameters (p, q, g)   |
+//! | `manifest`           | `manifest.json`               | the vote, its topics and their options  |
+//! | `context`            | `context.json`                | the sealers and the combined public key |
+//! | `guardians`          | `guardians/*.json`            | the sealers' public key shares + proofs |
+//! | `submitted_ballots`  | `submitted_ballots/*.json`    | the encrypted ballots (all cast)        |
+//! | `mix_rounds`         | - (extension)                 | the shuffle proofs in submission order  |
+//! | `encrypted_tally`    | `encrypted_tally.json`        | the encrypted sums (homomorphic topics) |
+//! | `decryption_shares`  | `plaintext_tally.json` shares | the decrypted shares + proofs           |
+//! | `plaintext_tally`    | `plaintext_tally.json`        | the tally of every topic                |
+//!
+//! Differences to ElectionGuard:
+//! - a contest (topic) is answered with one cipher per ballot (several for a write-in),
+//!   not with one cipher per selection. the selections of a submitted ballot are these ciphers.
+//! - the shares of a sealer are proven by a single batch proof per topic, not per selection.
+//! - the Schnorr proofs only contain the challenge and the response, the commitment can be
+//!   recomputed from them. every proof is bound to the SCALE encoded account of its sealer,
+//!   which is used as `guardian_id`. from proof version 2 on, the challenges are computed
+//!   with a labelled transcript bound to the vote id and the group (see `proof_version`).
+//! - the record does not contain the ElectionGuard hashes (base hash, extended base hash).
+//!
+//! All group elements and exponents are uppercase hex strings (big-endian).
+
+use crate::Record;
+use codec::Encode;
+use num_bigint::BigUint;
+use pallet_mixnet::types::{
+    Canonical, Cipher, DecryptedShareProof, PublicKeyShareProof, QuestionType,
+    RankingMethod, RowShuffleProof, ShuffleProof as MixnetShuffleProof, TopicDefinition,
+    TopicId,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+
+/// The election record of a vote in the ElectionGuard format.
+#[derive(Debug, Serialize)]
+pub struct ElectionRecord {
+    pub constants: Constants,
+    pub manifest: Manifest,
+    pub context: Context,
+    pub guardians: Vec<Guardian>,
+    pub submitted_ballots: Vec<SubmittedBallot>,
+    pub mix_rounds: Vec<MixRound>,
+    pub encrypted_tally: Vec<EncryptedContest>,
+    pub decryption_shares: Vec<DecryptionShare>,
+    pub plaintext_tally: PlaintextTally,
+}
+
+/// The group of the vote: p = 2q + 1, g generates the subgroup of order q.
+#[derive(Debug, Serialize)]
+pub struct Constants {
+    pub large_prime: String,
+    pub small_prime: String,
+    pub cofactor: String,
+    pub generator: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Manifest {
+    /// the vote id
+    pub election_scope_id: String,
+    pub spec_version: String,
+    /// the title of the vote
+    pub name: String,
+    pub candidates: Vec<Candidate>,
+    /// one contest per topic (in the order of the topics)
+    pub contests: Vec<Contest>,
+}
+
+/// An option of a topic (ElectionGuard has no separate notion of an option).
+#[derive(Debug, Serialize)]
+pub struct Candidate {
+    pub object_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Contest {
+    /// the topic id
+    pub object_id: String,
+    pub sequence_order: usize,
+    /// the question of the topic
+    pub ballot_title: String,
+    pub vote_variation: String,
+    pub number_elected: u32,
+    pub votes_allowed: u32,
+    /// the declared options (empty if the topic is not defined or a write-in)
+    pub ballot_selections: Vec<Selection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Selection {
+    pub object_id: String,
+    pub sequence_order: usize,
+    pub candidate_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Context {
+    pub number_of_guardians: usize,
+    /// all sealers are required to decrypt
+    pub quorum: usize,
+    /// the combined public key of all sealers
+    pub elgamal_public_key: String,
+    /// the version of the Fiat-Shamir transform of all proofs (extension)
+    pub proof_version: u8,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Guardian {
+    /// the SCALE encoded account of the sealer (hex)
+    pub guardian_id: String,
+    pub sequence_order: usize,
+    /// the public key share of the sealer
+    pub election_public_key: String,
+    pub election_proofs: Vec<SchnorrProof>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchnorrProof {
+    pub public_key: String,
+    pub challenge: String,
+    pub response: String,
+    pub usage: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmittedBallot {
+    /// the SCALE encoded account of the voter (hex)
+    pub object_id: String,
+    pub state: String,
+    /// the weight with which the ballot is counted (extension)
+    pub weight: u64,
+    pub contests: Vec<EncryptedContest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncryptedContest {
+    /// the topic id
+    pub object_id: String,
+    pub ballot_selections: Vec<EncryptedSelection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncryptedSelection {
+    pub object_id: String,
+    pub sequence_order: usize,
+    pub ciphertext: Ciphertext,
+}
+
+/// An ElGamal cipher (a, b) = (g^r, h^r * m) as ElectionGuard's (pad, data).
+#[derive(Debug, Serialize)]
+pub struct Ciphertext {
+    pub pad: String,
+    pub data: String,
+}
+
+/// A verifiable shuffle (re-encryption mix) of a batch of ciphers (extension).
+/// Topics are shuffled one cipher per ballot, ballot rows one row per ballot.
+#[derive(Debug, Serialize)]
+pub struct MixRound {
+    /// the topic id (not set if the ballots are shuffled as rows)
+    pub contest_id: Option<String>,
+    pub sequence_order: usize,
+    /// the number of shuffles performed before
+    pub iteration: u8,
+    pub start_position: u64,
+    pub batch_size: u64,
+    /// the shuffled ciphers (one row per ballot)
+    pub output: Vec<Vec<Ciphertext>>,
+    pub proof: ShuffleProof,
+}
+
+/// A shuffle proof (CHVote, Algorithm 8.47).
+#[derive(Debug, Serialize)]
+pub struct ShuffleProof {
+    pub challenge: String,
+    pub permutation_commitments: Vec<String>,
+    pub permutation_chain_commitments: Vec<String>,
+    pub s1: String,
+    pub s2: String,
+    pub s3: String,
+    /// s4 (shuffle of ciphers) or vec_s4 (shuffle of rows: one per column)
+    pub s4: Vec<String>,
+    pub s_hat: Vec<String>,
+    pub s_tilde: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecryptionShare {
+    /// the topic id
+    pub contest_id: String,
+    pub guardian_id: String,
+    /// the number of shuffles of the decrypted ciphers
+    pub shuffle_iteration: Option<u8>,
+    /// the partial decryption a^sk of every decrypted cipher
+    pub shares: Vec<String>,
+    pub proof: Option<ChaumPedersenProof>,
+}
+
+/// A batch Chaum-Pedersen proof of the decrypted shares of a sealer.
+#[derive(Debug, Serialize)]
+pub struct ChaumPedersenProof {
+    pub challenge: String,
+    pub response: String,
+    pub usage: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlaintextTally {
+    /// the vote id
+    pub object_id: String,
+    /// the tally of every topic, the counts per option (or per plaintext)
+    pub contests: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+/// The errors which can occur while exporting an election record.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExportError {
+    /// the vote has no public key
+    MissingPublicKey,
+    /// not every topic of the vote has been tallied
+    VoteNotFinished,
+    /// a proof is not encoded with the fixed width of the vote's group
+    NonCanonicalEncoding,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::MissingPublicKey => write!(f, "the vote has no public key"),
+            ExportError::VoteNotFinished => {
+                write!(f, "not every topic of the vote has been tallied")
+            }
+            ExportError::NonCanonicalEncoding => {
+                write!(f, "a proof is not canonically encoded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Converts the election record of a finished vote, i.e. a vote whose topics have all been tallied.
+pub fn export(record: &Record) -> Result<ElectionRecord, ExportError> {
+    let public_key = record
+        .public_key
+        .as_ref()
+        .ok_or(ExportError::MissingPublicKey)?;
+    let tallied =
+        |topic_id: &TopicId| record.tallies.iter().any(|(id, _)| id == topic_id);
+    if !record.topics.iter().all(|(topic_id, _)| tallied(topic_id)) {
+        return Err(ExportError::VoteNotFinished);
+    }
+
+    Ok(ElectionRecord {
+        constants: constants(record),
+        manifest: manifest(record),
+        context: Context {
+            number_of_guardians: record.sealers.len(),
+            quorum: record.sealers.len(),
+            elgamal_public_key: hex(&public_key.h),
+            proof_version: record.proof_version,
+        },
+        guardians: guardians(record)?,
+        submitted_ballots: submitted_ballots(record),
+        mix_rounds: mix_rounds(record)?,
+        encrypted_tally: encrypted_tally(record),
+        decryption_shares: decryption_shares(record)?,
+        plaintext_tally: plaintext_tally(record),
+    })
+}
+
+/// Converts the election record of a finished vote into pretty printed JSON.
+pub fn to_json(record: &Record) -> Result<String, ExportError> {
+    let exported = export(record)?;
+    Ok(serde_json::to_string_pretty(&exported)
+        .expect("the election record is valid JSON"))
+}
+
+fn constants(record: &Record) -> Constants {
+    let params = &record.vote.params;
+
+    // q = (p - 1) / 2: shift the big-endian bytes of p by one bit
+    let mut q: Vec<u8> = Vec::with_capacity(params.p.len());
+    let mut carry: u8 = 0;
+    for byte in params.p.iter() {
+        q.push((byte >> 1) | carry);
+        carry = (byte & 1) << 7;
+    }
+
+    Constants {
+        large_prime: hex(&params.p),
+        small_prime: hex(&q),
+        cofactor: hex(&[2u8]),
+        generator: hex(&params.g),
+    }
+}
+
+fn manifest(record: &Record) -> Manifest {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let contests: Vec<Contest> = record
+        .topics
+        .iter()
+        .enumerate()
+        .map(|(index, (topic_id, question))| {
+            let definition: Option<&TopicDefinition> = definition_of(record, topic_id);
+            let options: &[Vec<u8>] = definition.map_or(&[][..], |d| &d.options[..]);
+            let ballot_selections: Vec<Selection> = options
+                .iter()
+                .enumerate()
+                .map(|(sequence_order, option)| {
+                    let candidate_id = candidate_id(topic_id, option);
+                    candidates.push(Candidate {
+                        object_id: candidate_id.clone(),
+                        name: text(option),
+                    });
+                    Selection {
+                        object_id: candidate_id.clone(),
+                        sequence_order,
+                        candidate_id,
+                    }
+                })
+                .collect();
+            let (vote_variation, votes_allowed) = vote_variation(definition);
+
+            Contest {
+                object_id: text(topic_id),
+                sequence_order: index,
+                ballot_title: text(question),
+                vote_variation: vote_variation.to_string(),
+                number_elected: 1,
+                votes_allowed,
+                ballot_selections,
+            }
+        })
+        .collect();
+
+    Manifest {
+        election_scope_id: text(&record.transcript.vote_id),
+        spec_version: "1.0".to_string(),
+        name: text(&record.vote.title),
+        candidates,
+        contests,
+    }
+}
+
+/// the ElectionGuard vote variation and the number of options a voter may select
+fn vote_variation(definition: Option<&TopicDefinition>) -> (&'static str, u32) {
+    let nr_of_options: u32 = definition.map_or(0, |d| d.options.len() as u32);
+    match definition.map(|d| &d.question_type) {
+        Some(QuestionType::SingleChoice) | Some(QuestionType::YesNoAbstain) => {
+            ("one_of_m", 1)
+        }
+        Some(QuestionType::MultiChoice { max, .. }) => ("n_of_m", *max),
+        Some(QuestionType::Ranked(RankingMethod::InstantRunoff)) => {
+            ("rcv", nr_of_options)
+        }
+        Some(QuestionType::Ranked(RankingMethod::Borda)) => ("borda", nr_of_options),
+        Some(QuestionType::WriteIn { .. }) | None => ("other", 1),
+    }
+}
+
+fn guardians(record: &Record) -> Result<Vec<Guardian>, ExportError> {
+    let mut guardians: Vec<Guardian> = Vec::new();
+    for (sequence_order, sealer) in record.sealers.iter().enumerate() {
+        let share = record
+            .public_key_shares
+            .iter()
+            .find(|(account, _)| account == sealer)
+            .map(|(_, share)| share);
+        if let Some(share) = share {
+            guardians.push(Guardian {
+                guardian_id: account_id(sealer),
+                sequence_order,
+                election_public_key: hex(&share.pk),
+                election_proofs: vec![schnorr_proof(record, &share.pk, &share.proof)?],
+            });
+        }
+    }
+    Ok(guardians)
+}
+
+fn schnorr_proof(
+    record: &Record,
+    public_key: &[u8],
+    proof: &PublicKeyShareProof,
+) -> Result<SchnorrProof, ExportError> {
+    let proof = decode(record, proof)?;
+    Ok(SchnorrProof {
+        public_key: hex(public_key),
+        challenge: hex_value(&proof.challenge),
+        response: hex_value(&proof.response),
+        usage: "SecretValue".to_string(),
+    })
+}
+
+fn submitted_ballots(record: &Record) -> Vec<SubmittedBallot> {
+    record
+        .transcript
+        .ballots
+        .iter()
+        .map(|(voter, ballot)| {
+            let contests: Vec<EncryptedContest> = record
+                .topics
+                .iter()
+                .map(|(topic_id, _)| {
+                    let ciphers: Vec<&Cipher> = ballot
+                        .answers
+                        .iter()
+                        .filter(|(id, _)| id == topic_id)
+                        .map(|(_, cipher)| cipher)
+                        .collect();
+                    encrypted_contest(topic_id, ciphers)
+                })
+                .filter(|contest| !contest.ballot_selections.is_empty())
+                .collect();
+            let weight: u64 = record
+                .voter_weights
+                .iter()
+                .find(|(account, _)| account == voter)
+                .map_or(1, |(_, weight)| *weight);
+
+            SubmittedBallot {
+                object_id: account_id(voter),
+                state: "CAST".to_string(),
+                weight,
+                contests,
+            }
+        })
+        .collect()
+}
+
+fn encrypted_contest<'a>(
+    topic_id: &TopicId,
+    ciphers: impl IntoIterator<Item = &'a Cipher>,
+) -> EncryptedContest {
+    let ballot_selections: Vec<EncryptedSelection> = ciphers
+        .into_iter()
+        .enumerate()
+        .map(|(sequence_order, cipher)| EncryptedSelection {
+            object_id: format!("{}-{}", text(topic_id), sequence_order),
+            sequence_order,
+            ciphertext: ciphertext(cipher),
+        })
+        .collect();
+    EncryptedContest {
+        object_id: text(topic_id),
+        ballot_selections,
+    }
+}
+
+fn mix_rounds(record: &Record) -> Result<Vec<MixRound>, ExportError> {
+    let mut rounds: Vec<MixRound> = Vec::new();
+    for topic in record.transcript.topics.iter() {
+        for (sequence_order, payload) in topic.shuffle_proofs.iter().enumerate() {
+            rounds.push(MixRound {
+                contest_id: Some(text(&topic.topic_id)),
+                sequence_order,
+                iteration: payload.iteration,
+                start_position: payload.start_position,
+                batch_size: payload.batch_size,
+                output: payload
+                    .ciphers
+                    .iter()
+                    .map(|cipher| vec![ciphertext(cipher)])
+                    .collect(),
+                proof: shuffle_proof(&decode(record, &payload.proof)?),
+            });
+        }
+    }
+    for (sequence_order, payload) in
+        record.transcript.ballot_shuffle_proofs.iter().enumerate()
+    {
+        rounds.push(MixRound {
+            contest_id: None,
+            sequence_order,
+            iteration: payload.iteration,
+            start_position: payload.start_position,
+            batch_size: payload.batch_size,
+            output: payload
+                .rows
+                .iter()
+                .map(|row| row.iter().map(ciphertext).collect())
+                .collect(),
+            proof: row_shuffle_proof(&decode(record, &payload.proof)?),
+        });
+    }
+    Ok(rounds)
+}
+
+fn shuffle_proof(proof: &MixnetShuffleProof) -> ShuffleProof {
+    ShuffleProof {
+        challenge: hex_value(&proof.challenge),
+        permutation_commitments: hex_values(&proof.permutation_commitments),
+        permutation_chain_commitments: hex_values(&proof.permutation_chain_commitments),
+        s1: hex_value(&proof.S.s1),
+        s2: hex_value(&proof.S.s2),
+        s3: hex_value(&proof.S.s3),
+        s4: vec![hex_value(&proof.S.s4)],
+        s_hat: hex_values(&proof.S.vec_s_hat),
+        s_tilde: hex_values(&proof.S.vec_s_tilde),
+    }
+}
+
+fn row_shuffle_proof(proof: &RowShuffleProof) -> ShuffleProof {
+    ShuffleProof {
+        challenge: hex_value(&proof.challenge),
+        permutation_commitments: hex_values(&proof.permutation_commitments),
+        permutation_chain_commitments: hex_values(&proof.permutation_chain_commitments),
+        s1: hex_value(&proof.S.s1),
+        s2: hex_value(&proof.S.s2),
+        s3: hex_value(&proof.S.s3),
+        s4: hex_values(&proof.S.vec_s4),
+        s_hat: hex_values(&proof.S.vec_s_hat),
+        s_tilde: hex_values(&proof.S.vec_s_tilde),
+    }
+}
+
+fn encrypted_tally(record: &Record) -> Vec<EncryptedContest> {
+    record
+        .transcript
+        .topics
+        .iter()
+        .filter_map(|topic| {
+            let sum: &Cipher = topic.encrypted_sum.as_ref()?;
+            Some(encrypted_contest(&topic.topic_id, Some(sum)))
+        })
+        .collect()
+}
+
+fn decryption_shares(record: &Record) -> Result<Vec<DecryptionShare>, ExportError> {
+    let mut decryption_shares: Vec<DecryptionShare> = Vec::new();
+    for topic in record.transcript.topics.iter() {
+        for (sealer, shares) in topic.decrypted_shares.iter() {
+            if shares.is_empty() {
+                continue;
+            }
+            let proof: Option<(u8, &DecryptedShareProof)> = topic
+                .decryption_proofs
+                .iter()
+                .find(|(account, _, _)| account == sealer)
+                .map(|(_, iteration, proof)| (*iteration, proof));
+            let proof = match proof {
+                Some((iteration, proof)) => Some((iteration, decode(record, proof)?)),
+                None => None,
+            };
+
+            decryption_shares.push(DecryptionShare {
+                contest_id: text(&topic.topic_id),
+                guardian_id: account_id(sealer),
+                shuffle_iteration: proof.as_ref().map(|(iteration, _)| *iteration),
+                shares: hex_all(shares),
+                proof: proof.map(|(_, proof)| ChaumPedersenProof {
+                    challenge: hex_value(&proof.challenge),
+                    response: hex_value(&proof.response),
+                    usage: "SecretValue".to_string(),
+                }),
+            });
+        }
+    }
+    Ok(decryption_shares)
+}
+
+fn plaintext_tally(record: &Record) -> PlaintextTally {
+    let contests = record
+        .tallies
+        .iter()
+        .map(|(topic_id, result)| {
+            // declared topics are counted per option name, the others per plaintext
+            let is_defined: bool = definition_of(record, topic_id).is_some();
+            let counts: BTreeMap<String, u64> = result
+                .iter()
+                .map(|(plaintext, count)| {
+                    let selection: String = if is_defined {
+                        text(plaintext)
+                    } else {
+                        to_u64(plaintext).to_string()
+                    };
+                    (selection, to_u64(count))
+                })
+                .collect();
+            (text(topic_id), counts)
+        })
+        .collect();
+
+    PlaintextTally {
+        object_id: text(&record.transcript.vote_id),
+        contests,
+    }
+}
+
+fn definition_of<'a>(
+    record: &'a Record,
+    topic_id: &TopicId,
+) -> Option<&'a TopicDefinition> {
+    record
+        .definitions
+        .iter()
+        .find(|(id, _)| id == topic_id)
+        .map(|(_, definition)| definition)
+}
+
+fn candidate_id(topic_id: &TopicId, option: &[u8]) -> String {
+    format!("{}-{}", text(topic_id), text(option))
+}
+
+fn ciphertext(cipher: &Cipher) -> Ciphertext {
+    Ciphertext {
+        pad: hex(&cipher.a),
+        data: hex(&cipher.b),
+    }
+}
+
+fn account_id(account: &impl Encode) -> String {
+    hex(&account.encode())
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// a big-endian number (e.g. a count), saturated at u64::MAX
+fn to_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0u64, |value, byte| {
+        value
+            .checked_mul(256)
+            .map_or(u64::MAX, |value| value | *byte as u64)
+    })
+}
+
+/// decodes a proof with the fixed width of the vote's group
+fn decode<C: Canonical>(record: &Record, encoded: &C) -> Result<C::Value, ExportError> {
+    encoded
+        .decode_canonical(&record.vote.params.clone().into())
+        .ok_or(ExportError::NonCanonicalEncoding)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+fn hex_all(items: &[Vec<u8>]) -> Vec<String> {
+    items.iter().map(|item| hex(item)).collect()
+}
+
+fn hex_value(value: &BigUint) -> String {
+    hex(&value.to_bytes_be())
+}
+
+fn hex_values(values: &[BigUint]) -> Vec<String> {
+    values.iter().map(hex_value).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hex, to_u64};
+
+    #[test]
+    fn it_should_format_numbers() {
+        assert_eq!(hex(&[0x0a, 0xff]), "0AFF");
+        assert_eq!(to_u64(&[1, 0]), 256);
+        assert_eq!(to_u64(&[1; 9]), u64::MAX);
+    }
+}
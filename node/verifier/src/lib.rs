@@ -0,0 +1,71 @@
+//! Provotum election record verifier.
+//!
+//! Re-checks an exported election record (see `pallet_mixnet::Module::election_record`)
+//! end to end and independently of the chain: the key generation, the ballots,
+//! every shuffle proof, every decryption proof and the tally.
+//! The record can also be exported in the ElectionGuard format (see `election_guard`).
+
+pub mod election_guard;
+
+use codec::Decode;
+use pallet_mixnet::types::{ElectionRecord, VerificationError};
+use provotum_runtime::{AccountId, Runtime};
+use std::{fmt, fs, io, path::Path};
+
+type Mixnet = pallet_mixnet::Module<Runtime>;
+
+/// The election record of a vote on the Provotum chain.
+pub type Record = ElectionRecord<AccountId>;
+
+/// The result of a verification step.
+pub type StepResult = Result<(), VerificationError<AccountId>>;
+
+/// The verification steps in the order in which they are performed.
+/// Each step only relies on the steps before it.
+pub const STEPS: [(&str, fn(&Record) -> StepResult); 5] = [
+    ("key generation", Mixnet::verify_key_generation),
+    ("ballots", Mixnet::verify_ballots),
+    ("shuffles", Mixnet::verify_shuffles),
+    ("decryptions", Mixnet::verify_decryptions),
+    ("tallies", Mixnet::verify_tallies),
+];
+
+/// The errors which can occur while reading an election record.
+#[derive(Debug)]
+pub enum Error {
+    /// The record could not be read.
+    Io(io::Error),
+    /// The record is not a SCALE encoded election record.
+    Decode(codec::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "cannot read the election record: {}", error),
+            Error::Decode(error) => write!(f, "invalid election record: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Reads a SCALE encoded election record from a file.
+pub fn read_record(path: &Path) -> Result<Record, Error> {
+    let bytes: Vec<u8> = fs::read(path).map_err(Error::Io)?;
+    Record::decode(&mut &bytes[..]).map_err(Error::Decode)
+}
+
+/// Performs all verification steps in order and stops at the first step which fails.
+/// `on_step` is called with the name and the result of every performed step.
+pub fn verify_record(
+    record: &Record,
+    mut on_step: impl FnMut(&str, &StepResult),
+) -> StepResult {
+    for (name, step) in STEPS.iter() {
+        let result: StepResult = step(record);
+        on_step(name, &result);
+        result?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Verifies an exported election record and reports the first step which fails.
+use provotum_verifier::{election_guard, read_record, verify_record, Record};
+use std::{fs, path::PathBuf, process};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "provotum-verifier",
+    about = "Verifies a Provotum election record end to end."
+)]
+struct Opt {
+    /// The SCALE encoded election record of a vote
+    #[structopt(parse(from_os_str))]
+    record: PathBuf,
+
+    /// Writes the record of the (finished) vote as ElectionGuard compatible JSON to this file
+    #[structopt(long, parse(from_os_str))]
+    election_guard: Option<PathBuf>,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    let record: Record = match read_record(&opt.record) {
+        Ok(record) => record,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    };
+
+    if let Some(path) = opt.election_guard.as_ref() {
+        let written = election_guard::to_json(&record)
+            .map_err(|error| error.to_string())
+            .and_then(|json| fs::write(path, json).map_err(|error| error.to_string()));
+        match written {
+            Ok(()) => println!("exported the election record to {}", path.display()),
+            Err(error) => {
+                eprintln!("cannot export the election record: {}", error);
+                process::exit(2);
+            }
+        }
+    }
+
+    println!(
+        "verifying vote {} ({} topics, {} ballots)",
+        String::from_utf8_lossy(&record.transcript.vote_id),
+        record.topics.len(),
+        record.transcript.ballots.len()
+    );
+    let result = verify_record(&record, |step, result| match result {
+        Ok(()) => println!("  {:<16} ok", step),
+        Err(error) => println!("  {:<16} FAILED: {:?}", step, error),
+    });
+
+    match result {
+        Ok(()) => println!("the election record is valid"),
+        Err(_) => {
+            println!("the election record is invalid");
+            process::exit(1);
+        }
+    }
+}
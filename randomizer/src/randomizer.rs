@@ -1,6 +1,7 @@
 use actix_web::{post, web, Responder};
 use crypto::{
     encryption::ElGamal,
+    helper::ProofContext,
     proofs::re_encryption::ReEncryptionProof,
     random::Random,
     types::{Cipher, PublicKey},
@@ -11,6 +12,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 pub struct RequestBody {
+    pub vote_id: String,
     pub pk: PublicKey,
     pub cipher: Cipher,
 }
@@ -27,9 +29,10 @@ pub async fn randomize_ballot(body: web::Json<RequestBody>) -> impl Responder {
     let cipher = body.cipher.clone();
     let pk = body.pk.clone();
     let q = &pk.params.q();
+    let mut rng = rand::thread_rng();
 
     // 1. re-encrypt the cipher
-    let r1 = Random::get_random_less_than(q);
+    let r1 = Random::get_random_less_than(q, &mut rng);
     let re_encrypted_cipher = ElGamal::re_encrypt(&cipher, &r1, &pk);
 
     // 2. generate a proof to show that the re-encryption is valid/not something else
@@ -38,10 +41,11 @@ pub async fn randomize_ballot(body: web::Json<RequestBody>) -> impl Responder {
     let c_one = ElGamal::encrypt(&one, &r1, &pk);
 
     // 2.2 generate the proof
-    let r2 = Random::get_random_less_than(q);
-    let h2 = Random::get_random_less_than(q);
-    let s2 = Random::get_random_less_than(q);
-    let proof = ReEncryptionProof::generate(&r1, &r2, &h2, &s2, &c_one, &pk);
+    let r2 = Random::get_random_less_than(q, &mut rng);
+    let h2 = Random::get_random_less_than(q, &mut rng);
+    let s2 = Random::get_random_less_than(q, &mut rng);
+    let context = ProofContext::new(body.vote_id.as_bytes());
+    let proof = ReEncryptionProof::generate(&r1, &r2, &h2, &s2, &c_one, &pk, &context);
 
     // return the re-encrypted cipher
     web::Json(ResponseBody {
@@ -55,7 +59,9 @@ mod tests {
     use super::{randomize_ballot, RequestBody, ResponseBody};
     use actix_web::{test, App};
     use crypto::{
-        encryption::ElGamal, helper::Helper, proofs::re_encryption::ReEncryptionProof,
+        encryption::ElGamal,
+        helper::{Helper, ProofContext},
+        proofs::re_encryption::ReEncryptionProof,
         random::Random,
     };
     use num_bigint::BigUint;
@@ -77,9 +83,10 @@ mod tests {
         let (_, sk, pk) = Helper::setup_sm_system();
         let q = &pk.params.q();
         let vote = &BigUint::from(13u32);
-        let r = Random::get_random_less_than(q);
+        let r = Random::get_random_less_than(q, &mut rand::thread_rng());
         let cipher = ElGamal::encrypt(vote, &r, &pk);
         let request_body = RequestBody {
+            vote_id: String::from("20201212"),
             pk: pk.clone(),
             cipher: cipher.clone(),
         };
@@ -98,10 +105,17 @@ mod tests {
         assert_ne!(&re_encrypted_cipher, &cipher);
 
         // verify the re-encryption proof
+        let context = ProofContext::new(b"20201212");
         let proof_is_valid =
-            ReEncryptionProof::verify(&pk, &resp.proof, &cipher, &re_encrypted_cipher);
+            ReEncryptionProof::verify(&pk, &resp.proof, &cipher, &re_encrypted_cipher, &context);
         assert!(proof_is_valid);
 
+        // the proof is bound to the vote
+        let other = ProofContext::new(b"20201213");
+        let proof_is_valid =
+            ReEncryptionProof::verify(&pk, &resp.proof, &cipher, &re_encrypted_cipher, &other);
+        assert!(!proof_is_valid);
+
         // ensure that the decrypted re-encrypted vote is still 13
         let decrypted = ElGamal::decrypt(&re_encrypted_cipher, &sk);
         assert_eq!(&decrypted, vote);